@@ -0,0 +1,373 @@
+// ix-encryption/bin/ix-crypt.rs
+
+//! `ix-crypt`: command-line front end for the IX container format.
+//!
+//!   ix-crypt keygen  -o <secret key file>                 (writes <file>.pub alongside)
+//!   ix-crypt encrypt [-r <public key>]... [--anonymous] [--passphrase-file <f> | --passphrase-env <var>]
+//!                    [--kdf argon2id|scrypt|pbkdf2] [--calibrate <ms>]
+//!                    [--stack <id,id,...>] [--chunk-size <bytes>] [-i <in>] [-o <out>]
+//!   ix-crypt decrypt (-k <secret key> | --passphrase-file <f> | --passphrase-env <var>) [-i] [-o]
+//!   ix-crypt inspect [-i <in>]
+//!   ix-crypt rewrap  (-k ... | --passphrase-...) [-r <public key>]... [--new-passphrase-file <f> |
+//!                    --new-passphrase-env <var>] [-i] [-o]
+//!   ix-crypt add-recipients    (-k ... | --passphrase-...) [-r <public key>]... [--anonymous]
+//!                              [--new-passphrase-...] [-i] [-o]
+//!   ix-crypt remove-recipients (-k ... | --passphrase-...) [--remove <public key | key id hex>]...
+//!                              [--remove-index <n>]... [--remove-kind passphrase|lattice|anonymous]...
+//!                              [-i] [-o]
+//!
+//! age v1 files:
+//!   ix-crypt age-keygen  [--pq] [-o <identity file>]               (prints the recipient)
+//!   ix-crypt age-encrypt (-r <age1... | recipients file>... | --passphrase-...) [-i] [-o]
+//!   ix-crypt age-decrypt (-k <identity file> | --passphrase-...) [-i] [-o]
+//!
+//! Input and output default to stdin and stdout. A failed decrypt removes a partially written
+//! output file.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::process::exit;
+use std::time::Duration;
+use ix_encryption::core::age::age_format;
+use ix_encryption::core::age::recipients::{AgeIdentity, AgeRecipient, DEFAULT_SCRYPT_LOG_N};
+use ix_encryption::core::container::{self, ContainerOptions, Identity, Recipient, StanzaKind, StanzaSelector, KEY_ID_LEN};
+use ix_encryption::core::kdf::password::PasswordKdf;
+use ix_encryption::core::postquantum::lattice_kem::LatticeKEM;
+
+const PUBLIC_KEY_MAGIC: &[u8; 4] = b"IXPK";
+const SECRET_KEY_MAGIC: &[u8; 4] = b"IXSK";
+
+#[derive(Default)]
+struct Args {
+    input: Option<String>,
+    output: Option<String>,
+    recipients: Vec<String>,
+    anonymous: bool,
+    pq: bool,
+    remove: Vec<String>,
+    remove_index: Vec<usize>,
+    remove_kind: Vec<String>,
+    secret_key: Option<String>,
+    passphrase_file: Option<String>,
+    passphrase_env: Option<String>,
+    new_passphrase_file: Option<String>,
+    new_passphrase_env: Option<String>,
+    kdf: Option<String>,
+    calibrate_ms: Option<u64>,
+    stack: Option<String>,
+    chunk_size: Option<u32>,
+}
+
+fn usage() -> ! {
+    eprintln!("usage: ix-crypt <keygen|encrypt|decrypt|inspect|rewrap|add-recipients|remove-recipients|age-keygen|age-encrypt|age-decrypt> [options]");
+    exit(2);
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("ix-crypt: {}", message);
+    exit(1);
+}
+
+fn parse_args(raw: &[String]) -> Args {
+    let mut args = Args::default();
+    let mut iter = raw.iter();
+    while let Some(flag) = iter.next() {
+        let mut value = || iter.next().cloned().unwrap_or_else(|| usage());
+        match flag.as_str() {
+            "-i" | "--input" => args.input = Some(value()),
+            "-o" | "--output" => args.output = Some(value()),
+            "-r" | "--recipient" => args.recipients.push(value()),
+            "--anonymous" => args.anonymous = true,
+            "--pq" => args.pq = true,
+            "--remove" => args.remove.push(value()),
+            "--remove-index" => args.remove_index.push(value().parse().unwrap_or_else(|_| usage())),
+            "--remove-kind" => args.remove_kind.push(value()),
+            "-k" | "--key" => args.secret_key = Some(value()),
+            "--passphrase-file" => args.passphrase_file = Some(value()),
+            "--passphrase-env" => args.passphrase_env = Some(value()),
+            "--new-passphrase-file" => args.new_passphrase_file = Some(value()),
+            "--new-passphrase-env" => args.new_passphrase_env = Some(value()),
+            "--kdf" => args.kdf = Some(value()),
+            "--calibrate" => args.calibrate_ms = Some(value().parse().unwrap_or_else(|_| usage())),
+            "--stack" => args.stack = Some(value()),
+            "--chunk-size" => args.chunk_size = Some(value().parse().unwrap_or_else(|_| usage())),
+            _ => usage(),
+        }
+    }
+    args
+}
+
+fn read_passphrase(file: &Option<String>, env: &Option<String>) -> Option<Vec<u8>> {
+    let passphrase = match (file, env) {
+        (Some(path), _) => std::fs::read(path).unwrap_or_else(|_| fail("cannot read passphrase file")),
+        (None, Some(var)) => std::env::var(var).unwrap_or_else(|_| fail("passphrase variable not set")).into_bytes(),
+        (None, None) => return None,
+    };
+    // Passphrase files usually end in a newline that is not part of the secret
+    let end = passphrase.iter().rposition(|&b| b != b'\n' && b != b'\r').map_or(0, |i| i + 1);
+    Some(passphrase[..end].to_vec())
+}
+
+fn encode_key_file(magic: &[u8; 4], parts: &[&[u8]]) -> Vec<u8> {
+    let mut out = magic.to_vec();
+    out.push(1);
+    for part in parts {
+        out.extend_from_slice(&(part.len() as u32).to_be_bytes());
+        out.extend_from_slice(part);
+    }
+    out
+}
+
+fn decode_key_file(data: &[u8], magic: &[u8; 4], count: usize) -> Vec<Vec<u8>> {
+    if data.len() < 5 || &data[..4] != magic || data[4] != 1 {
+        fail("not an ix-crypt key file");
+    }
+    let mut parts = Vec::new();
+    let mut offset = 5;
+    for _ in 0..count {
+        let len = data
+            .get(offset..offset + 4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize)
+            .unwrap_or_else(|| fail("truncated key file"));
+        offset += 4;
+        let part = data.get(offset..offset + len).unwrap_or_else(|| fail("truncated key file"));
+        parts.push(part.to_vec());
+        offset += len;
+    }
+    parts
+}
+
+fn open_input(path: &Option<String>) -> Box<dyn Read> {
+    match path {
+        Some(p) if p != "-" => Box::new(BufReader::new(File::open(p).unwrap_or_else(|_| fail("cannot open input")))),
+        _ => Box::new(BufReader::new(io::stdin())),
+    }
+}
+
+fn open_output(path: &Option<String>) -> Box<dyn Write> {
+    match path {
+        Some(p) if p != "-" => Box::new(BufWriter::new(File::create(p).unwrap_or_else(|_| fail("cannot create output")))),
+        _ => Box::new(BufWriter::new(io::stdout())),
+    }
+}
+
+/// Create `path` for key material: owner-only permissions, and an existing file is never replaced.
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn write_new_file(path: &str, contents: &[u8], private: bool) -> io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if private {
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(contents).and_then(|_| file.sync_all())
+}
+
+fn identity(args: &Args) -> Identity {
+    if let Some(path) = &args.secret_key {
+        let data = std::fs::read(path).unwrap_or_else(|_| fail("cannot read secret key"));
+        let mut parts = decode_key_file(&data, SECRET_KEY_MAGIC, 2).into_iter();
+        let public_key = parts.next().unwrap_or_default();
+        let secret_key = parts.next().unwrap_or_default();
+        return Identity::Lattice { public_key, secret_key };
+    }
+    match read_passphrase(&args.passphrase_file, &args.passphrase_env) {
+        Some(passphrase) => Identity::Passphrase(passphrase),
+        None => fail("no identity given (use -k or --passphrase-file/--passphrase-env)"),
+    }
+}
+
+fn password_kdf(args: &Args) -> PasswordKdf {
+    let kdf = match args.kdf.as_deref() {
+        None | Some("argon2id") => PasswordKdf::default(),
+        Some("scrypt") => PasswordKdf::scrypt_default(),
+        Some("pbkdf2") => PasswordKdf::pbkdf2_default(),
+        Some(_) => fail("unknown KDF"),
+    };
+    match args.calibrate_ms {
+        Some(ms) => kdf.calibrate(Duration::from_millis(ms)).unwrap_or_else(|e| fail(e)),
+        None => kdf,
+    }
+}
+
+fn recipients(args: &Args, passphrase: Option<Vec<u8>>) -> Vec<Recipient> {
+    let mut recipients = Vec::new();
+    for path in &args.recipients {
+        let data = std::fs::read(path).unwrap_or_else(|_| fail("cannot read public key"));
+        let public_key = decode_key_file(&data, PUBLIC_KEY_MAGIC, 1).remove(0);
+        recipients.push(Recipient::Lattice { public_key, anonymous: args.anonymous });
+    }
+    if let Some(password) = passphrase {
+        recipients.push(Recipient::Passphrase { password, kdf: password_kdf(args) });
+    }
+    recipients
+}
+
+/// `--remove` accepts either a public key file or a key id as printed by `inspect`;
+/// `--remove-index` takes a stanza position and `--remove-kind` drops every stanza of a kind.
+fn removed_stanzas(args: &Args) -> Vec<StanzaSelector> {
+    let by_key_id = args.remove.iter().map(|target| match std::fs::read(target) {
+        Ok(data) => StanzaSelector::KeyId(container::key_id(&decode_key_file(&data, PUBLIC_KEY_MAGIC, 1)[0])),
+        Err(_) => hex::decode(target)
+            .ok()
+            .and_then(|id| <[u8; KEY_ID_LEN]>::try_from(id.as_slice()).ok())
+            .map(StanzaSelector::KeyId)
+            .unwrap_or_else(|| fail("--remove expects a public key file or key id")),
+    });
+    let by_index = args.remove_index.iter().map(|&index| StanzaSelector::Index(index));
+    let by_kind = args.remove_kind.iter().map(|kind| match kind.as_str() {
+        "passphrase" => StanzaSelector::Kind(StanzaKind::Passphrase),
+        "lattice" => StanzaSelector::Kind(StanzaKind::Lattice),
+        "anonymous" => StanzaSelector::Kind(StanzaKind::LatticeAnonymous),
+        _ => fail("--remove-kind expects passphrase, lattice or anonymous"),
+    });
+    by_key_id.chain(by_index).chain(by_kind).collect()
+}
+
+fn keygen(args: &Args) {
+    let path = args.output.as_ref().unwrap_or_else(|| fail("keygen requires -o <file>"));
+    let kem = LatticeKEM::keypair();
+    let secret = encode_key_file(SECRET_KEY_MAGIC, &[&kem.public_key, &kem.secret_key]);
+    let public = encode_key_file(PUBLIC_KEY_MAGIC, &[&kem.public_key]);
+    write_new_file(path, &secret, true).unwrap_or_else(|_| fail("cannot create secret key (file exists?)"));
+    write_new_file(&format!("{}.pub", path), &public, false).unwrap_or_else(|_| fail("cannot create public key (file exists?)"));
+}
+
+fn inspect(args: &Args) {
+    let header = container::inspect(&mut open_input(&args.input)).unwrap_or_else(|e| fail(e));
+    println!("version:    {}", container::CONTAINER_VERSION);
+    println!("chunk size: {}", header.chunk_size);
+    println!("stack:      {}", if header.stack.is_empty() { "-".to_string() } else { header.stack.join(", ") });
+    for (index, stanza) in header.stanzas.iter().enumerate() {
+        match stanza.kind {
+            StanzaKind::Passphrase => match PasswordKdf::decode(&stanza.body) {
+                Ok((kdf, _)) => println!("recipient:  [{}] passphrase {:?}", index, kdf),
+                Err(e) => println!("recipient:  [{}] passphrase ({})", index, e),
+            },
+            StanzaKind::Lattice => match stanza.key_id() {
+                Some(id) => println!("recipient:  [{}] lattice KEM, key id {}", index, hex::encode(id)),
+                None => println!("recipient:  [{}] lattice KEM (malformed stanza)", index),
+            },
+            StanzaKind::LatticeAnonymous => println!("recipient:  [{}] lattice KEM, anonymous", index),
+        }
+    }
+}
+
+/// Non-empty, non-comment lines of an age identity or recipients file.
+fn key_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+fn age_keygen(args: &Args) {
+    let identity = match args.pq {
+        #[cfg(feature = "ml_kem")]
+        true => AgeIdentity::generate_ixpq(),
+        #[cfg(not(feature = "ml_kem"))]
+        true => fail("--pq requires a build with the ml_kem feature"),
+        false => AgeIdentity::generate_x25519(),
+    };
+    let recipient = identity.to_recipient().and_then(|r| r.encode()).unwrap_or_default();
+    let contents = format!("# public key: {}\n{}\n", recipient, identity.encode().unwrap_or_default());
+    match &args.output {
+        Some(path) => write_new_file(path, contents.as_bytes(), true).unwrap_or_else(|_| fail("cannot create identity (file exists?)")),
+        None => print!("{}", contents),
+    }
+    eprintln!("Public key: {}", recipient);
+}
+
+fn age_recipients(args: &Args) -> Vec<AgeRecipient> {
+    let mut recipients = Vec::new();
+    for target in &args.recipients {
+        if target.starts_with("age1") {
+            recipients.push(AgeRecipient::parse(target).unwrap_or_else(|e| fail(e)));
+            continue;
+        }
+        let text = std::fs::read_to_string(target).unwrap_or_else(|_| fail("cannot read recipients file"));
+        for line in key_lines(&text) {
+            recipients.push(AgeRecipient::parse(line).unwrap_or_else(|e| fail(e)));
+        }
+    }
+    if let Some(passphrase) = read_passphrase(&args.passphrase_file, &args.passphrase_env) {
+        recipients.push(AgeRecipient::Scrypt { passphrase, log_n: DEFAULT_SCRYPT_LOG_N });
+    }
+    recipients
+}
+
+fn age_identities(args: &Args) -> Vec<AgeIdentity> {
+    if let Some(path) = &args.secret_key {
+        let text = std::fs::read_to_string(path).unwrap_or_else(|_| fail("cannot read identity file"));
+        return key_lines(&text).map(|line| AgeIdentity::parse(line).unwrap_or_else(|e| fail(e))).collect();
+    }
+    match read_passphrase(&args.passphrase_file, &args.passphrase_env) {
+        Some(passphrase) => vec![AgeIdentity::passphrase(&passphrase)],
+        None => fail("no identity given (use -k or --passphrase-file/--passphrase-env)"),
+    }
+}
+
+/// Run `operation`, removing a partially written output file if it fails.
+fn run_to_output<F>(args: &Args, operation: F)
+where
+    F: FnOnce(&mut dyn Read, &mut dyn Write) -> Result<(), &'static str>,
+{
+    let mut input = open_input(&args.input);
+    let mut output = open_output(&args.output);
+    if let Err(e) = operation(&mut input, &mut output) {
+        drop(output);
+        if let Some(path) = args.output.as_ref().filter(|p| p.as_str() != "-") {
+            let _ = std::fs::remove_file(path);
+        }
+        fail(e);
+    }
+}
+
+fn main() {
+    let raw: Vec<String> = std::env::args().skip(1).collect();
+    let (command, rest) = raw.split_first().unwrap_or_else(|| usage());
+    let args = parse_args(rest);
+
+    match command.as_str() {
+        "keygen" => keygen(&args),
+        "inspect" => inspect(&args),
+        "encrypt" => {
+            let recipients = recipients(&args, read_passphrase(&args.passphrase_file, &args.passphrase_env));
+            let mut options = ContainerOptions::default();
+            if let Some(stack) = &args.stack {
+                options.stack = stack.split(',').filter(|s| !s.is_empty()).map(str::to_string).collect();
+            }
+            if let Some(chunk_size) = args.chunk_size {
+                options.chunk_size = chunk_size;
+            }
+            run_to_output(&args, |input, output| container::encrypt(&recipients, &options, input, output));
+        }
+        "decrypt" => {
+            let identity = identity(&args);
+            run_to_output(&args, |input, output| container::decrypt(&identity, input, output));
+        }
+        "rewrap" => {
+            let identity = identity(&args);
+            let recipients = recipients(&args, read_passphrase(&args.new_passphrase_file, &args.new_passphrase_env));
+            run_to_output(&args, |input, output| container::rewrap(&identity, &recipients, input, output));
+        }
+        "add-recipients" => {
+            let identity = identity(&args);
+            let recipients = recipients(&args, read_passphrase(&args.new_passphrase_file, &args.new_passphrase_env));
+            run_to_output(&args, |input, output| container::update_recipients(&identity, &recipients, &[], input, output));
+        }
+        "remove-recipients" => {
+            let identity = identity(&args);
+            let removed = removed_stanzas(&args);
+            run_to_output(&args, |input, output| container::update_recipients(&identity, &[], &removed, input, output));
+        }
+        "age-keygen" => age_keygen(&args),
+        "age-encrypt" => {
+            let recipients = age_recipients(&args);
+            run_to_output(&args, |input, output| age_format::encrypt(&recipients, input, output));
+        }
+        "age-decrypt" => {
+            let identities = age_identities(&args);
+            run_to_output(&args, |input, output| age_format::decrypt(&identities, input, output));
+        }
+        _ => usage(),
+    }
+}
@@ -14,6 +14,12 @@ pub trait IXCipherCore {
     /// Decrypts a block of data. Returns plaintext as Vec<u8>.
     fn decrypt(&self, ciphertext: &[u8]) -> Vec<u8>;
 
+    /// Decrypts a block of data, returning an error instead of panicking on malformed or
+    /// unauthentic input. Authenticating ciphers override this; the default defers to `decrypt`.
+    fn try_decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        Ok(self.decrypt(ciphertext))
+    }
+
     /// Securely wipes internal state from memory.
     fn wipe(&mut self);
 
@@ -0,0 +1,349 @@
+// ix-encryption/core/age/age_format.rs
+
+//! Reader and writer for the age v1 file format (age-encryption.org/v1).
+//! The header is a version line, one `-> ` stanza per recipient with a base64 body wrapped
+//! at 64 columns, and a `---` line carrying an HMAC-SHA-256 over everything before it.
+//! The payload is a 16-byte nonce followed by 64 KiB STREAM chunks. age's STREAM nonce is an
+//! 11-byte counter and a last-chunk flag, which is exactly `StreamEncryptor` with a zero prefix.
+//! Unknown stanza types are skipped, so files can mix standard and `ixpq` recipients.
+
+use std::io::{Read, Write};
+use base64::engine::general_purpose::STANDARD_NO_PAD;
+use base64::Engine;
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+use crate::core::age::recipients::{AgeIdentity, AgeRecipient, AgeStanza, FILE_KEY_LEN};
+use crate::core::kdf::hkdf::{hkdf, HkdfHash};
+use crate::core::mac::hmac::HmacSha256;
+use crate::core::mac::mac_core::Mac;
+use crate::core::stream_aead::{StreamDecryptor, StreamEncryptor, STREAM_NONCE_PREFIX_LEN, STREAM_TAG_LEN};
+
+const VERSION_LINE: &str = "age-encryption.org/v1";
+const COLUMNS: usize = 64;
+const MAX_LINE_LEN: usize = 64 * 1024;
+const PAYLOAD_NONCE_LEN: usize = 16;
+pub const AGE_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Clone, Debug)]
+pub struct AgeHeader {
+    pub stanzas: Vec<AgeStanza>,
+    pub mac: [u8; 32],
+}
+
+fn valid_arg(arg: &str) -> bool {
+    !arg.is_empty() && arg.bytes().all(|c| (33..=126).contains(&c))
+}
+
+fn encode_stanzas(stanzas: &[AgeStanza]) -> Vec<u8> {
+    let mut out = format!("{}\n", VERSION_LINE).into_bytes();
+    for stanza in stanzas {
+        out.extend_from_slice(b"->");
+        for arg in std::iter::once(&stanza.tag).chain(&stanza.args) {
+            out.push(b' ');
+            out.extend_from_slice(arg.as_bytes());
+        }
+        out.push(b'\n');
+
+        let body = STANDARD_NO_PAD.encode(&stanza.body);
+        for line in body.as_bytes().chunks(COLUMNS) {
+            out.extend_from_slice(line);
+            out.push(b'\n');
+        }
+        // A body always ends with a line shorter than 64 columns, possibly empty
+        if body.len() % COLUMNS == 0 {
+            out.push(b'\n');
+        }
+    }
+    out.extend_from_slice(b"---");
+    out
+}
+
+fn header_mac(file_key: &[u8; FILE_KEY_LEN], header_up_to_dashes: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let mut mac_key = hkdf(HkdfHash::Sha256, None, file_key, b"header", 32)?;
+    let mac = HmacSha256::new(&mac_key).compute(header_up_to_dashes);
+    mac_key.zeroize();
+    Ok(mac)
+}
+
+/// Read one `\n`-terminated line, appending its raw bytes (newline included) to `raw`.
+fn read_line<R: Read + ?Sized>(input: &mut R, raw: &mut Vec<u8>) -> Result<String, &'static str> {
+    let mut line = Vec::new();
+    let mut byte = [0u8; 1];
+    loop {
+        match input.read(&mut byte) {
+            Ok(0) => return Err("Truncated age header"),
+            Ok(_) if byte[0] == b'\n' => break,
+            Ok(_) => line.push(byte[0]),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(_) => return Err("Failed to read input"),
+        }
+        if line.len() > MAX_LINE_LEN {
+            return Err("age header line too long");
+        }
+    }
+    raw.extend_from_slice(&line);
+    raw.push(b'\n');
+    String::from_utf8(line).map_err(|_| "age header is not text")
+}
+
+/// Parse the header, returning it with the exact bytes covered by its MAC: everything read
+/// up to and including the `---`, as it appeared in the input rather than re-encoded.
+pub fn read_header<R: Read + ?Sized>(input: &mut R) -> Result<(AgeHeader, Vec<u8>), &'static str> {
+    let mut raw = Vec::new();
+    if read_line(input, &mut raw)? != VERSION_LINE {
+        return Err("Not an age v1 file");
+    }
+    let mut stanzas = Vec::new();
+
+    let mut line_start = raw.len();
+    let mut line = read_line(input, &mut raw)?;
+    loop {
+        if let Some(encoded_mac) = line.strip_prefix("--- ") {
+            let mac: [u8; 32] = STANDARD_NO_PAD
+                .decode(encoded_mac)
+                .ok()
+                .and_then(|m| m.try_into().ok())
+                .ok_or("Malformed header MAC")?;
+            raw.truncate(line_start + "---".len());
+            return Ok((AgeHeader { stanzas, mac }, raw));
+        }
+
+        let fields = line.strip_prefix("-> ").ok_or("Malformed stanza line")?;
+        let mut args: Vec<String> = fields.split(' ').map(str::to_string).collect();
+        if !args.iter().all(|a| valid_arg(a)) {
+            return Err("Malformed stanza arguments");
+        }
+        let tag = args.remove(0);
+
+        let mut encoded_body = String::new();
+        loop {
+            let body_line = read_line(input, &mut raw)?;
+            if body_line.len() > COLUMNS {
+                return Err("Stanza body line too long");
+            }
+            let last = body_line.len() < COLUMNS;
+            encoded_body.push_str(&body_line);
+            if last {
+                break;
+            }
+        }
+        let body = STANDARD_NO_PAD.decode(&encoded_body).map_err(|_| "Invalid base64 in stanza body")?;
+        stanzas.push(AgeStanza { tag, args, body });
+
+        line_start = raw.len();
+        line = read_line(input, &mut raw)?;
+    }
+}
+
+fn find_file_key(identities: &[AgeIdentity], header: &AgeHeader) -> Result<[u8; FILE_KEY_LEN], &'static str> {
+    let scrypt_stanzas = header.stanzas.iter().filter(|s| s.tag == "scrypt").count();
+    if scrypt_stanzas > 0 && header.stanzas.len() != 1 {
+        return Err("scrypt stanza must be the only stanza");
+    }
+
+    // A stanza that fails to unwrap may just be addressed to someone else, so keep trying and
+    // report the first error only once no stanza opens with any identity.
+    let mut first_error = None;
+    for stanza in &header.stanzas {
+        for identity in identities {
+            match identity.unwrap(stanza) {
+                Ok(Some(file_key)) => return Ok(file_key),
+                Ok(None) => {}
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+    }
+    Err(first_error.unwrap_or("No identity matched any recipient"))
+}
+
+fn payload_key(file_key: &[u8; FILE_KEY_LEN], nonce: &[u8]) -> Result<Vec<u8>, &'static str> {
+    hkdf(HkdfHash::Sha256, Some(nonce), file_key, b"payload", 32)
+}
+
+/// Fill `buf` from `input`, stopping early only at end of input.
+fn read_up_to<R: Read + ?Sized>(input: &mut R, buf: &mut [u8]) -> Result<usize, &'static str> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match input.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(_) => return Err("Failed to read input"),
+        }
+    }
+    Ok(filled)
+}
+
+fn read_block<R: Read + ?Sized>(input: &mut R, size: usize) -> Result<Vec<u8>, &'static str> {
+    let mut block = vec![0u8; size];
+    let len = read_up_to(input, &mut block)?;
+    block.truncate(len);
+    Ok(block)
+}
+
+/// Encrypt `input` to `recipients` as an age v1 file.
+pub fn encrypt<R: Read + ?Sized, W: Write + ?Sized>(recipients: &[AgeRecipient], input: &mut R, output: &mut W) -> Result<(), &'static str> {
+    if recipients.is_empty() {
+        return Err("At least one recipient required");
+    }
+    let has_scrypt = recipients.iter().any(|r| matches!(r, AgeRecipient::Scrypt { .. }));
+    if has_scrypt && recipients.len() != 1 {
+        return Err("A passphrase cannot be combined with other recipients");
+    }
+
+    let mut file_key = [0u8; FILE_KEY_LEN];
+    let mut nonce = [0u8; PAYLOAD_NONCE_LEN];
+    getrandom::getrandom(&mut file_key).map_err(|_| "Key generation failed")?;
+    getrandom::getrandom(&mut nonce).map_err(|_| "Nonce generation failed")?;
+
+    let stanzas: Result<Vec<AgeStanza>, &'static str> = recipients.iter().map(|r| r.wrap(&file_key)).collect();
+    let header = stanzas.map(|s| encode_stanzas(&s));
+    let mac = header.as_ref().map_err(|e| *e).and_then(|h| header_mac(&file_key, h));
+    let key = payload_key(&file_key, &nonce);
+    file_key.zeroize();
+    let (header, mac, mut key) = (header?, mac?, key?);
+
+    output.write_all(&header).map_err(|_| "Failed to write output")?;
+    output
+        .write_all(format!(" {}\n", STANDARD_NO_PAD.encode(mac)).as_bytes())
+        .map_err(|_| "Failed to write output")?;
+    output.write_all(&nonce).map_err(|_| "Failed to write output")?;
+
+    let mut stream = StreamEncryptor::new(&key, [0u8; STREAM_NONCE_PREFIX_LEN])?;
+    key.zeroize();
+
+    let mut current = read_block(input, AGE_CHUNK_SIZE)?;
+    loop {
+        let next = if current.len() < AGE_CHUNK_SIZE { Vec::new() } else { read_block(input, AGE_CHUNK_SIZE)? };
+        let last = next.is_empty();
+        let sealed = stream.seal_chunk(&current, &[], last)?;
+        current.zeroize();
+        output.write_all(&sealed).map_err(|_| "Failed to write output")?;
+        if last {
+            break;
+        }
+        current = next;
+    }
+    output.flush().map_err(|_| "Failed to write output")
+}
+
+/// Decrypt an age v1 file with the first identity that opens a stanza. Output is written
+/// chunk by chunk after each chunk authenticates; on error, discard what was written.
+pub fn decrypt<R: Read + ?Sized, W: Write + ?Sized>(identities: &[AgeIdentity], input: &mut R, output: &mut W) -> Result<(), &'static str> {
+    let (header, covered) = read_header(input)?;
+    let mut file_key = find_file_key(identities, &header)?;
+    let mac = header_mac(&file_key, &covered);
+    let mut nonce = [0u8; PAYLOAD_NONCE_LEN];
+    let nonce_read = input.read_exact(&mut nonce);
+    let key = payload_key(&file_key, &nonce);
+    file_key.zeroize();
+
+    if !bool::from(mac?.ct_eq(&header.mac)) {
+        return Err("Header MAC mismatch");
+    }
+    nonce_read.map_err(|_| "Truncated payload")?;
+    let mut key = key?;
+    let mut stream = StreamDecryptor::new(&key, [0u8; STREAM_NONCE_PREFIX_LEN])?;
+    key.zeroize();
+
+    let sealed_size = AGE_CHUNK_SIZE + STREAM_TAG_LEN;
+    let mut current = read_block(input, sealed_size)?;
+    let mut first = true;
+    loop {
+        let next = if current.len() < sealed_size { Vec::new() } else { read_block(input, sealed_size)? };
+        let last = next.is_empty();
+        if current.len() < STREAM_TAG_LEN {
+            return Err("Truncated payload");
+        }
+
+        let mut chunk = stream.open_chunk(&current, &[], last)?;
+        if last && chunk.is_empty() && !first {
+            return Err("Empty final chunk");
+        }
+        output.write_all(&chunk).map_err(|_| "Failed to write output")?;
+        chunk.zeroize();
+
+        if last {
+            break;
+        }
+        current = next;
+        first = false;
+    }
+    output.flush().map_err(|_| "Failed to write output")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_util::h;
+
+    // Written by the reference age implementation (the `age` crate), grease stanza included.
+    const X25519_IDENTITY: &str = "AGE-SECRET-KEY-1QURSWPC8QURSWPC8QURSWPC8QURSWPC8QURSWPC8QURSWPC8QURSKMP32K";
+    const X25519_RECIPIENT: &str = "age1zwlyl6h27gzv0lfntr7fcqrjrzqazap8sy5zylkxwneh7llf0dksu37mwt";
+    const X25519_HEADER: &str = "age-encryption.org/v1
+-> X25519 agAF3H4o9Rx4b+ECQWNgy67LBsMQOeDMLXrrlj+DQDc
++IVcKHlQZ3mWsy86bQ/QcnwUG13k9ZY1CZfd+Uu3ydw
+-> U8-grease mcj-p`F= QKq-Oc) taZH@
+J/V177WvDnP0pXQWBmSADoGxILmi/+O3K27Sp6yeNxLyP1rnGuzY9Z7VMemJgPys
+cTrdIMefjKliLRU7/bfJ4R6D4A
+--- BfA9hrvxjzNjC+Wd1X6rA9WMRTrDPGroZLrP6c4DnCE
+";
+    const X25519_PAYLOAD: &str = "c72d05e94e7393d3752a4fcf59efd4a9ff32aa182bbaae4d531f84a6ccf6cde5b5dcc66267980a5a3c3ce6337655968855";
+    const SCRYPT_HEADER: &str = "age-encryption.org/v1
+-> scrypt eGXvu4Bs0V9TDy5zNSOTcA 10
+UN5gMHWHLYP5+BRTDYFJ1E0/86v49wCXB99qKXLy/Zs
+--- P642sd02fM1qNVBFbBdYtQHbEPhtZW9GeyUVPCyVS0M
+";
+    const SCRYPT_PAYLOAD: &str = "954ee783595bec7e00f217c591272b53bfc35738d56ddcd62db905662cfc7105ff87ad72eb6c3900b95ea7fa9731fc613c";
+    const PLAINTEXT: &[u8] = b"age interop test\n";
+
+    fn open(identity: AgeIdentity, header: &str, payload: &str) -> Result<Vec<u8>, &'static str> {
+        let file = [header.as_bytes(), &h(payload)].concat();
+        let mut plaintext = Vec::new();
+        decrypt(&[identity], &mut &file[..], &mut plaintext).map(|_| plaintext)
+    }
+
+    #[test]
+    fn x25519_file() {
+        let identity = AgeIdentity::parse(X25519_IDENTITY).unwrap();
+        assert_eq!(identity.to_recipient().unwrap().encode().unwrap(), X25519_RECIPIENT);
+        assert_eq!(identity.encode().unwrap(), X25519_IDENTITY);
+        assert_eq!(open(identity, X25519_HEADER, X25519_PAYLOAD).unwrap(), PLAINTEXT);
+        assert!(open(AgeIdentity::generate_x25519(), X25519_HEADER, X25519_PAYLOAD).is_err());
+    }
+
+    #[test]
+    fn scrypt_file() {
+        let passphrase = AgeIdentity::passphrase(b"interop passphrase");
+        assert_eq!(open(passphrase, SCRYPT_HEADER, SCRYPT_PAYLOAD).unwrap(), PLAINTEXT);
+        assert!(open(AgeIdentity::passphrase(b"wrong passphrase"), SCRYPT_HEADER, SCRYPT_PAYLOAD).is_err());
+        let capped = AgeIdentity::Scrypt { passphrase: b"interop passphrase".to_vec(), max_log_n: 9 };
+        assert_eq!(open(capped, SCRYPT_HEADER, SCRYPT_PAYLOAD), Err("scrypt work factor too high"));
+    }
+
+    #[test]
+    fn header_mac_covers_raw_header() {
+        let (header, covered) = read_header(&mut X25519_HEADER.as_bytes()).unwrap();
+        assert_eq!(header.stanzas.len(), 2);
+        assert_eq!(covered, X25519_HEADER.rsplit_once(' ').unwrap().0.as_bytes());
+
+        // The grease stanza is skipped but still covered by the MAC.
+        let identity = || AgeIdentity::parse(X25519_IDENTITY).unwrap();
+        let grease = X25519_HEADER.replace("U8-grease", "U9-grease");
+        assert_eq!(open(identity(), &grease, X25519_PAYLOAD), Err("Header MAC mismatch"));
+        let mac = X25519_HEADER.replace("BfA9hrvx", "CfA9hrvx");
+        assert_eq!(open(identity(), &mac, X25519_PAYLOAD), Err("Header MAC mismatch"));
+    }
+
+    #[test]
+    fn roundtrip() {
+        let identity = AgeIdentity::generate_x25519();
+        let mut sealed = Vec::new();
+        encrypt(&[identity.to_recipient().unwrap()], &mut &PLAINTEXT[..], &mut sealed).unwrap();
+        let mut plaintext = Vec::new();
+        decrypt(&[identity], &mut &sealed[..], &mut plaintext).unwrap();
+        assert_eq!(plaintext, PLAINTEXT);
+    }
+}
@@ -0,0 +1,136 @@
+// ix-encryption/core/age/bech32.rs
+
+//! Bech32 (BIP 173 checksum) as used for age recipients and identities.
+//! age keys exceed the 90-character limit of BIP 173, so no length limit is applied.
+
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+fn polymod(values: &[u8]) -> u32 {
+    let mut checksum: u32 = 1;
+    for &value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x01ff_ffff) << 5) ^ value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+fn hrp_expand(hrp: &[u8]) -> Vec<u8> {
+    let mut expanded: Vec<u8> = hrp.iter().map(|c| c >> 5).collect();
+    expanded.push(0);
+    expanded.extend(hrp.iter().map(|c| c & 31));
+    expanded
+}
+
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>, &'static str> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let mut out = Vec::new();
+    let max = (1u32 << to) - 1;
+    for &value in data {
+        acc = (acc << from) | value as u32;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || (acc << (to - bits)) & max != 0 {
+        return Err("Invalid bech32 padding");
+    }
+    Ok(out)
+}
+
+/// Encode `data` under a lowercase `hrp`. Pass `upper` for identities, which age prints in capitals.
+pub fn encode(hrp: &str, data: &[u8], upper: bool) -> String {
+    let hrp_lower = hrp.to_ascii_lowercase();
+    let mut values = convert_bits(data, 8, 5, true).expect("Padding always succeeds");
+
+    let mut check_input = hrp_expand(hrp_lower.as_bytes());
+    check_input.extend_from_slice(&values);
+    check_input.extend_from_slice(&[0; 6]);
+    let checksum = polymod(&check_input) ^ 1;
+    values.extend((0..6).map(|i| ((checksum >> (5 * (5 - i))) & 31) as u8));
+
+    let mut encoded = hrp_lower;
+    encoded.push('1');
+    encoded.extend(values.iter().map(|&v| CHARSET[v as usize] as char));
+    if upper {
+        encoded.make_ascii_uppercase();
+    }
+    encoded
+}
+
+/// Decode a bech32 string, returning its lowercase hrp and data bytes.
+pub fn decode(encoded: &str) -> Result<(String, Vec<u8>), &'static str> {
+    let has_lower = encoded.bytes().any(|c| c.is_ascii_lowercase());
+    let has_upper = encoded.bytes().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return Err("Mixed-case bech32 string");
+    }
+    let encoded = encoded.to_ascii_lowercase();
+
+    let separator = encoded.rfind('1').ok_or("Missing bech32 separator")?;
+    let (hrp, rest) = (&encoded[..separator], &encoded[separator + 1..]);
+    if hrp.is_empty() || rest.len() < 6 || !hrp.bytes().all(|c| (33..=126).contains(&c)) {
+        return Err("Malformed bech32 string");
+    }
+
+    let values = rest
+        .bytes()
+        .map(|c| CHARSET.iter().position(|&x| x == c).map(|p| p as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or("Invalid bech32 character")?;
+
+    let mut check_input = hrp_expand(hrp.as_bytes());
+    check_input.extend_from_slice(&values);
+    if polymod(&check_input) != 1 {
+        return Err("Invalid bech32 checksum");
+    }
+
+    let data = convert_bits(&values[..values.len() - 6], 5, 8, false)?;
+    Ok((hrp.to_string(), data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bip173_valid() {
+        assert_eq!(decode("a12uel5l").unwrap(), ("a".to_string(), vec![]));
+        assert_eq!(decode("A12UEL5L").unwrap(), ("a".to_string(), vec![]));
+        let (hrp, data) = decode("abcdef1qpzry9x8gf2tvdw0s3jn54khce6mua7lmqqqxw").unwrap();
+        assert_eq!(hrp, "abcdef");
+        assert_eq!(data, convert_bits(&(0..32).collect::<Vec<u8>>(), 5, 8, false).unwrap());
+        assert_eq!(decode("split1checkupstagehandshakeupstreamerranterredcaperred2y9e3w").unwrap().0, "split");
+    }
+
+    #[test]
+    fn bip173_invalid() {
+        assert_eq!(decode("a12uel5L"), Err("Mixed-case bech32 string"));
+        assert_eq!(decode("a12uel5m"), Err("Invalid bech32 checksum"));
+        assert_eq!(decode("pzry9x0s0muk"), Err("Missing bech32 separator"));
+        assert_eq!(decode("1pzry9x0s0muk"), Err("Malformed bech32 string"));
+        assert_eq!(decode("x1b4n0q5v"), Err("Invalid bech32 character"));
+    }
+
+    #[test]
+    fn age_recipient() {
+        // The example recipient from the age README.
+        let recipient = "age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p";
+        let (hrp, data) = decode(recipient).unwrap();
+        assert_eq!((hrp.as_str(), data.len()), ("age", 32));
+        assert_eq!(encode("age", &data, false), recipient);
+        assert_eq!(encode("age", &data, true), recipient.to_ascii_uppercase());
+    }
+}
@@ -0,0 +1,372 @@
+// ix-encryption/core/age/recipients.rs
+
+//! age recipient and identity types: the standard X25519 and scrypt recipients, and an
+//! `ixpq` hybrid recipient combining X25519 with `LatticeKEM`. The hybrid wrap key is derived
+//! from both shared secrets. `ixpq` is only built with the `ml_kem` feature, for a `LatticeKEM`
+//! that is a real FIPS 203 ML-KEM-768; over the placeholder backend it is no stronger than the
+//! X25519 share alone.
+//!
+//! Stanzas:
+//!   -> X25519 <ephemeral share>
+//!   -> scrypt <salt> <log2 N>
+//!   -> ixpq <ephemeral share> <kem ciphertext>
+//! each followed by the file key sealed with ChaCha20-Poly1305 under an all-zero nonce.
+
+use base64::engine::general_purpose::STANDARD_NO_PAD;
+use base64::Engine;
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+use zeroize::Zeroize;
+use crate::core::IXCipherCore;
+use crate::core::age::bech32;
+use crate::core::hybrid::{ChaChaQuantum, NONCE_LEN};
+use crate::core::kdf::hkdf::{hkdf, HkdfHash};
+use crate::core::kdf::password::PasswordKdf;
+#[cfg(feature = "ml_kem")]
+use crate::core::postquantum::hybrid_lattice::HybridLatticeCipher;
+#[cfg(feature = "ml_kem")]
+use crate::core::postquantum::lattice_kem::LatticeKEM;
+
+pub const FILE_KEY_LEN: usize = 16;
+/// Default scrypt work factor used by age.
+pub const DEFAULT_SCRYPT_LOG_N: u8 = 18;
+/// Highest scrypt work factor accepted when decrypting unless the caller raises it.
+pub const MAX_SCRYPT_LOG_N: u8 = 22;
+
+const X25519_LABEL: &[u8] = b"age-encryption.org/v1/X25519";
+const SCRYPT_LABEL: &[u8] = b"age-encryption.org/v1/scrypt";
+#[cfg(feature = "ml_kem")]
+const IXPQ_LABEL: &[u8] = b"IX-age/v1/ixpq";
+#[cfg(feature = "ml_kem")]
+const IXPQ_KEK_PURPOSE: &str = "IX-age/v1/ixpq kem";
+const WRAPPED_KEY_LEN: usize = FILE_KEY_LEN + 16;
+
+const X25519_RECIPIENT_HRP: &str = "age";
+const X25519_IDENTITY_HRP: &str = "age-secret-key-";
+#[cfg(feature = "ml_kem")]
+const IXPQ_RECIPIENT_HRP: &str = "age1ixpq";
+#[cfg(feature = "ml_kem")]
+const IXPQ_IDENTITY_HRP: &str = "age-plugin-ixpq-";
+
+/// One recipient stanza of an age header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AgeStanza {
+    pub tag: String,
+    pub args: Vec<String>,
+    pub body: Vec<u8>,
+}
+
+pub enum AgeRecipient {
+    X25519(PublicKey),
+    Scrypt { passphrase: Vec<u8>, log_n: u8 },
+    #[cfg(feature = "ml_kem")]
+    IxPq { x25519: PublicKey, lattice: Vec<u8> },
+}
+
+pub enum AgeIdentity {
+    X25519(StaticSecret),
+    Scrypt { passphrase: Vec<u8>, max_log_n: u8 },
+    #[cfg(feature = "ml_kem")]
+    IxPq { x25519: StaticSecret, lattice_public: Vec<u8>, lattice_secret: Vec<u8> },
+}
+
+fn seal_file_key(wrap_key: &[u8], file_key: &[u8; FILE_KEY_LEN]) -> Result<Vec<u8>, &'static str> {
+    let mut cipher = ChaChaQuantum::new();
+    cipher.initialize(wrap_key, None);
+    let sealed = cipher.seal_with_nonce(&[0u8; NONCE_LEN], &[], file_key);
+    cipher.wipe();
+    sealed
+}
+
+/// `Ok(None)` when the stanza is not for this key; errors are reserved for malformed stanzas.
+fn open_file_key(wrap_key: &[u8], body: &[u8]) -> Result<Option<[u8; FILE_KEY_LEN]>, &'static str> {
+    if body.len() != WRAPPED_KEY_LEN {
+        return Err("Invalid stanza body length");
+    }
+    let mut cipher = ChaChaQuantum::new();
+    cipher.initialize(wrap_key, None);
+    let opened = cipher.open_with_nonce(&[0u8; NONCE_LEN], &[], body);
+    cipher.wipe();
+
+    Ok(opened.ok().map(|mut key| {
+        let mut file_key = [0u8; FILE_KEY_LEN];
+        file_key.copy_from_slice(&key);
+        key.zeroize();
+        file_key
+    }))
+}
+
+fn decode_arg<const N: usize>(arg: &str) -> Result<[u8; N], &'static str> {
+    let bytes = STANDARD_NO_PAD.decode(arg).map_err(|_| "Invalid base64 in stanza")?;
+    bytes.try_into().map_err(|_| "Invalid stanza argument length")
+}
+
+fn x25519_shared(secret: &[u8; 32], public: &PublicKey) -> Result<[u8; 32], &'static str> {
+    let shared = StaticSecret::from(*secret).diffie_hellman(public);
+    if !shared.was_contributory() {
+        return Err("Low-order X25519 share");
+    }
+    Ok(shared.to_bytes())
+}
+
+impl AgeRecipient {
+    /// Parse `age1...` (X25519) or `age1ixpq1...` (hybrid) recipient strings.
+    pub fn parse(encoded: &str) -> Result<Self, &'static str> {
+        if encoded.bytes().any(|c| c.is_ascii_uppercase()) {
+            return Err("Recipients must be lowercase");
+        }
+        let (hrp, data) = bech32::decode(encoded)?;
+        match hrp.as_str() {
+            X25519_RECIPIENT_HRP => {
+                let key: [u8; 32] = data.try_into().map_err(|_| "Invalid X25519 recipient")?;
+                Ok(AgeRecipient::X25519(PublicKey::from(key)))
+            }
+            #[cfg(feature = "ml_kem")]
+            IXPQ_RECIPIENT_HRP if data.len() > 32 => {
+                let mut x25519 = [0u8; 32];
+                x25519.copy_from_slice(&data[..32]);
+                Ok(AgeRecipient::IxPq { x25519: PublicKey::from(x25519), lattice: data[32..].to_vec() })
+            }
+            _ => Err("Unknown recipient type"),
+        }
+    }
+
+    /// Bech32 form; `None` for passphrase recipients, which have no public encoding.
+    pub fn encode(&self) -> Option<String> {
+        match self {
+            AgeRecipient::X25519(key) => Some(bech32::encode(X25519_RECIPIENT_HRP, key.as_bytes(), false)),
+            #[cfg(feature = "ml_kem")]
+            AgeRecipient::IxPq { x25519, lattice } => {
+                let data = [x25519.as_bytes().as_slice(), lattice].concat();
+                Some(bech32::encode(IXPQ_RECIPIENT_HRP, &data, false))
+            }
+            AgeRecipient::Scrypt { .. } => None,
+        }
+    }
+
+    pub fn wrap(&self, file_key: &[u8; FILE_KEY_LEN]) -> Result<AgeStanza, &'static str> {
+        match self {
+            AgeRecipient::X25519(recipient) => {
+                let ephemeral = EphemeralSecret::random_from_rng(rand::rngs::OsRng);
+                let share = PublicKey::from(&ephemeral);
+                let shared = ephemeral.diffie_hellman(recipient);
+                if !shared.was_contributory() {
+                    return Err("Low-order X25519 recipient");
+                }
+
+                let salt = [share.as_bytes().as_slice(), recipient.as_bytes()].concat();
+                let mut wrap_key = hkdf(HkdfHash::Sha256, Some(&salt), shared.as_bytes(), X25519_LABEL, 32)?;
+                let body = seal_file_key(&wrap_key, file_key);
+                wrap_key.zeroize();
+
+                Ok(AgeStanza {
+                    tag: "X25519".to_string(),
+                    args: vec![STANDARD_NO_PAD.encode(share.as_bytes())],
+                    body: body?,
+                })
+            }
+            AgeRecipient::Scrypt { passphrase, log_n } => {
+                let mut salt = [0u8; 16];
+                getrandom::getrandom(&mut salt).map_err(|_| "Salt generation failed")?;
+                let full_salt = [SCRYPT_LABEL, &salt[..]].concat();
+
+                let kdf = PasswordKdf::Scrypt { log_n: *log_n, r: 8, p: 1 };
+                let mut wrap_key = kdf.derive(passphrase, &full_salt, 32)?;
+                let body = seal_file_key(&wrap_key, file_key);
+                wrap_key.zeroize();
+
+                Ok(AgeStanza {
+                    tag: "scrypt".to_string(),
+                    args: vec![STANDARD_NO_PAD.encode(salt), log_n.to_string()],
+                    body: body?,
+                })
+            }
+            #[cfg(feature = "ml_kem")]
+            AgeRecipient::IxPq { x25519, lattice } => {
+                let ephemeral = EphemeralSecret::random_from_rng(rand::rngs::OsRng);
+                let share = PublicKey::from(&ephemeral);
+                let shared = ephemeral.diffie_hellman(x25519);
+                if !shared.was_contributory() {
+                    return Err("Low-order X25519 recipient");
+                }
+
+                let mut hybrid = HybridLatticeCipher::new();
+                let kem_ciphertext = hybrid.encapsulate_key(lattice);
+                let mut kem_secret = hybrid.export_key(IXPQ_KEK_PURPOSE, 32)?;
+                hybrid.wipe();
+
+                let mut wrap_key = ixpq_wrap_key(shared.as_bytes(), &kem_secret, share.as_bytes(), x25519.as_bytes(), &kem_ciphertext)?;
+                kem_secret.zeroize();
+                let body = seal_file_key(&wrap_key, file_key);
+                wrap_key.zeroize();
+
+                Ok(AgeStanza {
+                    tag: "ixpq".to_string(),
+                    args: vec![STANDARD_NO_PAD.encode(share.as_bytes()), STANDARD_NO_PAD.encode(&kem_ciphertext)],
+                    body: body?,
+                })
+            }
+        }
+    }
+}
+
+/// Both shared secrets feed one HKDF; the transcript (shares and KEM ciphertext) is the salt.
+#[cfg(feature = "ml_kem")]
+fn ixpq_wrap_key(x25519_shared: &[u8], kem_secret: &[u8], share: &[u8], recipient: &[u8], kem_ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let mut ikm = [x25519_shared, kem_secret].concat();
+    let salt = [share, recipient, kem_ciphertext].concat();
+    let wrap_key = hkdf(HkdfHash::Sha256, Some(&salt), &ikm, IXPQ_LABEL, 32);
+    ikm.zeroize();
+    wrap_key
+}
+
+impl AgeIdentity {
+    pub fn generate_x25519() -> Self {
+        AgeIdentity::X25519(StaticSecret::random_from_rng(rand::rngs::OsRng))
+    }
+
+    #[cfg(feature = "ml_kem")]
+    pub fn generate_ixpq() -> Self {
+        let kem = LatticeKEM::keypair();
+        AgeIdentity::IxPq {
+            x25519: StaticSecret::random_from_rng(rand::rngs::OsRng),
+            lattice_public: kem.public_key.clone(),
+            lattice_secret: kem.secret_key.clone(),
+        }
+    }
+
+    pub fn passphrase(passphrase: &[u8]) -> Self {
+        AgeIdentity::Scrypt { passphrase: passphrase.to_vec(), max_log_n: MAX_SCRYPT_LOG_N }
+    }
+
+    /// Parse `AGE-SECRET-KEY-1...` or `AGE-PLUGIN-IXPQ-1...` identity strings.
+    pub fn parse(encoded: &str) -> Result<Self, &'static str> {
+        let (hrp, mut data) = bech32::decode(encoded)?;
+        let identity = match hrp.as_str() {
+            X25519_IDENTITY_HRP if data.len() == 32 => {
+                let mut secret = [0u8; 32];
+                secret.copy_from_slice(&data);
+                let identity = AgeIdentity::X25519(StaticSecret::from(secret));
+                secret.zeroize();
+                Ok(identity)
+            }
+            #[cfg(feature = "ml_kem")]
+            IXPQ_IDENTITY_HRP if data.len() > 36 => {
+                let mut secret = [0u8; 32];
+                secret.copy_from_slice(&data[..32]);
+                let public_len = u32::from_be_bytes([data[32], data[33], data[34], data[35]]) as usize;
+                let rest = &data[36..];
+                let identity = match rest.len() > public_len {
+                    true => Ok(AgeIdentity::IxPq {
+                        x25519: StaticSecret::from(secret),
+                        lattice_public: rest[..public_len].to_vec(),
+                        lattice_secret: rest[public_len..].to_vec(),
+                    }),
+                    false => Err("Invalid ixpq identity"),
+                };
+                secret.zeroize();
+                identity
+            }
+            _ => Err("Unknown identity type"),
+        };
+        data.zeroize();
+        identity
+    }
+
+    /// Bech32 form; `None` for passphrase identities.
+    pub fn encode(&self) -> Option<String> {
+        match self {
+            AgeIdentity::X25519(secret) => Some(bech32::encode(X25519_IDENTITY_HRP, secret.as_bytes(), true)),
+            #[cfg(feature = "ml_kem")]
+            AgeIdentity::IxPq { x25519, lattice_public, lattice_secret } => {
+                let mut data = x25519.to_bytes().to_vec();
+                data.extend_from_slice(&(lattice_public.len() as u32).to_be_bytes());
+                data.extend_from_slice(lattice_public);
+                data.extend_from_slice(lattice_secret);
+                let encoded = bech32::encode(IXPQ_IDENTITY_HRP, &data, true);
+                data.zeroize();
+                Some(encoded)
+            }
+            AgeIdentity::Scrypt { .. } => None,
+        }
+    }
+
+    pub fn to_recipient(&self) -> Option<AgeRecipient> {
+        match self {
+            AgeIdentity::X25519(secret) => Some(AgeRecipient::X25519(PublicKey::from(secret))),
+            #[cfg(feature = "ml_kem")]
+            AgeIdentity::IxPq { x25519, lattice_public, .. } => Some(AgeRecipient::IxPq {
+                x25519: PublicKey::from(x25519),
+                lattice: lattice_public.clone(),
+            }),
+            AgeIdentity::Scrypt { .. } => None,
+        }
+    }
+
+    /// Try to recover the file key from `stanza`. Stanzas of other types yield `Ok(None)`.
+    pub fn unwrap(&self, stanza: &AgeStanza) -> Result<Option<[u8; FILE_KEY_LEN]>, &'static str> {
+        match (self, stanza.tag.as_str()) {
+            (AgeIdentity::X25519(secret), "X25519") => {
+                let [share_arg] = stanza.args.as_slice() else {
+                    return Err("Malformed X25519 stanza");
+                };
+                let share = PublicKey::from(decode_arg::<32>(share_arg)?);
+                let recipient = PublicKey::from(secret);
+                let mut shared = x25519_shared(&secret.to_bytes(), &share)?;
+
+                let salt = [share.as_bytes().as_slice(), recipient.as_bytes()].concat();
+                let mut wrap_key = hkdf(HkdfHash::Sha256, Some(&salt), &shared, X25519_LABEL, 32)?;
+                shared.zeroize();
+                let file_key = open_file_key(&wrap_key, &stanza.body);
+                wrap_key.zeroize();
+                file_key
+            }
+            (AgeIdentity::Scrypt { passphrase, max_log_n }, "scrypt") => {
+                let [salt_arg, log_n_arg] = stanza.args.as_slice() else {
+                    return Err("Malformed scrypt stanza");
+                };
+                let salt = decode_arg::<16>(salt_arg)?;
+                if log_n_arg.is_empty() || log_n_arg.starts_with('0') || !log_n_arg.bytes().all(|c| c.is_ascii_digit()) {
+                    return Err("Malformed scrypt work factor");
+                }
+                let log_n: u8 = log_n_arg.parse().map_err(|_| "Malformed scrypt work factor")?;
+                if log_n > *max_log_n {
+                    return Err("scrypt work factor too high");
+                }
+
+                let full_salt = [SCRYPT_LABEL, &salt[..]].concat();
+                let kdf = PasswordKdf::Scrypt { log_n, r: 8, p: 1 };
+                let mut wrap_key = kdf.derive(passphrase, &full_salt, 32)?;
+                let file_key = open_file_key(&wrap_key, &stanza.body);
+                wrap_key.zeroize();
+                file_key
+            }
+            #[cfg(feature = "ml_kem")]
+            (AgeIdentity::IxPq { x25519, lattice_public, lattice_secret }, "ixpq") => {
+                let [share_arg, kem_arg] = stanza.args.as_slice() else {
+                    return Err("Malformed ixpq stanza");
+                };
+                let share = PublicKey::from(decode_arg::<32>(share_arg)?);
+                let kem_ciphertext = STANDARD_NO_PAD.decode(kem_arg).map_err(|_| "Invalid base64 in stanza")?;
+                let recipient = PublicKey::from(x25519);
+                let mut shared = x25519_shared(&x25519.to_bytes(), &share)?;
+
+                let mut hybrid = HybridLatticeCipher::with_keypair(LatticeKEM {
+                    public_key: lattice_public.clone(),
+                    secret_key: lattice_secret.clone(),
+                });
+                hybrid.decapsulate_key(&kem_ciphertext);
+                let mut kem_secret = hybrid.export_key(IXPQ_KEK_PURPOSE, 32)?;
+                hybrid.wipe();
+
+                let wrap_key = ixpq_wrap_key(&shared, &kem_secret, share.as_bytes(), recipient.as_bytes(), &kem_ciphertext);
+                shared.zeroize();
+                kem_secret.zeroize();
+                let mut wrap_key = wrap_key?;
+                let file_key = open_file_key(&wrap_key, &stanza.body);
+                wrap_key.zeroize();
+                file_key
+            }
+            _ => Ok(None),
+        }
+    }
+}
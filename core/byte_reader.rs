@@ -0,0 +1,85 @@
+// ix-encryption/core/byte_reader.rs
+
+//! Bounds-checked cursor shared by the length-prefixed binary formats (keystore, envelope,
+//! OpenPGP packets, CBOR).
+
+/// Reads fields front to back. A read past the end fails with the format's own truncation
+/// message and leaves the position unchanged.
+pub struct ByteReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    truncated: &'static str,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(data: &'a [u8], truncated: &'static str) -> Self {
+        Self { data, pos: 0, truncated }
+    }
+
+    /// Bytes consumed so far.
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// The unread bytes, without consuming them.
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.data[self.pos..]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos == self.data.len()
+    }
+
+    pub fn take(&mut self, len: usize) -> Result<&'a [u8], &'static str> {
+        let end = self.pos.checked_add(len).filter(|&end| end <= self.data.len()).ok_or(self.truncated)?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    pub fn take_array<const N: usize>(&mut self) -> Result<[u8; N], &'static str> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    pub fn take_u8(&mut self) -> Result<u8, &'static str> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn take_u16(&mut self) -> Result<u16, &'static str> {
+        self.take_array().map(u16::from_be_bytes)
+    }
+
+    pub fn take_u32(&mut self) -> Result<u32, &'static str> {
+        self.take_array().map(u32::from_be_bytes)
+    }
+
+    pub fn take_u64(&mut self) -> Result<u64, &'static str> {
+        self.take_array().map(u64::from_be_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_fields_in_order() {
+        let mut reader = ByteReader::new(&[1, 0, 2, 0, 0, 0, 3, 4, 5], "Truncated");
+        assert_eq!(reader.take_u8(), Ok(1));
+        assert_eq!(reader.take_u16(), Ok(2));
+        assert_eq!(reader.take_u32(), Ok(3));
+        assert_eq!(reader.position(), 7);
+        assert_eq!(reader.remaining(), &[4, 5]);
+        assert_eq!(reader.take(2), Ok(&[4, 5][..]));
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn truncated_reads_fail_without_consuming() {
+        let mut reader = ByteReader::new(&[1, 2, 3], "Truncated test");
+        assert_eq!(reader.take_u32(), Err("Truncated test"));
+        assert_eq!(reader.take(usize::MAX), Err("Truncated test"));
+        assert_eq!(reader.position(), 0);
+        assert_eq!(reader.take_array::<3>(), Ok([1, 2, 3]));
+    }
+}
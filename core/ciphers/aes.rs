@@ -0,0 +1,76 @@
+// ix-encryption/core/ciphers/aes.rs
+
+//! AES-128/192/256 exposed through the `BlockCipher` trait.
+//! Backed by the `aes` crate, which uses AES-NI/ARMv8 instructions when available
+//! and a bitsliced constant-time software fallback otherwise.
+
+use aes::cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit};
+use aes::{Aes128, Aes192, Aes256};
+use crate::core::blockcipher::BlockCipher;
+use crate::core::ciphers::keyed::KeyedBlockCipher;
+
+pub const AES_BLOCK_SIZE: usize = 16;
+
+enum AesKey {
+    Aes128(Aes128),
+    Aes192(Aes192),
+    Aes256(Aes256),
+}
+
+pub struct AesCipher {
+    key: AesKey,
+}
+
+impl AesCipher {
+    pub fn new(key: &[u8]) -> Result<Self, &'static str> {
+        let key = match key.len() {
+            16 => AesKey::Aes128(Aes128::new(GenericArray::from_slice(key))),
+            24 => AesKey::Aes192(Aes192::new(GenericArray::from_slice(key))),
+            32 => AesKey::Aes256(Aes256::new(GenericArray::from_slice(key))),
+            _ => return Err("AES key must be 16, 24 or 32 bytes"),
+        };
+        Ok(AesCipher { key })
+    }
+}
+
+impl BlockCipher for AesCipher {
+    fn block_size(&self) -> usize {
+        AES_BLOCK_SIZE
+    }
+
+    fn encrypt_block(&self, block: &[u8]) -> Vec<u8> {
+        assert_eq!(block.len(), AES_BLOCK_SIZE, "AES block must be 16 bytes");
+        let mut buf = GenericArray::clone_from_slice(block);
+        match &self.key {
+            AesKey::Aes128(c) => c.encrypt_block(&mut buf),
+            AesKey::Aes192(c) => c.encrypt_block(&mut buf),
+            AesKey::Aes256(c) => c.encrypt_block(&mut buf),
+        }
+        buf.to_vec()
+    }
+
+    fn decrypt_block(&self, block: &[u8]) -> Vec<u8> {
+        assert_eq!(block.len(), AES_BLOCK_SIZE, "AES block must be 16 bytes");
+        let mut buf = GenericArray::clone_from_slice(block);
+        match &self.key {
+            AesKey::Aes128(c) => c.decrypt_block(&mut buf),
+            AesKey::Aes192(c) => c.decrypt_block(&mut buf),
+            AesKey::Aes256(c) => c.decrypt_block(&mut buf),
+        }
+        buf.to_vec()
+    }
+}
+
+impl KeyedBlockCipher for AesCipher {
+    fn from_key(key: &[u8]) -> Result<Self, &'static str> {
+        AesCipher::new(key)
+    }
+
+    fn key_len(&self) -> usize {
+        match self.key {
+            AesKey::Aes128(_) => 16,
+            AesKey::Aes192(_) => 24,
+            AesKey::Aes256(_) => 32,
+        }
+    }
+}
@@ -0,0 +1,98 @@
+// ix-encryption/core/ciphers/camellia.rs
+
+//! Camellia block cipher (128-bit block, 128/192/256-bit keys) exposed through `BlockCipher`.
+//! Backed by the `camellia` crate. Its S-boxes are table lookups, so this implementation
+//! is not constant-time on CPUs with data-dependent cache timing.
+
+use camellia::cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit};
+use camellia::{Camellia128, Camellia192, Camellia256};
+use crate::core::blockcipher::BlockCipher;
+use crate::core::ciphers::keyed::KeyedBlockCipher;
+
+pub const CAMELLIA_BLOCK_SIZE: usize = 16;
+
+enum CamelliaKey {
+    Camellia128(Camellia128),
+    Camellia192(Camellia192),
+    Camellia256(Camellia256),
+}
+
+pub struct CamelliaCipher {
+    key: CamelliaKey,
+}
+
+impl CamelliaCipher {
+    pub fn new(key: &[u8]) -> Result<Self, &'static str> {
+        let key = match key.len() {
+            16 => CamelliaKey::Camellia128(Camellia128::new(GenericArray::from_slice(key))),
+            24 => CamelliaKey::Camellia192(Camellia192::new(GenericArray::from_slice(key))),
+            32 => CamelliaKey::Camellia256(Camellia256::new(GenericArray::from_slice(key))),
+            _ => return Err("Camellia key must be 16, 24 or 32 bytes"),
+        };
+        Ok(CamelliaCipher { key })
+    }
+}
+
+impl BlockCipher for CamelliaCipher {
+    fn block_size(&self) -> usize {
+        CAMELLIA_BLOCK_SIZE
+    }
+
+    fn encrypt_block(&self, block: &[u8]) -> Vec<u8> {
+        assert_eq!(block.len(), CAMELLIA_BLOCK_SIZE, "Camellia block must be 16 bytes");
+        let mut buf = GenericArray::clone_from_slice(block);
+        match &self.key {
+            CamelliaKey::Camellia128(c) => c.encrypt_block(&mut buf),
+            CamelliaKey::Camellia192(c) => c.encrypt_block(&mut buf),
+            CamelliaKey::Camellia256(c) => c.encrypt_block(&mut buf),
+        }
+        buf.to_vec()
+    }
+
+    fn decrypt_block(&self, block: &[u8]) -> Vec<u8> {
+        assert_eq!(block.len(), CAMELLIA_BLOCK_SIZE, "Camellia block must be 16 bytes");
+        let mut buf = GenericArray::clone_from_slice(block);
+        match &self.key {
+            CamelliaKey::Camellia128(c) => c.decrypt_block(&mut buf),
+            CamelliaKey::Camellia192(c) => c.decrypt_block(&mut buf),
+            CamelliaKey::Camellia256(c) => c.decrypt_block(&mut buf),
+        }
+        buf.to_vec()
+    }
+}
+
+impl KeyedBlockCipher for CamelliaCipher {
+    fn from_key(key: &[u8]) -> Result<Self, &'static str> {
+        CamelliaCipher::new(key)
+    }
+
+    fn key_len(&self) -> usize {
+        match self.key {
+            CamelliaKey::Camellia128(_) => 16,
+            CamelliaKey::Camellia192(_) => 24,
+            CamelliaKey::Camellia256(_) => 32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_util::h;
+
+    /// RFC 3713 Appendix A test data for 128-, 192- and 256-bit keys.
+    #[test]
+    fn rfc3713_vectors() {
+        let plaintext = h("0123456789abcdeffedcba9876543210");
+        let cases = [
+            ("0123456789abcdeffedcba9876543210", "67673138549669730857065648eabe43"),
+            ("0123456789abcdeffedcba98765432100011223344556677", "b4993401b3e996f84ee5cee7d79b09b9"),
+            ("0123456789abcdeffedcba987654321000112233445566778899aabbccddeeff", "9acc237dff16d76c20ef7c919e3a7509"),
+        ];
+        for (key, expected) in cases {
+            let cipher = CamelliaCipher::new(&h(key)).unwrap();
+            assert_eq!(cipher.encrypt_block(&plaintext), h(expected));
+            assert_eq!(cipher.decrypt_block(&h(expected)), plaintext);
+        }
+    }
+}
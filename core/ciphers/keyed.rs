@@ -0,0 +1,14 @@
+// ix-encryption/core/ciphers/keyed.rs
+
+//! Extension of `BlockCipher` for ciphers that can be instantiated from raw key bytes.
+//! Modes that derive fresh subkeys (GCM-SIV, SIV, cascades) rely on this to rekey internally.
+
+use crate::core::blockcipher::BlockCipher;
+
+pub trait KeyedBlockCipher: BlockCipher + Sized {
+    /// Build a keyed cipher instance. Fails on unsupported key lengths.
+    fn from_key(key: &[u8]) -> Result<Self, &'static str>;
+
+    /// Length in bytes of the key this instance was created with.
+    fn key_len(&self) -> usize;
+}
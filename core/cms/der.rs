@@ -0,0 +1,193 @@
+// ix-encryption/core/cms/der.rs
+
+//! Minimal ASN.1 DER encoder and reader for the CMS and key encoding structures: definite
+//! lengths only, single-byte tags, and SET OF elements sorted by encoding as DER requires.
+
+pub const INTEGER: u8 = 0x02;
+pub const BIT_STRING: u8 = 0x03;
+pub const OCTET_STRING: u8 = 0x04;
+pub const NULL: u8 = 0x05;
+pub const OID: u8 = 0x06;
+pub const SEQUENCE: u8 = 0x30;
+pub const SET: u8 = 0x31;
+
+/// Context-specific tag `[n]`, constructed for EXPLICIT tagging or constructed IMPLICIT types.
+pub const fn context(n: u8, constructed: bool) -> u8 {
+    0x80 | (if constructed { 0x20 } else { 0 }) | n
+}
+
+pub fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    match content.len() {
+        len @ 0..=0x7f => out.push(len as u8),
+        len => {
+            let bytes = len.to_be_bytes();
+            let skip = bytes.iter().take_while(|&&b| b == 0).count();
+            out.push(0x80 | (bytes.len() - skip) as u8);
+            out.extend_from_slice(&bytes[skip..]);
+        }
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+pub fn sequence(items: &[Vec<u8>]) -> Vec<u8> {
+    tlv(SEQUENCE, &items.concat())
+}
+
+pub fn set_of(items: &[Vec<u8>]) -> Vec<u8> {
+    let mut sorted = items.to_vec();
+    sorted.sort();
+    tlv(SET, &sorted.concat())
+}
+
+pub fn octet_string(data: &[u8]) -> Vec<u8> {
+    tlv(OCTET_STRING, data)
+}
+
+/// BIT STRING with no unused bits, as used for keys and signatures.
+pub fn bit_string(data: &[u8]) -> Vec<u8> {
+    tlv(BIT_STRING, &[&[0][..], data].concat())
+}
+
+pub fn integer(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().take_while(|&&b| b == 0).count().min(7);
+    let mut content = bytes[skip..].to_vec();
+    if content[0] & 0x80 != 0 {
+        content.insert(0, 0);
+    }
+    tlv(INTEGER, &content)
+}
+
+/// Content octets of an OBJECT IDENTIFIER, for encoding or comparing against a read value.
+pub fn oid_content(arcs: &[u64]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut push_base128 = |mut value: u64| {
+        let mut groups = vec![(value & 0x7f) as u8];
+        value >>= 7;
+        while value > 0 {
+            groups.push(0x80 | (value & 0x7f) as u8);
+            value >>= 7;
+        }
+        out.extend(groups.iter().rev());
+    };
+    push_base128(arcs[0] * 40 + arcs[1]);
+    arcs[2..].iter().for_each(|&arc| push_base128(arc));
+    out
+}
+
+pub fn oid(arcs: &[u64]) -> Vec<u8> {
+    tlv(OID, &oid_content(arcs))
+}
+
+/// AlgorithmIdentifier with absent parameters, or with the given encoded parameters.
+pub fn algorithm(arcs: &[u64], params: Option<Vec<u8>>) -> Vec<u8> {
+    let mut items = vec![oid(arcs)];
+    items.extend(params);
+    sequence(&items)
+}
+
+/// Sequential reader over the TLVs in one piece of DER content.
+pub struct DerReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> DerReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pos == self.data.len()
+    }
+
+    pub fn peek_tag(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    /// Read the next TLV, returning its tag, content and complete encoding.
+    pub fn read_any(&mut self) -> Result<(u8, &'a [u8], &'a [u8]), &'static str> {
+        let start = self.pos;
+        let tag = *self.data.get(start).ok_or("Truncated DER")?;
+        if tag & 0x1f == 0x1f {
+            return Err("Multi-byte DER tags unsupported");
+        }
+        let first = *self.data.get(start + 1).ok_or("Truncated DER")?;
+        let (len, header) = match first {
+            0..=0x7f => (first as usize, 2),
+            0x81..=0x84 => {
+                let count = (first & 0x7f) as usize;
+                let bytes = self.data.get(start + 2..start + 2 + count).ok_or("Truncated DER")?;
+                let len = bytes.iter().fold(0usize, |acc, &b| (acc << 8) | b as usize);
+                if bytes[0] == 0 || len < 0x80 {
+                    return Err("Non-minimal DER length");
+                }
+                (len, 2 + count)
+            }
+            _ => return Err("Unsupported DER length"),
+        };
+        let end = (start + header).checked_add(len).filter(|&end| end <= self.data.len()).ok_or("Truncated DER")?;
+        self.pos = end;
+        Ok((tag, &self.data[start + header..end], &self.data[start..end]))
+    }
+
+    /// Read a TLV that must carry `tag`, returning its content.
+    pub fn read(&mut self, tag: u8) -> Result<&'a [u8], &'static str> {
+        match self.read_any()? {
+            (found, content, _) if found == tag => Ok(content),
+            _ => Err("Unexpected DER tag"),
+        }
+    }
+
+    pub fn read_optional(&mut self, tag: u8) -> Result<Option<&'a [u8]>, &'static str> {
+        match self.peek_tag() == Some(tag) {
+            true => self.read(tag).map(Some),
+            false => Ok(None),
+        }
+    }
+
+    /// Read a BIT STRING with no unused bits, returning its bytes.
+    pub fn read_bit_string(&mut self) -> Result<&'a [u8], &'static str> {
+        match self.read(BIT_STRING)?.split_first() {
+            Some((0, bits)) => Ok(bits),
+            _ => Err("Unsupported DER bit string"),
+        }
+    }
+
+    pub fn read_integer(&mut self) -> Result<u64, &'static str> {
+        let content = self.read(INTEGER)?;
+        if content.is_empty() || content.len() > 9 || content[0] & 0x80 != 0 {
+            return Err("Unsupported DER integer");
+        }
+        // A leading zero is only allowed to keep the next byte's top bit from reading as a sign
+        if content.len() > 1 && content[0] == 0 && content[1] & 0x80 == 0 {
+            return Err("Non-minimal DER integer");
+        }
+        if content.len() == 9 && content[0] != 0 {
+            return Err("Unsupported DER integer");
+        }
+        Ok(content.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64))
+    }
+
+    /// Read an AlgorithmIdentifier, returning the OID content and the encoded parameters.
+    pub fn read_algorithm(&mut self) -> Result<(&'a [u8], Option<&'a [u8]>), &'static str> {
+        let mut inner = DerReader::new(self.read(SEQUENCE)?);
+        let oid = inner.read(OID)?;
+        let params = match inner.is_empty() {
+            true => None,
+            false => Some(inner.read_any()?.2),
+        };
+        inner.finish()?;
+        Ok((oid, params))
+    }
+
+    /// Require that every byte has been consumed.
+    pub fn finish(&self) -> Result<(), &'static str> {
+        match self.is_empty() {
+            true => Ok(()),
+            false => Err("Trailing DER data"),
+        }
+    }
+}
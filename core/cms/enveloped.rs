@@ -0,0 +1,387 @@
+// ix-encryption/core/cms/enveloped.rs
+
+//! CMS EnvelopedData (RFC 5652) with AES-256-CBC and AuthEnvelopedData (RFC 5083) with
+//! AES-256-GCM, wrapped in a ContentInfo and DER encoded. Recipients:
+//! - KeyTransRecipientInfo: RSAES-OAEP with SHA-256 and MGF1-SHA-256 (RFC 8017 / RFC 4055)
+//! - KEKRecipientInfo: AES-256 key wrap under a pre-shared KEK (RFC 3394 / RFC 3565)
+//! - KEMRecipientInfo (RFC 9629): `LatticeKEM` under an experimental private-arc identifier,
+//!   HKDF-SHA256 and AES-256 key wrap
+//!
+//! All recipients are identified by subject key identifier.
+
+use rand::rngs::OsRng;
+use rsa::{Oaep, RsaPrivateKey, RsaPublicKey};
+use sha2::Sha256;
+use zeroize::Zeroize;
+use crate::core::ciphers::aes::AesCipher;
+use crate::core::cms::der::{self, context, DerReader, OCTET_STRING, OID, SEQUENCE, SET};
+use crate::core::kdf::hkdf::{hkdf, HkdfHash};
+use crate::core::key_wrap::KeyWrap;
+use crate::core::mode_cbc::CBCMode;
+use crate::core::mode_gcm::{GCMMode, GCM_TAG_LEN};
+use crate::core::postquantum::lattice_kem::LatticeKEM;
+
+// Content types
+const ID_DATA: &[u64] = &[1, 2, 840, 113549, 1, 7, 1];
+const ID_ENVELOPED_DATA: &[u64] = &[1, 2, 840, 113549, 1, 7, 3];
+const ID_AUTH_ENVELOPED_DATA: &[u64] = &[1, 2, 840, 113549, 1, 9, 16, 1, 23];
+
+// Algorithms
+const ID_AES256_CBC: &[u64] = &[2, 16, 840, 1, 101, 3, 4, 1, 42];
+const ID_AES256_WRAP: &[u64] = &[2, 16, 840, 1, 101, 3, 4, 1, 45];
+const ID_AES256_GCM: &[u64] = &[2, 16, 840, 1, 101, 3, 4, 1, 46];
+const ID_RSAES_OAEP: &[u64] = &[1, 2, 840, 113549, 1, 1, 7];
+const ID_MGF1: &[u64] = &[1, 2, 840, 113549, 1, 1, 8];
+const ID_SHA256: &[u64] = &[2, 16, 840, 1, 101, 3, 4, 2, 1];
+const ID_ORI_KEM: &[u64] = &[1, 2, 840, 113549, 1, 9, 16, 13, 3];
+const ID_ALG_HKDF_SHA256: &[u64] = &[1, 2, 840, 113549, 1, 9, 16, 3, 28];
+// `LatticeKEM` is not FIPS 203 ML-KEM, so it must not claim id-alg-ml-kem-768; it sits under
+// a private arc (the RFC 5612 example enterprise number) until a real ML-KEM backend lands
+const ID_IX_LATTICE_KEM: &[u64] = &[1, 3, 6, 1, 4, 1, 32473, 1, 1];
+
+const CEK_LEN: usize = 32;
+const KEK_LEN: u64 = 32;
+const GCM_NONCE_LEN: usize = 12;
+
+// RecipientInfo CHOICE tags and the SubjectKeyIdentifier rid
+const TAG_KEKRI: u8 = context(2, true);
+const TAG_ORI: u8 = context(4, true);
+const TAG_SKI: u8 = context(0, false);
+
+/// Recipient description used when encrypting.
+pub enum CmsRecipient {
+    KeyTrans { key_id: Vec<u8>, public_key: RsaPublicKey },
+    Kek { key_id: Vec<u8>, kek: [u8; 32] },
+    Kem { key_id: Vec<u8>, public_key: Vec<u8> },
+}
+
+/// Private key material used when decrypting.
+pub enum CmsIdentity {
+    KeyTrans { key_id: Vec<u8>, private_key: Box<RsaPrivateKey> },
+    Kek { key_id: Vec<u8>, kek: [u8; 32] },
+    Kem { key_id: Vec<u8>, keypair: LatticeKEM },
+}
+
+impl CmsIdentity {
+    fn key_id(&self) -> &[u8] {
+        match self {
+            CmsIdentity::KeyTrans { key_id, .. } | CmsIdentity::Kek { key_id, .. } | CmsIdentity::Kem { key_id, .. } => key_id,
+        }
+    }
+}
+
+fn oaep_algorithm() -> Vec<u8> {
+    let sha256 = der::algorithm(ID_SHA256, None);
+    let params = der::sequence(&[
+        der::tlv(context(0, true), &sha256),
+        der::tlv(context(1, true), &der::algorithm(ID_MGF1, Some(sha256.clone()))),
+    ]);
+    der::algorithm(ID_RSAES_OAEP, Some(params))
+}
+
+/// Accept only the OAEP parameters this module emits (absent or NULL SHA-256 parameters).
+fn check_oaep_params(params: Option<&[u8]>) -> Result<(), &'static str> {
+    let is_sha256 = |alg: &[u8]| -> Result<(), &'static str> {
+        let (oid, params) = DerReader::new(alg).read_algorithm()?;
+        match oid == der::oid_content(ID_SHA256) && params.is_none_or(|p| p == [der::NULL, 0]) {
+            true => Ok(()),
+            false => Err("Unsupported RSAES-OAEP hash"),
+        }
+    };
+    let mut reader = DerReader::new(params.ok_or("Missing RSAES-OAEP parameters")?);
+    let mut inner = DerReader::new(reader.read(SEQUENCE)?);
+    is_sha256(inner.read(context(0, true))?)?;
+    let (mgf, mgf_hash) = DerReader::new(inner.read(context(1, true))?).read_algorithm()?;
+    if mgf != der::oid_content(ID_MGF1) {
+        return Err("Unsupported RSAES-OAEP mask generation");
+    }
+    is_sha256(mgf_hash.ok_or("Missing MGF1 hash")?)?;
+    if !inner.is_empty() {
+        return Err("Unsupported RSAES-OAEP label");
+    }
+    reader.finish()
+}
+
+/// CMSORIforKEMOtherInfo, the HKDF info input for KEMRecipientInfo (RFC 9629 §5).
+fn kem_other_info() -> Vec<u8> {
+    der::sequence(&[der::algorithm(ID_AES256_WRAP, None), der::integer(KEK_LEN)])
+}
+
+fn kem_kek(shared_secret: &[u8]) -> Result<Vec<u8>, &'static str> {
+    hkdf(HkdfHash::Sha256, None, shared_secret, &kem_other_info(), KEK_LEN as usize)
+}
+
+fn wrap(kek: &[u8], cek: &[u8]) -> Result<Vec<u8>, &'static str> {
+    KeyWrap::new(&AesCipher::new(kek)?).wrap(cek)
+}
+
+fn unwrap(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>, &'static str> {
+    KeyWrap::new(&AesCipher::new(kek)?).unwrap(wrapped)
+}
+
+fn recipient_info(recipient: &CmsRecipient, cek: &[u8]) -> Result<Vec<u8>, &'static str> {
+    match recipient {
+        CmsRecipient::KeyTrans { key_id, public_key } => {
+            let encrypted_key = public_key.encrypt(&mut OsRng, Oaep::new::<Sha256>(), cek).map_err(|_| "RSA encryption failed")?;
+            Ok(der::sequence(&[
+                der::integer(2),
+                der::tlv(TAG_SKI, key_id),
+                oaep_algorithm(),
+                der::octet_string(&encrypted_key),
+            ]))
+        }
+        CmsRecipient::Kek { key_id, kek } => Ok(der::tlv(
+            TAG_KEKRI,
+            &[
+                der::integer(4),
+                der::sequence(&[der::octet_string(key_id)]),
+                der::algorithm(ID_AES256_WRAP, None),
+                der::octet_string(&wrap(kek, cek)?),
+            ]
+            .concat(),
+        )),
+        CmsRecipient::Kem { key_id, public_key } => {
+            let (kem_ciphertext, mut shared_secret) = LatticeKEM::keypair().encapsulate(public_key);
+            let kek = kem_kek(&shared_secret);
+            shared_secret.zeroize();
+            let mut kek = kek?;
+            let wrapped = wrap(&kek, cek);
+            kek.zeroize();
+
+            let kem_recipient_info = der::sequence(&[
+                der::integer(0),
+                der::tlv(TAG_SKI, key_id),
+                der::algorithm(ID_IX_LATTICE_KEM, None),
+                der::octet_string(&kem_ciphertext),
+                der::algorithm(ID_ALG_HKDF_SHA256, None),
+                der::integer(KEK_LEN),
+                der::algorithm(ID_AES256_WRAP, None),
+                der::octet_string(&wrapped?),
+            ]);
+            Ok(der::tlv(TAG_ORI, &[der::oid(ID_ORI_KEM), kem_recipient_info].concat()))
+        }
+    }
+}
+
+/// Try to recover the CEK from one RecipientInfo; `Ok(None)` if it is not addressed to us.
+fn open_recipient_info(identity: &CmsIdentity, tag: u8, content: &[u8]) -> Result<Option<Vec<u8>>, &'static str> {
+    let mut reader = DerReader::new(content);
+    match (tag, identity) {
+        (SEQUENCE, CmsIdentity::KeyTrans { private_key, .. }) => {
+            reader.read_integer()?;
+            if reader.read_optional(TAG_SKI)? != Some(identity.key_id()) {
+                return Ok(None);
+            }
+            let (alg, params) = reader.read_algorithm()?;
+            if alg != der::oid_content(ID_RSAES_OAEP) {
+                return Err("Unsupported key transport algorithm");
+            }
+            check_oaep_params(params)?;
+            let encrypted_key = reader.read(OCTET_STRING)?;
+            reader.finish()?;
+            private_key.decrypt(Oaep::new::<Sha256>(), encrypted_key).map(Some).map_err(|_| "RSA decryption failed")
+        }
+        (TAG_KEKRI, CmsIdentity::Kek { kek, .. }) => {
+            if reader.read_integer()? != 4 {
+                return Err("Unsupported KEKRecipientInfo version");
+            }
+            let key_id = DerReader::new(reader.read(SEQUENCE)?).read(OCTET_STRING)?;
+            if key_id != identity.key_id() {
+                return Ok(None);
+            }
+            let (alg, _) = reader.read_algorithm()?;
+            if alg != der::oid_content(ID_AES256_WRAP) {
+                return Err("Unsupported key wrap algorithm");
+            }
+            let wrapped = reader.read(OCTET_STRING)?;
+            reader.finish()?;
+            unwrap(kek, wrapped).map(Some)
+        }
+        (TAG_ORI, CmsIdentity::Kem { keypair, .. }) => {
+            if reader.read(OID)? != der::oid_content(ID_ORI_KEM) {
+                return Ok(None);
+            }
+            let mut kemri = DerReader::new(reader.read(SEQUENCE)?);
+            reader.finish()?;
+            if kemri.read_integer()? != 0 {
+                return Err("Unsupported KEMRecipientInfo version");
+            }
+            if kemri.read_optional(TAG_SKI)? != Some(identity.key_id()) {
+                return Ok(None);
+            }
+            if kemri.read_algorithm()?.0 != der::oid_content(ID_IX_LATTICE_KEM) {
+                return Err("Unsupported KEM algorithm");
+            }
+            let kem_ciphertext = kemri.read(OCTET_STRING)?;
+            if kemri.read_algorithm()?.0 != der::oid_content(ID_ALG_HKDF_SHA256) {
+                return Err("Unsupported KEM key derivation");
+            }
+            if kemri.read_integer()? != KEK_LEN || kemri.peek_tag() == Some(context(0, true)) {
+                return Err("Unsupported KEMRecipientInfo parameters");
+            }
+            if kemri.read_algorithm()?.0 != der::oid_content(ID_AES256_WRAP) {
+                return Err("Unsupported key wrap algorithm");
+            }
+            let wrapped = kemri.read(OCTET_STRING)?;
+            kemri.finish()?;
+
+            let mut shared_secret = keypair.decapsulate(kem_ciphertext);
+            let kek = kem_kek(&shared_secret);
+            shared_secret.zeroize();
+            let mut kek = kek?;
+            let cek = unwrap(&kek, wrapped);
+            kek.zeroize();
+            cek.map(Some)
+        }
+        _ => Ok(None),
+    }
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>, &'static str> {
+    let mut bytes = vec![0u8; len];
+    getrandom::getrandom(&mut bytes).map_err(|_| "Random generation failed")?;
+    Ok(bytes)
+}
+
+fn recipient_infos(recipients: &[CmsRecipient], cek: &[u8]) -> Result<(Vec<u8>, bool), &'static str> {
+    if recipients.is_empty() {
+        return Err("At least one recipient required");
+    }
+    let infos = recipients.iter().map(|recipient| recipient_info(recipient, cek)).collect::<Result<Vec<_>, _>>()?;
+    let has_ori = recipients.iter().any(|recipient| matches!(recipient, CmsRecipient::Kem { .. }));
+    Ok((der::set_of(&infos), has_ori))
+}
+
+fn content_info(content_type: &[u64], content: Vec<u8>) -> Vec<u8> {
+    der::sequence(&[der::oid(content_type), der::tlv(context(0, true), &content)])
+}
+
+/// Encrypt `plaintext` as a DER ContentInfo holding EnvelopedData (AES-256-CBC).
+pub fn encrypt_enveloped(recipients: &[CmsRecipient], plaintext: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let mut cek = random_bytes(CEK_LEN)?;
+    let iv = random_bytes(16)?;
+    let infos = recipient_infos(recipients, &cek);
+    let ciphertext = AesCipher::new(&cek).map(|aes| CBCMode::new(&aes, iv.clone()).encrypt(plaintext));
+    cek.zeroize();
+    let ((infos, has_ori), ciphertext) = (infos?, ciphertext?);
+
+    let enveloped = der::sequence(&[
+        // KTRI/KEKRI with subject key identifiers need v2; any ori needs v3 (RFC 5652 §6.1)
+        der::integer(if has_ori { 3 } else { 2 }),
+        infos,
+        der::sequence(&[
+            der::oid(ID_DATA),
+            der::algorithm(ID_AES256_CBC, Some(der::octet_string(&iv))),
+            der::tlv(context(0, false), &ciphertext),
+        ]),
+    ]);
+    Ok(content_info(ID_ENVELOPED_DATA, enveloped))
+}
+
+/// Encrypt `plaintext` as a DER ContentInfo holding AuthEnvelopedData (AES-256-GCM).
+pub fn encrypt_auth_enveloped(recipients: &[CmsRecipient], plaintext: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let mut cek = random_bytes(CEK_LEN)?;
+    let nonce = random_bytes(GCM_NONCE_LEN)?;
+    let infos = recipient_infos(recipients, &cek);
+    let sealed = AesCipher::new(&cek).map(|aes| GCMMode::new(&aes, nonce.clone(), Vec::new()).encrypt_and_tag(plaintext));
+    cek.zeroize();
+    let ((infos, _), (ciphertext, tag)) = (infos?, sealed?);
+
+    let gcm_params = der::sequence(&[der::octet_string(&nonce), der::integer(GCM_TAG_LEN as u64)]);
+    let auth_enveloped = der::sequence(&[
+        der::integer(0),
+        infos,
+        der::sequence(&[
+            der::oid(ID_DATA),
+            der::algorithm(ID_AES256_GCM, Some(gcm_params)),
+            der::tlv(context(0, false), &ciphertext),
+        ]),
+        der::octet_string(&tag),
+    ]);
+    Ok(content_info(ID_AUTH_ENVELOPED_DATA, auth_enveloped))
+}
+
+/// Walk the RecipientInfos and return the first CEK `identity` can recover.
+fn recover_cek(identity: &CmsIdentity, infos: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let mut reader = DerReader::new(infos);
+    let mut last_error = "No RecipientInfo matched this identity";
+    while !reader.is_empty() {
+        let (tag, content, _) = reader.read_any()?;
+        match open_recipient_info(identity, tag, content) {
+            Ok(Some(cek)) if cek.len() == CEK_LEN => return Ok(cek),
+            Ok(Some(_)) => last_error = "Invalid content encryption key length",
+            Ok(None) => {}
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+/// Decrypt a DER ContentInfo holding EnvelopedData or AuthEnvelopedData.
+pub fn decrypt(identity: &CmsIdentity, der_bytes: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let mut outer = DerReader::new(der_bytes);
+    let mut content_info = DerReader::new(outer.read(SEQUENCE)?);
+    outer.finish()?;
+    let content_type = content_info.read(OID)?;
+    let authenticated = match content_type {
+        t if t == der::oid_content(ID_ENVELOPED_DATA) => false,
+        t if t == der::oid_content(ID_AUTH_ENVELOPED_DATA) => true,
+        _ => return Err("Unsupported CMS content type"),
+    };
+    let mut explicit = DerReader::new(content_info.read(context(0, true))?);
+    content_info.finish()?;
+    let mut body = DerReader::new(explicit.read(SEQUENCE)?);
+    explicit.finish()?;
+
+    body.read_integer()?;
+    // OriginatorInfo only carries certificates and CRLs, which play no part in decryption
+    body.read_optional(context(0, true))?;
+    let infos = body.read(SET)?;
+
+    let mut encrypted_content_info = DerReader::new(body.read(SEQUENCE)?);
+    if encrypted_content_info.read(OID)? != der::oid_content(ID_DATA) {
+        return Err("Unsupported inner content type");
+    }
+    let (alg, params) = encrypted_content_info.read_algorithm()?;
+    let ciphertext = encrypted_content_info.read(context(0, false)).map_err(|_| "Detached CMS content unsupported")?;
+    encrypted_content_info.finish()?;
+
+    let mut cek = recover_cek(identity, infos)?;
+    let aes = AesCipher::new(&cek);
+    cek.zeroize();
+    let aes = aes?;
+    let params = DerReader::new(params.ok_or("Missing content encryption parameters")?).read_any()?;
+
+    if !authenticated {
+        if alg != der::oid_content(ID_AES256_CBC) || params.0 != OCTET_STRING || params.1.len() != 16 {
+            return Err("Unsupported content encryption algorithm");
+        }
+        body.read_optional(context(1, true))?;
+        body.finish()?;
+        return CBCMode::new(&aes, params.1.to_vec()).decrypt(ciphertext);
+    }
+
+    if alg != der::oid_content(ID_AES256_GCM) || params.0 != SEQUENCE {
+        return Err("Unsupported content encryption algorithm");
+    }
+    let mut gcm_params = DerReader::new(params.1);
+    let nonce = gcm_params.read(OCTET_STRING)?;
+    let tag_len = match gcm_params.is_empty() {
+        true => 12,
+        false => gcm_params.read_integer()?,
+    };
+    gcm_params.finish()?;
+
+    // Authenticated attributes are MACed as a DER SET OF (RFC 5083 §2.1)
+    let aad = match body.peek_tag() == Some(context(1, true)) {
+        true => der::tlv(SET, body.read(context(1, true))?),
+        false => Vec::new(),
+    };
+    let tag = body.read(OCTET_STRING)?;
+    body.read_optional(context(2, true))?;
+    body.finish()?;
+    if tag.len() as u64 != tag_len || tag.len() != GCM_TAG_LEN || nonce.is_empty() {
+        return Err("Unsupported AES-GCM parameters");
+    }
+    GCMMode::new(&aes, nonce.to_vec(), aad).decrypt_and_verify(ciphertext, tag).ok_or("CMS authentication failed")
+}
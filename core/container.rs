@@ -0,0 +1,635 @@
+// ix-encryption/core/container.rs
+
+//! Versioned encrypted file container used by `ix-crypt`.
+//! A random 32-byte file key is wrapped once per recipient (passphrase or lattice KEM) and
+//! expanded into a header MAC key, a stream key and the key for an optional inner cipher stack
+//! run through `IXCipherMultiplexer`. The payload is chunked with the streaming AEAD, so files
+//! of any size are processed with bounded memory, and rewrapping touches only the header.
+//!
+//! Lattice stanzas normally carry a short key id so a recipient can find its own stanza; an
+//! anonymous stanza omits it and is found by trial decapsulation instead. Adding or removing
+//! recipients rewrites only the header; stanzas are removed by key id, position or kind, so
+//! passphrase and anonymous stanzas can be dropped too. Removal does not rotate the file key: a removed
+//! recipient who kept an earlier copy of the container can still read that copy.
+//!
+//! Layout: "IXCT" | version | header len (u32) | header | header MAC (32) | chunks
+//! Header: chunk size (u32) | nonce prefix (7) | stack count (u8) | [id len (u8) | id]*
+//!         | stanza count (u8) | [kind (u8) | body len (u16) | body]*
+//! Stanza bodies: passphrase  kdf params | salt len (u8) | salt | wrapped key
+//!                lattice     key id (8) | kem ct len (u16) | kem ct | wrapped key
+//!                anonymous   kem ct len (u16) | kem ct | wrapped key
+//! Chunk:  sealed len (u32) | sealed chunk
+
+use std::io::{Read, Write};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+use crate::core::IXCipherCore;
+use crate::core::ciphers::aes::AesCipher;
+use crate::core::hybrid::ChaChaQuantum;
+use crate::core::kdf::key_hierarchy::KeyHierarchy;
+use crate::core::kdf::password::PasswordKdf;
+use crate::core::key_wrap::KeyWrap;
+use crate::core::mac::hmac::HmacSha256;
+use crate::core::mac::mac_core::Mac;
+use crate::core::multiplexer::IXCipherMultiplexer;
+use crate::core::postquantum::hybrid_lattice::HybridLatticeCipher;
+use crate::core::postquantum::lattice_kem::LatticeKEM;
+use crate::core::siv_cipher::{AesGcmSivCipher, AesSivCipher};
+use crate::core::stream_aead::{StreamDecryptor, StreamEncryptor, STREAM_NONCE_PREFIX_LEN, STREAM_TAG_LEN};
+
+const MAGIC: &[u8; 4] = b"IXCT";
+pub const CONTAINER_VERSION: u8 = 1;
+pub const DEFAULT_CHUNK_SIZE: u32 = 64 * 1024;
+const MAX_CHUNK_SIZE: u32 = 64 * 1024 * 1024;
+const MAX_HEADER_LEN: u32 = 1024 * 1024;
+const MAX_LAYER_OVERHEAD: usize = 64;
+const HEADER_MAC_LEN: usize = 32;
+const FILE_KEY_LEN: usize = 32;
+const SALT_LEN: usize = 16;
+const KEM_KEK_PURPOSE: &str = "IX-Container-v1 kek";
+pub const KEY_ID_LEN: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StanzaKind {
+    Passphrase = 1,
+    Lattice = 2,
+    LatticeAnonymous = 3,
+}
+
+/// Selects stanzas for removal by `update_recipients`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StanzaSelector {
+    /// The identified lattice stanza with this key id.
+    KeyId([u8; KEY_ID_LEN]),
+    /// The stanza at this position in the header, counting from 0 in `inspect` order.
+    Index(usize),
+    /// Every stanza of this kind, e.g. all anonymous lattice stanzas.
+    Kind(StanzaKind),
+}
+
+impl StanzaSelector {
+    fn matches(&self, index: usize, stanza: &Stanza) -> bool {
+        match *self {
+            StanzaSelector::KeyId(id) => stanza.key_id() == Some(id),
+            StanzaSelector::Index(position) => position == index,
+            StanzaSelector::Kind(kind) => stanza.kind == kind,
+        }
+    }
+}
+
+/// One wrapped copy of the file key.
+#[derive(Clone, Debug)]
+pub struct Stanza {
+    pub kind: StanzaKind,
+    pub body: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ContainerHeader {
+    pub chunk_size: u32,
+    pub nonce_prefix: [u8; STREAM_NONCE_PREFIX_LEN],
+    /// Algorithm ids of the inner cipher stack, outermost last.
+    pub stack: Vec<String>,
+    pub stanzas: Vec<Stanza>,
+}
+
+/// Who a container is encrypted to.
+pub enum Recipient {
+    Passphrase { password: Vec<u8>, kdf: PasswordKdf },
+    /// `anonymous` leaves the key id out of the stanza, hiding who the recipients are.
+    Lattice { public_key: Vec<u8>, anonymous: bool },
+}
+
+/// Credential used to open a container.
+pub enum Identity {
+    Passphrase(Vec<u8>),
+    Lattice { public_key: Vec<u8>, secret_key: Vec<u8> },
+}
+
+pub struct ContainerOptions {
+    pub chunk_size: u32,
+    pub stack: Vec<String>,
+}
+
+impl Default for ContainerOptions {
+    /// AES-GCM-SIV inside the ChaCha20-Poly1305 stream: two independent primitive families.
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            stack: vec!["IX-AES-GCM-SIV-v1".to_string()],
+        }
+    }
+}
+
+/// Short public key fingerprint identifying a recipient's stanza.
+pub fn key_id(public_key: &[u8]) -> [u8; KEY_ID_LEN] {
+    let digest = Sha256::new()
+        .chain_update(b"IX-Container-v1 key id")
+        .chain_update(public_key)
+        .finalize();
+    let mut id = [0u8; KEY_ID_LEN];
+    id.copy_from_slice(&digest[..KEY_ID_LEN]);
+    id
+}
+
+/// Ciphers that may appear in a container stack.
+pub fn stack_cipher(algorithm_id: &str) -> Result<Box<dyn IXCipherCore>, &'static str> {
+    match algorithm_id {
+        "IX-XChaChaQuantum-v1" => Ok(Box::new(ChaChaQuantum::new_extended())),
+        "IX-AES-GCM-SIV-v1" => Ok(Box::new(AesGcmSivCipher::new())),
+        "IX-AES-SIV-v1" => Ok(Box::new(AesSivCipher::new())),
+        _ => Err("Unsupported cipher in stack"),
+    }
+}
+
+/// Native key length of a stack cipher; AES-SIV takes a double-length key.
+fn stack_key_len(algorithm_id: &str) -> usize {
+    match algorithm_id {
+        "IX-AES-SIV-v1" => 64,
+        _ => 32,
+    }
+}
+
+impl Stanza {
+    /// Key id of an identified lattice stanza; `None` for passphrase and anonymous stanzas.
+    pub fn key_id(&self) -> Option<[u8; KEY_ID_LEN]> {
+        if self.kind != StanzaKind::Lattice {
+            return None;
+        }
+        let mut id = [0u8; KEY_ID_LEN];
+        id.copy_from_slice(self.body.get(..KEY_ID_LEN)?);
+        Some(id)
+    }
+}
+
+impl ContainerHeader {
+    /// Fails when a count or length does not fit its field.
+    pub fn encode(&self) -> Result<Vec<u8>, &'static str> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&self.chunk_size.to_be_bytes());
+        out.extend_from_slice(&self.nonce_prefix);
+        out.push(u8::try_from(self.stack.len()).map_err(|_| "Cipher stack too deep")?);
+        for id in &self.stack {
+            out.push(u8::try_from(id.len()).map_err(|_| "Cipher id too long")?);
+            out.extend_from_slice(id.as_bytes());
+        }
+        out.push(u8::try_from(self.stanzas.len()).map_err(|_| "Too many stanzas")?);
+        for stanza in &self.stanzas {
+            out.push(stanza.kind as u8);
+            out.extend_from_slice(&u16::try_from(stanza.body.len()).map_err(|_| "Stanza too large")?.to_be_bytes());
+            out.extend_from_slice(&stanza.body);
+        }
+        Ok(out)
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, &'static str> {
+        let mut reader = FieldReader { data, offset: 0 };
+
+        let chunk_size = u32::from_be_bytes(reader.take_array()?);
+        if chunk_size == 0 || chunk_size > MAX_CHUNK_SIZE {
+            return Err("Invalid chunk size");
+        }
+        let nonce_prefix = reader.take_array()?;
+
+        let mut stack = Vec::new();
+        for _ in 0..reader.take(1)?[0] {
+            let len = reader.take(1)?[0] as usize;
+            let id = std::str::from_utf8(reader.take(len)?).map_err(|_| "Invalid cipher id")?;
+            stack.push(id.to_string());
+        }
+
+        let mut stanzas = Vec::new();
+        for _ in 0..reader.take(1)?[0] {
+            let kind = match reader.take(1)?[0] {
+                1 => StanzaKind::Passphrase,
+                2 => StanzaKind::Lattice,
+                3 => StanzaKind::LatticeAnonymous,
+                _ => return Err("Unknown stanza kind"),
+            };
+            let len = u16::from_be_bytes(reader.take_array()?) as usize;
+            stanzas.push(Stanza { kind, body: reader.take(len)?.to_vec() });
+        }
+
+        if reader.offset != data.len() {
+            return Err("Trailing bytes in header");
+        }
+        Ok(Self { chunk_size, nonce_prefix, stack, stanzas })
+    }
+}
+
+struct FieldReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> FieldReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], &'static str> {
+        let field = self.data.get(self.offset..self.offset + len).ok_or("Truncated header")?;
+        self.offset += len;
+        Ok(field)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], &'static str> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+}
+
+struct ContainerKeys {
+    header_mac: Vec<u8>,
+    stream: Vec<u8>,
+    stack: Vec<u8>,
+}
+
+impl ContainerKeys {
+    fn derive(file_key: &[u8]) -> Result<Self, &'static str> {
+        let keys = KeyHierarchy::from_master(file_key).child("IX-Container-v1");
+        Ok(Self {
+            header_mac: keys.purpose_key("header-mac", 32)?,
+            stream: keys.purpose_key("payload", 32)?,
+            stack: keys.purpose_key("stack", 32)?,
+        })
+    }
+}
+
+impl Drop for ContainerKeys {
+    fn drop(&mut self) {
+        self.header_mac.zeroize();
+        self.stream.zeroize();
+        self.stack.zeroize();
+    }
+}
+
+fn wrap_file_key(kek: &[u8], file_key: &[u8; FILE_KEY_LEN]) -> Result<Vec<u8>, &'static str> {
+    let kek = AesCipher::new(kek)?;
+    KeyWrap::new(&kek).wrap_chacha_key(file_key)
+}
+
+fn unwrap_file_key(kek: &[u8], wrapped: &[u8]) -> Result<[u8; FILE_KEY_LEN], &'static str> {
+    let kek = AesCipher::new(kek)?;
+    KeyWrap::new(&kek).unwrap_chacha_key(wrapped)
+}
+
+fn make_stanza(recipient: &Recipient, file_key: &[u8; FILE_KEY_LEN]) -> Result<Stanza, &'static str> {
+    match recipient {
+        Recipient::Passphrase { password, kdf } => {
+            // Parameters a reader would reject are refused before deriving.
+            kdf.validate()?;
+            let mut salt = [0u8; SALT_LEN];
+            getrandom::getrandom(&mut salt).map_err(|_| "Salt generation failed")?;
+            let mut kek = kdf.derive(password, &salt, 32)?;
+            let wrapped = wrap_file_key(&kek, file_key);
+            kek.zeroize();
+
+            let mut body = kdf.encode();
+            body.push(SALT_LEN as u8);
+            body.extend_from_slice(&salt);
+            body.extend_from_slice(&wrapped?);
+            Ok(Stanza { kind: StanzaKind::Passphrase, body })
+        }
+        Recipient::Lattice { public_key, anonymous } => {
+            let mut hybrid = HybridLatticeCipher::new();
+            let kem_ciphertext = hybrid.encapsulate_key(public_key);
+            let mut kek = hybrid.export_key(KEM_KEK_PURPOSE, 32)?;
+            let wrapped = wrap_file_key(&kek, file_key);
+            kek.zeroize();
+            hybrid.wipe();
+
+            let (kind, mut body) = match anonymous {
+                true => (StanzaKind::LatticeAnonymous, Vec::new()),
+                false => (StanzaKind::Lattice, key_id(public_key).to_vec()),
+            };
+            body.extend_from_slice(&u16::try_from(kem_ciphertext.len()).map_err(|_| "KEM ciphertext too large")?.to_be_bytes());
+            body.extend_from_slice(&kem_ciphertext);
+            body.extend_from_slice(&wrapped?);
+            Ok(Stanza { kind, body })
+        }
+    }
+}
+
+fn open_stanza(identity: &Identity, stanza: &Stanza) -> Result<[u8; FILE_KEY_LEN], &'static str> {
+    match (identity, stanza.kind) {
+        (Identity::Passphrase(password), StanzaKind::Passphrase) => {
+            // `decode` rejects cost parameters above the `PasswordKdf::validate` limits, so a
+            // crafted stanza cannot make the derivation below unbounded.
+            let (kdf, consumed) = PasswordKdf::decode(&stanza.body)?;
+            let mut reader = FieldReader { data: &stanza.body, offset: consumed };
+            let salt_len = reader.take(1)?[0] as usize;
+            let salt = reader.take(salt_len)?;
+            let wrapped = &stanza.body[reader.offset..];
+
+            let mut kek = kdf.derive(password, salt, 32)?;
+            let file_key = unwrap_file_key(&kek, wrapped);
+            kek.zeroize();
+            file_key
+        }
+        (Identity::Lattice { public_key, secret_key }, StanzaKind::Lattice | StanzaKind::LatticeAnonymous) => {
+            let mut reader = FieldReader { data: &stanza.body, offset: 0 };
+            if stanza.kind == StanzaKind::Lattice && reader.take(KEY_ID_LEN)? != key_id(public_key) {
+                return Err("Stanza is for another recipient");
+            }
+            let ct_len = u16::from_be_bytes(reader.take_array()?) as usize;
+            let kem_ciphertext = reader.take(ct_len)?;
+            let wrapped = &stanza.body[reader.offset..];
+
+            let mut hybrid = HybridLatticeCipher::with_keypair(LatticeKEM {
+                public_key: public_key.clone(),
+                secret_key: secret_key.clone(),
+            });
+            hybrid.decapsulate_key(kem_ciphertext);
+            let mut kek = hybrid.export_key(KEM_KEK_PURPOSE, 32)?;
+            let file_key = unwrap_file_key(&kek, wrapped);
+            kek.zeroize();
+            hybrid.wipe();
+            file_key
+        }
+        _ => Err("Stanza does not match identity"),
+    }
+}
+
+fn build_stack(ids: &[String], key: &[u8]) -> Result<Option<IXCipherMultiplexer>, &'static str> {
+    if ids.is_empty() {
+        return Ok(None);
+    }
+    let mut multiplexer = IXCipherMultiplexer::new_derived();
+    for id in ids {
+        multiplexer.add_cipher_with_key_len(stack_cipher(id)?, stack_key_len(id));
+    }
+    multiplexer.initialize(key, None);
+    Ok(Some(multiplexer))
+}
+
+fn header_mac(keys: &ContainerKeys, version_and_header: &[u8]) -> Vec<u8> {
+    HmacSha256::new(&keys.header_mac).compute(version_and_header)
+}
+
+fn write_header<W: Write + ?Sized>(header: &[u8], keys: &ContainerKeys, output: &mut W) -> Result<(), &'static str> {
+    let mut prologue = MAGIC.to_vec();
+    prologue.push(CONTAINER_VERSION);
+    let header_len = u32::try_from(header.len()).ok().filter(|&len| len <= MAX_HEADER_LEN).ok_or("Header too large")?;
+    prologue.extend_from_slice(&header_len.to_be_bytes());
+    prologue.extend_from_slice(header);
+    let mac = header_mac(keys, &prologue);
+
+    output.write_all(&prologue).map_err(|_| "Failed to write output")?;
+    output.write_all(&mac).map_err(|_| "Failed to write output")
+}
+
+/// Read and parse the header without any credential. The MAC is checked later by `decrypt`.
+pub fn read_header<R: Read + ?Sized>(input: &mut R) -> Result<(ContainerHeader, Vec<u8>, [u8; HEADER_MAC_LEN]), &'static str> {
+    let mut fixed = [0u8; 9];
+    input.read_exact(&mut fixed).map_err(|_| "Truncated container")?;
+    if &fixed[..4] != MAGIC {
+        return Err("Not an IX container");
+    }
+    if fixed[4] != CONTAINER_VERSION {
+        return Err("Unsupported container version");
+    }
+    let header_len = u32::from_be_bytes([fixed[5], fixed[6], fixed[7], fixed[8]]);
+    if header_len > MAX_HEADER_LEN {
+        return Err("Header too large");
+    }
+
+    let mut prologue = fixed.to_vec();
+    prologue.resize(9 + header_len as usize, 0);
+    input.read_exact(&mut prologue[9..]).map_err(|_| "Truncated container")?;
+    let mut mac = [0u8; HEADER_MAC_LEN];
+    input.read_exact(&mut mac).map_err(|_| "Truncated container")?;
+
+    let header = ContainerHeader::decode(&prologue[9..])?;
+    Ok((header, prologue, mac))
+}
+
+/// Find the file key for `identity` and authenticate the header with it.
+fn open_header(identity: &Identity, header: &ContainerHeader, prologue: &[u8], mac: &[u8]) -> Result<([u8; FILE_KEY_LEN], ContainerKeys), &'static str> {
+    check_passphrase_count(passphrase_stanzas(&header.stanzas))?;
+    for stanza in &header.stanzas {
+        if let Ok(mut file_key) = open_stanza(identity, stanza) {
+            let keys = ContainerKeys::derive(&file_key)?;
+            if bool::from(header_mac(&keys, prologue).ct_eq(mac)) {
+                return Ok((file_key, keys));
+            }
+            file_key.zeroize();
+        }
+    }
+    Err("No stanza could be opened with this identity")
+}
+
+/// Fill `buf` from `input`, stopping early only at end of input.
+fn read_up_to<R: Read + ?Sized>(input: &mut R, buf: &mut [u8]) -> Result<usize, &'static str> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match input.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(_) => return Err("Failed to read input"),
+        }
+    }
+    Ok(filled)
+}
+
+fn read_chunk<R: Read + ?Sized>(input: &mut R, chunk_size: usize) -> Result<Vec<u8>, &'static str> {
+    let mut chunk = vec![0u8; chunk_size];
+    let len = read_up_to(input, &mut chunk)?;
+    chunk.truncate(len);
+    Ok(chunk)
+}
+
+fn wrap_all(recipients: &[Recipient], file_key: &[u8; FILE_KEY_LEN]) -> Result<Vec<Stanza>, &'static str> {
+    recipients.iter().map(|r| make_stanza(r, file_key)).collect()
+}
+
+fn check_recipient_count(count: usize) -> Result<(), &'static str> {
+    if count == 0 || count > u8::MAX as usize {
+        return Err("Between 1 and 255 recipients required");
+    }
+    Ok(())
+}
+
+/// Every passphrase stanza costs a full KDF run to try, so a header may carry at most one.
+fn check_passphrase_count(count: usize) -> Result<(), &'static str> {
+    if count > 1 {
+        return Err("At most one passphrase recipient allowed");
+    }
+    Ok(())
+}
+
+fn passphrase_stanzas(stanzas: &[Stanza]) -> usize {
+    stanzas.iter().filter(|stanza| stanza.kind == StanzaKind::Passphrase).count()
+}
+
+/// Encrypt all of `input` to `recipients`.
+pub fn encrypt<R: Read + ?Sized, W: Write + ?Sized>(recipients: &[Recipient], options: &ContainerOptions, input: &mut R, output: &mut W) -> Result<(), &'static str> {
+    if options.chunk_size == 0 || options.chunk_size > MAX_CHUNK_SIZE {
+        return Err("Invalid chunk size");
+    }
+    if options.stack.len() > u8::MAX as usize {
+        return Err("Cipher stack too deep");
+    }
+    check_recipient_count(recipients.len())?;
+    check_passphrase_count(recipients.iter().filter(|r| matches!(r, Recipient::Passphrase { .. })).count())?;
+
+    let mut file_key = [0u8; FILE_KEY_LEN];
+    let mut nonce_prefix = [0u8; STREAM_NONCE_PREFIX_LEN];
+    getrandom::getrandom(&mut file_key).map_err(|_| "Key generation failed")?;
+    getrandom::getrandom(&mut nonce_prefix).map_err(|_| "Nonce generation failed")?;
+
+    let stanzas = wrap_all(recipients, &file_key);
+    let keys = ContainerKeys::derive(&file_key);
+    file_key.zeroize();
+    let (stanzas, keys) = (stanzas?, keys?);
+
+    let header = ContainerHeader {
+        chunk_size: options.chunk_size,
+        nonce_prefix,
+        stack: options.stack.clone(),
+        stanzas,
+    };
+    // Validate the stack and header before anything reaches the output.
+    let stack = build_stack(&header.stack, &keys.stack)?;
+    write_header(&header.encode()?, &keys, output)?;
+
+    let mut stream = StreamEncryptor::new(&keys.stream, nonce_prefix)?;
+    let chunk_size = options.chunk_size as usize;
+
+    let mut current = read_chunk(input, chunk_size)?;
+    loop {
+        // Look ahead one chunk so the final chunk can be flagged as such
+        let next = if current.len() < chunk_size { Vec::new() } else { read_chunk(input, chunk_size)? };
+        let last = next.is_empty();
+
+        let layered = match &stack {
+            Some(multiplexer) => multiplexer.encrypt(&current),
+            None => current.clone(),
+        };
+        current.zeroize();
+        let sealed = stream.seal_chunk(&layered, &[], last)?;
+
+        let sealed_len = u32::try_from(sealed.len()).map_err(|_| "Chunk too large")?;
+        output.write_all(&sealed_len.to_be_bytes()).map_err(|_| "Failed to write output")?;
+        output.write_all(&sealed).map_err(|_| "Failed to write output")?;
+
+        if last {
+            break;
+        }
+        current = next;
+    }
+
+    if let Some(mut multiplexer) = stack {
+        multiplexer.wipe();
+    }
+    output.flush().map_err(|_| "Failed to write output")
+}
+
+fn read_sealed<R: Read + ?Sized>(input: &mut R, max_len: usize) -> Result<Option<Vec<u8>>, &'static str> {
+    let mut len = [0u8; 4];
+    match read_up_to(input, &mut len)? {
+        0 => return Ok(None),
+        4 => {}
+        _ => return Err("Truncated chunk"),
+    }
+    let len = u32::from_be_bytes(len) as usize;
+    if len < STREAM_TAG_LEN || len > max_len {
+        return Err("Invalid chunk length");
+    }
+    let mut sealed = vec![0u8; len];
+    input.read_exact(&mut sealed).map_err(|_| "Truncated chunk")?;
+    Ok(Some(sealed))
+}
+
+/// Decrypt a container with `identity`. Chunks are authenticated before they are written, but
+/// output is produced incrementally: on error, discard whatever was already written.
+pub fn decrypt<R: Read + ?Sized, W: Write + ?Sized>(identity: &Identity, input: &mut R, output: &mut W) -> Result<(), &'static str> {
+    let (header, prologue, mac) = read_header(input)?;
+    let (mut file_key, keys) = open_header(identity, &header, &prologue, &mac)?;
+    file_key.zeroize();
+
+    let stack = build_stack(&header.stack, &keys.stack)?;
+    let mut stream = StreamDecryptor::new(&keys.stream, header.nonce_prefix)?;
+    let max_len = header.chunk_size as usize + STREAM_TAG_LEN + MAX_LAYER_OVERHEAD * header.stack.len();
+
+    let mut current = read_sealed(input, max_len)?.ok_or("Truncated container")?;
+    loop {
+        let next = read_sealed(input, max_len)?;
+        let last = next.is_none();
+
+        let layered = stream.open_chunk(&current, &[], last)?;
+        let mut chunk = match &stack {
+            Some(multiplexer) => multiplexer.try_decrypt(&layered)?,
+            None => layered,
+        };
+        output.write_all(&chunk).map_err(|_| "Failed to write output")?;
+        chunk.zeroize();
+
+        match next {
+            Some(sealed) => current = sealed,
+            None => break,
+        }
+    }
+
+    if let Some(mut multiplexer) = stack {
+        multiplexer.wipe();
+    }
+    output.flush().map_err(|_| "Failed to write output")
+}
+
+/// Parse the header for display. Nothing is authenticated.
+pub fn inspect<R: Read + ?Sized>(input: &mut R) -> Result<ContainerHeader, &'static str> {
+    read_header(input).map(|(header, _, _)| header)
+}
+
+/// Replace the recipients of a container. The payload is copied unchanged.
+pub fn rewrap<R: Read + ?Sized, W: Write + ?Sized>(identity: &Identity, recipients: &[Recipient], input: &mut R, output: &mut W) -> Result<(), &'static str> {
+    edit_recipients(identity, recipients, &[], true, input, output)
+}
+
+/// Add `add` as recipients and drop every stanza matched by a selector in `remove`, keeping the
+/// rest. Each selector must match at least one stanza. The payload is copied unchanged.
+pub fn update_recipients<R: Read + ?Sized, W: Write + ?Sized>(
+    identity: &Identity,
+    add: &[Recipient],
+    remove: &[StanzaSelector],
+    input: &mut R,
+    output: &mut W,
+) -> Result<(), &'static str> {
+    edit_recipients(identity, add, remove, false, input, output)
+}
+
+fn edit_recipients<R: Read + ?Sized, W: Write + ?Sized>(
+    identity: &Identity,
+    add: &[Recipient],
+    remove: &[StanzaSelector],
+    replace: bool,
+    input: &mut R,
+    output: &mut W,
+) -> Result<(), &'static str> {
+    let (mut header, prologue, mac) = read_header(input)?;
+    let (mut file_key, keys) = open_header(identity, &header, &prologue, &mac)?;
+    let added = wrap_all(add, &file_key);
+    file_key.zeroize();
+
+    if replace {
+        header.stanzas.clear();
+    } else {
+        let matched = |selector: &StanzaSelector| header.stanzas.iter().enumerate().any(|(i, stanza)| selector.matches(i, stanza));
+        if !remove.iter().all(matched) {
+            return Err("Recipient to remove not found");
+        }
+        let mut index = 0;
+        header.stanzas.retain(|stanza| {
+            let keep = !remove.iter().any(|selector| selector.matches(index, stanza));
+            index += 1;
+            keep
+        });
+    }
+    header.stanzas.extend(added?);
+    check_recipient_count(header.stanzas.len())?;
+    check_passphrase_count(passphrase_stanzas(&header.stanzas))?;
+
+    write_header(&header.encode()?, &keys, output)?;
+    std::io::copy(input, output).map_err(|_| "Failed to copy payload")?;
+    output.flush().map_err(|_| "Failed to write output")
+}
@@ -0,0 +1,196 @@
+// ix-encryption/core/cose/cbor.rs
+
+//! Minimal CBOR (RFC 8949) codec for COSE: integers, byte and text strings, arrays, maps,
+//! tags and the simple values false/true/null. Encoding is always definite-length with the
+//! shortest argument form; floats and indefinite lengths are rejected on decode.
+
+use std::collections::HashSet;
+use crate::core::byte_reader::ByteReader;
+
+/// Nesting limit for decoding untrusted input.
+const MAX_DEPTH: usize = 16;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Cbor {
+    Int(i64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Cbor>),
+    Map(Vec<(Cbor, Cbor)>),
+    Tag(u64, Box<Cbor>),
+    Bool(bool),
+    Null,
+}
+
+impl Cbor {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.encode_into(&mut out);
+        out
+    }
+
+    fn encode_into(&self, out: &mut Vec<u8>) {
+        match self {
+            Cbor::Int(n) if *n >= 0 => write_head(out, 0, *n as u64),
+            Cbor::Int(n) => write_head(out, 1, !(*n) as u64),
+            Cbor::Bytes(bytes) => {
+                write_head(out, 2, bytes.len() as u64);
+                out.extend_from_slice(bytes);
+            }
+            Cbor::Text(text) => {
+                write_head(out, 3, text.len() as u64);
+                out.extend_from_slice(text.as_bytes());
+            }
+            Cbor::Array(items) => {
+                write_head(out, 4, items.len() as u64);
+                items.iter().for_each(|item| item.encode_into(out));
+            }
+            Cbor::Map(entries) => {
+                write_head(out, 5, entries.len() as u64);
+                for (key, value) in entries {
+                    key.encode_into(out);
+                    value.encode_into(out);
+                }
+            }
+            Cbor::Tag(tag, inner) => {
+                write_head(out, 6, *tag);
+                inner.encode_into(out);
+            }
+            Cbor::Bool(false) => out.push(0xf4),
+            Cbor::Bool(true) => out.push(0xf5),
+            Cbor::Null => out.push(0xf6),
+        }
+    }
+
+    /// Decode exactly one item; trailing bytes are an error.
+    pub fn decode(data: &[u8]) -> Result<Self, &'static str> {
+        let mut reader = ByteReader::new(data, "Truncated CBOR");
+        let item = decode_item(&mut reader, 0)?;
+        if !reader.is_empty() {
+            return Err("Trailing bytes after CBOR item");
+        }
+        Ok(item)
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Cbor::Int(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Cbor::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Cbor]> {
+        match self {
+            Cbor::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_map(&self) -> Option<&[(Cbor, Cbor)]> {
+        match self {
+            Cbor::Map(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Look up an integer label in a map.
+    pub fn get(&self, label: i64) -> Option<&Cbor> {
+        self.as_map()?.iter().find(|(key, _)| *key == Cbor::Int(label)).map(|(_, value)| value)
+    }
+
+    /// Strip an outer tag if it has the expected number.
+    pub fn untag(&self, tag: u64) -> &Cbor {
+        match self {
+            Cbor::Tag(t, inner) if *t == tag => inner,
+            other => other,
+        }
+    }
+}
+
+fn write_head(out: &mut Vec<u8>, major: u8, value: u64) {
+    let major = major << 5;
+    match value {
+        0..=23 => out.push(major | value as u8),
+        24..=0xff => out.extend_from_slice(&[major | 24, value as u8]),
+        0x100..=0xffff => {
+            out.push(major | 25);
+            out.extend_from_slice(&(value as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(major | 26);
+            out.extend_from_slice(&(value as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(major | 27);
+            out.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+}
+
+fn read_head(reader: &mut ByteReader) -> Result<(u8, u8, u64), &'static str> {
+    let initial = reader.take_u8()?;
+    let (major, info) = (initial >> 5, initial & 0x1f);
+    let value = match info {
+        0..=23 => info as u64,
+        24 => reader.take_u8()? as u64,
+        25 => reader.take_u16()? as u64,
+        26 => reader.take_u32()? as u64,
+        27 => reader.take_u64()?,
+        _ => return Err("Unsupported CBOR length encoding"),
+    };
+    Ok((major, info, value))
+}
+
+fn decode_item(reader: &mut ByteReader, depth: usize) -> Result<Cbor, &'static str> {
+    if depth > MAX_DEPTH {
+        return Err("CBOR nesting too deep");
+    }
+    let (major, info, value) = read_head(reader)?;
+    // Every item occupies at least one byte, which bounds pre-allocation for counts
+    let remaining = reader.remaining().len();
+    let count = || usize::try_from(value).ok().filter(|&n| n <= remaining).ok_or("Truncated CBOR");
+
+    match major {
+        0 => i64::try_from(value).map(Cbor::Int).map_err(|_| "CBOR integer out of range"),
+        1 => i64::try_from(value).map(|n| Cbor::Int(!n)).map_err(|_| "CBOR integer out of range"),
+        2 => Ok(Cbor::Bytes(reader.take(count()?)?.to_vec())),
+        3 => {
+            let bytes = reader.take(count()?)?;
+            String::from_utf8(bytes.to_vec()).map(Cbor::Text).map_err(|_| "Invalid UTF-8 in CBOR text")
+        }
+        4 => {
+            let mut items = Vec::with_capacity(count()?);
+            for _ in 0..value {
+                items.push(decode_item(reader, depth + 1)?);
+            }
+            Ok(Cbor::Array(items))
+        }
+        5 => {
+            let mut entries = Vec::with_capacity(count()?);
+            for _ in 0..value {
+                let key = decode_item(reader, depth + 1)?;
+                entries.push((key, decode_item(reader, depth + 1)?));
+            }
+            // Hashing keeps the duplicate check linear for large untrusted maps
+            let mut keys = HashSet::with_capacity(entries.len());
+            if !entries.iter().all(|(key, _)| keys.insert(key)) {
+                return Err("Duplicate CBOR map key");
+            }
+            Ok(Cbor::Map(entries))
+        }
+        6 => Ok(Cbor::Tag(value, Box::new(decode_item(reader, depth + 1)?))),
+        _ => match info {
+            20 => Ok(Cbor::Bool(false)),
+            21 => Ok(Cbor::Bool(true)),
+            22 => Ok(Cbor::Null),
+            _ => Err("Unsupported CBOR simple value"),
+        },
+    }
+}
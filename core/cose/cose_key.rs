@@ -0,0 +1,281 @@
+// ix-encryption/core/cose/cose_key.rs
+
+//! COSE_Key (RFC 9052 §7) serialization for symmetric keys, OKP (Ed25519/X25519), EC2 P-256
+//! and `LatticeKEM` key pairs. Lattice keys use the draft `AKP` key type (public key at -1,
+//! private key at -2) and should be treated as experimental; conversion to and from
+//! `LatticeKEM` is only built with the `ml_kem` feature.
+
+use p256::elliptic_curve::sec1::ToEncodedPoint;
+use rand::rngs::OsRng;
+use zeroize::Zeroize;
+use crate::core::cose::cbor::Cbor;
+#[cfg(feature = "ml_kem")]
+use crate::core::postquantum::lattice_kem::LatticeKEM;
+
+// Key type values (RFC 9053 §7, draft AKP)
+pub const KTY_OKP: i64 = 1;
+pub const KTY_EC2: i64 = 2;
+pub const KTY_SYMMETRIC: i64 = 4;
+pub const KTY_AKP: i64 = 7;
+
+// Curve identifiers
+pub const CRV_P256: i64 = 1;
+pub const CRV_X25519: i64 = 4;
+pub const CRV_ED25519: i64 = 6;
+
+// Common key parameter labels
+const LABEL_KTY: i64 = 1;
+const LABEL_KID: i64 = 2;
+const LABEL_ALG: i64 = 3;
+
+/// Key material; private parameters are zeroized on drop and left out of `Debug`.
+#[derive(Clone, PartialEq, Eq)]
+pub enum CoseKeyMaterial {
+    Symmetric { k: Vec<u8> },
+    Okp { crv: i64, x: [u8; 32], d: Option<[u8; 32]> },
+    Ec2 { x: [u8; 32], y: [u8; 32], d: Option<[u8; 32]> },
+    Akp { public: Vec<u8>, private: Option<Vec<u8>> },
+}
+
+impl Drop for CoseKeyMaterial {
+    fn drop(&mut self) {
+        match self {
+            CoseKeyMaterial::Symmetric { k } => k.zeroize(),
+            CoseKeyMaterial::Okp { d, .. } | CoseKeyMaterial::Ec2 { d, .. } => d.zeroize(),
+            CoseKeyMaterial::Akp { private, .. } => private.zeroize(),
+        }
+    }
+}
+
+impl std::fmt::Debug for CoseKeyMaterial {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CoseKeyMaterial::Symmetric { .. } => f.debug_struct("Symmetric").finish_non_exhaustive(),
+            CoseKeyMaterial::Okp { crv, x, .. } => f.debug_struct("Okp").field("crv", crv).field("x", x).finish_non_exhaustive(),
+            CoseKeyMaterial::Ec2 { x, y, .. } => f.debug_struct("Ec2").field("x", x).field("y", y).finish_non_exhaustive(),
+            CoseKeyMaterial::Akp { public, .. } => f.debug_struct("Akp").field("public", public).finish_non_exhaustive(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CoseKey {
+    pub material: CoseKeyMaterial,
+    pub kid: Option<Vec<u8>>,
+    pub alg: Option<i64>,
+}
+
+fn bytes32(value: Option<&Cbor>) -> Result<[u8; 32], &'static str> {
+    value.and_then(Cbor::as_bytes).ok_or("Missing COSE_Key parameter")?.try_into().map_err(|_| "Invalid COSE_Key parameter length")
+}
+
+fn optional32(value: Option<&Cbor>) -> Result<Option<[u8; 32]>, &'static str> {
+    value.map(|v| bytes32(Some(v))).transpose()
+}
+
+impl CoseKey {
+    pub fn new(material: CoseKeyMaterial) -> Self {
+        Self { material, kid: None, alg: None }
+    }
+
+    pub fn with_kid(mut self, kid: &[u8]) -> Self {
+        self.kid = Some(kid.to_vec());
+        self
+    }
+
+    pub fn with_alg(mut self, alg: i64) -> Self {
+        self.alg = Some(alg);
+        self
+    }
+
+    pub fn symmetric(k: &[u8]) -> Self {
+        Self::new(CoseKeyMaterial::Symmetric { k: k.to_vec() })
+    }
+
+    pub fn generate_ed25519() -> Result<Self, &'static str> {
+        let mut d = [0u8; 32];
+        getrandom::getrandom(&mut d).map_err(|_| "Key generation failed")?;
+        let x = ed25519_dalek::SigningKey::from_bytes(&d).verifying_key().to_bytes();
+        let key = Self::new(CoseKeyMaterial::Okp { crv: CRV_ED25519, x, d: Some(d) });
+        d.zeroize();
+        Ok(key)
+    }
+
+    pub fn generate_p256() -> Result<Self, &'static str> {
+        let secret = p256::SecretKey::random(&mut OsRng);
+        let point = secret.public_key().to_encoded_point(false);
+        let mut d: [u8; 32] = secret.to_bytes().into();
+        let material = CoseKeyMaterial::Ec2 {
+            x: point.x().ok_or("Invalid P-256 point")?.as_slice().try_into().map_err(|_| "Invalid P-256 point")?,
+            y: point.y().ok_or("Invalid P-256 point")?.as_slice().try_into().map_err(|_| "Invalid P-256 point")?,
+            d: Some(d),
+        };
+        d.zeroize();
+        Ok(Self::new(material))
+    }
+
+    /// Wraps a `LatticeKEM` key pair; the private half is kept.
+    #[cfg(feature = "ml_kem")]
+    pub fn from_lattice(kem: &LatticeKEM) -> Self {
+        Self::new(CoseKeyMaterial::Akp {
+            public: kem.public_key.clone(),
+            private: Some(kem.secret_key.clone()),
+        })
+    }
+
+    /// Rebuilds the `LatticeKEM` key pair from a private AKP key.
+    #[cfg(feature = "ml_kem")]
+    pub fn to_lattice(&self) -> Result<LatticeKEM, &'static str> {
+        match &self.material {
+            CoseKeyMaterial::Akp { public, private: Some(private) } => Ok(LatticeKEM {
+                public_key: public.clone(),
+                secret_key: private.clone(),
+            }),
+            _ => Err("Not a private lattice key"),
+        }
+    }
+
+    pub fn is_private(&self) -> bool {
+        match &self.material {
+            CoseKeyMaterial::Symmetric { .. } => true,
+            CoseKeyMaterial::Okp { d, .. } | CoseKeyMaterial::Ec2 { d, .. } => d.is_some(),
+            CoseKeyMaterial::Akp { private, .. } => private.is_some(),
+        }
+    }
+
+    /// The same key without private parameters. Symmetric keys have no public form.
+    pub fn to_public(&self) -> Result<Self, &'static str> {
+        let material = match &self.material {
+            CoseKeyMaterial::Symmetric { .. } => return Err("Symmetric keys have no public form"),
+            CoseKeyMaterial::Okp { crv, x, .. } => CoseKeyMaterial::Okp { crv: *crv, x: *x, d: None },
+            CoseKeyMaterial::Ec2 { x, y, .. } => CoseKeyMaterial::Ec2 { x: *x, y: *y, d: None },
+            CoseKeyMaterial::Akp { public, .. } => CoseKeyMaterial::Akp { public: public.clone(), private: None },
+        };
+        Ok(Self { material, kid: self.kid.clone(), alg: self.alg })
+    }
+
+    pub fn to_cbor(&self) -> Cbor {
+        let mut map = Vec::new();
+        let mut put = |label: i64, value: Cbor| map.push((Cbor::Int(label), value));
+
+        match &self.material {
+            CoseKeyMaterial::Symmetric { k } => {
+                put(LABEL_KTY, Cbor::Int(KTY_SYMMETRIC));
+                put(-1, Cbor::Bytes(k.clone()));
+            }
+            CoseKeyMaterial::Okp { crv, x, d } => {
+                put(LABEL_KTY, Cbor::Int(KTY_OKP));
+                put(-1, Cbor::Int(*crv));
+                put(-2, Cbor::Bytes(x.to_vec()));
+                if let Some(d) = d {
+                    put(-4, Cbor::Bytes(d.to_vec()));
+                }
+            }
+            CoseKeyMaterial::Ec2 { x, y, d } => {
+                put(LABEL_KTY, Cbor::Int(KTY_EC2));
+                put(-1, Cbor::Int(CRV_P256));
+                put(-2, Cbor::Bytes(x.to_vec()));
+                put(-3, Cbor::Bytes(y.to_vec()));
+                if let Some(d) = d {
+                    put(-4, Cbor::Bytes(d.to_vec()));
+                }
+            }
+            CoseKeyMaterial::Akp { public, private } => {
+                put(LABEL_KTY, Cbor::Int(KTY_AKP));
+                put(-1, Cbor::Bytes(public.clone()));
+                if let Some(private) = private {
+                    put(-2, Cbor::Bytes(private.clone()));
+                }
+            }
+        }
+        if let Some(kid) = &self.kid {
+            put(LABEL_KID, Cbor::Bytes(kid.clone()));
+        }
+        if let Some(alg) = self.alg {
+            put(LABEL_ALG, Cbor::Int(alg));
+        }
+        Cbor::Map(map)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        self.to_cbor().encode()
+    }
+
+    pub fn from_cbor(key: &Cbor) -> Result<Self, &'static str> {
+        if key.as_map().is_none() {
+            return Err("COSE_Key must be a map");
+        }
+        let kty = key.get(LABEL_KTY).and_then(Cbor::as_int).ok_or("Missing COSE_Key kty")?;
+        let bytes = |label: i64| key.get(label).and_then(Cbor::as_bytes).map(<[u8]>::to_vec);
+
+        let material = match kty {
+            KTY_SYMMETRIC => CoseKeyMaterial::Symmetric { k: bytes(-1).ok_or("Missing symmetric key")? },
+            KTY_OKP => {
+                let crv = key.get(-1).and_then(Cbor::as_int).ok_or("Missing OKP curve")?;
+                if crv != CRV_ED25519 && crv != CRV_X25519 {
+                    return Err("Unsupported OKP curve");
+                }
+                CoseKeyMaterial::Okp { crv, x: bytes32(key.get(-2))?, d: optional32(key.get(-4))? }
+            }
+            KTY_EC2 => {
+                if key.get(-1).and_then(Cbor::as_int) != Some(CRV_P256) {
+                    return Err("Unsupported EC2 curve");
+                }
+                CoseKeyMaterial::Ec2 { x: bytes32(key.get(-2))?, y: bytes32(key.get(-3))?, d: optional32(key.get(-4))? }
+            }
+            KTY_AKP => CoseKeyMaterial::Akp { public: bytes(-1).ok_or("Missing AKP public key")?, private: bytes(-2) },
+            _ => return Err("Unsupported COSE_Key type"),
+        };
+
+        Ok(Self {
+            material,
+            kid: bytes(LABEL_KID),
+            alg: key.get(LABEL_ALG).and_then(Cbor::as_int),
+        })
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, &'static str> {
+        Self::from_cbor(&Cbor::decode(data)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_roundtrip() {
+        let keys = [
+            CoseKey::symmetric(&[7; 32]).with_kid(b"sym").with_alg(3),
+            CoseKey::generate_ed25519().unwrap().with_kid(b"ed"),
+            CoseKey::generate_p256().unwrap().with_alg(-7),
+            CoseKey::new(CoseKeyMaterial::Akp { public: vec![1; 40], private: Some(vec![2; 64]) }),
+        ];
+        for key in keys {
+            assert_eq!(CoseKey::decode(&key.encode()).unwrap(), key);
+            if let Ok(public) = key.to_public() {
+                assert!(!public.is_private());
+                assert_eq!(CoseKey::decode(&public.encode()).unwrap(), public);
+            }
+        }
+        assert!(CoseKey::symmetric(&[7; 32]).to_public().is_err());
+    }
+
+    #[test]
+    fn rejects_unsupported_keys() {
+        let okp = |crv: i64| Cbor::Map(vec![(Cbor::Int(LABEL_KTY), Cbor::Int(KTY_OKP)), (Cbor::Int(-1), Cbor::Int(crv)), (Cbor::Int(-2), Cbor::Bytes(vec![0; 32]))]);
+        assert!(CoseKey::from_cbor(&okp(CRV_X25519)).is_ok());
+        assert_eq!(CoseKey::from_cbor(&okp(7)), Err("Unsupported OKP curve"));
+        assert_eq!(CoseKey::from_cbor(&Cbor::Map(vec![(Cbor::Int(LABEL_KTY), Cbor::Int(3))])), Err("Unsupported COSE_Key type"));
+        assert_eq!(CoseKey::from_cbor(&Cbor::Int(1)), Err("COSE_Key must be a map"));
+    }
+
+    #[test]
+    fn debug_redacts_private_parameters() {
+        let key = CoseKey::symmetric(&[0x5a; 32]);
+        assert!(!format!("{:?}", key).contains("90"));
+        let ed = CoseKey::generate_ed25519().unwrap();
+        let CoseKeyMaterial::Okp { d: Some(d), .. } = &ed.material else { unreachable!() };
+        assert!(!format!("{:?}", ed).contains(&format!("{:?}", d)));
+    }
+}
@@ -0,0 +1,550 @@
+// ix-encryption/core/cose/messages.rs
+
+//! COSE message structures (RFC 9052): COSE_Encrypt0, COSE_Encrypt, COSE_Mac0 and COSE_Sign1.
+//! Content encryption uses A256GCM (`GCMMode`), AES-CCM-16-128-256 (`CCMMode`) or
+//! ChaCha20/Poly1305 (`ChaChaQuantum`); MACs are HMAC-SHA-256/512 and signatures EdDSA or ES256.
+//! COSE_Encrypt recipients use direct keys, A256KW, or, with the `ml_kem` feature, the
+//! experimental private-use ML-KEM-768+A256KW algorithm backed by `HybridLatticeCipher`.
+//! HMAC keys must be at least 256 bits.
+//! Messages are emitted with their CBOR tag; decoding accepts tagged or untagged input.
+
+use p256::ecdsa::signature::{Signer, Verifier};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+use crate::core::IXCipherCore;
+use crate::core::ciphers::aes::AesCipher;
+use crate::core::cose::cbor::Cbor;
+use crate::core::cose::cose_key::{CoseKey, CoseKeyMaterial, CRV_ED25519};
+use crate::core::hybrid::ChaChaQuantum;
+use crate::core::key_wrap::KeyWrap;
+use crate::core::mac::hmac::{HmacSha256, HmacSha512};
+use crate::core::mac::mac_core::Mac;
+use crate::core::mode_ccm::CCMMode;
+use crate::core::mode_gcm::GCMMode;
+#[cfg(feature = "ml_kem")]
+use crate::core::postquantum::hybrid_lattice::HybridLatticeCipher;
+
+// CBOR tags for tagged messages
+const TAG_ENCRYPT0: u64 = 16;
+const TAG_MAC0: u64 = 17;
+const TAG_SIGN1: u64 = 18;
+const TAG_ENCRYPT: u64 = 96;
+
+// Header labels
+const HEADER_ALG: i64 = 1;
+const HEADER_KID: i64 = 4;
+const HEADER_IV: i64 = 5;
+/// Private-use label carrying the ML-KEM ciphertext in a recipient's unprotected header.
+#[cfg(feature = "ml_kem")]
+const HEADER_KEM_CIPHERTEXT: i64 = -65601;
+
+const CEK_LEN: usize = 32;
+const AEAD_TAG_LEN: usize = 16;
+/// HMAC keys must be at least as long as the SHA-256 output, as for JWS HS256 (RFC 7518 §3.2).
+const MIN_HMAC_KEY_LEN: usize = 32;
+#[cfg(feature = "ml_kem")]
+const ML_KEM_EXPORT_PURPOSE: &str = "IX-COSE ML-KEM-768+A256KW";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoseAlgorithm {
+    A256Gcm,
+    AesCcm16_128_256,
+    ChaCha20Poly1305,
+    HmacSha256,
+    HmacSha512,
+    EdDsa,
+    Es256,
+    Direct,
+    A256Kw,
+    /// Experimental, private-use identifier.
+    #[cfg(feature = "ml_kem")]
+    MlKem768A256Kw,
+}
+
+impl CoseAlgorithm {
+    pub fn id(&self) -> i64 {
+        match self {
+            CoseAlgorithm::A256Gcm => 3,
+            CoseAlgorithm::AesCcm16_128_256 => 31,
+            CoseAlgorithm::ChaCha20Poly1305 => 24,
+            CoseAlgorithm::HmacSha256 => 5,
+            CoseAlgorithm::HmacSha512 => 7,
+            CoseAlgorithm::EdDsa => -8,
+            CoseAlgorithm::Es256 => -7,
+            CoseAlgorithm::Direct => -6,
+            CoseAlgorithm::A256Kw => -5,
+            #[cfg(feature = "ml_kem")]
+            CoseAlgorithm::MlKem768A256Kw => -65600,
+        }
+    }
+
+    pub fn from_id(id: i64) -> Result<Self, &'static str> {
+        [
+            CoseAlgorithm::A256Gcm,
+            CoseAlgorithm::AesCcm16_128_256,
+            CoseAlgorithm::ChaCha20Poly1305,
+            CoseAlgorithm::HmacSha256,
+            CoseAlgorithm::HmacSha512,
+            CoseAlgorithm::EdDsa,
+            CoseAlgorithm::Es256,
+            CoseAlgorithm::Direct,
+            CoseAlgorithm::A256Kw,
+            #[cfg(feature = "ml_kem")]
+            CoseAlgorithm::MlKem768A256Kw,
+        ]
+        .into_iter()
+        .find(|alg| alg.id() == id)
+        .ok_or("Unsupported COSE algorithm")
+    }
+
+    fn iv_len(&self) -> Result<usize, &'static str> {
+        match self {
+            CoseAlgorithm::A256Gcm | CoseAlgorithm::ChaCha20Poly1305 => Ok(12),
+            CoseAlgorithm::AesCcm16_128_256 => Ok(13),
+            _ => Err("Not a content encryption algorithm"),
+        }
+    }
+}
+
+/// Reject keys whose `alg` parameter names a different algorithm.
+fn check_key_alg(key: &CoseKey, alg: CoseAlgorithm) -> Result<(), &'static str> {
+    match key.alg {
+        Some(expected) if expected != alg.id() => Err("Algorithm not permitted for this key"),
+        _ => Ok(()),
+    }
+}
+
+fn symmetric_key(key: &CoseKey, len: Option<usize>) -> Result<&[u8], &'static str> {
+    match &key.material {
+        CoseKeyMaterial::Symmetric { k } if len.is_none_or(|len| k.len() == len) => Ok(k),
+        _ => Err("Algorithm requires a symmetric key of the right length"),
+    }
+}
+
+/// Returns ciphertext || tag, as COSE carries them.
+fn aead_seal(alg: CoseAlgorithm, key: &[u8], iv: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, &'static str> {
+    match alg {
+        CoseAlgorithm::A256Gcm => {
+            let (ciphertext, tag) = GCMMode::new(&AesCipher::new(key)?, iv.to_vec(), aad.to_vec()).encrypt_and_tag(plaintext);
+            Ok([ciphertext, tag].concat())
+        }
+        CoseAlgorithm::AesCcm16_128_256 => {
+            let (ciphertext, tag) = CCMMode::new(&AesCipher::new(key)?, iv.to_vec(), aad.to_vec(), AEAD_TAG_LEN).encrypt_and_tag(plaintext)?;
+            Ok([ciphertext, tag].concat())
+        }
+        CoseAlgorithm::ChaCha20Poly1305 => {
+            let mut cipher = ChaChaQuantum::new();
+            cipher.initialize(key, None);
+            let sealed = cipher.seal_with_nonce(iv.try_into().map_err(|_| "Invalid COSE IV length")?, aad, plaintext);
+            cipher.wipe();
+            sealed
+        }
+        _ => Err("Not a content encryption algorithm"),
+    }
+}
+
+fn aead_open(alg: CoseAlgorithm, key: &[u8], iv: &[u8], aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>, &'static str> {
+    if iv.len() != alg.iv_len()? || sealed.len() < AEAD_TAG_LEN {
+        return Err("Malformed COSE ciphertext");
+    }
+    let (ciphertext, tag) = sealed.split_at(sealed.len() - AEAD_TAG_LEN);
+    let opened = match alg {
+        CoseAlgorithm::A256Gcm => GCMMode::new(&AesCipher::new(key)?, iv.to_vec(), aad.to_vec()).decrypt_and_verify(ciphertext, tag),
+        CoseAlgorithm::AesCcm16_128_256 => {
+            CCMMode::new(&AesCipher::new(key)?, iv.to_vec(), aad.to_vec(), AEAD_TAG_LEN).decrypt_and_verify(ciphertext, tag)
+        }
+        CoseAlgorithm::ChaCha20Poly1305 => {
+            let mut cipher = ChaChaQuantum::new();
+            cipher.initialize(key, None);
+            let opened = cipher.open_with_nonce(iv.try_into().map_err(|_| "Invalid COSE IV length")?, aad, sealed).ok();
+            cipher.wipe();
+            opened
+        }
+        _ => return Err("Not a content encryption algorithm"),
+    };
+    opened.ok_or("COSE decryption failed")
+}
+
+fn header_map(entries: Vec<(i64, Cbor)>) -> Cbor {
+    Cbor::Map(entries.into_iter().map(|(label, value)| (Cbor::Int(label), value)).collect())
+}
+
+/// Serialized protected header; an empty map is sent as a zero-length string (RFC 9052 §3).
+fn protected_bytes(entries: Vec<(i64, Cbor)>) -> Vec<u8> {
+    match entries.is_empty() {
+        true => Vec::new(),
+        false => header_map(entries).encode(),
+    }
+}
+
+fn decode_protected(bytes: &[u8]) -> Result<Cbor, &'static str> {
+    match bytes.is_empty() {
+        true => Ok(Cbor::Map(Vec::new())),
+        false => Cbor::decode(bytes).and_then(|map| map.as_map().map(|_| map.clone()).ok_or("Protected header must be a map")),
+    }
+}
+
+fn kid_entry(key: &CoseKey) -> Vec<(i64, Cbor)> {
+    key.kid.iter().map(|kid| (HEADER_KID, Cbor::Bytes(kid.clone()))).collect()
+}
+
+/// Split a COSE message into its array items after checking the tag and length.
+fn message_items(message: &[u8], tag: u64, len: usize) -> Result<Vec<Cbor>, &'static str> {
+    match Cbor::decode(message)?.untag(tag) {
+        Cbor::Array(items) if items.len() == len => Ok(items.clone()),
+        _ => Err("Malformed COSE message"),
+    }
+}
+
+fn item_bytes(item: &Cbor) -> Result<&[u8], &'static str> {
+    item.as_bytes().ok_or("Malformed COSE message")
+}
+
+/// The algorithm from the protected header (content layer) or, failing that, the unprotected one.
+fn message_alg(protected: &Cbor, unprotected: &Cbor) -> Result<CoseAlgorithm, &'static str> {
+    if unprotected.as_map().is_none() {
+        return Err("Unprotected header must be a map");
+    }
+    let alg = protected.get(HEADER_ALG).or_else(|| unprotected.get(HEADER_ALG));
+    CoseAlgorithm::from_id(alg.and_then(Cbor::as_int).ok_or("Missing COSE alg")?)
+}
+
+/// Enc_structure, MAC_structure and Sig_structure all share this shape.
+fn to_be_authenticated(context: &str, protected: &[u8], external_aad: &[u8], payload: Option<&[u8]>) -> Vec<u8> {
+    let mut items = vec![Cbor::Text(context.to_string()), Cbor::Bytes(protected.to_vec()), Cbor::Bytes(external_aad.to_vec())];
+    items.extend(payload.map(|payload| Cbor::Bytes(payload.to_vec())));
+    Cbor::Array(items).encode()
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>, &'static str> {
+    let mut bytes = vec![0u8; len];
+    getrandom::getrandom(&mut bytes).map_err(|_| "Random generation failed")?;
+    Ok(bytes)
+}
+
+/// Encrypt to a single pre-shared key as a tagged COSE_Encrypt0.
+pub fn encrypt0(key: &CoseKey, alg: CoseAlgorithm, plaintext: &[u8], external_aad: &[u8]) -> Result<Vec<u8>, &'static str> {
+    check_key_alg(key, alg)?;
+    let iv = random_bytes(alg.iv_len()?)?;
+    let protected = protected_bytes(vec![(HEADER_ALG, Cbor::Int(alg.id()))]);
+    let mut unprotected = kid_entry(key);
+    unprotected.push((HEADER_IV, Cbor::Bytes(iv.clone())));
+
+    let aad = to_be_authenticated("Encrypt0", &protected, external_aad, None);
+    let ciphertext = aead_seal(alg, symmetric_key(key, Some(CEK_LEN))?, &iv, &aad, plaintext)?;
+    let message = Cbor::Array(vec![Cbor::Bytes(protected), header_map(unprotected), Cbor::Bytes(ciphertext)]);
+    Ok(Cbor::Tag(TAG_ENCRYPT0, Box::new(message)).encode())
+}
+
+pub fn decrypt0(key: &CoseKey, message: &[u8], external_aad: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let items = message_items(message, TAG_ENCRYPT0, 3)?;
+    let protected = item_bytes(&items[0])?;
+    let alg = message_alg(&decode_protected(protected)?, &items[1])?;
+    check_key_alg(key, alg)?;
+    let iv = items[1].get(HEADER_IV).and_then(Cbor::as_bytes).ok_or("Missing COSE IV")?;
+    let aad = to_be_authenticated("Encrypt0", protected, external_aad, None);
+    aead_open(alg, symmetric_key(key, Some(CEK_LEN))?, iv, &aad, item_bytes(&items[2])?)
+}
+
+/// Build one COSE_recipient, replacing `cek` with the key itself for direct recipients.
+fn seal_recipient(key: &CoseKey, alg: CoseAlgorithm, cek: &mut Vec<u8>) -> Result<Cbor, &'static str> {
+    check_key_alg(key, alg)?;
+    let mut unprotected = vec![(HEADER_ALG, Cbor::Int(alg.id()))];
+    unprotected.extend(kid_entry(key));
+
+    let encrypted_key = match alg {
+        CoseAlgorithm::Direct => {
+            *cek = symmetric_key(key, Some(CEK_LEN))?.to_vec();
+            Vec::new()
+        }
+        CoseAlgorithm::A256Kw => KeyWrap::new(&AesCipher::new(symmetric_key(key, Some(32))?)?).wrap(cek)?,
+        #[cfg(feature = "ml_kem")]
+        CoseAlgorithm::MlKem768A256Kw => {
+            let CoseKeyMaterial::Akp { public, .. } = &key.material else {
+                return Err("ML-KEM recipients require an AKP key");
+            };
+            let mut hybrid = HybridLatticeCipher::new();
+            unprotected.push((HEADER_KEM_CIPHERTEXT, Cbor::Bytes(hybrid.encapsulate_key(public))));
+            let kek = hybrid.export_key(ML_KEM_EXPORT_PURPOSE, 32);
+            hybrid.wipe();
+            let mut kek = kek?;
+            let wrapped = KeyWrap::new(&AesCipher::new(&kek)?).wrap(cek);
+            kek.zeroize();
+            wrapped?
+        }
+        _ => return Err("Not a key management algorithm"),
+    };
+    Ok(Cbor::Array(vec![Cbor::Bytes(Vec::new()), header_map(unprotected), Cbor::Bytes(encrypted_key)]))
+}
+
+fn open_recipient(key: &CoseKey, recipient: &Cbor) -> Result<Vec<u8>, &'static str> {
+    let items = match recipient.as_array() {
+        Some(items) if items.len() == 3 => items,
+        _ => return Err("Malformed COSE recipient"),
+    };
+    let alg = message_alg(&decode_protected(item_bytes(&items[0])?)?, &items[1])?;
+    check_key_alg(key, alg)?;
+    let encrypted_key = item_bytes(&items[2])?;
+
+    match alg {
+        CoseAlgorithm::Direct if encrypted_key.is_empty() => Ok(symmetric_key(key, Some(CEK_LEN))?.to_vec()),
+        CoseAlgorithm::A256Kw => KeyWrap::new(&AesCipher::new(symmetric_key(key, Some(32))?)?).unwrap(encrypted_key),
+        #[cfg(feature = "ml_kem")]
+        CoseAlgorithm::MlKem768A256Kw => {
+            let kem_ciphertext = items[1].get(HEADER_KEM_CIPHERTEXT).and_then(Cbor::as_bytes).ok_or("Missing ML-KEM ciphertext")?;
+            let mut hybrid = HybridLatticeCipher::with_keypair(key.to_lattice()?);
+            hybrid.decapsulate_key(kem_ciphertext);
+            let kek = hybrid.export_key(ML_KEM_EXPORT_PURPOSE, 32);
+            hybrid.wipe();
+            let mut kek = kek?;
+            let cek = KeyWrap::new(&AesCipher::new(&kek)?).unwrap(encrypted_key);
+            kek.zeroize();
+            cek
+        }
+        _ => Err("Unsupported COSE recipient"),
+    }
+}
+
+/// Encrypt to one or more recipients as a tagged COSE_Encrypt. A direct recipient must be
+/// the only one, since its key becomes the content key.
+pub fn encrypt(recipients: &[(&CoseKey, CoseAlgorithm)], alg: CoseAlgorithm, plaintext: &[u8], external_aad: &[u8]) -> Result<Vec<u8>, &'static str> {
+    if recipients.is_empty() {
+        return Err("At least one recipient required");
+    }
+    if recipients.len() > 1 && recipients.iter().any(|(_, recipient_alg)| *recipient_alg == CoseAlgorithm::Direct) {
+        return Err("Direct recipients cannot be combined with others");
+    }
+
+    let mut cek = random_bytes(CEK_LEN)?;
+    let mut entries = Vec::with_capacity(recipients.len());
+    for (key, recipient_alg) in recipients {
+        match seal_recipient(key, *recipient_alg, &mut cek) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => {
+                cek.zeroize();
+                return Err(e);
+            }
+        }
+    }
+
+    let iv = random_bytes(alg.iv_len()?)?;
+    let protected = protected_bytes(vec![(HEADER_ALG, Cbor::Int(alg.id()))]);
+    let aad = to_be_authenticated("Encrypt", &protected, external_aad, None);
+    let sealed = aead_seal(alg, &cek, &iv, &aad, plaintext);
+    cek.zeroize();
+
+    let message = Cbor::Array(vec![
+        Cbor::Bytes(protected),
+        header_map(vec![(HEADER_IV, Cbor::Bytes(iv))]),
+        Cbor::Bytes(sealed?),
+        Cbor::Array(entries),
+    ]);
+    Ok(Cbor::Tag(TAG_ENCRYPT, Box::new(message)).encode())
+}
+
+/// Decrypt a COSE_Encrypt with `key`, trying each recipient whose kid matches.
+pub fn decrypt(key: &CoseKey, message: &[u8], external_aad: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let items = message_items(message, TAG_ENCRYPT, 4)?;
+    let protected = item_bytes(&items[0])?;
+    let alg = message_alg(&decode_protected(protected)?, &items[1])?;
+    let iv = items[1].get(HEADER_IV).and_then(Cbor::as_bytes).ok_or("Missing COSE IV")?;
+    let aad = to_be_authenticated("Encrypt", protected, external_aad, None);
+    let ciphertext = item_bytes(&items[2])?;
+
+    let mut last_error = "No recipient matched this key";
+    for recipient in items[3].as_array().ok_or("Malformed COSE recipients")? {
+        let recipient_kid = recipient.as_array().and_then(|r| r.get(1)).and_then(|h| h.get(HEADER_KID)).and_then(Cbor::as_bytes);
+        if let (Some(kid), Some(wanted)) = (recipient_kid, &key.kid) {
+            if kid != wanted.as_slice() {
+                continue;
+            }
+        }
+        match open_recipient(key, recipient) {
+            Ok(mut cek) => {
+                let opened = aead_open(alg, &cek, iv, &aad, ciphertext);
+                cek.zeroize();
+                match opened {
+                    Ok(plaintext) => return Ok(plaintext),
+                    Err(e) => last_error = e,
+                }
+            }
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+fn mac_key(key: &CoseKey) -> Result<&[u8], &'static str> {
+    let k = symmetric_key(key, None)?;
+    if k.len() < MIN_HMAC_KEY_LEN {
+        return Err("HMAC requires a key of at least 256 bits");
+    }
+    Ok(k)
+}
+
+fn compute_mac(alg: CoseAlgorithm, key: &[u8], data: &[u8]) -> Result<Vec<u8>, &'static str> {
+    match alg {
+        CoseAlgorithm::HmacSha256 => Ok(HmacSha256::new(key).compute(data)),
+        CoseAlgorithm::HmacSha512 => Ok(HmacSha512::new(key).compute(data)),
+        _ => Err("Not a MAC algorithm"),
+    }
+}
+
+/// MAC `payload` with a pre-shared key as a tagged COSE_Mac0.
+pub fn mac0(key: &CoseKey, alg: CoseAlgorithm, payload: &[u8], external_aad: &[u8]) -> Result<Vec<u8>, &'static str> {
+    check_key_alg(key, alg)?;
+    let protected = protected_bytes(vec![(HEADER_ALG, Cbor::Int(alg.id()))]);
+    let tag = compute_mac(alg, mac_key(key)?, &to_be_authenticated("MAC0", &protected, external_aad, Some(payload)))?;
+    let message = Cbor::Array(vec![Cbor::Bytes(protected), header_map(kid_entry(key)), Cbor::Bytes(payload.to_vec()), Cbor::Bytes(tag)]);
+    Ok(Cbor::Tag(TAG_MAC0, Box::new(message)).encode())
+}
+
+/// Verify a COSE_Mac0 and return its payload.
+pub fn verify_mac0(key: &CoseKey, message: &[u8], external_aad: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let items = message_items(message, TAG_MAC0, 4)?;
+    let protected = item_bytes(&items[0])?;
+    let alg = message_alg(&decode_protected(protected)?, &items[1])?;
+    check_key_alg(key, alg)?;
+    let payload = item_bytes(&items[2])?;
+    let data = to_be_authenticated("MAC0", protected, external_aad, Some(payload));
+
+    let expected = compute_mac(alg, mac_key(key)?, &data)?;
+    let tag = item_bytes(&items[3])?;
+    match expected.len() == tag.len() && bool::from(expected.ct_eq(tag)) {
+        true => Ok(payload.to_vec()),
+        false => Err("COSE MAC verification failed"),
+    }
+}
+
+fn signature_alg(key: &CoseKey) -> Result<CoseAlgorithm, &'static str> {
+    let alg = match &key.material {
+        CoseKeyMaterial::Okp { crv: CRV_ED25519, .. } => CoseAlgorithm::EdDsa,
+        CoseKeyMaterial::Ec2 { .. } => CoseAlgorithm::Es256,
+        _ => return Err("Key type cannot be used for COSE signatures"),
+    };
+    check_key_alg(key, alg)?;
+    Ok(alg)
+}
+
+/// Sign `payload` as a tagged COSE_Sign1; the algorithm follows from the key type.
+pub fn sign1(key: &CoseKey, payload: &[u8], external_aad: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let alg = signature_alg(key)?;
+    let protected = protected_bytes(vec![(HEADER_ALG, Cbor::Int(alg.id()))]);
+    let data = to_be_authenticated("Signature1", &protected, external_aad, Some(payload));
+
+    let signature = match &key.material {
+        CoseKeyMaterial::Okp { d: Some(d), .. } => ed25519_dalek::SigningKey::from_bytes(d).sign(&data).to_bytes().to_vec(),
+        CoseKeyMaterial::Ec2 { d: Some(d), .. } => {
+            let signing = p256::ecdsa::SigningKey::from_slice(d).map_err(|_| "Invalid P-256 key")?;
+            let signature: p256::ecdsa::Signature = signing.sign(&data);
+            signature.to_bytes().to_vec()
+        }
+        _ => return Err("Signing requires a private key"),
+    };
+    let message = Cbor::Array(vec![Cbor::Bytes(protected), header_map(kid_entry(key)), Cbor::Bytes(payload.to_vec()), Cbor::Bytes(signature)]);
+    Ok(Cbor::Tag(TAG_SIGN1, Box::new(message)).encode())
+}
+
+/// Verify a COSE_Sign1 and return its payload. The header `alg` must match the key type.
+pub fn verify_sign1(key: &CoseKey, message: &[u8], external_aad: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let items = message_items(message, TAG_SIGN1, 4)?;
+    let protected = item_bytes(&items[0])?;
+    if message_alg(&decode_protected(protected)?, &items[1])? != signature_alg(key)? {
+        return Err("COSE alg does not match key");
+    }
+    let payload = item_bytes(&items[2])?;
+    let signature = item_bytes(&items[3])?;
+    let data = to_be_authenticated("Signature1", protected, external_aad, Some(payload));
+
+    let valid = match &key.material {
+        CoseKeyMaterial::Okp { x, .. } => match (ed25519_dalek::VerifyingKey::from_bytes(x), ed25519_dalek::Signature::from_slice(signature)) {
+            (Ok(verifying), Ok(signature)) => verifying.verify_strict(&data, &signature).is_ok(),
+            _ => false,
+        },
+        CoseKeyMaterial::Ec2 { x, y, .. } => {
+            let point = p256::EncodedPoint::from_affine_coordinates(x.into(), y.into(), false);
+            match (p256::ecdsa::VerifyingKey::from_encoded_point(&point), p256::ecdsa::Signature::from_slice(signature)) {
+                (Ok(verifying), Ok(signature)) => verifying.verify(&data, &signature).is_ok(),
+                _ => false,
+            }
+        }
+        _ => false,
+    };
+    match valid {
+        true => Ok(payload.to_vec()),
+        false => Err("COSE signature verification failed"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt0_roundtrip() {
+        let key = CoseKey::symmetric(&[9; 32]).with_kid(b"k1");
+        for alg in [CoseAlgorithm::A256Gcm, CoseAlgorithm::AesCcm16_128_256, CoseAlgorithm::ChaCha20Poly1305] {
+            let message = encrypt0(&key, alg, b"payload", b"aad").unwrap();
+            assert_eq!(decrypt0(&key, &message, b"aad").unwrap(), b"payload");
+            assert!(decrypt0(&key, &message, b"other").is_err());
+            assert!(decrypt0(&CoseKey::symmetric(&[8; 32]), &message, b"aad").is_err());
+
+            let mut tampered = message.clone();
+            let last = tampered.len() - 1;
+            tampered[last] ^= 1;
+            assert_eq!(decrypt0(&key, &tampered, b"aad"), Err("COSE decryption failed"));
+        }
+        assert!(decrypt0(&key.clone().with_alg(CoseAlgorithm::A256Gcm.id()), &encrypt0(&key, CoseAlgorithm::ChaCha20Poly1305, b"", b"").unwrap(), b"").is_err());
+    }
+
+    #[test]
+    fn encrypt_to_several_recipients() {
+        let first = CoseKey::symmetric(&[1; 32]).with_kid(b"first");
+        let second = CoseKey::symmetric(&[2; 32]).with_kid(b"second");
+        let recipients = [(&first, CoseAlgorithm::A256Kw), (&second, CoseAlgorithm::A256Kw)];
+        let message = encrypt(&recipients, CoseAlgorithm::A256Gcm, b"payload", b"").unwrap();
+        assert_eq!(decrypt(&first, &message, b"").unwrap(), b"payload");
+        assert_eq!(decrypt(&second, &message, b"").unwrap(), b"payload");
+        assert!(decrypt(&CoseKey::symmetric(&[3; 32]), &message, b"").is_err());
+
+        let direct = [(&first, CoseAlgorithm::Direct), (&second, CoseAlgorithm::A256Kw)];
+        assert!(encrypt(&direct, CoseAlgorithm::A256Gcm, b"payload", b"").is_err());
+    }
+
+    #[test]
+    fn mac0_requires_a_256_bit_key() {
+        let key = CoseKey::symmetric(&[4; 32]);
+        for alg in [CoseAlgorithm::HmacSha256, CoseAlgorithm::HmacSha512] {
+            let message = mac0(&key, alg, b"payload", b"").unwrap();
+            assert_eq!(verify_mac0(&key, &message, b"").unwrap(), b"payload");
+            assert_eq!(verify_mac0(&CoseKey::symmetric(&[5; 32]), &message, b""), Err("COSE MAC verification failed"));
+        }
+        let short = CoseKey::symmetric(&[4; 31]);
+        assert_eq!(mac0(&short, CoseAlgorithm::HmacSha256, b"payload", b""), Err("HMAC requires a key of at least 256 bits"));
+        let message = mac0(&key, CoseAlgorithm::HmacSha256, b"payload", b"").unwrap();
+        assert_eq!(verify_mac0(&short, &message, b""), Err("HMAC requires a key of at least 256 bits"));
+    }
+
+    #[test]
+    fn sign1_roundtrip() {
+        for key in [CoseKey::generate_ed25519().unwrap(), CoseKey::generate_p256().unwrap()] {
+            let message = sign1(&key, b"payload", b"aad").unwrap();
+            let public = key.to_public().unwrap();
+            assert_eq!(verify_sign1(&public, &message, b"aad").unwrap(), b"payload");
+            assert!(verify_sign1(&public, &message, b"other").is_err());
+            assert!(sign1(&public, b"payload", b"").is_err());
+        }
+        let message = sign1(&CoseKey::generate_ed25519().unwrap(), b"payload", b"").unwrap();
+        assert_eq!(verify_sign1(&CoseKey::generate_p256().unwrap(), &message, b""), Err("COSE alg does not match key"));
+    }
+
+    #[cfg(feature = "ml_kem")]
+    #[test]
+    fn ml_kem_recipient() {
+        use crate::core::postquantum::lattice_kem::LatticeKEM;
+        let key = CoseKey::from_lattice(&LatticeKEM::keypair()).with_kid(b"pq");
+        let recipients = [(&key.to_public().unwrap(), CoseAlgorithm::MlKem768A256Kw)];
+        let message = encrypt(&recipients, CoseAlgorithm::ChaCha20Poly1305, b"payload", b"").unwrap();
+        assert_eq!(decrypt(&key, &message, b"").unwrap(), b"payload");
+    }
+}
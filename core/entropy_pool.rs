@@ -5,6 +5,7 @@
 
 use rand::rngs::OsRng;
 use rand::RngCore;
+use crate::core::kdf::hkdf::{hkdf, HkdfHash};
 
 #[cfg(feature = "hw_trng")]
 use crate::hw::trng::HardwareTrng;
@@ -31,15 +32,9 @@ impl EntropyPool {
         EntropyPool { buffer }
     }
 
+    /// Derive `output_size` bytes of seed material via HKDF-SHA-512 (up to 16320 bytes).
     pub fn derive_seed(&self, output_size: usize) -> Vec<u8> {
-        use sha2::{Digest, Sha512};
-
-        let mut seed = Vec::with_capacity(output_size);
-        let mut hasher = Sha512::new();
-        hasher.update(&self.buffer);
-        let hash = hasher.finalize();
-
-        seed.extend_from_slice(&hash[..output_size.min(hash.len())]);
-        seed
+        hkdf(HkdfHash::Sha512, None, &self.buffer, b"IX-EntropyPool-v1 seed", output_size)
+            .expect("Seed length exceeds HKDF-SHA-512 limit")
     }
 }
@@ -0,0 +1,318 @@
+// ix-encryption/core/envelope/envelope_format.rs
+
+//! Envelope encryption: a fresh 32-byte data key per message encrypts the payload locally with
+//! `ChaChaQuantum` or AES-256-GCM, and a `KeyEncryptionProvider` wraps the data key. The header
+//! records which provider, KEK id and KEK version wrapped it, so `decrypt` can route to the
+//! right provider and `rewrap` can move a message to a new KEK without touching the payload.
+//!
+//! Layout: "IXEV" | version | cipher (u8) | provider len (u8) | provider | key id len (u8)
+//!         | key id | kek version (u32) | wrapped len (u16) | wrapped key | nonce (12)
+//!         | ciphertext || tag (16)
+//!
+//! The payload AAD is magic | version | cipher | nonce | caller AAD. The provider binds the
+//! wrapped key to magic | version | cipher | provider | caller AAD plus its own key id and version.
+
+use zeroize::Zeroize;
+use crate::core::IXCipherCore;
+use crate::core::byte_reader::ByteReader;
+use crate::core::ciphers::aes::AesCipher;
+use crate::core::envelope::provider::{KeyEncryptionProvider, WrappedKey};
+use crate::core::hybrid::{ChaChaQuantum, NONCE_LEN};
+use crate::core::mode_gcm::{GCMMode, GCM_TAG_LEN};
+
+const MAGIC: &[u8; 4] = b"IXEV";
+pub const ENVELOPE_VERSION: u8 = 1;
+pub const DATA_KEY_LEN: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataCipher {
+    ChaChaQuantum = 1,
+    Aes256Gcm = 2,
+}
+
+impl DataCipher {
+    fn from_id(id: u8) -> Result<Self, &'static str> {
+        match id {
+            1 => Ok(DataCipher::ChaChaQuantum),
+            2 => Ok(DataCipher::Aes256Gcm),
+            _ => Err("Unsupported envelope data cipher"),
+        }
+    }
+}
+
+/// Parsed envelope header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnvelopeHeader {
+    pub cipher: DataCipher,
+    pub provider_id: String,
+    pub wrapped_key: WrappedKey,
+    pub nonce: [u8; NONCE_LEN],
+}
+
+impl EnvelopeHeader {
+    fn encode(&self) -> Result<Vec<u8>, &'static str> {
+        if self.provider_id.len() > 255 || self.wrapped_key.key_id.len() > 255 {
+            return Err("Provider or key id too long");
+        }
+        let wrapped_len = u16::try_from(self.wrapped_key.ciphertext.len()).map_err(|_| "Wrapped key too long")?;
+
+        let mut out = MAGIC.to_vec();
+        out.push(ENVELOPE_VERSION);
+        out.push(self.cipher as u8);
+        out.push(self.provider_id.len() as u8);
+        out.extend_from_slice(self.provider_id.as_bytes());
+        out.push(self.wrapped_key.key_id.len() as u8);
+        out.extend_from_slice(self.wrapped_key.key_id.as_bytes());
+        out.extend_from_slice(&self.wrapped_key.kek_version.to_be_bytes());
+        out.extend_from_slice(&wrapped_len.to_be_bytes());
+        out.extend_from_slice(&self.wrapped_key.ciphertext);
+        out.extend_from_slice(&self.nonce);
+        Ok(out)
+    }
+
+    /// Parse the header, returning it and its encoded length.
+    pub fn parse(envelope: &[u8]) -> Result<(Self, usize), &'static str> {
+        if envelope.len() < MAGIC.len() + 2 || &envelope[..4] != MAGIC {
+            return Err("Not an envelope");
+        }
+        if envelope[4] != ENVELOPE_VERSION {
+            return Err("Unsupported envelope version");
+        }
+        let cipher = DataCipher::from_id(envelope[5])?;
+
+        let mut reader = ByteReader::new(envelope, "Truncated envelope");
+        // Magic, version and cipher id, checked above
+        reader.take(MAGIC.len() + 2)?;
+        let provider_id = take_str(&mut reader)?;
+        let key_id = take_str(&mut reader)?;
+        let kek_version = reader.take_u32()?;
+        let wrapped_len = reader.take_u16()? as usize;
+        let ciphertext = reader.take(wrapped_len)?.to_vec();
+        let nonce = reader.take_array()?;
+
+        let wrapped_key = WrappedKey { key_id, kek_version, ciphertext };
+        Ok((Self { cipher, provider_id, wrapped_key, nonce }, reader.position()))
+    }
+
+    fn wrap_context(cipher: DataCipher, provider_id: &str, aad: &[u8]) -> Vec<u8> {
+        let mut context = MAGIC.to_vec();
+        context.extend_from_slice(&[ENVELOPE_VERSION, cipher as u8, provider_id.len() as u8]);
+        context.extend_from_slice(provider_id.as_bytes());
+        context.extend_from_slice(aad);
+        context
+    }
+
+    fn payload_aad(&self, aad: &[u8]) -> Vec<u8> {
+        let mut payload_aad = MAGIC.to_vec();
+        payload_aad.extend_from_slice(&[ENVELOPE_VERSION, self.cipher as u8]);
+        payload_aad.extend_from_slice(&self.nonce);
+        payload_aad.extend_from_slice(aad);
+        payload_aad
+    }
+}
+
+/// A string with a one-byte length prefix.
+fn take_str(reader: &mut ByteReader) -> Result<String, &'static str> {
+    let len = reader.take_u8()? as usize;
+    String::from_utf8(reader.take(len)?.to_vec()).map_err(|_| "Invalid envelope string")
+}
+
+fn seal_payload(cipher: DataCipher, data_key: &[u8], nonce: &[u8; NONCE_LEN], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, &'static str> {
+    match cipher {
+        DataCipher::ChaChaQuantum => {
+            let mut chacha = ChaChaQuantum::new();
+            chacha.initialize(data_key, None);
+            let sealed = chacha.seal_with_nonce(nonce, aad, plaintext);
+            chacha.wipe();
+            sealed
+        }
+        DataCipher::Aes256Gcm => {
+            let aes = AesCipher::new(data_key)?;
+            let (mut ciphertext, tag) = GCMMode::new(&aes, nonce.to_vec(), aad.to_vec()).encrypt_and_tag(plaintext);
+            ciphertext.extend_from_slice(&tag);
+            Ok(ciphertext)
+        }
+    }
+}
+
+fn open_payload(cipher: DataCipher, data_key: &[u8], nonce: &[u8; NONCE_LEN], aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>, &'static str> {
+    match cipher {
+        DataCipher::ChaChaQuantum => {
+            let mut chacha = ChaChaQuantum::new();
+            chacha.initialize(data_key, None);
+            let opened = chacha.open_with_nonce(nonce, aad, sealed);
+            chacha.wipe();
+            opened.map_err(|_| "Envelope authentication failed")
+        }
+        DataCipher::Aes256Gcm => {
+            if sealed.len() < GCM_TAG_LEN {
+                return Err("Envelope too short");
+            }
+            let (ciphertext, tag) = sealed.split_at(sealed.len() - GCM_TAG_LEN);
+            GCMMode::new(&AesCipher::new(data_key)?, nonce.to_vec(), aad.to_vec())
+                .decrypt_and_verify(ciphertext, tag)
+                .ok_or("Envelope authentication failed")
+        }
+    }
+}
+
+/// Encrypt `plaintext` under a fresh data key wrapped by `provider` with KEK `key_id`.
+pub fn encrypt(provider: &dyn KeyEncryptionProvider, key_id: &str, cipher: DataCipher, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let mut data_key = [0u8; DATA_KEY_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut data_key).map_err(|_| "Data key generation failed")?;
+    getrandom::getrandom(&mut nonce).map_err(|_| "Nonce generation failed")?;
+
+    let provider_id = provider.provider_id().to_string();
+    let header = match provider.wrap(key_id, &data_key, &EnvelopeHeader::wrap_context(cipher, &provider_id, aad)) {
+        Ok(wrapped_key) => EnvelopeHeader { cipher, provider_id, wrapped_key, nonce },
+        Err(e) => {
+            data_key.zeroize();
+            return Err(e);
+        }
+    };
+    let sealed = seal_payload(cipher, &data_key, &nonce, &header.payload_aad(aad), plaintext);
+    data_key.zeroize();
+
+    let mut out = header.encode()?;
+    out.extend_from_slice(&sealed?);
+    Ok(out)
+}
+
+fn unwrap_data_key(providers: &[&dyn KeyEncryptionProvider], header: &EnvelopeHeader, aad: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let provider = providers
+        .iter()
+        .find(|provider| provider.provider_id() == header.provider_id)
+        .ok_or("No provider for this envelope")?;
+    let mut data_key = provider.unwrap(&header.wrapped_key, &EnvelopeHeader::wrap_context(header.cipher, &header.provider_id, aad))?;
+    if data_key.len() != DATA_KEY_LEN {
+        data_key.zeroize();
+        return Err("Invalid data key length");
+    }
+    Ok(data_key)
+}
+
+/// Decrypt an envelope, unwrapping its data key with whichever of `providers` the header names.
+pub fn decrypt(providers: &[&dyn KeyEncryptionProvider], envelope: &[u8], aad: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let (header, header_len) = EnvelopeHeader::parse(envelope)?;
+    let mut data_key = unwrap_data_key(providers, &header, aad)?;
+    let plaintext = open_payload(header.cipher, &data_key, &header.nonce, &header.payload_aad(aad), &envelope[header_len..]);
+    data_key.zeroize();
+    plaintext
+}
+
+/// Rewrap the data key of `envelope` under `to` / `key_id` (e.g. after KEK rotation or a move
+/// between providers). The payload is copied unchanged.
+pub fn rewrap(
+    providers: &[&dyn KeyEncryptionProvider],
+    envelope: &[u8],
+    aad: &[u8],
+    to: &dyn KeyEncryptionProvider,
+    key_id: &str,
+) -> Result<Vec<u8>, &'static str> {
+    let (header, header_len) = EnvelopeHeader::parse(envelope)?;
+    let mut data_key = unwrap_data_key(providers, &header, aad)?;
+
+    let provider_id = to.provider_id().to_string();
+    let wrapped = to.wrap(key_id, &data_key, &EnvelopeHeader::wrap_context(header.cipher, &provider_id, aad));
+    data_key.zeroize();
+
+    let rewrapped = EnvelopeHeader { provider_id, wrapped_key: wrapped?, ..header };
+    let mut out = rewrapped.encode()?;
+    out.extend_from_slice(&envelope[header_len..]);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::envelope::provider::{LocalFileProvider, MockKms};
+    use crate::core::kdf::password::PasswordKdf;
+    use crate::core::keystore::{KeyUsage, Keystore, Master};
+
+    const AAD: &[u8] = b"tenant=42";
+
+    fn kms() -> MockKms {
+        let mut kms = MockKms::new();
+        kms.create_key("kek").unwrap();
+        kms
+    }
+
+    /// A provider over a keystore that is never saved, so nothing touches the disk.
+    fn local_provider() -> LocalFileProvider {
+        let mut suffix = [0u8; 8];
+        getrandom::getrandom(&mut suffix).unwrap();
+        let path = std::env::temp_dir().join(format!("ix-envelope-{:016x}.ks", u64::from_be_bytes(suffix)));
+        let keystore = Keystore::create(path, &Master::Key(&[9; 32]), PasswordKdf::Pbkdf2Sha256 { iterations: 1000 }).unwrap();
+        let mut provider = LocalFileProvider::new(keystore);
+        provider.keystore().generate_symmetric("local-kek", "AES-256", 32, KeyUsage::WRAP | KeyUsage::UNWRAP, None).unwrap();
+        provider
+    }
+
+    #[test]
+    fn roundtrip_with_both_ciphers() {
+        let kms = kms();
+        for cipher in [DataCipher::ChaChaQuantum, DataCipher::Aes256Gcm] {
+            let envelope = encrypt(&kms, "kek", cipher, b"payload", AAD).unwrap();
+            let (header, _) = EnvelopeHeader::parse(&envelope).unwrap();
+            assert_eq!((header.cipher, header.provider_id.as_str()), (cipher, MockKms::PROVIDER_ID));
+            assert_eq!((header.wrapped_key.key_id.as_str(), header.wrapped_key.kek_version), ("kek", 1));
+            assert_eq!(decrypt(&[&kms], &envelope, AAD).unwrap(), b"payload");
+            assert_eq!(decrypt(&[&kms], &encrypt(&kms, "kek", cipher, b"", AAD).unwrap(), AAD).unwrap(), b"");
+        }
+        assert!(encrypt(&kms, "missing", DataCipher::Aes256Gcm, b"payload", AAD).is_err());
+    }
+
+    #[test]
+    fn rejects_tampering() {
+        let kms = kms();
+        for cipher in [DataCipher::ChaChaQuantum, DataCipher::Aes256Gcm] {
+            let envelope = encrypt(&kms, "kek", cipher, b"payload", AAD).unwrap();
+            let (_, header_len) = EnvelopeHeader::parse(&envelope).unwrap();
+            assert!(decrypt(&[&kms], &envelope, b"tenant=43").is_err());
+            for index in [5, header_len - NONCE_LEN - 1, header_len - 1, header_len, envelope.len() - 1] {
+                let mut tampered = envelope.clone();
+                tampered[index] ^= 1;
+                assert!(decrypt(&[&kms], &tampered, AAD).is_err(), "byte {index}");
+            }
+            assert!(decrypt(&[&kms], &envelope[..header_len + 8], AAD).is_err());
+            assert!(decrypt(&[&kms], &envelope[..header_len - 1], AAD).is_err());
+        }
+    }
+
+    #[test]
+    fn unwraps_old_kek_versions_until_disabled() {
+        let mut kms = kms();
+        let old = encrypt(&kms, "kek", DataCipher::Aes256Gcm, b"payload", AAD).unwrap();
+        assert_eq!(kms.rotate("kek").unwrap(), 2);
+        let new = encrypt(&kms, "kek", DataCipher::Aes256Gcm, b"payload", AAD).unwrap();
+        assert_eq!(EnvelopeHeader::parse(&new).unwrap().0.wrapped_key.kek_version, 2);
+        assert_eq!(decrypt(&[&kms], &old, AAD).unwrap(), b"payload");
+
+        let rewrapped = rewrap(&[&kms], &old, AAD, &kms, "kek").unwrap();
+        assert_eq!(EnvelopeHeader::parse(&rewrapped).unwrap().0.wrapped_key.kek_version, 2);
+
+        kms.set_enabled("kek", 1, false).unwrap();
+        assert_eq!(decrypt(&[&kms], &old, AAD).err(), Some("KMS key version is disabled"));
+        assert_eq!(decrypt(&[&kms], &new, AAD).unwrap(), b"payload");
+        assert_eq!(decrypt(&[&kms], &rewrapped, AAD).unwrap(), b"payload");
+    }
+
+    #[test]
+    fn rewrap_between_providers() {
+        let kms = kms();
+        let local = local_provider();
+        let envelope = encrypt(&kms, "kek", DataCipher::ChaChaQuantum, b"payload", AAD).unwrap();
+        let (_, header_len) = EnvelopeHeader::parse(&envelope).unwrap();
+
+        let moved = rewrap(&[&kms], &envelope, AAD, &local, "local-kek").unwrap();
+        let (header, moved_header_len) = EnvelopeHeader::parse(&moved).unwrap();
+        assert_eq!(header.provider_id, LocalFileProvider::PROVIDER_ID);
+        assert_eq!(moved[moved_header_len..], envelope[header_len..]);
+
+        assert_eq!(decrypt(&[&local], &moved, AAD).unwrap(), b"payload");
+        assert_eq!(decrypt(&[&kms, &local], &moved, AAD).unwrap(), b"payload");
+        assert_eq!(decrypt(&[&kms], &moved, AAD).err(), Some("No provider for this envelope"));
+        assert!(rewrap(&[&kms], &envelope, b"other", &local, "local-kek").is_err());
+    }
+}
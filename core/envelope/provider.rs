@@ -0,0 +1,201 @@
+// ix-encryption/core/envelope/provider.rs
+
+//! Key-encryption providers for envelope encryption. A provider holds key-encryption keys
+//! (KEKs) under string ids with numbered versions and wraps locally generated data keys; the
+//! KEK never leaves the provider. `LocalFileProvider` keeps KEKs in an encrypted `Keystore`
+//! file; `MockKms` is an in-process stand-in for a remote KMS.
+//!
+//! Both built-in providers seal the data key with `ChaChaQuantum` under the KEK version, with
+//! the key id, version and caller context as associated data.
+//! Wrapped key: nonce (12) | sealed data key
+
+use std::collections::HashMap;
+use zeroize::Zeroize;
+use crate::core::IXCipherCore;
+use crate::core::hybrid::{ChaChaQuantum, NONCE_LEN};
+use crate::core::keystore::{KeyKind, KeyUsage, Keystore, Master};
+
+pub const KEK_LEN: usize = 32;
+
+/// A data key wrapped by a provider, with the KEK id and version needed to unwrap it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WrappedKey {
+    pub key_id: String,
+    pub kek_version: u32,
+    pub ciphertext: Vec<u8>,
+}
+
+/// Wraps and unwraps data keys under KEKs it holds.
+pub trait KeyEncryptionProvider {
+    /// Identifier recorded in envelope headers to route decryption back to this provider.
+    fn provider_id(&self) -> &str;
+    /// Wrap `data_key` under the current version of `key_id`, bound to `context`.
+    fn wrap(&self, key_id: &str, data_key: &[u8], context: &[u8]) -> Result<WrappedKey, &'static str>;
+    /// Unwrap with the KEK version recorded in `wrapped`; `context` must match the one used to wrap.
+    fn unwrap(&self, wrapped: &WrappedKey, context: &[u8]) -> Result<Vec<u8>, &'static str>;
+}
+
+/// Associated data binding a wrapped data key to its KEK id and version and the caller context.
+pub(crate) fn wrap_aad(key_id: &str, kek_version: u32, context: &[u8]) -> Vec<u8> {
+    let mut aad = vec![key_id.len() as u8];
+    aad.extend_from_slice(key_id.as_bytes());
+    aad.extend_from_slice(&kek_version.to_be_bytes());
+    aad.extend_from_slice(context);
+    aad
+}
+
+fn seal_data_key(kek: &[u8], key_id: &str, kek_version: u32, data_key: &[u8], context: &[u8]) -> Result<WrappedKey, &'static str> {
+    if key_id.len() > 255 {
+        return Err("Key id too long");
+    }
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut nonce).map_err(|_| "Nonce generation failed")?;
+
+    let mut cipher = ChaChaQuantum::new();
+    cipher.initialize(kek, None);
+    let sealed = cipher.seal_with_nonce(&nonce, &wrap_aad(key_id, kek_version, context), data_key);
+    cipher.wipe();
+
+    let mut ciphertext = nonce.to_vec();
+    ciphertext.extend_from_slice(&sealed?);
+    Ok(WrappedKey { key_id: key_id.to_string(), kek_version, ciphertext })
+}
+
+fn open_data_key(kek: &[u8], wrapped: &WrappedKey, context: &[u8]) -> Result<Vec<u8>, &'static str> {
+    if wrapped.ciphertext.len() < NONCE_LEN {
+        return Err("Wrapped key too short");
+    }
+    let (nonce, sealed) = wrapped.ciphertext.split_at(NONCE_LEN);
+
+    let mut cipher = ChaChaQuantum::new();
+    cipher.initialize(kek, None);
+    let data_key = cipher.open_with_nonce(nonce.try_into().unwrap(), &wrap_aad(&wrapped.key_id, wrapped.kek_version, context), sealed);
+    cipher.wipe();
+    data_key.map_err(|_| "Data key unwrap failed")
+}
+
+// ---- Local keystore file ----
+
+/// KEKs held as 32-byte symmetric entries of a `Keystore`. Wrapping uses the active version and
+/// needs `KeyUsage::WRAP`; unwrapping accepts decrypt-only versions and needs `KeyUsage::UNWRAP`.
+pub struct LocalFileProvider {
+    keystore: Keystore,
+}
+
+impl LocalFileProvider {
+    pub const PROVIDER_ID: &'static str = "local-file";
+
+    pub fn new(keystore: Keystore) -> Self {
+        Self { keystore }
+    }
+
+    pub fn open(path: impl AsRef<std::path::Path>, master: &Master) -> Result<Self, &'static str> {
+        Keystore::open(path, master).map(Self::new)
+    }
+
+    /// The underlying keystore, e.g. to generate or rotate KEKs before saving.
+    pub fn keystore(&mut self) -> &mut Keystore {
+        &mut self.keystore
+    }
+}
+
+impl KeyEncryptionProvider for LocalFileProvider {
+    fn provider_id(&self) -> &str {
+        Self::PROVIDER_ID
+    }
+
+    fn wrap(&self, key_id: &str, data_key: &[u8], context: &[u8]) -> Result<WrappedKey, &'static str> {
+        let entry = self.keystore.active(key_id)?;
+        entry.require(KeyUsage::WRAP)?;
+        if entry.metadata.kind != KeyKind::Symmetric || entry.secret()?.len() != KEK_LEN {
+            return Err("Key-encryption keys must be 32-byte symmetric keys");
+        }
+        seal_data_key(entry.secret()?, key_id, entry.metadata.version, data_key, context)
+    }
+
+    fn unwrap(&self, wrapped: &WrappedKey, context: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let entry = self.keystore.get(&wrapped.key_id, wrapped.kek_version)?;
+        entry.require(KeyUsage::UNWRAP)?;
+        open_data_key(entry.secret()?, wrapped, context)
+    }
+}
+
+// ---- In-process mock KMS ----
+
+/// In-memory KMS for tests and local development. Each key id holds a list of KEK versions;
+/// versions can be disabled to simulate revoked access.
+#[derive(Default)]
+pub struct MockKms {
+    keys: HashMap<String, Vec<MockKek>>,
+}
+
+struct MockKek {
+    key: [u8; KEK_LEN],
+    enabled: bool,
+}
+
+impl Drop for MockKek {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+impl MockKms {
+    pub const PROVIDER_ID: &'static str = "mock-kms";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create `key_id` with a random first version, or add a version if it exists.
+    /// Returns the new version number.
+    pub fn create_key(&mut self, key_id: &str) -> Result<u32, &'static str> {
+        let mut key = [0u8; KEK_LEN];
+        getrandom::getrandom(&mut key).map_err(|_| "Random generation failed")?;
+        let versions = self.keys.entry(key_id.to_string()).or_default();
+        versions.push(MockKek { key, enabled: true });
+        key.zeroize();
+        Ok(versions.len() as u32)
+    }
+
+    pub fn rotate(&mut self, key_id: &str) -> Result<u32, &'static str> {
+        match self.keys.contains_key(key_id) {
+            true => self.create_key(key_id),
+            false => Err("Unknown KMS key id"),
+        }
+    }
+
+    pub fn set_enabled(&mut self, key_id: &str, version: u32, enabled: bool) -> Result<(), &'static str> {
+        self.version_mut(key_id, version)?.enabled = enabled;
+        Ok(())
+    }
+
+    fn version(&self, key_id: &str, version: u32) -> Result<&MockKek, &'static str> {
+        let index = version.checked_sub(1).ok_or("Unknown KMS key version")? as usize;
+        let kek = self.keys.get(key_id).and_then(|versions| versions.get(index)).ok_or("Unknown KMS key version")?;
+        match kek.enabled {
+            true => Ok(kek),
+            false => Err("KMS key version is disabled"),
+        }
+    }
+
+    fn version_mut(&mut self, key_id: &str, version: u32) -> Result<&mut MockKek, &'static str> {
+        let index = version.checked_sub(1).ok_or("Unknown KMS key version")? as usize;
+        self.keys.get_mut(key_id).and_then(|versions| versions.get_mut(index)).ok_or("Unknown KMS key version")
+    }
+}
+
+impl KeyEncryptionProvider for MockKms {
+    fn provider_id(&self) -> &str {
+        Self::PROVIDER_ID
+    }
+
+    fn wrap(&self, key_id: &str, data_key: &[u8], context: &[u8]) -> Result<WrappedKey, &'static str> {
+        let version = self.keys.get(key_id).map(|versions| versions.len() as u32).ok_or("Unknown KMS key id")?;
+        seal_data_key(&self.version(key_id, version)?.key, key_id, version, data_key, context)
+    }
+
+    fn unwrap(&self, wrapped: &WrappedKey, context: &[u8]) -> Result<Vec<u8>, &'static str> {
+        open_data_key(&self.version(&wrapped.key_id, wrapped.kek_version)?.key, wrapped, context)
+    }
+}
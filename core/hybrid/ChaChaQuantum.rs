@@ -2,62 +2,158 @@
 
 //! Hybrid implementation using ChaCha20-Poly1305 with planned lattice augmentation.
 //! Designed for embedded, high-performance, and space-grade environments.
+//!
+//! Two variants are available:
+//! - `ChaChaVariant::Standard`: ChaCha20-Poly1305 with a 96-bit nonce fixed per instance.
+//! - `ChaChaVariant::Extended`: XChaCha20-Poly1305 (HChaCha20 subkey derivation) with a
+//!   fresh random 192-bit nonce per message, prepended to the ciphertext. Random nonces of
+//!   this size are safe to draw independently across many writers sharing one key.
 
 use chacha20poly1305::{
-    aead::{Aead, KeyInit, OsRng},
-    ChaCha20Poly1305, Key, Nonce,
+    aead::{Aead, KeyInit, OsRng, Payload},
+    ChaCha20Poly1305, Key, Nonce, XChaCha20Poly1305, XNonce,
 };
 use crate::core::IXCipherCore;
 
+/// Nonce length of the standard ChaCha20-Poly1305 variant.
+pub const NONCE_LEN: usize = 12;
+
+/// Nonce length of the extended XChaCha20-Poly1305 variant.
+pub const XNONCE_LEN: usize = 24;
+
+/// Selects the AEAD construction used by `ChaChaQuantum`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChaChaVariant {
+    Standard,
+    Extended,
+}
+
+enum ChaChaCipher {
+    Standard(ChaCha20Poly1305),
+    Extended(XChaCha20Poly1305),
+}
+
 pub struct ChaChaQuantum {
-    cipher: Option<ChaCha20Poly1305>,
-    nonce: [u8; 12],
+    variant: ChaChaVariant,
+    cipher: Option<ChaChaCipher>,
+    nonce: [u8; NONCE_LEN],
     lockdown_enabled: bool,
 }
 
 impl ChaChaQuantum {
     pub fn new() -> Self {
-        let mut nonce = [0u8; 12];
+        Self::with_variant(ChaChaVariant::Standard)
+    }
+
+    /// Creates an XChaCha20-Poly1305 instance using random 192-bit nonces per message.
+    pub fn new_extended() -> Self {
+        Self::with_variant(ChaChaVariant::Extended)
+    }
+
+    pub fn with_variant(variant: ChaChaVariant) -> Self {
+        let mut nonce = [0u8; NONCE_LEN];
         getrandom::getrandom(&mut nonce).expect("Nonce generation failed");
         Self {
+            variant,
             cipher: None,
             nonce,
             lockdown_enabled: false,
         }
     }
+
+    pub fn variant(&self) -> ChaChaVariant {
+        self.variant
+    }
+
+    /// Seal under a caller-chosen 96-bit nonce with associated data, for formats that define
+    /// their own nonce schedule (e.g. age stanzas, JWE). Standard variant only; the caller owns
+    /// nonce uniqueness.
+    pub fn seal_with_nonce(&self, nonce: &[u8; NONCE_LEN], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        match self.cipher.as_ref().ok_or("Cipher not initialized")? {
+            ChaChaCipher::Standard(cipher) => cipher
+                .encrypt(Nonce::from_slice(nonce), Payload { msg: plaintext, aad })
+                .map_err(|_| "Encryption failed"),
+            ChaChaCipher::Extended(_) => Err("Explicit nonces require the standard variant"),
+        }
+    }
+
+    pub fn open_with_nonce(&self, nonce: &[u8; NONCE_LEN], aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        match self.cipher.as_ref().ok_or("Cipher not initialized")? {
+            ChaChaCipher::Standard(cipher) => cipher
+                .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+                .map_err(|_| "Decryption failed"),
+            ChaChaCipher::Extended(_) => Err("Explicit nonces require the standard variant"),
+        }
+    }
 }
 
 impl IXCipherCore for ChaChaQuantum {
-    fn initialize(&mut self, key: &[u8], _salt: Option<&[u8]>) {
+    /// A 12-byte `salt` replaces the instance nonce of the standard variant, so a fresh instance
+    /// can decrypt when the nonce travels with the message; other salts are ignored.
+    fn initialize(&mut self, key: &[u8], salt: Option<&[u8]>) {
+        if let Some(nonce) = salt.and_then(|salt| <[u8; NONCE_LEN]>::try_from(salt).ok()) {
+            self.nonce = nonce;
+        }
         let key = Key::from_slice(&key[0..32]); // truncate or pad key externally
-        self.cipher = Some(ChaCha20Poly1305::new(key));
+        self.cipher = Some(match self.variant {
+            ChaChaVariant::Standard => ChaChaCipher::Standard(ChaCha20Poly1305::new(key)),
+            ChaChaVariant::Extended => ChaChaCipher::Extended(XChaCha20Poly1305::new(key)),
+        });
     }
 
     fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
-        let nonce = Nonce::from_slice(&self.nonce);
-        self.cipher
-            .as_ref()
-            .expect("Cipher not initialized")
-            .encrypt(nonce, plaintext)
-            .expect("Encryption failed")
+        match self.cipher.as_ref().expect("Cipher not initialized") {
+            ChaChaCipher::Standard(cipher) => {
+                let nonce = Nonce::from_slice(&self.nonce);
+                cipher.encrypt(nonce, plaintext).expect("Encryption failed")
+            }
+            ChaChaCipher::Extended(cipher) => {
+                let mut nonce = [0u8; XNONCE_LEN];
+                getrandom::getrandom(&mut nonce).expect("Nonce generation failed");
+                let sealed = cipher
+                    .encrypt(XNonce::from_slice(&nonce), plaintext)
+                    .expect("Encryption failed");
+
+                let mut output = Vec::with_capacity(XNONCE_LEN + sealed.len());
+                output.extend_from_slice(&nonce);
+                output.extend_from_slice(&sealed);
+                output
+            }
+        }
     }
 
     fn decrypt(&self, ciphertext: &[u8]) -> Vec<u8> {
-        let nonce = Nonce::from_slice(&self.nonce);
-        self.cipher
-            .as_ref()
-            .expect("Cipher not initialized")
-            .decrypt(nonce, ciphertext)
-            .expect("Decryption failed")
+        self.try_decrypt(ciphertext).expect("Decryption failed")
+    }
+
+    fn try_decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        match self.cipher.as_ref().ok_or("Cipher not initialized")? {
+            ChaChaCipher::Standard(cipher) => {
+                let nonce = Nonce::from_slice(&self.nonce);
+                cipher.decrypt(nonce, ciphertext).map_err(|_| "Decryption failed")
+            }
+            ChaChaCipher::Extended(cipher) => {
+                if ciphertext.len() < XNONCE_LEN {
+                    return Err("Ciphertext missing nonce");
+                }
+                let (nonce, sealed) = ciphertext.split_at(XNONCE_LEN);
+                cipher
+                    .decrypt(XNonce::from_slice(nonce), sealed)
+                    .map_err(|_| "Decryption failed")
+            }
+        }
     }
 
     fn wipe(&mut self) {
         self.cipher = None;
-        self.nonce = [0u8; 12];
+        self.nonce = [0u8; NONCE_LEN];
     }
 
     fn algorithm_id(&self) -> &'static str {
-        "IX-ChaChaQuantum-v1"
+        match self.variant {
+            ChaChaVariant::Standard => "IX-ChaChaQuantum-v1",
+            ChaChaVariant::Extended => "IX-XChaChaQuantum-v1",
+        }
     }
 
     fn trigger_lockdown(&self) -> bool {
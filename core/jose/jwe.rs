@@ -0,0 +1,635 @@
+// ix-encryption/core/jose/jwe.rs
+
+//! JSON Web Encryption (RFC 7516) in compact and JSON serialization.
+//! Key management: `dir`, `A256KW`, `ECDH-ES` (X25519 or P-256, Concat KDF per RFC 7518 §4.6,
+//! with `apu`/`apv` party info) and, with the `ml_kem` feature, the experimental
+//! `IX-ML-KEM-768-v1` / `IX-ML-KEM-768+A256KW-v1`, which carry the KEM ciphertext in an `ek`
+//! header parameter. Content encryption: `A256GCM` via `GCMMode` and `C20P`
+//! (ChaCha20-Poly1305) via `ChaChaQuantum`. Headers with `crit` are rejected.
+
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+use crate::core::IXCipherCore;
+use crate::core::ciphers::aes::AesCipher;
+use crate::core::hybrid::ChaChaQuantum;
+use crate::core::jose::jwk::{b64, p256_public, unb64, Jwk, JwkKey};
+#[cfg(feature = "ml_kem")]
+use crate::core::jose::jwk::ML_KEM_768;
+use crate::core::key_wrap::KeyWrap;
+use crate::core::mode_gcm::{GCMMode, GCM_TAG_LEN};
+#[cfg(feature = "ml_kem")]
+use crate::core::postquantum::hybrid_lattice::HybridLatticeCipher;
+#[cfg(feature = "ml_kem")]
+use crate::core::postquantum::lattice_kem::LatticeKEM;
+
+const CEK_LEN: usize = 32;
+const IV_LEN: usize = 12;
+#[cfg(feature = "ml_kem")]
+const ML_KEM_EXPORT_PURPOSE: &str = "IX-JOSE ML-KEM shared secret";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JweAlgorithm {
+    Dir,
+    A256Kw,
+    EcdhEs,
+    /// Experimental: direct key agreement with ML-KEM.
+    #[cfg(feature = "ml_kem")]
+    MlKem768,
+    /// Experimental: ML-KEM derived key-encryption key with AES Key Wrap.
+    #[cfg(feature = "ml_kem")]
+    MlKem768A256Kw,
+}
+
+/// Agreement PartyUInfo and PartyVInfo (`apu`/`apv`, RFC 7518 §4.6.1), fed to the Concat KDF of
+/// the key agreement algorithms. Both are empty by default.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PartyInfo {
+    pub apu: Vec<u8>,
+    pub apv: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JweEncryption {
+    A256Gcm,
+    C20P,
+}
+
+impl JweAlgorithm {
+    pub fn name(&self) -> &'static str {
+        match self {
+            JweAlgorithm::Dir => "dir",
+            JweAlgorithm::A256Kw => "A256KW",
+            JweAlgorithm::EcdhEs => "ECDH-ES",
+            #[cfg(feature = "ml_kem")]
+            JweAlgorithm::MlKem768 => ML_KEM_768,
+            #[cfg(feature = "ml_kem")]
+            JweAlgorithm::MlKem768A256Kw => "IX-ML-KEM-768+A256KW-v1",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self, &'static str> {
+        match name {
+            "dir" => Ok(JweAlgorithm::Dir),
+            "A256KW" => Ok(JweAlgorithm::A256Kw),
+            "ECDH-ES" => Ok(JweAlgorithm::EcdhEs),
+            #[cfg(feature = "ml_kem")]
+            ML_KEM_768 => Ok(JweAlgorithm::MlKem768),
+            #[cfg(feature = "ml_kem")]
+            "IX-ML-KEM-768+A256KW-v1" => Ok(JweAlgorithm::MlKem768A256Kw),
+            _ => Err("Unsupported JWE alg"),
+        }
+    }
+
+    /// Algorithms that derive or carry the CEK itself cannot share one CEK across recipients.
+    fn is_direct(&self) -> bool {
+        match self {
+            JweAlgorithm::Dir | JweAlgorithm::EcdhEs => true,
+            #[cfg(feature = "ml_kem")]
+            JweAlgorithm::MlKem768 => true,
+            _ => false,
+        }
+    }
+}
+
+impl JweEncryption {
+    pub fn name(&self) -> &'static str {
+        match self {
+            JweEncryption::A256Gcm => "A256GCM",
+            JweEncryption::C20P => "C20P",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self, &'static str> {
+        match name {
+            "A256GCM" => Ok(JweEncryption::A256Gcm),
+            "C20P" => Ok(JweEncryption::C20P),
+            _ => Err("Unsupported JWE enc"),
+        }
+    }
+
+    fn seal(&self, cek: &[u8], iv: &[u8; IV_LEN], aad: &[u8], plaintext: &[u8]) -> Result<(Vec<u8>, Vec<u8>), &'static str> {
+        match self {
+            JweEncryption::A256Gcm => {
+                let cipher = AesCipher::new(cek)?;
+                Ok(GCMMode::new(&cipher, iv.to_vec(), aad.to_vec()).encrypt_and_tag(plaintext))
+            }
+            JweEncryption::C20P => {
+                let mut cipher = ChaChaQuantum::new();
+                cipher.initialize(cek, None);
+                let sealed = cipher.seal_with_nonce(iv, aad, plaintext);
+                cipher.wipe();
+                let mut ciphertext = sealed?;
+                let tag = ciphertext.split_off(ciphertext.len() - GCM_TAG_LEN);
+                Ok((ciphertext, tag))
+            }
+        }
+    }
+
+    fn open(&self, cek: &[u8], iv: &[u8], aad: &[u8], ciphertext: &[u8], tag: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let iv: &[u8; IV_LEN] = iv.try_into().map_err(|_| "Invalid JWE IV length")?;
+        match self {
+            JweEncryption::A256Gcm => {
+                let cipher = AesCipher::new(cek)?;
+                GCMMode::new(&cipher, iv.to_vec(), aad.to_vec())
+                    .decrypt_and_verify(ciphertext, tag)
+                    .ok_or("JWE authentication failed")
+            }
+            JweEncryption::C20P => {
+                if tag.len() != GCM_TAG_LEN {
+                    return Err("JWE authentication failed");
+                }
+                let mut cipher = ChaChaQuantum::new();
+                cipher.initialize(cek, None);
+                let opened = cipher.open_with_nonce(iv, aad, &[ciphertext, tag].concat());
+                cipher.wipe();
+                opened.map_err(|_| "JWE authentication failed")
+            }
+        }
+    }
+}
+
+/// Concat KDF (NIST SP 800-56A, RFC 7518 §4.6.2) with SHA-256.
+fn concat_kdf(z: &[u8], algorithm_id: &str, apu: &[u8], apv: &[u8], key_len: usize) -> Vec<u8> {
+    let mut other_info = Vec::new();
+    for part in [algorithm_id.as_bytes(), apu, apv] {
+        other_info.extend_from_slice(&(part.len() as u32).to_be_bytes());
+        other_info.extend_from_slice(part);
+    }
+    other_info.extend_from_slice(&((key_len * 8) as u32).to_be_bytes());
+
+    let mut output = Vec::with_capacity(key_len);
+    let mut counter = 1u32;
+    while output.len() < key_len {
+        let digest = Sha256::new()
+            .chain_update(counter.to_be_bytes())
+            .chain_update(z)
+            .chain_update(&other_info)
+            .finalize();
+        output.extend_from_slice(&digest);
+        counter += 1;
+    }
+    output.truncate(key_len);
+    output
+}
+
+fn wrap_cek(kek: &[u8], cek: &[u8]) -> Result<Vec<u8>, &'static str> {
+    KeyWrap::new(&AesCipher::new(kek)?).wrap(cek)
+}
+
+fn unwrap_cek(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>, &'static str> {
+    KeyWrap::new(&AesCipher::new(kek)?).unwrap(wrapped)
+}
+
+fn symmetric_key(jwk: &Jwk) -> Result<&[u8], &'static str> {
+    match &jwk.key {
+        JwkKey::Oct { k } if k.len() == CEK_LEN => Ok(k),
+        _ => Err("Algorithm requires a 256-bit oct key"),
+    }
+}
+
+/// Ephemeral-static ECDH against the recipient key; returns Z and the `epk` header value.
+fn ecdh_sender(recipient: &Jwk) -> Result<(Vec<u8>, Value), &'static str> {
+    match &recipient.key {
+        JwkKey::X25519 { x, .. } => {
+            let ephemeral = Jwk::generate_x25519()?;
+            let JwkKey::X25519 { d: Some(d), .. } = &ephemeral.key else { unreachable!() };
+            let shared = x25519_dalek::StaticSecret::from(*d).diffie_hellman(&x25519_dalek::PublicKey::from(*x));
+            if !shared.was_contributory() {
+                return Err("Low-order X25519 key");
+            }
+            Ok((shared.as_bytes().to_vec(), ephemeral.to_public()?.to_value(false)))
+        }
+        JwkKey::P256 { .. } => {
+            let public = p256_public(&recipient.key)?;
+            let ephemeral = Jwk::generate_p256()?;
+            let JwkKey::P256 { d: Some(d), .. } = &ephemeral.key else { unreachable!() };
+            let secret = p256::SecretKey::from_slice(d).map_err(|_| "Invalid P-256 key")?;
+            let shared = p256::ecdh::diffie_hellman(secret.to_nonzero_scalar(), public.as_affine());
+            Ok((shared.raw_secret_bytes().to_vec(), ephemeral.to_public()?.to_value(false)))
+        }
+        _ => Err("ECDH-ES requires an X25519 or P-256 key"),
+    }
+}
+
+fn ecdh_recipient(recipient: &Jwk, epk: &Value) -> Result<Vec<u8>, &'static str> {
+    let epk = Jwk::from_value(epk)?;
+    match (&recipient.key, &epk.key) {
+        (JwkKey::X25519 { d: Some(d), .. }, JwkKey::X25519 { x, .. }) => {
+            let shared = x25519_dalek::StaticSecret::from(*d).diffie_hellman(&x25519_dalek::PublicKey::from(*x));
+            if !shared.was_contributory() {
+                return Err("Low-order X25519 key");
+            }
+            Ok(shared.as_bytes().to_vec())
+        }
+        (JwkKey::P256 { d: Some(d), .. }, JwkKey::P256 { .. }) => {
+            let secret = p256::SecretKey::from_slice(d).map_err(|_| "Invalid P-256 key")?;
+            let shared = p256::ecdh::diffie_hellman(secret.to_nonzero_scalar(), p256_public(&epk.key)?.as_affine());
+            Ok(shared.raw_secret_bytes().to_vec())
+        }
+        _ => Err("epk does not match recipient key"),
+    }
+}
+
+#[cfg(feature = "ml_kem")]
+fn ml_kem_public(recipient: &Jwk) -> Result<&[u8], &'static str> {
+    match &recipient.key {
+        JwkKey::Akp { alg, public, .. } if alg == ML_KEM_768 => Ok(public),
+        _ => Err("ML-KEM algorithms require an ML-KEM-768 AKP key"),
+    }
+}
+
+#[cfg(feature = "ml_kem")]
+fn ml_kem_sender(recipient: &Jwk) -> Result<(Vec<u8>, Vec<u8>), &'static str> {
+    let mut hybrid = HybridLatticeCipher::new();
+    let kem_ciphertext = hybrid.encapsulate_key(ml_kem_public(recipient)?);
+    let shared = hybrid.export_key(ML_KEM_EXPORT_PURPOSE, 32);
+    hybrid.wipe();
+    Ok((shared?, kem_ciphertext))
+}
+
+#[cfg(feature = "ml_kem")]
+fn ml_kem_recipient(recipient: &Jwk, kem_ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let JwkKey::Akp { public, private: Some(private), .. } = &recipient.key else {
+        return Err("ML-KEM decryption requires a private AKP key");
+    };
+    ml_kem_public(recipient)?;
+    let mut hybrid = HybridLatticeCipher::with_keypair(LatticeKEM { public_key: public.clone(), secret_key: private.clone() });
+    hybrid.decapsulate_key(kem_ciphertext);
+    let shared = hybrid.export_key(ML_KEM_EXPORT_PURPOSE, 32);
+    hybrid.wipe();
+    shared
+}
+
+/// Check `alg` against the key. A key without an `alg` member is only accepted where its type
+/// allows a single algorithm; an `oct` key could serve both `dir` and `A256KW`, so it must say.
+fn check_key_alg(recipient: &Jwk, alg: JweAlgorithm) -> Result<(), &'static str> {
+    let expected = match (&recipient.alg, &recipient.key) {
+        (Some(expected), _) => expected.as_str(),
+        (None, JwkKey::X25519 { .. } | JwkKey::P256 { .. }) => JweAlgorithm::EcdhEs.name(),
+        (None, JwkKey::Akp { alg, .. }) => alg.as_str(),
+        (None, _) => return Err("Key must specify alg for JWE"),
+    };
+    // An ML-KEM key serves both ML-KEM algorithms under its own name
+    #[cfg(feature = "ml_kem")]
+    let kem_key = expected == ML_KEM_768 && alg == JweAlgorithm::MlKem768A256Kw;
+    #[cfg(not(feature = "ml_kem"))]
+    let kem_key = false;
+    if expected != alg.name() && !kem_key {
+        return Err("alg not permitted for this key");
+    }
+    Ok(())
+}
+
+/// Key management on the sending side. For direct algorithms `cek` is replaced by the agreed
+/// key; otherwise it is wrapped. Returns the encrypted key and header parameters to add.
+fn manage_key_send(
+    alg: JweAlgorithm,
+    enc: JweEncryption,
+    recipient: &Jwk,
+    party: &PartyInfo,
+    cek: &mut Vec<u8>,
+) -> Result<(Vec<u8>, Map<String, Value>), &'static str> {
+    check_key_alg(recipient, alg)?;
+    let mut params = Map::new();
+    if !matches!(alg, JweAlgorithm::Dir | JweAlgorithm::A256Kw) {
+        for (name, value) in [("apu", &party.apu), ("apv", &party.apv)] {
+            if !value.is_empty() {
+                params.insert(name.into(), b64(value).into());
+            }
+        }
+    }
+    let encrypted_key = match alg {
+        JweAlgorithm::Dir => {
+            *cek = symmetric_key(recipient)?.to_vec();
+            Vec::new()
+        }
+        JweAlgorithm::A256Kw => wrap_cek(symmetric_key(recipient)?, cek)?,
+        JweAlgorithm::EcdhEs => {
+            let (mut z, epk) = ecdh_sender(recipient)?;
+            *cek = concat_kdf(&z, enc.name(), &party.apu, &party.apv, CEK_LEN);
+            z.zeroize();
+            params.insert("epk".into(), epk);
+            Vec::new()
+        }
+        #[cfg(feature = "ml_kem")]
+        JweAlgorithm::MlKem768 | JweAlgorithm::MlKem768A256Kw => {
+            let (mut shared, kem_ciphertext) = ml_kem_sender(recipient)?;
+            params.insert("ek".into(), b64(&kem_ciphertext).into());
+            let encrypted_key = if alg == JweAlgorithm::MlKem768 {
+                *cek = concat_kdf(&shared, enc.name(), &party.apu, &party.apv, CEK_LEN);
+                Vec::new()
+            } else {
+                let mut kek = concat_kdf(&shared, alg.name(), &party.apu, &party.apv, 32);
+                let wrapped = wrap_cek(&kek, cek);
+                kek.zeroize();
+                wrapped?
+            };
+            shared.zeroize();
+            encrypted_key
+        }
+    };
+    Ok((encrypted_key, params))
+}
+
+/// `apu`/`apv` from a received header; absent members are empty.
+fn party_info(header: &Map<String, Value>) -> Result<PartyInfo, &'static str> {
+    let member = |name: &str| match header.get(name) {
+        Some(value) => unb64(value.as_str().ok_or("Invalid apu or apv")?),
+        None => Ok(Vec::new()),
+    };
+    Ok(PartyInfo { apu: member("apu")?, apv: member("apv")? })
+}
+
+fn manage_key_receive(header: &Map<String, Value>, recipient: &Jwk, encrypted_key: &[u8]) -> Result<(JweEncryption, Vec<u8>), &'static str> {
+    if header.contains_key("crit") {
+        return Err("Unsupported critical JWE header parameters");
+    }
+    let alg = JweAlgorithm::from_name(header.get("alg").and_then(Value::as_str).ok_or("Missing alg")?)?;
+    let enc = JweEncryption::from_name(header.get("enc").and_then(Value::as_str).ok_or("Missing enc")?)?;
+    check_key_alg(recipient, alg)?;
+    if alg.is_direct() && !encrypted_key.is_empty() {
+        return Err("Direct algorithms must have an empty encrypted key");
+    }
+
+    let cek = match alg {
+        JweAlgorithm::Dir => symmetric_key(recipient)?.to_vec(),
+        JweAlgorithm::A256Kw => unwrap_cek(symmetric_key(recipient)?, encrypted_key)?,
+        JweAlgorithm::EcdhEs => {
+            let party = party_info(header)?;
+            let mut z = ecdh_recipient(recipient, header.get("epk").ok_or("Missing epk")?)?;
+            let cek = concat_kdf(&z, enc.name(), &party.apu, &party.apv, CEK_LEN);
+            z.zeroize();
+            cek
+        }
+        #[cfg(feature = "ml_kem")]
+        JweAlgorithm::MlKem768 | JweAlgorithm::MlKem768A256Kw => {
+            let party = party_info(header)?;
+            let kem_ciphertext = unb64(header.get("ek").and_then(Value::as_str).ok_or("Missing ek")?)?;
+            let mut shared = ml_kem_recipient(recipient, &kem_ciphertext)?;
+            let cek = if alg == JweAlgorithm::MlKem768 {
+                Ok(concat_kdf(&shared, enc.name(), &party.apu, &party.apv, CEK_LEN))
+            } else {
+                let mut kek = concat_kdf(&shared, alg.name(), &party.apu, &party.apv, 32);
+                let cek = unwrap_cek(&kek, encrypted_key);
+                kek.zeroize();
+                cek
+            };
+            shared.zeroize();
+            cek?
+        }
+    };
+    if cek.len() != CEK_LEN {
+        return Err("Invalid content encryption key length");
+    }
+    Ok((enc, cek))
+}
+
+fn random_cek_and_iv() -> Result<(Vec<u8>, [u8; IV_LEN]), &'static str> {
+    let mut cek = vec![0u8; CEK_LEN];
+    let mut iv = [0u8; IV_LEN];
+    getrandom::getrandom(&mut cek).map_err(|_| "Key generation failed")?;
+    getrandom::getrandom(&mut iv).map_err(|_| "IV generation failed")?;
+    Ok((cek, iv))
+}
+
+fn decode_header(encoded: &str) -> Result<Map<String, Value>, &'static str> {
+    let bytes = unb64(encoded)?;
+    match serde_json::from_slice(&bytes) {
+        Ok(Value::Object(header)) => Ok(header),
+        _ => Err("Invalid JWE header"),
+    }
+}
+
+/// Merge header members, rejecting names that appear in more than one header (RFC 7516 §7.2.1).
+fn merge_headers(parts: &[Option<&Map<String, Value>>]) -> Result<Map<String, Value>, &'static str> {
+    let mut merged = Map::new();
+    for part in parts.iter().flatten() {
+        for (name, value) in part.iter() {
+            if merged.insert(name.clone(), value.clone()).is_some() {
+                return Err("Duplicate JWE header parameter");
+            }
+        }
+    }
+    Ok(merged)
+}
+
+/// Encrypt to a single recipient in compact serialization.
+pub fn encrypt_compact(plaintext: &[u8], recipient: &Jwk, alg: JweAlgorithm, enc: JweEncryption) -> Result<String, &'static str> {
+    encrypt_compact_with_party_info(plaintext, recipient, alg, enc, &PartyInfo::default())
+}
+
+/// As `encrypt_compact`, binding `party` into key agreement and the protected header.
+pub fn encrypt_compact_with_party_info(
+    plaintext: &[u8],
+    recipient: &Jwk,
+    alg: JweAlgorithm,
+    enc: JweEncryption,
+    party: &PartyInfo,
+) -> Result<String, &'static str> {
+    let (mut cek, iv) = random_cek_and_iv()?;
+    let managed = manage_key_send(alg, enc, recipient, party, &mut cek);
+    let (encrypted_key, params) = managed.inspect_err(|_| cek.zeroize())?;
+
+    let mut header = json!({ "alg": alg.name(), "enc": enc.name() });
+    let members = header.as_object_mut().expect("header is an object");
+    members.extend(params);
+    if let Some(kid) = &recipient.kid {
+        members.insert("kid".into(), kid.clone().into());
+    }
+    let protected = b64(header.to_string().as_bytes());
+
+    let sealed = enc.seal(&cek, &iv, protected.as_bytes(), plaintext);
+    cek.zeroize();
+    let (ciphertext, tag) = sealed?;
+    Ok([protected, b64(&encrypted_key), b64(&iv), b64(&ciphertext), b64(&tag)].join("."))
+}
+
+pub fn decrypt_compact(token: &str, key: &Jwk) -> Result<Vec<u8>, &'static str> {
+    let parts: Vec<&str> = token.split('.').collect();
+    let [protected, encrypted_key, iv, ciphertext, tag] = parts.as_slice() else {
+        return Err("Compact JWE must have five parts");
+    };
+    let header = decode_header(protected)?;
+    let (enc, mut cek) = manage_key_receive(&header, key, &unb64(encrypted_key)?)?;
+    let plaintext = enc.open(&cek, &unb64(iv)?, protected.as_bytes(), &unb64(ciphertext)?, &unb64(tag)?);
+    cek.zeroize();
+    plaintext
+}
+
+/// Encrypt to one or more recipients in general JSON serialization. `alg` and per-recipient
+/// parameters go in each recipient's unprotected header; `enc` is integrity protected.
+pub fn encrypt_json(plaintext: &[u8], recipients: &[(&Jwk, JweAlgorithm)], enc: JweEncryption, aad: Option<&[u8]>) -> Result<String, &'static str> {
+    encrypt_json_with_party_info(plaintext, recipients, enc, aad, &PartyInfo::default())
+}
+
+/// As `encrypt_json`, binding `party` into every key agreement recipient.
+pub fn encrypt_json_with_party_info(
+    plaintext: &[u8],
+    recipients: &[(&Jwk, JweAlgorithm)],
+    enc: JweEncryption,
+    aad: Option<&[u8]>,
+    party: &PartyInfo,
+) -> Result<String, &'static str> {
+    if recipients.is_empty() {
+        return Err("At least one recipient required");
+    }
+    if recipients.len() > 1 && recipients.iter().any(|(_, alg)| alg.is_direct()) {
+        return Err("Direct key agreement cannot be combined with other recipients");
+    }
+
+    let (mut cek, iv) = random_cek_and_iv()?;
+    let mut entries = Vec::new();
+    for (recipient, alg) in recipients {
+        let managed = manage_key_send(*alg, enc, recipient, party, &mut cek);
+        let (encrypted_key, params) = managed.inspect_err(|_| cek.zeroize())?;
+        let mut header = json!({ "alg": alg.name() });
+        let members = header.as_object_mut().expect("header is an object");
+        members.extend(params);
+        if let Some(kid) = &recipient.kid {
+            members.insert("kid".into(), kid.clone().into());
+        }
+
+        let mut entry = json!({ "header": header });
+        if !encrypted_key.is_empty() {
+            entry["encrypted_key"] = b64(&encrypted_key).into();
+        }
+        entries.push(entry);
+    }
+
+    let protected = b64(json!({ "enc": enc.name() }).to_string().as_bytes());
+    let mut full_aad = protected.clone();
+    if let Some(aad) = aad {
+        full_aad.push('.');
+        full_aad.push_str(&b64(aad));
+    }
+    let sealed = enc.seal(&cek, &iv, full_aad.as_bytes(), plaintext);
+    cek.zeroize();
+    let (ciphertext, tag) = sealed?;
+
+    let mut jwe = json!({
+        "protected": protected,
+        "recipients": entries,
+        "iv": b64(&iv),
+        "ciphertext": b64(&ciphertext),
+        "tag": b64(&tag),
+    });
+    if let Some(aad) = aad {
+        jwe["aad"] = b64(aad).into();
+    }
+    Ok(jwe.to_string())
+}
+
+/// Decrypt general or flattened JSON serialization with `key`, trying each recipient entry.
+pub fn decrypt_json(json: &str, key: &Jwk) -> Result<Vec<u8>, &'static str> {
+    let jwe: Value = serde_json::from_str(json).map_err(|_| "Invalid JWE JSON")?;
+    let text = |name: &str| jwe.get(name).and_then(Value::as_str);
+
+    let protected_b64 = text("protected").unwrap_or("");
+    let protected = match protected_b64 {
+        "" => None,
+        encoded => Some(decode_header(encoded)?),
+    };
+    let shared = jwe.get("unprotected").and_then(Value::as_object);
+
+    let mut full_aad = protected_b64.to_string();
+    if let Some(aad) = text("aad") {
+        full_aad.push('.');
+        full_aad.push_str(aad);
+    }
+    let iv = unb64(text("iv").ok_or("Missing iv")?)?;
+    let ciphertext = unb64(text("ciphertext").ok_or("Missing ciphertext")?)?;
+    let tag = unb64(text("tag").ok_or("Missing tag")?)?;
+
+    // Flattened serialization is one recipient with header/encrypted_key at the top level
+    let entries: Vec<&Value> = match jwe.get("recipients").and_then(Value::as_array) {
+        Some(recipients) => recipients.iter().collect(),
+        None => vec![&jwe],
+    };
+
+    let mut last_error = "No recipient entry matched this key";
+    for entry in entries {
+        let per_recipient = entry.get("header").and_then(Value::as_object);
+        let header = merge_headers(&[protected.as_ref(), shared, per_recipient])?;
+        if let (Some(kid), Some(wanted)) = (header.get("kid").and_then(Value::as_str), &key.kid) {
+            if kid != wanted {
+                continue;
+            }
+        }
+        let encrypted_key = match entry.get("encrypted_key").and_then(Value::as_str) {
+            Some(encoded) => unb64(encoded)?,
+            None => Vec::new(),
+        };
+
+        match manage_key_receive(&header, key, &encrypted_key) {
+            Ok((enc, mut cek)) => {
+                let plaintext = enc.open(&cek, &iv, full_aad.as_bytes(), &ciphertext, &tag);
+                cek.zeroize();
+                match plaintext {
+                    Ok(plaintext) => return Ok(plaintext),
+                    Err(e) => last_error = e,
+                }
+            }
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RFC 7518 Appendix C: ECDH-ES key agreement with apu "Alice" and apv "Bob".
+    #[test]
+    fn rfc7518_appendix_c() {
+        let bob = Jwk::from_json(
+            r#"{"kty":"EC","crv":"P-256",
+                "x":"weNJy2HscCSM6AEDTDg04biOvhFhyyWvOHQfeF_PxMQ",
+                "y":"e8lnCO-AlStT-NJVX-crhB7QRYhiix03illJOVAOyck",
+                "d":"VEmDZpDXXK8p8N0Cndsxs924q6nS1RXFASRl6BfUqdw"}"#,
+        )
+        .unwrap();
+        let epk = json!({
+            "kty": "EC",
+            "crv": "P-256",
+            "x": "gI0GAILBdu7T53akrFmMyGcsF3n5dO7MmwNBHKW5SV0",
+            "y": "SLW_xSffzlPWrHEVI30DHM_4egVwt3NQqeUD7nMFpps",
+        });
+        let header = json!({ "alg": "ECDH-ES", "enc": "A128GCM", "epk": epk, "apu": "QWxpY2U", "apv": "Qm9i" });
+        let party = party_info(header.as_object().unwrap()).unwrap();
+        assert_eq!(party, PartyInfo { apu: b"Alice".to_vec(), apv: b"Bob".to_vec() });
+
+        let z = ecdh_recipient(&bob, &epk).unwrap();
+        let key = concat_kdf(&z, "A128GCM", &party.apu, &party.apv, 16);
+        assert_eq!(b64(&key), "VqqN6vgjbSBcIijNcacQGg");
+    }
+
+    #[test]
+    fn party_info_binds_the_key() {
+        let recipient = Jwk::generate_x25519().unwrap();
+        let party = PartyInfo { apu: b"Alice".to_vec(), apv: b"Bob".to_vec() };
+        let recipients = [(&recipient, JweAlgorithm::EcdhEs)];
+        let jwe = encrypt_json_with_party_info(b"payload", &recipients, JweEncryption::A256Gcm, None, &party).unwrap();
+        assert_eq!(decrypt_json(&jwe, &recipient).unwrap(), b"payload");
+
+        // apu/apv sit in the unprotected per-recipient header; only the derived key guards them
+        let tampered = jwe.replace(&b64(b"Bob"), &b64(b"Eve"));
+        assert_ne!(tampered, jwe);
+        assert_eq!(decrypt_json(&tampered, &recipient), Err("JWE authentication failed"));
+
+        let token = encrypt_compact_with_party_info(b"payload", &recipient, JweAlgorithm::EcdhEs, JweEncryption::C20P, &party).unwrap();
+        let header = decode_header(token.split('.').next().unwrap()).unwrap();
+        assert_eq!(party_info(&header).unwrap(), party);
+        assert_eq!(decrypt_compact(&token, &recipient).unwrap(), b"payload");
+    }
+
+    #[test]
+    fn rejects_crit() {
+        let key = Jwk::oct(&[7; 32]).with_alg("dir");
+        let header = b64(json!({ "alg": "dir", "enc": "A256GCM", "crit": ["exp"], "exp": 0 }).to_string().as_bytes());
+        let (ciphertext, tag) = JweEncryption::A256Gcm.seal(&[7; 32], &[0; IV_LEN], header.as_bytes(), b"payload").unwrap();
+        let token = [header, String::new(), b64(&[0; IV_LEN]), b64(&ciphertext), b64(&tag)].join(".");
+        assert_eq!(decrypt_compact(&token, &key), Err("Unsupported critical JWE header parameters"));
+    }
+}
@@ -0,0 +1,290 @@
+// ix-encryption/core/jose/jwk.rs
+
+//! JSON Web Keys (RFC 7517) for the key types the JOSE layer understands:
+//! `oct` symmetric keys, `OKP` X25519/Ed25519 (RFC 8037), `EC` P-256, and the experimental
+//! `AKP` type carrying post-quantum keys (`pub`/`priv`) for ML-KEM and ML-DSA algorithms.
+//! ML-KEM keys are only generated with the `ml_kem` feature, under a private `IX-` name.
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use p256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+#[cfg(feature = "ml_kem")]
+use crate::core::postquantum::lattice_kem::LatticeKEM;
+
+/// Experimental ML-KEM identifier used for `AKP` keys and JWE key management.
+#[cfg(feature = "ml_kem")]
+pub const ML_KEM_768: &str = "IX-ML-KEM-768-v1";
+/// Experimental ML-DSA identifier used for `AKP` keys and JWS.
+pub const ML_DSA_65: &str = "ML-DSA-65";
+
+/// Key material; private members are zeroized on drop and left out of `Debug`.
+#[derive(Clone, PartialEq, Eq)]
+pub enum JwkKey {
+    Oct { k: Vec<u8> },
+    X25519 { x: [u8; 32], d: Option<[u8; 32]> },
+    Ed25519 { x: [u8; 32], d: Option<[u8; 32]> },
+    P256 { x: [u8; 32], y: [u8; 32], d: Option<[u8; 32]> },
+    Akp { alg: String, public: Vec<u8>, private: Option<Vec<u8>> },
+}
+
+impl Drop for JwkKey {
+    fn drop(&mut self) {
+        match self {
+            JwkKey::Oct { k } => k.zeroize(),
+            JwkKey::X25519 { d, .. } | JwkKey::Ed25519 { d, .. } | JwkKey::P256 { d, .. } => d.zeroize(),
+            JwkKey::Akp { private, .. } => private.zeroize(),
+        }
+    }
+}
+
+impl std::fmt::Debug for JwkKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JwkKey::Oct { .. } => f.debug_struct("Oct").finish_non_exhaustive(),
+            JwkKey::X25519 { x, .. } => f.debug_struct("X25519").field("x", x).finish_non_exhaustive(),
+            JwkKey::Ed25519 { x, .. } => f.debug_struct("Ed25519").field("x", x).finish_non_exhaustive(),
+            JwkKey::P256 { x, y, .. } => f.debug_struct("P256").field("x", x).field("y", y).finish_non_exhaustive(),
+            JwkKey::Akp { alg, public, .. } => f.debug_struct("Akp").field("alg", alg).field("public", public).finish_non_exhaustive(),
+        }
+    }
+}
+
+/// `Debug` goes through `JwkKey`, so private members never appear in it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Jwk {
+    pub key: JwkKey,
+    pub kid: Option<String>,
+    pub alg: Option<String>,
+}
+
+pub(crate) fn b64(data: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(data)
+}
+
+pub(crate) fn unb64(data: &str) -> Result<Vec<u8>, &'static str> {
+    URL_SAFE_NO_PAD.decode(data).map_err(|_| "Invalid base64url")
+}
+
+fn field(object: &Map<String, Value>, name: &str) -> Result<Vec<u8>, &'static str> {
+    object.get(name).and_then(Value::as_str).ok_or("Missing JWK member").and_then(unb64)
+}
+
+fn field32(object: &Map<String, Value>, name: &str) -> Result<[u8; 32], &'static str> {
+    field(object, name)?.try_into().map_err(|_| "Invalid JWK member length")
+}
+
+fn optional32(object: &Map<String, Value>, name: &str) -> Result<Option<[u8; 32]>, &'static str> {
+    match object.contains_key(name) {
+        true => field32(object, name).map(Some),
+        false => Ok(None),
+    }
+}
+
+fn random32() -> Result<[u8; 32], &'static str> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|_| "Key generation failed")?;
+    Ok(bytes)
+}
+
+impl Jwk {
+    pub fn new(key: JwkKey) -> Self {
+        Self { key, kid: None, alg: None }
+    }
+
+    pub fn with_kid(mut self, kid: &str) -> Self {
+        self.kid = Some(kid.to_string());
+        self
+    }
+
+    pub fn with_alg(mut self, alg: &str) -> Self {
+        self.alg = Some(alg.to_string());
+        self
+    }
+
+    pub fn oct(k: &[u8]) -> Self {
+        Self::new(JwkKey::Oct { k: k.to_vec() })
+    }
+
+    pub fn generate_oct(len: usize) -> Result<Self, &'static str> {
+        let mut k = vec![0u8; len];
+        if getrandom::getrandom(&mut k).is_err() {
+            k.zeroize();
+            return Err("Key generation failed");
+        }
+        Ok(Self::new(JwkKey::Oct { k }))
+    }
+
+    pub fn generate_x25519() -> Result<Self, &'static str> {
+        let mut d = random32()?;
+        let x = x25519_dalek::PublicKey::from(&x25519_dalek::StaticSecret::from(d)).to_bytes();
+        let key = JwkKey::X25519 { x, d: Some(d) };
+        d.zeroize();
+        Ok(Self::new(key))
+    }
+
+    pub fn generate_ed25519() -> Result<Self, &'static str> {
+        let mut d = random32()?;
+        let x = ed25519_dalek::SigningKey::from_bytes(&d).verifying_key().to_bytes();
+        let key = JwkKey::Ed25519 { x, d: Some(d) };
+        d.zeroize();
+        Ok(Self::new(key))
+    }
+
+    pub fn generate_p256() -> Result<Self, &'static str> {
+        let secret = p256::SecretKey::random(&mut rand::rngs::OsRng);
+        let point = secret.public_key().to_encoded_point(false);
+        let mut d: [u8; 32] = secret.to_bytes().into();
+        let key = JwkKey::P256 {
+            x: point.x().ok_or("Invalid P-256 point")?.as_slice().try_into().map_err(|_| "Invalid P-256 point")?,
+            y: point.y().ok_or("Invalid P-256 point")?.as_slice().try_into().map_err(|_| "Invalid P-256 point")?,
+            d: Some(d),
+        };
+        d.zeroize();
+        Ok(Self::new(key))
+    }
+
+    /// Experimental ML-KEM key pair backed by `LatticeKEM`.
+    #[cfg(feature = "ml_kem")]
+    pub fn generate_ml_kem() -> Self {
+        let kem = LatticeKEM::keypair();
+        Self::new(JwkKey::Akp {
+            alg: ML_KEM_768.to_string(),
+            public: kem.public_key.clone(),
+            private: Some(kem.secret_key.clone()),
+        })
+        .with_alg(ML_KEM_768)
+    }
+
+    pub fn is_private(&self) -> bool {
+        match &self.key {
+            JwkKey::Oct { .. } => true,
+            JwkKey::X25519 { d, .. } | JwkKey::Ed25519 { d, .. } | JwkKey::P256 { d, .. } => d.is_some(),
+            JwkKey::Akp { private, .. } => private.is_some(),
+        }
+    }
+
+    /// The same key without private members. Symmetric keys have no public form.
+    pub fn to_public(&self) -> Result<Self, &'static str> {
+        let key = match &self.key {
+            JwkKey::Oct { .. } => return Err("Symmetric keys have no public form"),
+            JwkKey::X25519 { x, .. } => JwkKey::X25519 { x: *x, d: None },
+            JwkKey::Ed25519 { x, .. } => JwkKey::Ed25519 { x: *x, d: None },
+            JwkKey::P256 { x, y, .. } => JwkKey::P256 { x: *x, y: *y, d: None },
+            JwkKey::Akp { alg, public, .. } => JwkKey::Akp { alg: alg.clone(), public: public.clone(), private: None },
+        };
+        Ok(Self { key, kid: self.kid.clone(), alg: self.alg.clone() })
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, &'static str> {
+        let value: Value = serde_json::from_str(json).map_err(|_| "Invalid JWK JSON")?;
+        Self::from_value(&value)
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self, &'static str> {
+        let object = value.as_object().ok_or("JWK must be a JSON object")?;
+        let kty = object.get("kty").and_then(Value::as_str).ok_or("Missing kty")?;
+        let crv = object.get("crv").and_then(Value::as_str);
+
+        let key = match (kty, crv) {
+            ("oct", _) => JwkKey::Oct { k: field(object, "k")? },
+            ("OKP", Some("X25519")) => JwkKey::X25519 { x: field32(object, "x")?, d: optional32(object, "d")? },
+            ("OKP", Some("Ed25519")) => JwkKey::Ed25519 { x: field32(object, "x")?, d: optional32(object, "d")? },
+            ("EC", Some("P-256")) => {
+                let key = JwkKey::P256 { x: field32(object, "x")?, y: field32(object, "y")?, d: optional32(object, "d")? };
+                p256_public(&key)?;
+                key
+            }
+            ("AKP", _) => JwkKey::Akp {
+                alg: object.get("alg").and_then(Value::as_str).ok_or("AKP keys require alg")?.to_string(),
+                public: field(object, "pub")?,
+                private: match object.contains_key("priv") {
+                    true => Some(field(object, "priv")?),
+                    false => None,
+                },
+            },
+            _ => return Err("Unsupported JWK type"),
+        };
+
+        Ok(Self {
+            key,
+            kid: object.get("kid").and_then(Value::as_str).map(str::to_string),
+            alg: object.get("alg").and_then(Value::as_str).map(str::to_string),
+        })
+    }
+
+    /// Serialize to a JSON object; private members are included only if `include_private`.
+    pub fn to_value(&self, include_private: bool) -> Value {
+        let mut object = match &self.key {
+            JwkKey::Oct { k } => json!({ "kty": "oct", "k": b64(k) }),
+            JwkKey::X25519 { x, .. } => json!({ "kty": "OKP", "crv": "X25519", "x": b64(x) }),
+            JwkKey::Ed25519 { x, .. } => json!({ "kty": "OKP", "crv": "Ed25519", "x": b64(x) }),
+            JwkKey::P256 { x, y, .. } => json!({ "kty": "EC", "crv": "P-256", "x": b64(x), "y": b64(y) }),
+            JwkKey::Akp { alg, public, .. } => json!({ "kty": "AKP", "alg": alg, "pub": b64(public) }),
+        };
+        let members = object.as_object_mut().expect("JWK is an object");
+
+        if include_private {
+            match &self.key {
+                JwkKey::X25519 { d: Some(d), .. } | JwkKey::Ed25519 { d: Some(d), .. } | JwkKey::P256 { d: Some(d), .. } => {
+                    members.insert("d".into(), b64(d).into());
+                }
+                JwkKey::Akp { private: Some(private), .. } => {
+                    members.insert("priv".into(), b64(private).into());
+                }
+                _ => {}
+            }
+        } else if let JwkKey::Oct { .. } = self.key {
+            members.remove("k");
+        }
+        if let Some(kid) = &self.kid {
+            members.insert("kid".into(), kid.clone().into());
+        }
+        if let Some(alg) = &self.alg {
+            members.insert("alg".into(), alg.clone().into());
+        }
+        object
+    }
+
+    pub fn to_json(&self, include_private: bool) -> String {
+        self.to_value(include_private).to_string()
+    }
+
+    /// RFC 7638 SHA-256 thumbprint over the required public members, base64url encoded.
+    pub fn thumbprint(&self) -> String {
+        // serde_json orders object keys lexicographically, as RFC 7638 requires
+        let canonical = match &self.key {
+            JwkKey::Oct { k } => json!({ "k": b64(k), "kty": "oct" }),
+            JwkKey::X25519 { x, .. } => json!({ "crv": "X25519", "kty": "OKP", "x": b64(x) }),
+            JwkKey::Ed25519 { x, .. } => json!({ "crv": "Ed25519", "kty": "OKP", "x": b64(x) }),
+            JwkKey::P256 { x, y, .. } => json!({ "crv": "P-256", "kty": "EC", "x": b64(x), "y": b64(y) }),
+            JwkKey::Akp { alg, public, .. } => json!({ "alg": alg, "kty": "AKP", "pub": b64(public) }),
+        };
+        b64(&Sha256::digest(canonical.to_string().as_bytes()))
+    }
+}
+
+pub(crate) fn p256_public(key: &JwkKey) -> Result<p256::PublicKey, &'static str> {
+    let JwkKey::P256 { x, y, .. } = key else {
+        return Err("Not a P-256 key");
+    };
+    let point = p256::EncodedPoint::from_affine_coordinates(x.into(), y.into(), false);
+    Option::from(p256::PublicKey::from_encoded_point(&point)).ok_or("P-256 point not on curve")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_redacts_private_members() {
+        let key = Jwk::oct(&[0x5a; 32]).with_kid("k1");
+        let debug = format!("{:?}", key);
+        assert!(debug.contains("k1") && !debug.contains("90"));
+        let x25519 = Jwk::generate_x25519().unwrap();
+        let JwkKey::X25519 { d: Some(d), .. } = &x25519.key else { unreachable!() };
+        assert!(!format!("{:?}", x25519).contains(&format!("{:?}", d)));
+    }
+}
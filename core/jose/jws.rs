@@ -0,0 +1,208 @@
+// ix-encryption/core/jose/jws.rs
+
+//! JSON Web Signature (RFC 7515) in compact and general JSON serialization.
+//! `Jwk` signs and verifies `HS256`, `EdDSA` (Ed25519, RFC 8037) and `ES256`. The experimental
+//! `ML-DSA-65` identifier is recognised on `AKP` keys, but signing goes through an external
+//! `JwsSigner` / `JwsVerifier` implementation until the lattice signature backend lands.
+
+use p256::ecdsa::signature::{Signer, Verifier};
+use serde_json::{json, Map, Value};
+use crate::core::jose::jwk::{b64, p256_public, unb64, Jwk, JwkKey, ML_DSA_65};
+use crate::core::mac::hmac::HmacSha256;
+use crate::core::mac::mac_core::Mac;
+
+/// Produces signatures for one JWS `alg`.
+pub trait JwsSigner {
+    fn alg(&self) -> Result<&str, &'static str>;
+    fn kid(&self) -> Option<&str> {
+        None
+    }
+    fn sign(&self, signing_input: &[u8]) -> Result<Vec<u8>, &'static str>;
+}
+
+/// Checks signatures for one JWS `alg`.
+pub trait JwsVerifier {
+    fn alg(&self) -> Result<&str, &'static str>;
+    fn kid(&self) -> Option<&str> {
+        None
+    }
+    fn verify(&self, signing_input: &[u8], signature: &[u8]) -> bool;
+}
+
+/// HS256 keys must be at least as long as the hash output (RFC 7518 §3.2).
+const MIN_HS256_KEY_LEN: usize = 32;
+
+/// The algorithm implied by the key type, which must agree with any `alg` member on the key.
+fn jwk_alg(jwk: &Jwk) -> Result<&str, &'static str> {
+    let implied = match &jwk.key {
+        JwkKey::Oct { k } if k.len() < MIN_HS256_KEY_LEN => return Err("HS256 requires a key of at least 256 bits"),
+        JwkKey::Oct { .. } => "HS256",
+        JwkKey::Ed25519 { .. } => "EdDSA",
+        JwkKey::P256 { .. } => "ES256",
+        JwkKey::Akp { alg, .. } if alg == ML_DSA_65 => ML_DSA_65,
+        _ => return Err("Key type cannot be used for JWS"),
+    };
+    match jwk.alg.as_deref() {
+        Some(alg) if alg != implied => Err("alg not permitted for this key"),
+        _ => Ok(implied),
+    }
+}
+
+impl JwsSigner for Jwk {
+    fn alg(&self) -> Result<&str, &'static str> {
+        jwk_alg(self)
+    }
+
+    fn kid(&self) -> Option<&str> {
+        self.kid.as_deref()
+    }
+
+    fn sign(&self, signing_input: &[u8]) -> Result<Vec<u8>, &'static str> {
+        match &self.key {
+            JwkKey::Oct { k } => Ok(HmacSha256::new(k).compute(signing_input)),
+            JwkKey::Ed25519 { d: Some(d), .. } => {
+                Ok(ed25519_dalek::SigningKey::from_bytes(d).sign(signing_input).to_bytes().to_vec())
+            }
+            JwkKey::P256 { d: Some(d), .. } => {
+                let key = p256::ecdsa::SigningKey::from_slice(d).map_err(|_| "Invalid P-256 key")?;
+                let signature: p256::ecdsa::Signature = key.sign(signing_input);
+                Ok(signature.to_bytes().to_vec())
+            }
+            JwkKey::Akp { alg, .. } if alg == ML_DSA_65 => Err("ML-DSA-65 requires an external JwsSigner"),
+            _ => Err("Signing requires a private key"),
+        }
+    }
+}
+
+impl JwsVerifier for Jwk {
+    fn alg(&self) -> Result<&str, &'static str> {
+        jwk_alg(self)
+    }
+
+    fn kid(&self) -> Option<&str> {
+        self.kid.as_deref()
+    }
+
+    fn verify(&self, signing_input: &[u8], signature: &[u8]) -> bool {
+        match &self.key {
+            JwkKey::Oct { k } => {
+                let mut mac = HmacSha256::new(k);
+                mac.update(signing_input);
+                mac.verify(signature)
+            }
+            JwkKey::Ed25519 { x, .. } => {
+                let (Ok(key), Ok(signature)) = (
+                    ed25519_dalek::VerifyingKey::from_bytes(x),
+                    ed25519_dalek::Signature::from_slice(signature),
+                ) else {
+                    return false;
+                };
+                key.verify_strict(signing_input, &signature).is_ok()
+            }
+            JwkKey::P256 { .. } => {
+                let (Ok(key), Ok(signature)) = (p256_public(&self.key), p256::ecdsa::Signature::from_slice(signature)) else {
+                    return false;
+                };
+                p256::ecdsa::VerifyingKey::from(key).verify(signing_input, &signature).is_ok()
+            }
+            _ => false,
+        }
+    }
+}
+
+fn protected_header(signer: &dyn JwsSigner, extra: Option<&Map<String, Value>>) -> Result<String, &'static str> {
+    let mut header = json!({ "alg": signer.alg()? });
+    let members = header.as_object_mut().expect("header is an object");
+    if let Some(kid) = signer.kid() {
+        members.insert("kid".into(), kid.into());
+    }
+    if let Some(extra) = extra {
+        for (name, value) in extra {
+            if members.insert(name.clone(), value.clone()).is_some() {
+                return Err("Duplicate JWS header parameter");
+            }
+        }
+    }
+    Ok(b64(header.to_string().as_bytes()))
+}
+
+/// Decode a protected header and check its `alg` against the verifier before any signature work.
+fn check_protected(encoded: &str, verifier: &dyn JwsVerifier) -> Result<Map<String, Value>, &'static str> {
+    let header = match serde_json::from_slice(&unb64(encoded)?) {
+        Ok(Value::Object(header)) => header,
+        _ => return Err("Invalid JWS header"),
+    };
+    if header.get("alg").and_then(Value::as_str) != Some(verifier.alg()?) {
+        return Err("JWS alg does not match key");
+    }
+    if header.contains_key("crit") {
+        return Err("Unsupported critical JWS header parameters");
+    }
+    Ok(header)
+}
+
+/// Sign `payload` in compact serialization; `extra` adds protected header members (e.g. `typ`).
+pub fn sign_compact(payload: &[u8], signer: &dyn JwsSigner, extra: Option<&Map<String, Value>>) -> Result<String, &'static str> {
+    let protected = protected_header(signer, extra)?;
+    let signing_input = format!("{}.{}", protected, b64(payload));
+    let signature = signer.sign(signing_input.as_bytes())?;
+    Ok(format!("{}.{}", signing_input, b64(&signature)))
+}
+
+/// Verify a compact JWS and return its payload and protected header.
+pub fn verify_compact(token: &str, verifier: &dyn JwsVerifier) -> Result<(Vec<u8>, Map<String, Value>), &'static str> {
+    let parts: Vec<&str> = token.split('.').collect();
+    let [protected, payload, signature] = parts.as_slice() else {
+        return Err("Compact JWS must have three parts");
+    };
+    let header = check_protected(protected, verifier)?;
+    let signing_input = format!("{}.{}", protected, payload);
+    if !verifier.verify(signing_input.as_bytes(), &unb64(signature)?) {
+        return Err("JWS signature verification failed");
+    }
+    Ok((unb64(payload)?, header))
+}
+
+/// Sign `payload` with every signer in general JSON serialization.
+pub fn sign_json(payload: &[u8], signers: &[&dyn JwsSigner]) -> Result<String, &'static str> {
+    if signers.is_empty() {
+        return Err("At least one signer required");
+    }
+    let encoded_payload = b64(payload);
+    let mut signatures = Vec::with_capacity(signers.len());
+    for signer in signers {
+        let protected = protected_header(*signer, None)?;
+        let signature = signer.sign(format!("{}.{}", protected, encoded_payload).as_bytes())?;
+        signatures.push(json!({ "protected": protected, "signature": b64(&signature) }));
+    }
+    Ok(json!({ "payload": encoded_payload, "signatures": signatures }).to_string())
+}
+
+/// Verify general or flattened JSON serialization; succeeds if any signature verifies with
+/// `verifier`. Returns the payload and the protected header of the matching signature.
+pub fn verify_json(json: &str, verifier: &dyn JwsVerifier) -> Result<(Vec<u8>, Map<String, Value>), &'static str> {
+    let jws: Value = serde_json::from_str(json).map_err(|_| "Invalid JWS JSON")?;
+    let payload = jws.get("payload").and_then(Value::as_str).ok_or("Missing payload")?;
+    let entries: Vec<&Value> = match jws.get("signatures").and_then(Value::as_array) {
+        Some(signatures) => signatures.iter().collect(),
+        None => vec![&jws],
+    };
+
+    let mut last_error = "No signature matched this key";
+    for entry in entries {
+        let protected = entry.get("protected").and_then(Value::as_str).ok_or("Missing protected header")?;
+        let signature = unb64(entry.get("signature").and_then(Value::as_str).ok_or("Missing signature")?)?;
+        let header = match check_protected(protected, verifier) {
+            Ok(header) => header,
+            Err(e) => {
+                last_error = e;
+                continue;
+            }
+        };
+        if verifier.verify(format!("{}.{}", protected, payload).as_bytes(), &signature) {
+            return Ok((unb64(payload)?, header));
+        }
+        last_error = "JWS signature verification failed";
+    }
+    Err(last_error)
+}
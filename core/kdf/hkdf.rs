@@ -0,0 +1,122 @@
+// ix-encryption/core/kdf/hkdf.rs
+
+//! HKDF (RFC 5869) extract-and-expand key derivation over HMAC-SHA-256 or HMAC-SHA-512.
+
+use zeroize::Zeroize;
+use crate::core::mac::hmac::{HmacSha256, HmacSha512};
+use crate::core::mac::mac_core::Mac;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HkdfHash {
+    Sha256,
+    Sha512,
+}
+
+impl HkdfHash {
+    pub fn output_len(&self) -> usize {
+        match self {
+            HkdfHash::Sha256 => 32,
+            HkdfHash::Sha512 => 64,
+        }
+    }
+
+    fn hmac(&self, key: &[u8]) -> Box<dyn Mac> {
+        match self {
+            HkdfHash::Sha256 => Box::new(HmacSha256::new(key)),
+            HkdfHash::Sha512 => Box::new(HmacSha512::new(key)),
+        }
+    }
+}
+
+/// HKDF-Extract: PRK = HMAC(salt, IKM). A missing salt is a string of hash-length zeros.
+pub fn hkdf_extract(hash: HkdfHash, salt: Option<&[u8]>, ikm: &[u8]) -> Vec<u8> {
+    let zero_salt = vec![0u8; hash.output_len()];
+    hash.hmac(salt.unwrap_or(&zero_salt)).compute(ikm)
+}
+
+/// HKDF-Expand: up to 255 hash-lengths of output keying material from a PRK.
+pub fn hkdf_expand(hash: HkdfHash, prk: &[u8], info: &[u8], output_len: usize) -> Result<Vec<u8>, &'static str> {
+    if output_len > 255 * hash.output_len() {
+        return Err("HKDF output length exceeds 255 hash lengths");
+    }
+
+    let mut mac = hash.hmac(prk);
+    let mut okm = Vec::with_capacity(output_len);
+    let mut previous: Vec<u8> = Vec::new();
+    let mut counter = 1u8;
+
+    while okm.len() < output_len {
+        mac.update(&previous);
+        mac.update(info);
+        mac.update(&[counter]);
+        previous.zeroize();
+        previous = mac.finalize_reset();
+
+        let take = (output_len - okm.len()).min(previous.len());
+        okm.extend_from_slice(&previous[..take]);
+        counter = counter.wrapping_add(1);
+    }
+
+    previous.zeroize();
+    Ok(okm)
+}
+
+/// One-shot HKDF: extract then expand.
+pub fn hkdf(hash: HkdfHash, salt: Option<&[u8]>, ikm: &[u8], info: &[u8], output_len: usize) -> Result<Vec<u8>, &'static str> {
+    let mut prk = hkdf_extract(hash, salt, ikm);
+    let okm = hkdf_expand(hash, &prk, info, output_len);
+    prk.zeroize();
+    okm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_util::h;
+
+    /// RFC 5869 Appendix A, test cases 1 to 3 (HMAC-SHA-256).
+    #[test]
+    fn rfc5869_sha256_test_cases() {
+        let long_ikm: Vec<u8> = (0x00..=0x4f).collect();
+        let long_salt: Vec<u8> = (0x60..=0xaf).collect();
+        let long_info: Vec<u8> = (0xb0..=0xff).collect();
+        let cases = [
+            (
+                vec![0x0b; 22],
+                h("000102030405060708090a0b0c"),
+                h("f0f1f2f3f4f5f6f7f8f9"),
+                "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5",
+                "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865",
+            ),
+            (
+                long_ikm,
+                long_salt,
+                long_info,
+                "06a6b88c5853361a06104c9ceb35b45cef760014904671014a193f40c15fc244",
+                concat!(
+                    "b11e398dc80327a1c8e7f78c596a49344f012eda2d4efad8a050cc4c19afa97c59045a99cac7827271cb41c65e590e09",
+                    "da3275600c2f09b8367793a9aca3db71cc30c58179ec3e87c14c01d5c1f3434f1d87",
+                ),
+            ),
+            (
+                vec![0x0b; 22],
+                vec![],
+                vec![],
+                "19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04",
+                "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8",
+            ),
+        ];
+        for (ikm, salt, info, prk, okm) in cases {
+            assert_eq!(hkdf_extract(HkdfHash::Sha256, Some(&salt), &ikm), h(prk));
+            assert_eq!(hkdf_expand(HkdfHash::Sha256, &h(prk), &info, h(okm).len()).unwrap(), h(okm));
+            assert_eq!(hkdf(HkdfHash::Sha256, Some(&salt), &ikm, &info, h(okm).len()).unwrap(), h(okm));
+        }
+    }
+
+    #[test]
+    fn missing_salt_is_hash_length_zeros() {
+        let ikm = [0x0b; 22];
+        assert_eq!(hkdf_extract(HkdfHash::Sha256, None, &ikm), hkdf_extract(HkdfHash::Sha256, Some(&[0u8; 32]), &ikm));
+        assert!(hkdf_expand(HkdfHash::Sha256, &[0u8; 32], b"", 255 * 32 + 1).is_err());
+    }
+}
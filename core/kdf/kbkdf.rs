@@ -0,0 +1,97 @@
+// ix-encryption/core/kdf/kbkdf.rs
+
+//! Key-based key derivation functions from SP 800-108r1: counter mode and feedback mode.
+//! The PRF is any keyed `Mac` (HMAC, CMAC or KMAC), already keyed with the key-derivation key.
+//! Fixed input data is encoded as Label || 0x00 || Context || [L]_32 with a 32-bit counter.
+
+use zeroize::Zeroize;
+use crate::core::mac::mac_core::Mac;
+
+/// Counter mode: K(i) = PRF(K_I, [i]_32 || FixedInput)
+pub fn kbkdf_counter(prf: &mut dyn Mac, label: &[u8], context: &[u8], output_len: usize) -> Result<Vec<u8>, &'static str> {
+    let fixed = fixed_input(label, context, output_len)?;
+    let mut output = Vec::with_capacity(output_len);
+    let mut counter = 1u32;
+
+    while output.len() < output_len {
+        prf.update(&counter.to_be_bytes());
+        prf.update(&fixed);
+        let mut block = prf.finalize_reset();
+
+        let take = (output_len - output.len()).min(block.len());
+        output.extend_from_slice(&block[..take]);
+        block.zeroize();
+        counter = counter.checked_add(1).ok_or("KBKDF counter overflow")?;
+    }
+
+    Ok(output)
+}
+
+/// Feedback mode: K(i) = PRF(K_I, K(i-1) || [i]_32 || FixedInput), with K(0) = IV.
+pub fn kbkdf_feedback(prf: &mut dyn Mac, iv: &[u8], label: &[u8], context: &[u8], output_len: usize) -> Result<Vec<u8>, &'static str> {
+    let fixed = fixed_input(label, context, output_len)?;
+    let mut output = Vec::with_capacity(output_len);
+    let mut previous = iv.to_vec();
+    let mut counter = 1u32;
+
+    while output.len() < output_len {
+        prf.update(&previous);
+        prf.update(&counter.to_be_bytes());
+        prf.update(&fixed);
+        previous.zeroize();
+        previous = prf.finalize_reset();
+
+        let take = (output_len - output.len()).min(previous.len());
+        output.extend_from_slice(&previous[..take]);
+        counter = counter.checked_add(1).ok_or("KBKDF counter overflow")?;
+    }
+
+    previous.zeroize();
+    Ok(output)
+}
+
+fn fixed_input(label: &[u8], context: &[u8], output_len: usize) -> Result<Vec<u8>, &'static str> {
+    let output_bits = u32::try_from(output_len as u64 * 8).map_err(|_| "KBKDF output length too large")?;
+    let mut fixed = label.to_vec();
+    fixed.push(0x00);
+    fixed.extend_from_slice(context);
+    fixed.extend_from_slice(&output_bits.to_be_bytes());
+    Ok(fixed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::mac::hmac::HmacSha256;
+    use crate::core::test_util::h;
+
+    /// RFC 8009 section 3 derives Kerberos keys with SP 800-108 counter mode over HMAC-SHA-256
+    /// and this fixed-input layout (empty context); its appendix lists the aes128-cts-hmac-sha256-128
+    /// key-usage-2 subkeys.
+    #[test]
+    fn rfc8009_counter_mode_vectors() {
+        let base_key = h("3705d96080c17728a0e800eab6e0d23c");
+        let cases = [
+            ("0000000299", "b31a018a48f54776f403e9a396325dc3"),
+            ("00000002aa", "9b197dd1e8c5609d6e67c3e37c62c72e"),
+            ("0000000255", "9fda0e56ab2d85e1569a688696c26a6c"),
+        ];
+        for (label, expected) in cases {
+            let mut prf = HmacSha256::new(&base_key);
+            assert_eq!(kbkdf_counter(&mut prf, &h(label), &[], 16).unwrap(), h(expected));
+        }
+    }
+
+    #[test]
+    fn feedback_mode_chains_previous_block() {
+        let key = [7u8; 32];
+        let iv = [9u8; 32];
+        let output = kbkdf_feedback(&mut HmacSha256::new(&key), &iv, b"label", b"context", 48).unwrap();
+
+        let fixed = fixed_input(b"label", b"context", 48).unwrap();
+        let mut prf = HmacSha256::new(&key);
+        let first = prf.compute(&[&iv[..], &1u32.to_be_bytes(), &fixed].concat());
+        let second = prf.compute(&[&first[..], &2u32.to_be_bytes(), &fixed].concat());
+        assert_eq!(output, [first, second[..16].to_vec()].concat());
+    }
+}
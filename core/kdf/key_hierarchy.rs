@@ -0,0 +1,77 @@
+// ix-encryption/core/kdf/key_hierarchy.rs
+
+//! Labelled key-derivation tree (root -> tenant -> purpose) built on HKDF-SHA-256.
+//! Every node holds a 32-byte key; children are derived with a length-prefixed label so that
+//! no two paths through the tree can collide. Leaf keys are never reused as node keys.
+
+use zeroize::Zeroize;
+use crate::core::kdf::hkdf::{hkdf_expand, hkdf_extract, HkdfHash};
+
+const NODE_KEY_LEN: usize = 32;
+const TREE_DOMAIN: &[u8] = b"IX-KeyTree-v1";
+
+pub struct KeyHierarchy {
+    node_key: Vec<u8>,
+}
+
+impl KeyHierarchy {
+    /// Create the root node from master key material (any length).
+    pub fn from_master(master_key: &[u8]) -> Self {
+        Self {
+            node_key: hkdf_extract(HkdfHash::Sha256, Some(TREE_DOMAIN), master_key),
+        }
+    }
+
+    /// Derive an intermediate node, e.g. a tenant or a subsystem.
+    pub fn child(&self, label: &str) -> Self {
+        let node_key = self
+            .expand(b"node", label, NODE_KEY_LEN)
+            .expect("Node key length within HKDF limits");
+        Self { node_key }
+    }
+
+    /// Derive a tenant node directly below this one.
+    pub fn tenant(&self, tenant_id: &str) -> Self {
+        self.child(&format!("tenant:{}", tenant_id))
+    }
+
+    /// Derive leaf key material for a named purpose (e.g. "file-encryption", "audit-mac").
+    pub fn purpose_key(&self, purpose: &str, output_len: usize) -> Result<Vec<u8>, &'static str> {
+        self.expand(b"leaf", purpose, output_len)
+    }
+
+    /// Derive leaf key material along a path of node labels, ending with a purpose.
+    pub fn derive_path(&self, path: &[&str], purpose: &str, output_len: usize) -> Result<Vec<u8>, &'static str> {
+        match path.split_first() {
+            None => self.purpose_key(purpose, output_len),
+            Some((first, rest)) => {
+                let mut node = self.child(first);
+                for label in rest {
+                    node = node.child(label);
+                }
+                node.purpose_key(purpose, output_len)
+            }
+        }
+    }
+
+    /// Derive the key for `epoch` of a purpose. Epoch keys are independent of one another, but
+    /// every epoch is derivable from this node's key at any time: this gives key separation
+    /// between epochs, not forward secrecy.
+    pub fn rekey(&self, purpose: &str, epoch: u64, output_len: usize) -> Result<Vec<u8>, &'static str> {
+        self.purpose_key(&format!("{}#epoch:{}", purpose, epoch), output_len)
+    }
+
+    fn expand(&self, kind: &[u8], label: &str, output_len: usize) -> Result<Vec<u8>, &'static str> {
+        let mut info = TREE_DOMAIN.to_vec();
+        info.extend_from_slice(kind);
+        info.extend_from_slice(&(label.len() as u32).to_be_bytes());
+        info.extend_from_slice(label.as_bytes());
+        hkdf_expand(HkdfHash::Sha256, &self.node_key, &info, output_len)
+    }
+}
+
+impl Drop for KeyHierarchy {
+    fn drop(&mut self) {
+        self.node_key.zeroize();
+    }
+}
@@ -0,0 +1,240 @@
+// ix-encryption/core/kdf/password.rs
+
+//! Password-based key derivation: Argon2id (default), scrypt and PBKDF2-HMAC-SHA-256.
+//! Cost parameters are explicit and serializable so they can travel in a ciphertext header,
+//! and can be auto-calibrated to a target derivation time on the current machine.
+
+use std::time::{Duration, Instant};
+use argon2::{Algorithm, Argon2, Params as Argon2Params, Version};
+use sha2::Sha256;
+
+/// Upper bound on Argon2id memory (1 GiB). Headers are decoded against these limits, so they
+/// cap what a crafted header can make a reader allocate.
+pub const MAX_ARGON2_MEMORY_KIB: u32 = 1024 * 1024;
+pub const MAX_ARGON2_ITERATIONS: u32 = 64;
+pub const MAX_ARGON2_PARALLELISM: u32 = 16;
+pub const MAX_SCRYPT_LOG_N: u8 = 22;
+pub const MAX_SCRYPT_R: u32 = 32;
+pub const MAX_SCRYPT_P: u32 = 16;
+/// Upper bound on scrypt's 128 * r * N working memory (1 GiB, N = 2^20 with r = 8).
+pub const MAX_SCRYPT_MEMORY: u64 = 1024 * 1024 * 1024;
+pub const MAX_PBKDF2_ITERATIONS: u32 = 10_000_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PasswordKdf {
+    /// Memory in KiB, number of passes, lanes.
+    Argon2id { memory_kib: u32, iterations: u32, parallelism: u32 },
+    /// N = 2^log_n, block size r, parallelism p.
+    Scrypt { log_n: u8, r: u32, p: u32 },
+    Pbkdf2Sha256 { iterations: u32 },
+}
+
+impl Default for PasswordKdf {
+    /// RFC 9106 second recommended option: 64 MiB, 3 passes, 4 lanes.
+    fn default() -> Self {
+        PasswordKdf::Argon2id { memory_kib: 64 * 1024, iterations: 3, parallelism: 4 }
+    }
+}
+
+impl PasswordKdf {
+    /// scrypt defaults (N = 2^17, r = 8, p = 1).
+    pub fn scrypt_default() -> Self {
+        PasswordKdf::Scrypt { log_n: 17, r: 8, p: 1 }
+    }
+
+    /// PBKDF2 default per current OWASP guidance for HMAC-SHA-256.
+    pub fn pbkdf2_default() -> Self {
+        PasswordKdf::Pbkdf2Sha256 { iterations: 600_000 }
+    }
+
+    /// Reject cost parameters outside the limits above, or too small to be usable.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        match *self {
+            PasswordKdf::Argon2id { memory_kib, iterations, parallelism } => {
+                if !(1..=MAX_ARGON2_PARALLELISM).contains(&parallelism)
+                    || !(1..=MAX_ARGON2_ITERATIONS).contains(&iterations)
+                    || memory_kib < 8 * parallelism
+                    || memory_kib > MAX_ARGON2_MEMORY_KIB
+                {
+                    return Err("Argon2id parameters out of range");
+                }
+            }
+            PasswordKdf::Scrypt { log_n, r, p } => {
+                if !(1..=MAX_SCRYPT_LOG_N).contains(&log_n)
+                    || !(1..=MAX_SCRYPT_R).contains(&r)
+                    || !(1..=MAX_SCRYPT_P).contains(&p)
+                    || self.memory_cost() > MAX_SCRYPT_MEMORY
+                {
+                    return Err("scrypt parameters out of range");
+                }
+            }
+            PasswordKdf::Pbkdf2Sha256 { iterations } => {
+                if !(1..=MAX_PBKDF2_ITERATIONS).contains(&iterations) {
+                    return Err("PBKDF2 iteration count out of range");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Working memory in bytes that one derivation allocates (zero for PBKDF2).
+    pub fn memory_cost(&self) -> u64 {
+        match *self {
+            PasswordKdf::Argon2id { memory_kib, .. } => memory_kib as u64 * 1024,
+            PasswordKdf::Scrypt { log_n, r, .. } => (128 * r as u64).saturating_mul(1u64.checked_shl(log_n as u32).unwrap_or(u64::MAX)),
+            PasswordKdf::Pbkdf2Sha256 { .. } => 0,
+        }
+    }
+
+    pub fn derive(&self, password: &[u8], salt: &[u8], output_len: usize) -> Result<Vec<u8>, &'static str> {
+        let mut output = vec![0u8; output_len];
+        match *self {
+            PasswordKdf::Argon2id { memory_kib, iterations, parallelism } => {
+                let params = Argon2Params::new(memory_kib, iterations, parallelism, Some(output_len))
+                    .map_err(|_| "Invalid Argon2id parameters")?;
+                Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                    .hash_password_into(password, salt, &mut output)
+                    .map_err(|_| "Argon2id derivation failed")?;
+            }
+            PasswordKdf::Scrypt { log_n, r, p } => {
+                let params = scrypt::Params::new(log_n, r, p, output_len)
+                    .map_err(|_| "Invalid scrypt parameters")?;
+                scrypt::scrypt(password, salt, &params, &mut output)
+                    .map_err(|_| "scrypt derivation failed")?;
+            }
+            PasswordKdf::Pbkdf2Sha256 { iterations } => {
+                if iterations == 0 {
+                    return Err("PBKDF2 iteration count must be non-zero");
+                }
+                pbkdf2::pbkdf2_hmac::<Sha256>(password, salt, iterations, &mut output);
+            }
+        }
+        Ok(output)
+    }
+
+    /// Raise the time cost of `self` until one derivation takes at least `target`, or until the
+    /// next step would leave the `validate` limits. Memory settings are kept as given; only
+    /// iterations (or scrypt's N) grow.
+    pub fn calibrate(&self, target: Duration) -> Result<Self, &'static str> {
+        let salt = [0u8; 16];
+        let mut candidate = *self;
+
+        loop {
+            let start = Instant::now();
+            candidate.derive(b"IX calibration password", &salt, 32)?;
+            if start.elapsed() >= target {
+                return Ok(candidate);
+            }
+
+            let next = match candidate {
+                PasswordKdf::Argon2id { memory_kib, iterations, parallelism } => PasswordKdf::Argon2id {
+                    memory_kib,
+                    iterations: iterations.saturating_mul(2),
+                    parallelism,
+                },
+                PasswordKdf::Scrypt { log_n, r, p } => PasswordKdf::Scrypt { log_n: log_n.saturating_add(1), r, p },
+                PasswordKdf::Pbkdf2Sha256 { iterations } => PasswordKdf::Pbkdf2Sha256 {
+                    iterations: iterations.saturating_mul(2),
+                },
+            };
+            if next.validate().is_err() {
+                return Ok(candidate);
+            }
+            candidate = next;
+        }
+    }
+
+    /// Serialize as: kdf id (1 byte) followed by big-endian parameters.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        match *self {
+            PasswordKdf::Argon2id { memory_kib, iterations, parallelism } => {
+                encoded.push(1);
+                encoded.extend_from_slice(&memory_kib.to_be_bytes());
+                encoded.extend_from_slice(&iterations.to_be_bytes());
+                encoded.extend_from_slice(&parallelism.to_be_bytes());
+            }
+            PasswordKdf::Scrypt { log_n, r, p } => {
+                encoded.push(2);
+                encoded.push(log_n);
+                encoded.extend_from_slice(&r.to_be_bytes());
+                encoded.extend_from_slice(&p.to_be_bytes());
+            }
+            PasswordKdf::Pbkdf2Sha256 { iterations } => {
+                encoded.push(3);
+                encoded.extend_from_slice(&iterations.to_be_bytes());
+            }
+        }
+        encoded
+    }
+
+    /// Parse parameters written by `encode`, returning them and the number of bytes consumed.
+    /// Parameters outside the `validate` limits are rejected.
+    pub fn decode(data: &[u8]) -> Result<(Self, usize), &'static str> {
+        Self::decode_with_limit(data, u64::MAX)
+    }
+
+    /// As `decode`, and also rejects parameters whose `memory_cost` exceeds `max_memory`, for
+    /// callers that must stay below the crate-wide limits.
+    pub fn decode_with_limit(data: &[u8], max_memory: u64) -> Result<(Self, usize), &'static str> {
+        let read_u32 = |offset: usize| -> Result<u32, &'static str> {
+            data.get(offset..offset + 4)
+                .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+                .ok_or("Truncated KDF parameters")
+        };
+
+        let (kdf, consumed) = match data.first() {
+            Some(1) => (
+                PasswordKdf::Argon2id {
+                    memory_kib: read_u32(1)?,
+                    iterations: read_u32(5)?,
+                    parallelism: read_u32(9)?,
+                },
+                13,
+            ),
+            Some(2) => (
+                PasswordKdf::Scrypt {
+                    log_n: *data.get(1).ok_or("Truncated KDF parameters")?,
+                    r: read_u32(2)?,
+                    p: read_u32(6)?,
+                },
+                10,
+            ),
+            Some(3) => (PasswordKdf::Pbkdf2Sha256 { iterations: read_u32(1)? }, 5),
+            _ => return Err("Unknown password KDF"),
+        };
+        kdf.validate()?;
+        if kdf.memory_cost() > max_memory {
+            return Err("KDF memory cost exceeds the caller's limit");
+        }
+        Ok((kdf, consumed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_decode_roundtrip() {
+        for kdf in [PasswordKdf::default(), PasswordKdf::scrypt_default(), PasswordKdf::pbkdf2_default()] {
+            let encoded = kdf.encode();
+            assert_eq!(PasswordKdf::decode(&encoded).unwrap(), (kdf, encoded.len()));
+        }
+    }
+
+    #[test]
+    fn decode_rejects_costs_above_the_limits() {
+        let argon2 = PasswordKdf::Argon2id { memory_kib: MAX_ARGON2_MEMORY_KIB + 1, iterations: 1, parallelism: 1 };
+        assert_eq!(PasswordKdf::decode(&argon2.encode()), Err("Argon2id parameters out of range"));
+        let scrypt = PasswordKdf::Scrypt { log_n: 21, r: 8, p: 1 };
+        assert_eq!(PasswordKdf::decode(&scrypt.encode()), Err("scrypt parameters out of range"));
+
+        let encoded = PasswordKdf::default().encode();
+        assert!(PasswordKdf::decode_with_limit(&encoded, 64 * 1024 * 1024).is_ok());
+        assert_eq!(
+            PasswordKdf::decode_with_limit(&encoded, 64 * 1024 * 1024 - 1),
+            Err("KDF memory cost exceeds the caller's limit")
+        );
+    }
+}
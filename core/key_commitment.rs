@@ -0,0 +1,176 @@
+// ix-encryption/core/key_commitment.rs
+
+//! Key-committing wrapper for any `IXCipherCore` implementation.
+//! ChaCha20-Poly1305 and GCM ciphertexts can be crafted to decrypt validly under several keys
+//! (partitioning-oracle / invisible-salamander attacks). This wrapper derives independent
+//! encryption and commitment keys from the caller's key and prefixes every ciphertext with
+//! a random nonce and an HMAC-SHA-256 commitment over it, so decryption under any other key
+//! is rejected before the inner cipher runs. The per-message nonce keeps commitments from
+//! linking ciphertexts made under the same key.
+//!
+//! Output layout: nonce (16) || commitment (32) || inner ciphertext.
+
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+use crate::core::IXCipherCore;
+use crate::core::kdf::hkdf::{hkdf_expand, hkdf_extract, HkdfHash};
+use crate::core::mac::hmac::HmacSha256;
+use crate::core::mac::mac_core::Mac;
+
+pub const COMMITMENT_NONCE_LEN: usize = 16;
+pub const COMMITMENT_LEN: usize = 32;
+
+const ENCRYPTION_KEY_LABEL: &[u8] = b"IX-KeyCommit-v2 encryption key";
+const COMMITMENT_KEY_LABEL: &[u8] = b"IX-KeyCommit-v2 commitment key";
+
+pub struct CommittingCipher<C: IXCipherCore> {
+    inner: C,
+    commit_key: Option<Vec<u8>>,
+}
+
+impl<C: IXCipherCore> CommittingCipher<C> {
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            commit_key: None,
+        }
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Commitment binds the derived commitment key, the inner algorithm identifier and the nonce.
+    fn compute_commitment(&self, nonce: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let commit_key = self.commit_key.as_ref().ok_or("Cipher not initialized")?;
+        let mut mac = HmacSha256::new(commit_key);
+        mac.update(b"IX-KeyCommit-v2");
+        mac.update(self.inner.algorithm_id().as_bytes());
+        Ok(mac.compute(nonce))
+    }
+}
+
+impl<C: IXCipherCore> IXCipherCore for CommittingCipher<C> {
+    fn initialize(&mut self, key: &[u8], salt: Option<&[u8]>) {
+        let mut prk = hkdf_extract(HkdfHash::Sha256, salt, key);
+        let mut encryption_key = hkdf_expand(HkdfHash::Sha256, &prk, ENCRYPTION_KEY_LABEL, key.len())
+            .expect("Key length too large for HKDF-SHA-256");
+        let commit_key = hkdf_expand(HkdfHash::Sha256, &prk, COMMITMENT_KEY_LABEL, 32)
+            .expect("HKDF expand failed");
+        prk.zeroize();
+
+        self.inner.initialize(&encryption_key, salt);
+        if let Some(old) = self.commit_key.as_mut() {
+            old.zeroize();
+        }
+        self.commit_key = Some(commit_key);
+        encryption_key.zeroize();
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        let mut output = vec![0u8; COMMITMENT_NONCE_LEN];
+        getrandom::getrandom(&mut output).expect("Nonce generation failed");
+        let commitment = self.compute_commitment(&output).expect("Cipher not initialized");
+        output.extend_from_slice(&commitment);
+        output.extend_from_slice(&self.inner.encrypt(plaintext));
+        output
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Vec<u8> {
+        self.try_decrypt(ciphertext).expect("Decryption failed")
+    }
+
+    /// Rejects a ciphertext whose commitment does not match before the inner cipher runs.
+    fn try_decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        if ciphertext.len() < COMMITMENT_NONCE_LEN + COMMITMENT_LEN {
+            return Err("Ciphertext missing key commitment");
+        }
+
+        let (nonce, rest) = ciphertext.split_at(COMMITMENT_NONCE_LEN);
+        let (received, body) = rest.split_at(COMMITMENT_LEN);
+        let expected = self.compute_commitment(nonce)?;
+        if !bool::from(expected.ct_eq(received)) {
+            return Err("Key commitment mismatch");
+        }
+        self.inner.try_decrypt(body)
+    }
+
+    fn wipe(&mut self) {
+        if let Some(commit_key) = self.commit_key.as_mut() {
+            commit_key.zeroize();
+        }
+        self.commit_key = None;
+        self.inner.wipe();
+    }
+
+    fn algorithm_id(&self) -> &'static str {
+        "IX-KeyCommit-v2"
+    }
+
+    fn trigger_lockdown(&self) -> bool {
+        self.inner.trigger_lockdown()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::hybrid::ChaChaQuantum;
+
+    fn committing(key: &[u8]) -> CommittingCipher<ChaChaQuantum> {
+        let mut cipher = CommittingCipher::new(ChaChaQuantum::new_extended());
+        cipher.initialize(key, None);
+        cipher
+    }
+
+    #[test]
+    fn roundtrip_and_layout() {
+        let cipher = committing(&[1u8; 32]);
+        let ciphertext = cipher.encrypt(b"committed");
+        assert!(ciphertext.len() > COMMITMENT_NONCE_LEN + COMMITMENT_LEN);
+        assert_eq!(cipher.try_decrypt(&ciphertext).unwrap(), b"committed");
+        assert_eq!(cipher.decrypt(&ciphertext), b"committed");
+    }
+
+    #[test]
+    fn rejects_other_key_before_inner_cipher() {
+        let ciphertext = committing(&[1u8; 32]).encrypt(b"committed");
+        assert_eq!(committing(&[2u8; 32]).try_decrypt(&ciphertext), Err("Key commitment mismatch"));
+    }
+
+    #[test]
+    fn commitments_are_unlinkable() {
+        let cipher = committing(&[1u8; 32]);
+        let first = cipher.encrypt(b"same");
+        let second = cipher.encrypt(b"same");
+        assert_ne!(first[..COMMITMENT_NONCE_LEN + COMMITMENT_LEN], second[..COMMITMENT_NONCE_LEN + COMMITMENT_LEN]);
+    }
+
+    #[test]
+    fn rejects_tampering_and_truncation() {
+        let cipher = committing(&[1u8; 32]);
+        let ciphertext = cipher.encrypt(b"committed");
+
+        let mut bad_nonce = ciphertext.clone();
+        bad_nonce[0] ^= 1;
+        assert_eq!(cipher.try_decrypt(&bad_nonce), Err("Key commitment mismatch"));
+
+        let mut bad_commitment = ciphertext.clone();
+        bad_commitment[COMMITMENT_NONCE_LEN] ^= 1;
+        assert_eq!(cipher.try_decrypt(&bad_commitment), Err("Key commitment mismatch"));
+
+        let mut bad_body = ciphertext.clone();
+        *bad_body.last_mut().unwrap() ^= 1;
+        assert_eq!(cipher.try_decrypt(&bad_body), Err("Decryption failed"));
+
+        assert_eq!(cipher.try_decrypt(&ciphertext[..COMMITMENT_NONCE_LEN]), Err("Ciphertext missing key commitment"));
+    }
+
+    #[test]
+    fn wiped_cipher_refuses() {
+        let mut cipher = committing(&[1u8; 32]);
+        let ciphertext = cipher.encrypt(b"committed");
+        cipher.wipe();
+        assert_eq!(cipher.try_decrypt(&ciphertext), Err("Cipher not initialized"));
+    }
+}
@@ -0,0 +1,227 @@
+// ix-encryption/core/key_wrap.rs
+
+//! AES Key Wrap (RFC 3394, AES-KW) and Key Wrap with Padding (RFC 5649, AES-KWP).
+//! Wraps data-encryption keys under a key-encryption key (KEK) with a built-in integrity check,
+//! compatible with cloud KMS key exports.
+
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+use crate::core::blockcipher::BlockCipher;
+
+/// Default initial value for RFC 3394.
+const KW_IV: [u8; 8] = [0xa6; 8];
+
+/// Alternative initial value prefix for RFC 5649.
+const KWP_AIV_PREFIX: [u8; 4] = [0xa6, 0x59, 0x59, 0xa6];
+
+pub struct KeyWrap<'a, C: BlockCipher> {
+    kek: &'a C,
+}
+
+impl<'a, C: BlockCipher> KeyWrap<'a, C> {
+    pub fn new(kek: &'a C) -> Self {
+        assert_eq!(kek.block_size(), 16, "Key wrap requires a 128-bit block cipher");
+        KeyWrap { kek }
+    }
+
+    /// AES-KW: wrap key material that is a multiple of 8 bytes and at least 16 bytes long.
+    pub fn wrap(&self, key: &[u8]) -> Result<Vec<u8>, &'static str> {
+        if key.len() < 16 || key.len() % 8 != 0 {
+            return Err("AES-KW input must be a multiple of 8 bytes and at least 16 bytes");
+        }
+        Ok(self.wrap_blocks(KW_IV, key))
+    }
+
+    /// AES-KW unwrap. Fails if the integrity check value does not match.
+    pub fn unwrap(&self, wrapped: &[u8]) -> Result<Vec<u8>, &'static str> {
+        if wrapped.len() < 24 || wrapped.len() % 8 != 0 {
+            return Err("AES-KW ciphertext must be a multiple of 8 bytes and at least 24 bytes");
+        }
+        let (a, mut key) = self.unwrap_blocks(wrapped);
+        if !bool::from(a.ct_eq(&KW_IV)) {
+            key.zeroize();
+            return Err("Key unwrap integrity check failed");
+        }
+        Ok(key)
+    }
+
+    /// AES-KWP: wrap key material of any non-zero length.
+    pub fn wrap_with_padding(&self, key: &[u8]) -> Result<Vec<u8>, &'static str> {
+        if key.is_empty() || key.len() > u32::MAX as usize {
+            return Err("AES-KWP input must be 1 to 2^32 - 1 bytes");
+        }
+
+        let mut aiv = [0u8; 8];
+        aiv[..4].copy_from_slice(&KWP_AIV_PREFIX);
+        aiv[4..].copy_from_slice(&(key.len() as u32).to_be_bytes());
+
+        let mut padded = key.to_vec();
+        padded.resize((key.len() + 7) / 8 * 8, 0);
+
+        let wrapped = if padded.len() == 8 {
+            let mut block = aiv.to_vec();
+            block.extend_from_slice(&padded);
+            let encrypted = self.kek.encrypt_block(&block);
+            block.zeroize();
+            encrypted
+        } else {
+            self.wrap_blocks(aiv, &padded)
+        };
+        padded.zeroize();
+        Ok(wrapped)
+    }
+
+    /// AES-KWP unwrap. Fails if the integrity check value, length or padding is invalid.
+    pub fn unwrap_with_padding(&self, wrapped: &[u8]) -> Result<Vec<u8>, &'static str> {
+        if wrapped.len() < 16 || wrapped.len() % 8 != 0 {
+            return Err("AES-KWP ciphertext must be a multiple of 8 bytes and at least 16 bytes");
+        }
+
+        let (a, mut padded) = if wrapped.len() == 16 {
+            let block = self.kek.decrypt_block(wrapped);
+            let mut a = [0u8; 8];
+            a.copy_from_slice(&block[..8]);
+            (a, block[8..].to_vec())
+        } else {
+            self.unwrap_blocks(wrapped)
+        };
+
+        let message_len = u32::from_be_bytes([a[4], a[5], a[6], a[7]]) as usize;
+        let prefix_ok = a[..4].ct_eq(&KWP_AIV_PREFIX);
+        let length_ok = message_len > padded.len().saturating_sub(8) && message_len <= padded.len();
+        let padding_ok = length_ok
+            && bool::from(padded[message_len..].ct_eq(&vec![0u8; padded.len() - message_len]));
+
+        if !(bool::from(prefix_ok) && length_ok && padding_ok) {
+            padded.zeroize();
+            return Err("Key unwrap integrity check failed");
+        }
+        padded.truncate(message_len);
+        Ok(padded)
+    }
+
+    /// Wrap a 32-byte key as consumed by `ChaChaQuantum::initialize` (40-byte output).
+    pub fn wrap_chacha_key(&self, key: &[u8; 32]) -> Result<Vec<u8>, &'static str> {
+        self.wrap(key)
+    }
+
+    /// Unwrap a key produced by `wrap_chacha_key`, ready to pass to `ChaChaQuantum::initialize`.
+    pub fn unwrap_chacha_key(&self, wrapped: &[u8]) -> Result<[u8; 32], &'static str> {
+        if wrapped.len() != 40 {
+            return Err("Wrapped ChaCha key must be 40 bytes");
+        }
+        let mut unwrapped = self.unwrap(wrapped)?;
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&unwrapped);
+        unwrapped.zeroize();
+        Ok(key)
+    }
+
+    /// Wrapping function W from RFC 3394 section 2.2.1 (index-based form).
+    fn wrap_blocks(&self, iv: [u8; 8], plaintext: &[u8]) -> Vec<u8> {
+        let n = plaintext.len() / 8;
+        let mut a = iv;
+        let mut r = plaintext.to_vec();
+        let mut block = [0u8; 16];
+
+        for j in 0..6 {
+            for i in 0..n {
+                block[..8].copy_from_slice(&a);
+                block[8..].copy_from_slice(&r[i * 8..i * 8 + 8]);
+                let b = self.kek.encrypt_block(&block);
+
+                let t = (n * j + i + 1) as u64;
+                a.copy_from_slice(&b[..8]);
+                for (a_byte, t_byte) in a.iter_mut().zip(t.to_be_bytes().iter()) {
+                    *a_byte ^= t_byte;
+                }
+                r[i * 8..i * 8 + 8].copy_from_slice(&b[8..]);
+            }
+        }
+
+        block.zeroize();
+        let mut output = a.to_vec();
+        output.extend_from_slice(&r);
+        r.zeroize();
+        output
+    }
+
+    /// Inverse of `wrap_blocks`, returning the recovered integrity value and key data.
+    fn unwrap_blocks(&self, ciphertext: &[u8]) -> ([u8; 8], Vec<u8>) {
+        let n = ciphertext.len() / 8 - 1;
+        let mut a = [0u8; 8];
+        a.copy_from_slice(&ciphertext[..8]);
+        let mut r = ciphertext[8..].to_vec();
+        let mut block = [0u8; 16];
+
+        for j in (0..6).rev() {
+            for i in (0..n).rev() {
+                let t = (n * j + i + 1) as u64;
+                for (a_byte, t_byte) in a.iter_mut().zip(t.to_be_bytes().iter()) {
+                    *a_byte ^= t_byte;
+                }
+                block[..8].copy_from_slice(&a);
+                block[8..].copy_from_slice(&r[i * 8..i * 8 + 8]);
+                let b = self.kek.decrypt_block(&block);
+
+                a.copy_from_slice(&b[..8]);
+                r[i * 8..i * 8 + 8].copy_from_slice(&b[8..]);
+            }
+        }
+
+        block.zeroize();
+        (a, r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ciphers::aes::AesCipher;
+    use crate::core::test_util::h;
+
+    /// RFC 3394 section 4: every KEK size against 128-, 192- and 256-bit key data.
+    #[test]
+    fn rfc3394_section4_vectors() {
+        let kek = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+        let data = "00112233445566778899aabbccddeeff000102030405060708090a0b0c0d0e0f";
+        let cases = [
+            (16, 16, "1fa68b0a8112b447aef34bd8fb5a7b829d3e862371d2cfe5"),
+            (24, 16, "96778b25ae6ca435f92b5b97c050aed2468ab8a17ad84e5d"),
+            (32, 16, "64e8c3f9ce0f5ba263e9777905818a2a93c8191e7d6e8ae7"),
+            (24, 24, "031d33264e15d33268f24ec260743edce1c6c7ddee725a936ba814915c6762d2"),
+            (32, 24, "a8f9bc1612c68b3ff6e6f4fbe30e71e4769c8b80a32cb8958cd5d17d6b254da1"),
+            (32, 32, "28c9f404c4b810f4cbccb35cfb87f8263f5786e2d80ed326cbc7f0e71a99f43bfb988b9b7a02dd21"),
+        ];
+        for (kek_len, data_len, expected) in cases {
+            let cipher = AesCipher::new(&h(kek)[..kek_len]).unwrap();
+            let wrap = KeyWrap::new(&cipher);
+            let key = &h(data)[..data_len];
+            assert_eq!(wrap.wrap(key).unwrap(), h(expected));
+            assert_eq!(wrap.unwrap(&h(expected)).unwrap(), key);
+
+            let mut tampered = h(expected);
+            tampered[0] ^= 1;
+            assert_eq!(wrap.unwrap(&tampered), Err("Key unwrap integrity check failed"));
+        }
+    }
+
+    /// RFC 5649 section 6: a 20-byte and a 7-byte key under a 192-bit KEK.
+    #[test]
+    fn rfc5649_section6_vectors() {
+        let cipher = AesCipher::new(&h("5840df6e29b02af1ab493b705bf16ea1ae8338f4dcc176a8")).unwrap();
+        let wrap = KeyWrap::new(&cipher);
+        let cases = [
+            ("c37b7e6492584340bed12207808941155068f738", "138bdeaa9b8fa7fc61f97742e72248ee5ae6ae5360d1ae6a5f54f373fa543b6a"),
+            ("466f7250617369", "afbeb0f07dfbf5419200f2ccb50bb24f"),
+        ];
+        for (key, expected) in cases {
+            assert_eq!(wrap.wrap_with_padding(&h(key)).unwrap(), h(expected));
+            assert_eq!(wrap.unwrap_with_padding(&h(expected)).unwrap(), h(key));
+
+            let mut tampered = h(expected);
+            tampered[0] ^= 1;
+            assert_eq!(wrap.unwrap_with_padding(&tampered), Err("Key unwrap integrity check failed"));
+        }
+    }
+}
@@ -0,0 +1,149 @@
+// ix-encryption/core/keyformat/pbes2.rs
+
+//! Password-encrypted PKCS#8 (EncryptedPrivateKeyInfo, RFC 5958 §3) using PBES2 (RFC 8018)
+//! with PBKDF2-HMAC-SHA256 or scrypt (RFC 7914) and AES-CBC. PBKDF2 with the legacy
+//! HMAC-SHA1 default PRF is rejected on import.
+
+use zeroize::Zeroize;
+use crate::core::ciphers::aes::AesCipher;
+use crate::core::cms::der::{self, DerReader, INTEGER, NULL, OCTET_STRING, SEQUENCE};
+use crate::core::kdf::password::PasswordKdf;
+use crate::core::mode_cbc::CBCMode;
+
+const ID_PBES2: &[u64] = &[1, 2, 840, 113549, 1, 5, 13];
+const ID_PBKDF2: &[u64] = &[1, 2, 840, 113549, 1, 5, 12];
+const ID_SCRYPT: &[u64] = &[1, 3, 6, 1, 4, 1, 11591, 4, 11];
+const ID_HMAC_SHA256: &[u64] = &[1, 2, 840, 113549, 2, 9];
+const ID_AES128_CBC: &[u64] = &[2, 16, 840, 1, 101, 3, 4, 1, 2];
+const ID_AES192_CBC: &[u64] = &[2, 16, 840, 1, 101, 3, 4, 1, 22];
+const ID_AES256_CBC: &[u64] = &[2, 16, 840, 1, 101, 3, 4, 1, 42];
+
+const SALT_LEN: usize = 16;
+const IV_LEN: usize = 16;
+
+/// Encrypt a DER PrivateKeyInfo under `password` with AES-256-CBC. Only the PBKDF2 and scrypt
+/// variants of `kdf` have PBES2 identifiers.
+pub fn encrypt(private_key_info: &[u8], password: &[u8], kdf: &PasswordKdf) -> Result<Vec<u8>, &'static str> {
+    kdf.validate()?;
+    let mut salt = [0u8; SALT_LEN];
+    let mut iv = [0u8; IV_LEN];
+    getrandom::getrandom(&mut salt).map_err(|_| "Salt generation failed")?;
+    getrandom::getrandom(&mut iv).map_err(|_| "IV generation failed")?;
+
+    let kdf_algorithm = match *kdf {
+        PasswordKdf::Pbkdf2Sha256 { iterations } => der::algorithm(
+            ID_PBKDF2,
+            Some(der::sequence(&[
+                der::octet_string(&salt),
+                der::integer(iterations as u64),
+                der::algorithm(ID_HMAC_SHA256, Some(der::tlv(NULL, &[]))),
+            ])),
+        ),
+        PasswordKdf::Scrypt { log_n, r, p } => der::algorithm(
+            ID_SCRYPT,
+            Some(der::sequence(&[
+                der::octet_string(&salt),
+                der::integer(1u64 << log_n),
+                der::integer(r as u64),
+                der::integer(p as u64),
+            ])),
+        ),
+        PasswordKdf::Argon2id { .. } => return Err("PBES2 supports only PBKDF2 and scrypt"),
+    };
+
+    let mut key = kdf.derive(password, &salt, 32)?;
+    let cipher = AesCipher::new(&key);
+    key.zeroize();
+    let encrypted = CBCMode::new(&cipher?, iv.to_vec()).encrypt(private_key_info);
+
+    let scheme = der::sequence(&[kdf_algorithm, der::algorithm(ID_AES256_CBC, Some(der::octet_string(&iv)))]);
+    Ok(der::sequence(&[der::algorithm(ID_PBES2, Some(scheme)), der::octet_string(&encrypted)]))
+}
+
+/// Decrypt an EncryptedPrivateKeyInfo, returning the DER PrivateKeyInfo.
+pub fn decrypt(encrypted_private_key_info: &[u8], password: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let mut outer = DerReader::new(encrypted_private_key_info);
+    let mut info = DerReader::new(outer.read(SEQUENCE)?);
+    outer.finish()?;
+
+    let (oid, params) = info.read_algorithm()?;
+    if oid != der::oid_content(ID_PBES2) {
+        return Err("Unsupported private key encryption scheme");
+    }
+    let encrypted = info.read(OCTET_STRING)?;
+    info.finish()?;
+
+    let mut params = DerReader::new(params.ok_or("Missing PBES2 parameters")?);
+    let mut scheme = DerReader::new(params.read(SEQUENCE)?);
+    params.finish()?;
+    let (kdf_oid, kdf_params) = scheme.read_algorithm()?;
+    let (cipher_oid, cipher_params) = scheme.read_algorithm()?;
+    scheme.finish()?;
+
+    let key_len = match cipher_oid {
+        oid if oid == der::oid_content(ID_AES128_CBC) => 16,
+        oid if oid == der::oid_content(ID_AES192_CBC) => 24,
+        oid if oid == der::oid_content(ID_AES256_CBC) => 32,
+        _ => return Err("Unsupported PBES2 encryption scheme"),
+    };
+    let mut iv_reader = DerReader::new(cipher_params.ok_or("Missing AES-CBC IV")?);
+    let iv = iv_reader.read(OCTET_STRING)?;
+    iv_reader.finish()?;
+    if iv.len() != IV_LEN {
+        return Err("Invalid AES-CBC IV length");
+    }
+
+    let (kdf, salt) = read_kdf(kdf_oid, kdf_params.ok_or("Missing PBES2 KDF parameters")?, key_len)?;
+    let mut key = kdf.derive(password, salt, key_len)?;
+    let cipher = AesCipher::new(&key);
+    key.zeroize();
+    // A padding failure almost always means a wrong password.
+    CBCMode::new(&cipher?, iv.to_vec()).decrypt(encrypted).map_err(|_| "Private key decryption failed")
+}
+
+fn read_kdf<'a>(oid: &[u8], params: &'a [u8], key_len: usize) -> Result<(PasswordKdf, &'a [u8]), &'static str> {
+    let mut outer = DerReader::new(params);
+    let mut params = DerReader::new(outer.read(SEQUENCE)?);
+    outer.finish()?;
+    let salt = params.read(OCTET_STRING)?;
+
+    let kdf = if oid == der::oid_content(ID_PBKDF2) {
+        let iterations = u32::try_from(params.read_integer()?).map_err(|_| "Unsupported PBKDF2 iteration count")?;
+        read_key_length(&mut params, key_len)?;
+        // An absent PRF means HMAC-SHA1, which is not accepted.
+        match params.is_empty() {
+            true => return Err("Unsupported PBKDF2 PRF"),
+            false => {
+                let (prf, prf_params) = params.read_algorithm()?;
+                if prf != der::oid_content(ID_HMAC_SHA256) || prf_params.is_some_and(|p| p != der::tlv(NULL, &[])) {
+                    return Err("Unsupported PBKDF2 PRF");
+                }
+            }
+        }
+        PasswordKdf::Pbkdf2Sha256 { iterations }
+    } else if oid == der::oid_content(ID_SCRYPT) {
+        let n = params.read_integer()?;
+        let r = params.read_integer()?;
+        let p = params.read_integer()?;
+        read_key_length(&mut params, key_len)?;
+        if !n.is_power_of_two() || n < 2 {
+            return Err("Unsupported scrypt cost parameter");
+        }
+        let (r, p) = u32::try_from(r).and_then(|r| Ok((r, u32::try_from(p)?))).map_err(|_| "Unsupported scrypt parameters")?;
+        PasswordKdf::Scrypt { log_n: n.trailing_zeros() as u8, r, p }
+    } else {
+        return Err("Unsupported PBES2 key derivation function");
+    };
+    params.finish()?;
+    // The shared limits bound imported cost parameters, so a crafted file cannot stall the caller
+    kdf.validate()?;
+    Ok((kdf, salt))
+}
+
+/// The optional keyLength field must agree with the cipher when present.
+fn read_key_length(params: &mut DerReader, key_len: usize) -> Result<(), &'static str> {
+    if params.peek_tag() == Some(INTEGER) && params.read_integer()? != key_len as u64 {
+        return Err("PBES2 key length does not match the cipher");
+    }
+    Ok(())
+}
@@ -0,0 +1,67 @@
+// ix-encryption/core/keyformat/pem.rs
+
+//! Strict PEM textual encoding (RFC 7468 §3): one labelled block, standard base64 with padding,
+//! no encapsulated headers. Lines are written at 64 characters; CRLF is accepted on input.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+pub const LABEL_PUBLIC_KEY: &str = "PUBLIC KEY";
+pub const LABEL_PRIVATE_KEY: &str = "PRIVATE KEY";
+pub const LABEL_ENCRYPTED_PRIVATE_KEY: &str = "ENCRYPTED PRIVATE KEY";
+
+const LINE_LEN: usize = 64;
+
+pub fn encode(label: &str, der: &[u8]) -> String {
+    let body = STANDARD.encode(der);
+    let mut out = format!("-----BEGIN {label}-----\n");
+    for line in body.as_bytes().chunks(LINE_LEN) {
+        out.push_str(std::str::from_utf8(line).expect("base64 is ASCII"));
+        out.push('\n');
+    }
+    out.push_str(&format!("-----END {label}-----\n"));
+    out
+}
+
+/// Decode a single PEM block, returning its label and DER contents.
+pub fn decode(pem: &str) -> Result<(String, Vec<u8>), &'static str> {
+    let mut lines = pem.trim().lines().map(|line| line.trim_end_matches('\r'));
+    let label = lines
+        .next()
+        .and_then(|line| line.strip_prefix("-----BEGIN "))
+        .and_then(|line| line.strip_suffix("-----"))
+        .ok_or("Missing PEM BEGIN line")?;
+    if label.is_empty() || !label.bytes().all(|b| b.is_ascii_uppercase() || b == b' ' || b.is_ascii_digit()) {
+        return Err("Invalid PEM label");
+    }
+
+    let mut body = String::new();
+    let mut ended = false;
+    for line in lines.by_ref() {
+        if line == format!("-----END {label}-----") {
+            ended = true;
+            break;
+        }
+        if line.contains(':') {
+            return Err("PEM headers are not supported");
+        }
+        body.push_str(line.trim());
+    }
+    if !ended {
+        return Err("Missing or mismatched PEM END line");
+    }
+    if lines.next().is_some() {
+        return Err("Trailing data after PEM block");
+    }
+
+    let der = STANDARD.decode(&body).map_err(|_| "Invalid base64 in PEM body")?;
+    Ok((label.to_string(), der))
+}
+
+/// Decode a PEM block that must carry `label`.
+pub fn decode_label(pem: &str, label: &str) -> Result<Vec<u8>, &'static str> {
+    match decode(pem)? {
+        (found, der) if found == label => Ok(der),
+        _ => Err("Unexpected PEM label"),
+    }
+}
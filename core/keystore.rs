@@ -0,0 +1,706 @@
+// ix-encryption/core/keystore.rs
+
+//! File-backed encrypted keystore for symmetric keys, KEM key pairs and signing keys.
+//! A random store key seals the entry table with `ChaChaQuantum`; the store key itself is
+//! AES-KW wrapped under a key-encryption key derived from a passphrase or from a caller-held
+//! 32-byte master key (for example one unwrapped by a KMS or HSM). Changing the master only
+//! rewraps the store key.
+//!
+//! Every key id holds a list of versions. Rotation adds a version and demotes the previous
+//! active one to decrypt-only; at most one version per id is active. Destroying a version
+//! wipes its material but keeps its metadata as a tombstone.
+//!
+//! Layout: "IXKS" | version | master kind (u8) | master params | wrapped store key (40)
+//!         | nonce (12) | sealed entry table
+//! Master: passphrase  kdf params | salt len (u8) | salt
+//!         key         (none)
+//! Table:  entry count (u32) | [id len (u8) | id | version (u32) | kind (u8) | alg len (u8) | alg
+//!         | created (u64) | expires (u64, 0 = never) | usage (u8) | state (u8) | key len (u32)
+//!         | secret len (u32) | secret | public len (u32) | public]*
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use zeroize::Zeroize;
+use crate::core::IXCipherCore;
+use crate::core::byte_reader::ByteReader;
+use crate::core::ciphers::aes::AesCipher;
+use crate::core::hybrid::{ChaChaQuantum, NONCE_LEN};
+use crate::core::kdf::hkdf::{hkdf, HkdfHash};
+use crate::core::kdf::password::PasswordKdf;
+use crate::core::key_wrap::KeyWrap;
+use crate::core::postquantum::lattice_kem::LatticeKEM;
+
+const MAGIC: &[u8; 4] = b"IXKS";
+pub const KEYSTORE_VERSION: u8 = 1;
+const STORE_KEY_LEN: usize = 32;
+const WRAPPED_STORE_KEY_LEN: usize = STORE_KEY_LEN + 8;
+const SALT_LEN: usize = 16;
+const KEK_INFO: &[u8] = b"IX-Keystore-v1 kek";
+
+const MASTER_PASSPHRASE: u8 = 1;
+const MASTER_KEY: u8 = 2;
+
+/// Algorithm labels used by the built-in generators; imported keys may use any label.
+/// `LatticeKEM` is a placeholder rather than FIPS 203 ML-KEM, so its keys are not labelled as such.
+pub const ALG_LATTICE_KEM: &str = "IX-LatticeKEM-v1";
+pub const ALG_ED25519: &str = "Ed25519";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyKind {
+    Symmetric = 1,
+    KemKeypair = 2,
+    SigningKey = 3,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyState {
+    Active = 1,
+    /// Kept for decrypting or verifying existing data; not used for new operations.
+    DecryptOnly = 2,
+    /// Material wiped; only metadata remains.
+    Destroyed = 3,
+}
+
+/// Bit set of permitted operations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyUsage(pub u8);
+
+impl KeyUsage {
+    pub const ENCRYPT: KeyUsage = KeyUsage(0x01);
+    pub const DECRYPT: KeyUsage = KeyUsage(0x02);
+    pub const SIGN: KeyUsage = KeyUsage(0x04);
+    pub const VERIFY: KeyUsage = KeyUsage(0x08);
+    pub const WRAP: KeyUsage = KeyUsage(0x10);
+    pub const UNWRAP: KeyUsage = KeyUsage(0x20);
+    pub const DERIVE: KeyUsage = KeyUsage(0x40);
+
+    pub fn contains(self, other: KeyUsage) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Usages that only consume existing data and stay allowed on decrypt-only versions.
+    fn is_reverse_only(self) -> bool {
+        self.0 & !(KeyUsage::DECRYPT.0 | KeyUsage::VERIFY.0 | KeyUsage::UNWRAP.0) == 0
+    }
+}
+
+impl std::ops::BitOr for KeyUsage {
+    type Output = KeyUsage;
+
+    fn bitor(self, other: KeyUsage) -> KeyUsage {
+        KeyUsage(self.0 | other.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyMetadata {
+    pub id: String,
+    pub version: u32,
+    pub kind: KeyKind,
+    pub algorithm: String,
+    /// Seconds since the Unix epoch.
+    pub created: u64,
+    pub expires: Option<u64>,
+    pub usage: KeyUsage,
+    pub state: KeyState,
+    /// Length of the secret material, kept after destruction so the key can still be rotated.
+    pub key_len: usize,
+}
+
+/// One key version. The secret is wiped on drop and on destruction.
+pub struct KeyEntry {
+    pub metadata: KeyMetadata,
+    secret: Vec<u8>,
+    public: Vec<u8>,
+}
+
+impl KeyEntry {
+    /// Secret material, or the symmetric key itself.
+    pub fn secret(&self) -> Result<&[u8], &'static str> {
+        match self.metadata.state {
+            KeyState::Destroyed => Err("Key version has been destroyed"),
+            _ => Ok(&self.secret),
+        }
+    }
+
+    /// Public half of a key pair; empty for symmetric keys.
+    pub fn public(&self) -> &[u8] {
+        &self.public
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.metadata.expires.is_some_and(|expires| now() >= expires)
+    }
+
+    /// Check that this version may be used for `usage` in its current state.
+    pub fn require(&self, usage: KeyUsage) -> Result<(), &'static str> {
+        if !self.metadata.usage.contains(usage) {
+            return Err("Key usage not permitted");
+        }
+        match self.metadata.state {
+            KeyState::Destroyed => Err("Key version has been destroyed"),
+            KeyState::DecryptOnly if !usage.is_reverse_only() => Err("Key version is decrypt-only"),
+            KeyState::Active if !usage.is_reverse_only() && self.is_expired() => Err("Key version has expired"),
+            _ => Ok(()),
+        }
+    }
+
+    /// Rebuild the `LatticeKEM` key pair of a KEM entry.
+    pub fn to_lattice(&self) -> Result<LatticeKEM, &'static str> {
+        if self.metadata.kind != KeyKind::KemKeypair {
+            return Err("Not a KEM key pair");
+        }
+        Ok(LatticeKEM { public_key: self.public.clone(), secret_key: self.secret()?.to_vec() })
+    }
+}
+
+impl Drop for KeyEntry {
+    fn drop(&mut self) {
+        self.secret.zeroize();
+    }
+}
+
+/// Credential that unlocks a keystore.
+pub enum Master<'a> {
+    Passphrase(&'a [u8]),
+    Key(&'a [u8; 32]),
+}
+
+pub struct Keystore {
+    path: PathBuf,
+    /// Encoded master section: kind and parameters, without the wrapped store key.
+    master: Vec<u8>,
+    wrapped_store_key: Vec<u8>,
+    store_key: Vec<u8>,
+    entries: Vec<KeyEntry>,
+}
+
+impl Drop for Keystore {
+    fn drop(&mut self) {
+        self.store_key.zeroize();
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn random_bytes(len: usize) -> Result<Vec<u8>, &'static str> {
+    let mut bytes = vec![0u8; len];
+    getrandom::getrandom(&mut bytes).map_err(|_| "Random generation failed")?;
+    Ok(bytes)
+}
+
+/// A string with a one-byte length prefix.
+fn take_string(reader: &mut ByteReader) -> Result<String, &'static str> {
+    let len = reader.take_u8()? as usize;
+    String::from_utf8(reader.take(len)?.to_vec()).map_err(|_| "Invalid keystore string")
+}
+
+/// Key-encryption key for the store key, from the master section and the credential.
+fn derive_kek(master: &[u8], credential: &Master) -> Result<Vec<u8>, &'static str> {
+    match (master.first(), credential) {
+        (Some(&MASTER_PASSPHRASE), Master::Passphrase(password)) => {
+            let (kdf, consumed) = PasswordKdf::decode(&master[1..])?;
+            let mut reader = ByteReader::new(master, "Truncated keystore");
+            reader.take(1 + consumed)?;
+            let salt_len = reader.take_u8()? as usize;
+            let salt = reader.take(salt_len)?;
+            let mut stretched = kdf.derive(password, salt, 32)?;
+            let kek = hkdf(HkdfHash::Sha256, None, &stretched, KEK_INFO, 32);
+            stretched.zeroize();
+            kek
+        }
+        (Some(&MASTER_KEY), Master::Key(key)) => hkdf(HkdfHash::Sha256, None, &key[..], KEK_INFO, 32),
+        (Some(&MASTER_PASSPHRASE), _) => Err("Keystore is protected by a passphrase"),
+        (Some(&MASTER_KEY), _) => Err("Keystore is protected by a master key"),
+        _ => Err("Unsupported keystore master kind"),
+    }
+}
+
+fn master_section(credential: &Master, kdf: &PasswordKdf) -> Result<Vec<u8>, &'static str> {
+    match credential {
+        Master::Passphrase(_) => {
+            kdf.validate()?;
+            let mut section = vec![MASTER_PASSPHRASE];
+            section.extend_from_slice(&kdf.encode());
+            section.push(SALT_LEN as u8);
+            section.extend_from_slice(&random_bytes(SALT_LEN)?);
+            Ok(section)
+        }
+        Master::Key(_) => Ok(vec![MASTER_KEY]),
+    }
+}
+
+fn wrap_store_key(master: &[u8], credential: &Master, store_key: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let mut kek = derive_kek(master, credential)?;
+    let wrapped = AesCipher::new(&kek).and_then(|cipher| KeyWrap::new(&cipher).wrap(store_key));
+    kek.zeroize();
+    wrapped
+}
+
+impl Keystore {
+    /// Create an empty keystore at `path`; `kdf` is used only for passphrase masters.
+    /// Nothing is written until `save`.
+    pub fn create(path: impl AsRef<Path>, credential: &Master, kdf: PasswordKdf) -> Result<Self, &'static str> {
+        if path.as_ref().exists() {
+            return Err("Keystore file already exists");
+        }
+        let master = master_section(credential, &kdf)?;
+        let store_key = random_bytes(STORE_KEY_LEN)?;
+        let wrapped_store_key = wrap_store_key(&master, credential, &store_key)?;
+        Ok(Self { path: path.as_ref().to_path_buf(), master, wrapped_store_key, store_key, entries: Vec::new() })
+    }
+
+    pub fn open(path: impl AsRef<Path>, credential: &Master) -> Result<Self, &'static str> {
+        let data = fs::read(path.as_ref()).map_err(|_| "Cannot read keystore file")?;
+        if data.len() < MAGIC.len() + 2 || &data[..4] != MAGIC {
+            return Err("Not a keystore file");
+        }
+        if data[4] != KEYSTORE_VERSION {
+            return Err("Unsupported keystore version");
+        }
+
+        let mut reader = ByteReader::new(&data, "Truncated keystore");
+        reader.take(MAGIC.len() + 1)?;
+        let master_len = match data[5] {
+            MASTER_PASSPHRASE => {
+                // decode bounds the cost parameters, so a crafted file cannot stall the open
+                let (_, consumed) = PasswordKdf::decode(&data[6..])?;
+                let salt_len = *data.get(6 + consumed).ok_or("Truncated keystore")? as usize;
+                2 + consumed + salt_len
+            }
+            _ => 1,
+        };
+        let master = reader.take(master_len)?.to_vec();
+        let wrapped_store_key = reader.take(WRAPPED_STORE_KEY_LEN)?.to_vec();
+        let header_len = reader.position();
+        let nonce: [u8; NONCE_LEN] = reader.take_array()?;
+
+        let mut kek = derive_kek(&master, credential)?;
+        let store_key = AesCipher::new(&kek).and_then(|cipher| KeyWrap::new(&cipher).unwrap(&wrapped_store_key));
+        kek.zeroize();
+        let store_key = store_key.map_err(|_| "Wrong keystore passphrase or master key")?;
+
+        let mut cipher = ChaChaQuantum::new();
+        cipher.initialize(&store_key, None);
+        let table = cipher.open_with_nonce(&nonce, &data[..header_len], reader.remaining());
+        cipher.wipe();
+        let mut table = table.map_err(|_| "Keystore integrity check failed")?;
+
+        let entries = Self::decode_table(&table);
+        table.zeroize();
+        Ok(Self { path: path.as_ref().to_path_buf(), master, wrapped_store_key, store_key, entries: entries? })
+    }
+
+    /// Write the keystore atomically: a freshly named temporary file is written, synced and
+    /// renamed, so a concurrent or interrupted save never clobbers another's temporary file.
+    pub fn save(&self) -> Result<(), &'static str> {
+        let mut header = MAGIC.to_vec();
+        header.push(KEYSTORE_VERSION);
+        header.extend_from_slice(&self.master);
+        header.extend_from_slice(&self.wrapped_store_key);
+        let nonce: [u8; NONCE_LEN] = random_bytes(NONCE_LEN)?.try_into().unwrap();
+
+        let mut table = self.encode_table()?;
+        let mut cipher = ChaChaQuantum::new();
+        cipher.initialize(&self.store_key, None);
+        let sealed = cipher.seal_with_nonce(&nonce, &header, &table);
+        cipher.wipe();
+        table.zeroize();
+
+        let mut output = header;
+        output.extend_from_slice(&nonce);
+        output.extend_from_slice(&sealed?);
+
+        let mut temp = self.path.clone().into_os_string();
+        let suffix = u64::from_be_bytes(random_bytes(8)?.try_into().unwrap());
+        temp.push(format!(".{suffix:016x}.tmp"));
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&temp).map_err(|_| "Cannot write keystore file")?;
+        let written = file.write_all(&output).and_then(|_| file.sync_all()).map_err(|_| "Cannot write keystore file");
+        let replaced = written.and_then(|_| fs::rename(&temp, &self.path).map_err(|_| "Cannot replace keystore file"));
+        if replaced.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+        replaced
+    }
+
+    /// Rewrap the store key under a new credential; entries are untouched until the next `save`.
+    pub fn change_master(&mut self, credential: &Master, kdf: PasswordKdf) -> Result<(), &'static str> {
+        let master = master_section(credential, &kdf)?;
+        self.wrapped_store_key = wrap_store_key(&master, credential, &self.store_key)?;
+        self.master = master;
+        Ok(())
+    }
+
+    fn encode_table(&self) -> Result<Vec<u8>, &'static str> {
+        let mut out = (self.entries.len() as u32).to_be_bytes().to_vec();
+        for entry in &self.entries {
+            let meta = &entry.metadata;
+            out.push(meta.id.len() as u8);
+            out.extend_from_slice(meta.id.as_bytes());
+            out.extend_from_slice(&meta.version.to_be_bytes());
+            out.push(meta.kind as u8);
+            out.push(meta.algorithm.len() as u8);
+            out.extend_from_slice(meta.algorithm.as_bytes());
+            out.extend_from_slice(&meta.created.to_be_bytes());
+            out.extend_from_slice(&meta.expires.unwrap_or(0).to_be_bytes());
+            out.push(meta.usage.0);
+            out.push(meta.state as u8);
+            out.extend_from_slice(&u32::try_from(meta.key_len).map_err(|_| "Key material too large")?.to_be_bytes());
+            for material in [&entry.secret, &entry.public] {
+                out.extend_from_slice(&u32::try_from(material.len()).map_err(|_| "Key material too large")?.to_be_bytes());
+                out.extend_from_slice(material);
+            }
+        }
+        Ok(out)
+    }
+
+    fn decode_table(table: &[u8]) -> Result<Vec<KeyEntry>, &'static str> {
+        let mut reader = ByteReader::new(table, "Truncated keystore");
+        let count = reader.take_u32()?;
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            let id = take_string(&mut reader)?;
+            let version = reader.take_u32()?;
+            let kind = match reader.take_u8()? {
+                1 => KeyKind::Symmetric,
+                2 => KeyKind::KemKeypair,
+                3 => KeyKind::SigningKey,
+                _ => return Err("Unknown key kind in keystore"),
+            };
+            let algorithm = take_string(&mut reader)?;
+            let created = reader.take_u64()?;
+            let expires = Some(reader.take_u64()?).filter(|&expires| expires != 0);
+            let usage = KeyUsage(reader.take_u8()?);
+            let state = match reader.take_u8()? {
+                1 => KeyState::Active,
+                2 => KeyState::DecryptOnly,
+                3 => KeyState::Destroyed,
+                _ => return Err("Unknown key state in keystore"),
+            };
+            let key_len = reader.take_u32()? as usize;
+            let secret_len = reader.take_u32()? as usize;
+            let secret = reader.take(secret_len)?.to_vec();
+            let public_len = reader.take_u32()? as usize;
+            let public = reader.take(public_len)?.to_vec();
+
+            entries.push(KeyEntry {
+                metadata: KeyMetadata { id, version, kind, algorithm, created, expires, usage, state, key_len },
+                secret,
+                public,
+            });
+        }
+        if !reader.is_empty() {
+            return Err("Trailing data in keystore");
+        }
+        Ok(entries)
+    }
+
+    // ---- Adding keys ----
+
+    /// Add `secret` (and `public` for key pairs) as the newest version of `id`, demoting the
+    /// current active version to decrypt-only. Returns the new version number.
+    #[allow(clippy::too_many_arguments)]
+    pub fn import(
+        &mut self,
+        id: &str,
+        kind: KeyKind,
+        algorithm: &str,
+        secret: &[u8],
+        public: &[u8],
+        usage: KeyUsage,
+        expires: Option<u64>,
+    ) -> Result<u32, &'static str> {
+        if id.is_empty() || id.len() > 255 || algorithm.len() > 255 {
+            return Err("Key id and algorithm must be 1 to 255 bytes");
+        }
+        if secret.is_empty() || (kind == KeyKind::Symmetric) != public.is_empty() {
+            return Err("Key pairs need both halves; symmetric keys have no public half");
+        }
+
+        let version = self.versions(id).last().map_or(Ok(1), |latest| latest.version.checked_add(1).ok_or("Version overflow"))?;
+        for entry in self.entries.iter_mut().filter(|entry| entry.metadata.id == id) {
+            if entry.metadata.state == KeyState::Active {
+                entry.metadata.state = KeyState::DecryptOnly;
+            }
+        }
+        self.entries.push(KeyEntry {
+            metadata: KeyMetadata {
+                id: id.to_string(),
+                version,
+                kind,
+                algorithm: algorithm.to_string(),
+                created: now(),
+                expires,
+                usage,
+                state: KeyState::Active,
+                key_len: secret.len(),
+            },
+            secret: secret.to_vec(),
+            public: public.to_vec(),
+        });
+        Ok(version)
+    }
+
+    pub fn generate_symmetric(&mut self, id: &str, algorithm: &str, key_len: usize, usage: KeyUsage, expires: Option<u64>) -> Result<u32, &'static str> {
+        let mut key = random_bytes(key_len)?;
+        let version = self.import(id, KeyKind::Symmetric, algorithm, &key, &[], usage, expires);
+        key.zeroize();
+        version
+    }
+
+    pub fn generate_kem_keypair(&mut self, id: &str, usage: KeyUsage, expires: Option<u64>) -> Result<u32, &'static str> {
+        let mut kem = LatticeKEM::keypair();
+        let version = self.import(id, KeyKind::KemKeypair, ALG_LATTICE_KEM, &kem.secret_key, &kem.public_key, usage, expires);
+        kem.secret_key.zeroize();
+        version
+    }
+
+    pub fn generate_signing_key(&mut self, id: &str, usage: KeyUsage, expires: Option<u64>) -> Result<u32, &'static str> {
+        let mut seed = random_bytes(32)?;
+        let public = ed25519_dalek::SigningKey::from_bytes(&seed[..].try_into().unwrap()).verifying_key().to_bytes();
+        let version = self.import(id, KeyKind::SigningKey, ALG_ED25519, &seed, &public, usage, expires);
+        seed.zeroize();
+        version
+    }
+
+    /// Generate a fresh version of `id` with the latest version's algorithm and usage. Keys with
+    /// algorithms the store cannot generate must be rotated by `import`.
+    pub fn rotate(&mut self, id: &str, expires: Option<u64>) -> Result<u32, &'static str> {
+        let latest = self.entries.iter().filter(|entry| entry.metadata.id == id).max_by_key(|entry| entry.metadata.version).ok_or("Unknown key id")?;
+        let (kind, algorithm, usage, key_len) = (latest.metadata.kind, latest.metadata.algorithm.clone(), latest.metadata.usage, latest.metadata.key_len);
+
+        match (kind, algorithm.as_str()) {
+            (KeyKind::Symmetric, _) if key_len > 0 => self.generate_symmetric(id, &algorithm, key_len, usage, expires),
+            (KeyKind::KemKeypair, ALG_LATTICE_KEM) => self.generate_kem_keypair(id, usage, expires),
+            (KeyKind::SigningKey, ALG_ED25519) => self.generate_signing_key(id, usage, expires),
+            _ => Err("Rotation not supported for this algorithm; import a new version"),
+        }
+    }
+
+    // ---- Lookup and lifecycle ----
+
+    /// Metadata of every version of `id`, oldest first.
+    pub fn versions(&self, id: &str) -> Vec<&KeyMetadata> {
+        let mut versions: Vec<&KeyMetadata> = self.entries.iter().map(|entry| &entry.metadata).filter(|meta| meta.id == id).collect();
+        versions.sort_by_key(|meta| meta.version);
+        versions
+    }
+
+    /// Distinct key ids in the store.
+    pub fn ids(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = self.entries.iter().map(|entry| entry.metadata.id.as_str()).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    /// The active, unexpired version of `id`, for new encryptions or signatures.
+    pub fn active(&self, id: &str) -> Result<&KeyEntry, &'static str> {
+        let entry = self
+            .entries
+            .iter()
+            .find(|entry| entry.metadata.id == id && entry.metadata.state == KeyState::Active)
+            .ok_or("No active version for key id")?;
+        match entry.is_expired() {
+            true => Err("Key version has expired"),
+            false => Ok(entry),
+        }
+    }
+
+    /// A specific version, e.g. the one recorded alongside a ciphertext. Destroyed versions
+    /// are returned so their metadata can be inspected, but their secret is unavailable.
+    pub fn get(&self, id: &str, version: u32) -> Result<&KeyEntry, &'static str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.metadata.id == id && entry.metadata.version == version)
+            .ok_or("Unknown key version")
+    }
+
+    fn get_mut(&mut self, id: &str, version: u32) -> Result<&mut KeyEntry, &'static str> {
+        self.entries
+            .iter_mut()
+            .find(|entry| entry.metadata.id == id && entry.metadata.version == version)
+            .ok_or("Unknown key version")
+    }
+
+    /// Change a version's state. Activating a version demotes the current active one;
+    /// destruction is permanent and wipes the material.
+    pub fn set_state(&mut self, id: &str, version: u32, state: KeyState) -> Result<(), &'static str> {
+        if self.get(id, version)?.metadata.state == KeyState::Destroyed {
+            return Err("Destroyed key versions cannot change state");
+        }
+        if state == KeyState::Active {
+            for entry in self.entries.iter_mut().filter(|entry| entry.metadata.id == id) {
+                if entry.metadata.state == KeyState::Active {
+                    entry.metadata.state = KeyState::DecryptOnly;
+                }
+            }
+        }
+
+        let entry = self.get_mut(id, version)?;
+        entry.metadata.state = state;
+        if state == KeyState::Destroyed {
+            entry.secret.zeroize();
+            entry.secret.clear();
+        }
+        Ok(())
+    }
+
+    pub fn destroy(&mut self, id: &str, version: u32) -> Result<(), &'static str> {
+        self.set_state(id, version, KeyState::Destroyed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MASTER_KEY_BYTES: [u8; 32] = [7; 32];
+    const FAST_KDF: PasswordKdf = PasswordKdf::Pbkdf2Sha256 { iterations: 1000 };
+
+    /// A fresh directory per test, removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let suffix = u64::from_be_bytes(random_bytes(8).unwrap().try_into().unwrap());
+            let dir = std::env::temp_dir().join(format!("ix-keystore-{name}-{suffix:016x}"));
+            fs::create_dir(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn save_and_open() {
+        let dir = TempDir::new("roundtrip");
+        let path = dir.0.join("keys.ks");
+        let mut store = Keystore::create(&path, &Master::Passphrase(b"correct horse"), FAST_KDF).unwrap();
+        store.generate_symmetric("data", "AES-256-GCM", 32, KeyUsage::ENCRYPT | KeyUsage::DECRYPT, None).unwrap();
+        store.generate_signing_key("sign", KeyUsage::SIGN | KeyUsage::VERIFY, Some(u64::MAX)).unwrap();
+        store.generate_kem_keypair("kem", KeyUsage::DECRYPT, None).unwrap();
+        store.save().unwrap();
+        assert!(Keystore::create(&path, &Master::Key(&MASTER_KEY_BYTES), FAST_KDF).is_err());
+
+        let opened = Keystore::open(&path, &Master::Passphrase(b"correct horse")).unwrap();
+        assert_eq!(opened.ids(), ["data", "kem", "sign"]);
+        for id in opened.ids() {
+            let (saved, loaded) = (store.active(id).unwrap(), opened.active(id).unwrap());
+            assert_eq!(saved.metadata, loaded.metadata);
+            assert_eq!(saved.secret().unwrap(), loaded.secret().unwrap());
+            assert_eq!(saved.public(), loaded.public());
+        }
+        let kem = opened.active("kem").unwrap().to_lattice().unwrap();
+        assert_eq!(kem.public_key, store.active("kem").unwrap().public());
+
+        assert_eq!(fs::read(&path).unwrap()[4], KEYSTORE_VERSION);
+        let names: Vec<_> = fs::read_dir(&dir.0).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        assert_eq!(names, ["keys.ks"]);
+    }
+
+    #[test]
+    fn rejects_wrong_credentials() {
+        let dir = TempDir::new("credentials");
+        let path = dir.0.join("keys.ks");
+        Keystore::create(&path, &Master::Passphrase(b"right"), FAST_KDF).unwrap().save().unwrap();
+        assert_eq!(Keystore::open(&path, &Master::Passphrase(b"wrong")).err(), Some("Wrong keystore passphrase or master key"));
+        assert_eq!(Keystore::open(&path, &Master::Key(&MASTER_KEY_BYTES)).err(), Some("Keystore is protected by a passphrase"));
+    }
+
+    #[test]
+    fn change_master() {
+        let dir = TempDir::new("master");
+        let path = dir.0.join("keys.ks");
+        let mut store = Keystore::create(&path, &Master::Passphrase(b"old"), FAST_KDF).unwrap();
+        store.generate_symmetric("data", "AES-256", 32, KeyUsage::ENCRYPT, None).unwrap();
+        store.change_master(&Master::Key(&MASTER_KEY_BYTES), FAST_KDF).unwrap();
+        store.save().unwrap();
+
+        assert!(Keystore::open(&path, &Master::Passphrase(b"old")).is_err());
+        let opened = Keystore::open(&path, &Master::Key(&MASTER_KEY_BYTES)).unwrap();
+        assert_eq!(opened.active("data").unwrap().secret().unwrap(), store.active("data").unwrap().secret().unwrap());
+    }
+
+    #[test]
+    fn rejects_tampering_and_other_versions() {
+        let dir = TempDir::new("tamper");
+        let path = dir.0.join("keys.ks");
+        let mut store = Keystore::create(&path, &Master::Key(&MASTER_KEY_BYTES), FAST_KDF).unwrap();
+        store.generate_symmetric("data", "AES-256", 32, KeyUsage::ENCRYPT, None).unwrap();
+        store.save().unwrap();
+        let original = fs::read(&path).unwrap();
+
+        let mut tampered = original.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        fs::write(&path, &tampered).unwrap();
+        assert_eq!(Keystore::open(&path, &Master::Key(&MASTER_KEY_BYTES)).err(), Some("Keystore integrity check failed"));
+
+        let mut other_version = original.clone();
+        other_version[4] = 2;
+        fs::write(&path, &other_version).unwrap();
+        assert_eq!(Keystore::open(&path, &Master::Key(&MASTER_KEY_BYTES)).err(), Some("Unsupported keystore version"));
+
+        fs::write(&path, &original[..original.len() / 2]).unwrap();
+        assert!(Keystore::open(&path, &Master::Key(&MASTER_KEY_BYTES)).is_err());
+    }
+
+    #[test]
+    fn rotation_and_lifecycle() {
+        let dir = TempDir::new("lifecycle");
+        let mut store = Keystore::create(dir.0.join("keys.ks"), &Master::Key(&MASTER_KEY_BYTES), FAST_KDF).unwrap();
+        let usage = KeyUsage::ENCRYPT | KeyUsage::DECRYPT;
+        assert_eq!(store.generate_symmetric("data", "AES-256", 24, usage, None).unwrap(), 1);
+        assert_eq!(store.rotate("data", None).unwrap(), 2);
+
+        let old = store.get("data", 1).unwrap();
+        assert_eq!(old.metadata.state, KeyState::DecryptOnly);
+        assert!(old.require(KeyUsage::DECRYPT).is_ok());
+        assert_eq!(old.require(KeyUsage::ENCRYPT), Err("Key version is decrypt-only"));
+        assert_eq!(store.active("data").unwrap().metadata.version, 2);
+
+        store.set_state("data", 1, KeyState::Active).unwrap();
+        assert_eq!(store.get("data", 2).unwrap().metadata.state, KeyState::DecryptOnly);
+        store.destroy("data", 1).unwrap();
+        let destroyed = store.get("data", 1).unwrap();
+        assert!(destroyed.secret().is_err());
+        assert_eq!(destroyed.metadata.key_len, 24);
+        assert!(store.set_state("data", 1, KeyState::Active).is_err());
+        assert!(store.active("data").is_err());
+
+        assert_eq!(store.rotate("data", None).unwrap(), 3);
+        assert_eq!(store.active("data").unwrap().secret().unwrap().len(), 24);
+        assert_eq!(store.versions("data").len(), 3);
+    }
+
+    #[test]
+    fn enforces_usage_and_expiry() {
+        let dir = TempDir::new("usage");
+        let mut store = Keystore::create(dir.0.join("keys.ks"), &Master::Key(&MASTER_KEY_BYTES), FAST_KDF).unwrap();
+        store.generate_symmetric("expired", "AES-256", 32, KeyUsage::ENCRYPT | KeyUsage::DECRYPT, Some(1)).unwrap();
+        assert_eq!(store.active("expired").err(), Some("Key version has expired"));
+        let entry = store.get("expired", 1).unwrap();
+        assert_eq!(entry.require(KeyUsage::ENCRYPT), Err("Key version has expired"));
+        assert!(entry.require(KeyUsage::DECRYPT).is_ok());
+        assert_eq!(entry.require(KeyUsage::SIGN), Err("Key usage not permitted"));
+
+        assert!(store.import("pair", KeyKind::KemKeypair, "X", &[1], &[], KeyUsage::DECRYPT, None).is_err());
+        assert!(store.import("", KeyKind::Symmetric, "X", &[1], &[], KeyUsage::DECRYPT, None).is_err());
+        store.import("custom", KeyKind::Symmetric, "X", &[1; 16], &[], KeyUsage::DECRYPT, None).unwrap();
+        assert!(store.rotate("custom", None).is_ok());
+        store.import("custom-pair", KeyKind::KemKeypair, "X", &[1], &[2], KeyUsage::DECRYPT, None).unwrap();
+        assert!(store.rotate("custom-pair", None).is_err());
+    }
+}
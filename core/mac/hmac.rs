@@ -0,0 +1,138 @@
+// ix-encryption/core/mac/hmac.rs
+
+//! HMAC-SHA-256 and HMAC-SHA-512 (RFC 2104 / FIPS 198-1).
+
+use hmac::{Hmac, Mac as _};
+use sha2::{Sha256, Sha512};
+use crate::core::mac::mac_core::Mac;
+
+pub struct HmacSha256 {
+    keyed: Hmac<Sha256>,
+    inner: Hmac<Sha256>,
+}
+
+impl HmacSha256 {
+    /// HMAC accepts keys of any length; keys longer than the block size are hashed.
+    pub fn new(key: &[u8]) -> Self {
+        let keyed = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+        Self {
+            inner: keyed.clone(),
+            keyed,
+        }
+    }
+}
+
+impl Mac for HmacSha256 {
+    fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    fn finalize_reset(&mut self) -> Vec<u8> {
+        let state = std::mem::replace(&mut self.inner, self.keyed.clone());
+        state.finalize().into_bytes().to_vec()
+    }
+
+    fn tag_len(&self) -> usize {
+        32
+    }
+
+    fn algorithm_id(&self) -> &'static str {
+        "IX-HMAC-SHA256-v1"
+    }
+}
+
+pub struct HmacSha512 {
+    keyed: Hmac<Sha512>,
+    inner: Hmac<Sha512>,
+}
+
+impl HmacSha512 {
+    pub fn new(key: &[u8]) -> Self {
+        let keyed = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts any key length");
+        Self {
+            inner: keyed.clone(),
+            keyed,
+        }
+    }
+}
+
+impl Mac for HmacSha512 {
+    fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    fn finalize_reset(&mut self) -> Vec<u8> {
+        let state = std::mem::replace(&mut self.inner, self.keyed.clone());
+        state.finalize().into_bytes().to_vec()
+    }
+
+    fn tag_len(&self) -> usize {
+        64
+    }
+
+    fn algorithm_id(&self) -> &'static str {
+        "IX-HMAC-SHA512-v1"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RFC 4231 test cases 1-4, 6 and 7 (case 5 only checks truncation).
+    #[test]
+    fn rfc4231_test_cases() {
+        let cases: [(Vec<u8>, &[u8], &str, &str); 6] = [
+            (
+                vec![0x0b; 20],
+                b"Hi There",
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+                "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cdedaa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854",
+            ),
+            (
+                b"Jefe".to_vec(),
+                b"what do ya want for nothing?",
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+                "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea2505549758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+            ),
+            (
+                vec![0xaa; 20],
+                &[0xdd; 50],
+                "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe",
+                "fa73b0089d56a284efb0f0756c890be9b1b5dbdd8ee81a3655f83e33b2279d39bf3e848279a722c806b485a47e67c807b946a337bee8942674278859e13292fb",
+            ),
+            (
+                (1..=25).collect(),
+                &[0xcd; 50],
+                "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b",
+                "b0ba465637458c6990e5a8c5f61d4af7e576d97ff94b872de76f8050361ee3dba91ca5c11aa25eb4d679275cc5788063a5f19741120c4f2de2adebeb10a298dd",
+            ),
+            (
+                vec![0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First",
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+                "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f3526b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598",
+            ),
+            (
+                vec![0xaa; 131],
+                b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm.",
+                "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2",
+                "e37b6a775dc87dbaa4dfa9f96e5e3ffddebd71f8867289865df5a32d20cdc944b6022cac3c4982b10d5eeb55c3e4de15134676fb6de0446065c97440fa8c6a58",
+            ),
+        ];
+
+        for (key, data, sha256, sha512) in cases {
+            assert_eq!(HmacSha256::new(&key).compute(data), hex::decode(sha256).unwrap());
+            assert_eq!(HmacSha512::new(&key).compute(data), hex::decode(sha512).unwrap());
+        }
+    }
+
+    #[test]
+    fn finalize_reset_restarts_from_key() {
+        let mut mac = HmacSha256::new(b"Jefe");
+        let first = mac.compute(b"what do ya want for nothing?");
+        mac.update(b"what do ya want ");
+        mac.update(b"for nothing?");
+        assert!(mac.verify(&first));
+    }
+}
@@ -0,0 +1,253 @@
+// ix-encryption/core/mac/kmac.rs
+
+//! KMAC128 and KMAC256 (SP 800-185), built on cSHAKE with function name "KMAC", plus the
+//! KMACXOF variants whose output does not depend on the requested length.
+
+use sha3::digest::core_api::CoreWrapper;
+use sha3::digest::{ExtendableOutput, Update, XofReader};
+use sha3::{CShake128, CShake128Core, CShake256, CShake256Core};
+use zeroize::Zeroize;
+use crate::core::mac::mac_core::Mac;
+
+const KMAC128_RATE: usize = 168;
+const KMAC256_RATE: usize = 136;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KmacVariant {
+    Kmac128,
+    Kmac256,
+}
+
+enum KmacState {
+    Kmac128(CShake128),
+    Kmac256(CShake256),
+}
+
+pub struct Kmac {
+    variant: KmacVariant,
+    key: Vec<u8>,
+    customization: Vec<u8>,
+    output_len: usize,
+    xof: bool,
+    state: KmacState,
+}
+
+impl Kmac {
+    /// `output_len` is the tag length L in bytes; `customization` is the string S.
+    pub fn new(variant: KmacVariant, key: &[u8], customization: &[u8], output_len: usize) -> Self {
+        assert!(output_len > 0, "KMAC output length must be non-zero");
+        let state = Self::keyed_state(variant, key, customization);
+        Self {
+            variant,
+            key: key.to_vec(),
+            customization: customization.to_vec(),
+            output_len,
+            xof: false,
+            state,
+        }
+    }
+
+    /// KMACXOF128 or KMACXOF256 producing `output_len` bytes; shorter outputs are prefixes of
+    /// longer ones under the same key, customization and message.
+    pub fn new_xof(variant: KmacVariant, key: &[u8], customization: &[u8], output_len: usize) -> Self {
+        let mut kmac = Self::new(variant, key, customization, output_len);
+        kmac.xof = true;
+        kmac
+    }
+
+    /// KMAC128 with a 32-byte tag.
+    pub fn kmac128(key: &[u8], customization: &[u8]) -> Self {
+        Self::new(KmacVariant::Kmac128, key, customization, 32)
+    }
+
+    /// KMAC256 with a 64-byte tag.
+    pub fn kmac256(key: &[u8], customization: &[u8]) -> Self {
+        Self::new(KmacVariant::Kmac256, key, customization, 64)
+    }
+
+    fn keyed_state(variant: KmacVariant, key: &[u8], customization: &[u8]) -> KmacState {
+        match variant {
+            KmacVariant::Kmac128 => {
+                let mut state = CoreWrapper::from_core(CShake128Core::new_with_function_name(b"KMAC", customization));
+                let mut padded_key = bytepad(&encode_string(key), KMAC128_RATE);
+                state.update(&padded_key);
+                padded_key.zeroize();
+                KmacState::Kmac128(state)
+            }
+            KmacVariant::Kmac256 => {
+                let mut state = CoreWrapper::from_core(CShake256Core::new_with_function_name(b"KMAC", customization));
+                let mut padded_key = bytepad(&encode_string(key), KMAC256_RATE);
+                state.update(&padded_key);
+                padded_key.zeroize();
+                KmacState::Kmac256(state)
+            }
+        }
+    }
+}
+
+impl Mac for Kmac {
+    fn update(&mut self, data: &[u8]) {
+        match &mut self.state {
+            KmacState::Kmac128(state) => state.update(data),
+            KmacState::Kmac256(state) => state.update(data),
+        }
+    }
+
+    fn finalize_reset(&mut self) -> Vec<u8> {
+        let fresh = Self::keyed_state(self.variant, &self.key, &self.customization);
+        let mut tag = vec![0u8; self.output_len];
+        let length_suffix = right_encode(if self.xof { 0 } else { self.output_len as u64 * 8 });
+        match std::mem::replace(&mut self.state, fresh) {
+            KmacState::Kmac128(mut state) => {
+                state.update(&length_suffix);
+                state.finalize_xof().read(&mut tag);
+            }
+            KmacState::Kmac256(mut state) => {
+                state.update(&length_suffix);
+                state.finalize_xof().read(&mut tag);
+            }
+        }
+        tag
+    }
+
+    fn tag_len(&self) -> usize {
+        self.output_len
+    }
+
+    fn algorithm_id(&self) -> &'static str {
+        match (self.variant, self.xof) {
+            (KmacVariant::Kmac128, false) => "IX-KMAC128-v1",
+            (KmacVariant::Kmac256, false) => "IX-KMAC256-v1",
+            (KmacVariant::Kmac128, true) => "IX-KMACXOF128-v1",
+            (KmacVariant::Kmac256, true) => "IX-KMACXOF256-v1",
+        }
+    }
+}
+
+impl Drop for Kmac {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+fn left_encode(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().position(|&b| b != 0).unwrap_or(7);
+    let mut encoded = vec![(8 - skip) as u8];
+    encoded.extend_from_slice(&bytes[skip..]);
+    encoded
+}
+
+fn right_encode(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().position(|&b| b != 0).unwrap_or(7);
+    let mut encoded = bytes[skip..].to_vec();
+    encoded.push((8 - skip) as u8);
+    encoded
+}
+
+fn encode_string(data: &[u8]) -> Vec<u8> {
+    let mut encoded = left_encode(data.len() as u64 * 8);
+    encoded.extend_from_slice(data);
+    encoded
+}
+
+fn bytepad(data: &[u8], rate: usize) -> Vec<u8> {
+    let mut padded = left_encode(rate as u64);
+    padded.extend_from_slice(data);
+    let fill = (rate - padded.len() % rate) % rate;
+    padded.resize(padded.len() + fill, 0);
+    padded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_util::h;
+
+    const KEY: &str = "404142434445464748494a4b4c4d4e4f505152535455565758595a5b5c5d5e5f";
+    const TAGGED: &[u8] = b"My Tagged Application";
+
+    fn sample_data(long: bool) -> Vec<u8> {
+        if long {
+            (0..=0xc7).collect()
+        } else {
+            vec![0, 1, 2, 3]
+        }
+    }
+
+    /// NIST SP 800-185 KMAC samples 1 to 6.
+    #[test]
+    fn sp800_185_kmac_samples() {
+        let cases: [(KmacVariant, bool, &[u8], usize, &str); 6] = [
+            (KmacVariant::Kmac128, false, b"", 32, "e5780b0d3ea6f7d3a429c5706aa43a00fadbd7d49628839e3187243f456ee14e"),
+            (KmacVariant::Kmac128, false, TAGGED, 32, "3b1fba963cd8b0b59e8c1a6d71888b7143651af8ba0a7070c0979e2811324aa5"),
+            (KmacVariant::Kmac128, true, TAGGED, 32, "1f5b4e6cca02209e0dcb5ca635b89a15e271ecc760071dfd805faa38f9729230"),
+            (
+                KmacVariant::Kmac256,
+                false,
+                TAGGED,
+                64,
+                "20c570c31346f703c9ac36c61c03cb64c3970d0cfc787e9b79599d273a68d2f7f69d4cc3de9d104a351689f27cf6f5951f0103f33f4f24871024d9c27773a8dd",
+            ),
+            (
+                KmacVariant::Kmac256,
+                true,
+                b"",
+                64,
+                "75358cf39e41494e949707927cee0af20a3ff553904c86b08f21cc414bcfd691589d27cf5e15369cbbff8b9a4c2eb17800855d0235ff635da82533ec6b759b69",
+            ),
+            (
+                KmacVariant::Kmac256,
+                true,
+                TAGGED,
+                64,
+                "b58618f71f92e1d56c1b8c55ddd7cd188b97b4ca4d99831eb2699a837da2e4d970fbacfde50033aea585f1a2708510c32d07880801bd182898fe476876fc8965",
+            ),
+        ];
+        for (variant, long, customization, output_len, expected) in cases {
+            let mut mac = Kmac::new(variant, &h(KEY), customization, output_len);
+            assert_eq!(mac.compute(&sample_data(long)), h(expected));
+            mac.update(&sample_data(long));
+            assert!(mac.verify(&h(expected)));
+        }
+    }
+
+    /// NIST SP 800-185 KMACXOF samples 1 to 6.
+    #[test]
+    fn sp800_185_kmacxof_samples() {
+        let cases: [(KmacVariant, bool, &[u8], usize, &str); 6] = [
+            (KmacVariant::Kmac128, false, b"", 32, "cd83740bbd92ccc8cf032b1481a0f4460e7ca9dd12b08a0c4031178bacd6ec35"),
+            (KmacVariant::Kmac128, false, TAGGED, 32, "31a44527b4ed9f5c6101d11de6d26f0620aa5c341def41299657fe9df1a3b16c"),
+            (KmacVariant::Kmac128, true, TAGGED, 32, "47026c7cd793084aa0283c253ef658490c0db61438b8326fe9bddf281b83ae0f"),
+            (
+                KmacVariant::Kmac256,
+                false,
+                TAGGED,
+                64,
+                "1755133f1534752aad0748f2c706fb5c784512cab835cd15676b16c0c6647fa96faa7af634a0bf8ff6df39374fa00fad9a39e322a7c92065a64eb1fb0801eb2b",
+            ),
+            (
+                KmacVariant::Kmac256,
+                true,
+                b"",
+                64,
+                "ff7b171f1e8a2b24683eed37830ee797538ba8dc563f6da1e667391a75edc02ca633079f81ce12a25f45615ec89972031d18337331d24ceb8f8ca8e6a19fd98b",
+            ),
+            (
+                KmacVariant::Kmac256,
+                true,
+                TAGGED,
+                64,
+                "d5be731c954ed7732846bb59dbe3a8e30f83e77a4bff4459f2f1c2b4ecebb8ce67ba01c62e8ab8578d2d499bd1bb276768781190020a306a97de281dcc30305d",
+            ),
+        ];
+        for (variant, long, customization, output_len, expected) in cases {
+            let mut xof = Kmac::new_xof(variant, &h(KEY), customization, output_len);
+            assert_eq!(xof.compute(&sample_data(long)), h(expected));
+
+            let mut prefix = Kmac::new_xof(variant, &h(KEY), customization, 16);
+            assert_eq!(prefix.compute(&sample_data(long)), h(expected)[..16]);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+// ix-encryption/core/mac/mac_core.rs
+
+//! Common interface for message authentication codes (HMAC, CMAC, KMAC, Poly1305).
+
+use subtle::ConstantTimeEq;
+
+pub trait Mac {
+    /// Absorb more message data; may be called any number of times.
+    fn update(&mut self, data: &[u8]);
+
+    /// Produce the tag and reset to the freshly keyed state.
+    fn finalize_reset(&mut self) -> Vec<u8>;
+
+    /// Length in bytes of the produced tag.
+    fn tag_len(&self) -> usize;
+
+    /// Returns a unique identifier for this MAC algorithm.
+    fn algorithm_id(&self) -> &'static str;
+
+    /// One-shot tag over `data`.
+    fn compute(&mut self, data: &[u8]) -> Vec<u8> {
+        self.update(data);
+        self.finalize_reset()
+    }
+
+    /// Compare the tag of everything absorbed so far against `tag` in constant time.
+    fn verify(&mut self, tag: &[u8]) -> bool {
+        let expected = self.finalize_reset();
+        expected.len() == tag.len() && bool::from(expected.ct_eq(tag))
+    }
+}
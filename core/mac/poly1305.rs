@@ -0,0 +1,117 @@
+// ix-encryption/core/mac/poly1305.rs
+
+//! Standalone Poly1305 one-time authenticator (RFC 8439).
+//! A key must never authenticate more than one message; derive a fresh key per message
+//! (e.g. from a ChaCha20 keystream as in ChaCha20-Poly1305). The instance forgets its key when
+//! the tag is produced and must be given a new one with `rekey` before it can be used again.
+
+use poly1305::universal_hash::{KeyInit, UniversalHash};
+use poly1305::{Block, Poly1305 as Poly1305Core};
+use zeroize::Zeroize;
+use crate::core::mac::mac_core::Mac;
+
+pub const POLY1305_KEY_LEN: usize = 32;
+
+pub struct Poly1305 {
+    state: Option<Poly1305Core>,
+    pending: Vec<u8>,
+}
+
+impl Poly1305 {
+    pub fn new(key: &[u8; POLY1305_KEY_LEN]) -> Self {
+        Self {
+            state: Some(Poly1305Core::new(key.into())),
+            pending: Vec::with_capacity(16),
+        }
+    }
+
+    /// Start a new message under a fresh one-time key.
+    pub fn rekey(&mut self, key: &[u8; POLY1305_KEY_LEN]) {
+        self.state = Some(Poly1305Core::new(key.into()));
+        self.pending.zeroize();
+        self.pending.clear();
+    }
+
+    fn state(&mut self) -> &mut Poly1305Core {
+        self.state.as_mut().expect("Poly1305 key already used; rekey before the next message")
+    }
+}
+
+impl Mac for Poly1305 {
+    fn update(&mut self, mut data: &[u8]) {
+        if !self.pending.is_empty() {
+            let take = (16 - self.pending.len()).min(data.len());
+            self.pending.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.pending.len() < 16 {
+                return;
+            }
+            let block = Block::clone_from_slice(&self.pending);
+            self.state().update(&[block]);
+            self.pending.clear();
+        }
+
+        let full = data.len() / 16 * 16;
+        let blocks: Vec<Block> = data[..full].chunks(16).map(Block::clone_from_slice).collect();
+        self.state().update(&blocks);
+        self.pending.extend_from_slice(&data[full..]);
+    }
+
+    /// Produce the tag and discard the key; the next message needs `rekey`.
+    fn finalize_reset(&mut self) -> Vec<u8> {
+        let state = self.state.take().expect("Poly1305 key already used; rekey before the next message");
+        let tag = state.compute_unpadded(&self.pending).to_vec();
+        self.pending.zeroize();
+        self.pending.clear();
+        tag
+    }
+
+    fn tag_len(&self) -> usize {
+        16
+    }
+
+    fn algorithm_id(&self) -> &'static str {
+        "IX-Poly1305-v1"
+    }
+}
+
+impl Drop for Poly1305 {
+    fn drop(&mut self) {
+        self.pending.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b";
+
+    /// RFC 8439 Section 2.5.2.
+    #[test]
+    fn rfc8439_tag_generation() {
+        let key: [u8; POLY1305_KEY_LEN] = hex::decode(KEY).unwrap().try_into().unwrap();
+        let mut mac = Poly1305::new(&key);
+        mac.update(b"Cryptographic ");
+        mac.update(b"Forum Research Group");
+        assert_eq!(mac.finalize_reset(), hex::decode("a8061dc1305136c6c22b8baf0c0127a9").unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "Poly1305 key already used")]
+    fn key_is_single_use() {
+        let key: [u8; POLY1305_KEY_LEN] = hex::decode(KEY).unwrap().try_into().unwrap();
+        let mut mac = Poly1305::new(&key);
+        mac.compute(b"first message");
+        mac.compute(b"second message");
+    }
+
+    #[test]
+    fn rekey_starts_a_new_message() {
+        let key: [u8; POLY1305_KEY_LEN] = hex::decode(KEY).unwrap().try_into().unwrap();
+        let mut mac = Poly1305::new(&[7u8; POLY1305_KEY_LEN]);
+        mac.compute(b"first message");
+        mac.rekey(&key);
+        assert_eq!(mac.compute(b"Cryptographic Forum Research Group"), hex::decode("a8061dc1305136c6c22b8baf0c0127a9").unwrap());
+    }
+}
@@ -1,8 +1,20 @@
 // ix-encryption/core/mode_cascade.rs
 
-//! Cascade mode: sequential block cipher chaining for layered security
+//! Cascade mode: sequential block cipher chaining for layered security.
+//! `CascadeMode` chains raw single-block calls over borrowed, already-keyed ciphers;
+//! `AuthenticatedCascade` owns its stages, derives independent stage keys and adds one outer MAC.
 
+use zeroize::Zeroize;
+use crate::core::IXCipherCore;
 use crate::core::blockcipher::BlockCipher;
+use crate::core::ciphers::keyed::KeyedBlockCipher;
+use crate::core::kdf::hkdf::{hkdf_expand, hkdf_extract, HkdfHash};
+use crate::core::mac::hmac::HmacSha256;
+use crate::core::mac::mac_core::Mac;
+use crate::core::mode_cbc::CBCMode;
+use crate::core::mode_cfb::CFBMode;
+use crate::core::mode_ctr::CTRMode;
+use crate::core::mode_ofb::OFBMode;
 
 /// CascadeMode allows chaining multiple block ciphers in sequence
 pub struct CascadeMode<'a> {
@@ -31,3 +43,229 @@ impl<'a> CascadeMode<'a> {
         self.ciphers.iter().rev().fold(block.to_vec(), |acc, cipher| cipher.decrypt_block(&acc))
     }
 }
+
+/// Mode applied by one stage of an `AuthenticatedCascade`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StageMode {
+    Ctr,
+    Cbc,
+    Cfb,
+    Ofb,
+}
+
+struct StageSpec {
+    key_len: usize,
+    mode: StageMode,
+    build: fn(&[u8]) -> Result<Box<dyn BlockCipher>, &'static str>,
+}
+
+fn build_stage<C: KeyedBlockCipher + 'static>(key: &[u8]) -> Result<Box<dyn BlockCipher>, &'static str> {
+    Ok(Box::new(C::from_key(key)?))
+}
+
+impl BlockCipher for Box<dyn BlockCipher> {
+    fn block_size(&self) -> usize {
+        (**self).block_size()
+    }
+
+    fn encrypt_block(&self, block: &[u8]) -> Vec<u8> {
+        (**self).encrypt_block(block)
+    }
+
+    fn decrypt_block(&self, block: &[u8]) -> Vec<u8> {
+        (**self).decrypt_block(block)
+    }
+}
+
+pub const CASCADE_NONCE_LEN: usize = 16;
+pub const CASCADE_TAG_LEN: usize = 32;
+
+/// Cascade that owns its stages and keys each one independently from a master key.
+///
+/// Composition: each stage applies an unauthenticated mode (CTR/CBC/CFB/OFB) under its own
+/// HKDF-derived key and IV, then a single HMAC-SHA-256 is computed over nonce || final
+/// ciphertext (encrypt-then-MAC). Authentication therefore covers the whole stack and is
+/// checked before any stage is decrypted; the individual stages are not authenticated on
+/// their own. Breaking confidentiality requires breaking every stage cipher.
+///
+/// Output layout: nonce || ciphertext || tag.
+pub struct AuthenticatedCascade {
+    specs: Vec<StageSpec>,
+    stages: Vec<Box<dyn BlockCipher>>,
+    iv_key: Vec<u8>,
+    mac_key: Vec<u8>,
+}
+
+impl AuthenticatedCascade {
+    pub fn new() -> Self {
+        Self {
+            specs: Vec::new(),
+            stages: Vec::new(),
+            iv_key: Vec::new(),
+            mac_key: Vec::new(),
+        }
+    }
+
+    /// Append a stage; stages are applied in insertion order when encrypting.
+    pub fn add_stage<C: KeyedBlockCipher + 'static>(&mut self, key_len: usize, mode: StageMode) {
+        self.specs.push(StageSpec {
+            key_len,
+            mode,
+            build: build_stage::<C>,
+        });
+    }
+
+    /// Per-message IV for a stage: HMAC(iv_key, index || nonce) truncated to the block size.
+    fn stage_iv(&self, index: usize, block_size: usize, nonce: &[u8]) -> Vec<u8> {
+        let mut mac = HmacSha256::new(&self.iv_key);
+        mac.update(&(index as u32).to_be_bytes());
+        mac.update(nonce);
+        mac.finalize_reset()[..block_size].to_vec()
+    }
+}
+
+impl IXCipherCore for AuthenticatedCascade {
+    fn initialize(&mut self, key: &[u8], salt: Option<&[u8]>) {
+        assert!(!self.specs.is_empty(), "At least one stage required for cascade mode");
+        let mut prk = hkdf_extract(HkdfHash::Sha256, salt, key);
+
+        self.stages.clear();
+        for (index, spec) in self.specs.iter().enumerate() {
+            let info = format!("IX-Cascade-v1 stage {}", index);
+            let mut stage_key = hkdf_expand(HkdfHash::Sha256, &prk, info.as_bytes(), spec.key_len).expect("HKDF expand failed");
+            self.stages.push((spec.build)(&stage_key).expect("Stage key setup failed"));
+            stage_key.zeroize();
+        }
+
+        self.iv_key.zeroize();
+        self.iv_key = hkdf_expand(HkdfHash::Sha256, &prk, b"IX-Cascade-v1 iv", 32).expect("HKDF expand failed");
+        self.mac_key.zeroize();
+        self.mac_key = hkdf_expand(HkdfHash::Sha256, &prk, b"IX-Cascade-v1 mac", 32).expect("HKDF expand failed");
+        prk.zeroize();
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        assert!(!self.stages.is_empty(), "Cipher not initialized");
+        let mut nonce = vec![0u8; CASCADE_NONCE_LEN];
+        getrandom::getrandom(&mut nonce).expect("Nonce generation failed");
+
+        let mut data = plaintext.to_vec();
+        for (index, (stage, spec)) in self.stages.iter().zip(self.specs.iter()).enumerate() {
+            let iv = self.stage_iv(index, stage.block_size(), &nonce);
+            data = match spec.mode {
+                StageMode::Ctr => CTRMode::new(stage, iv).process(&data),
+                StageMode::Cbc => CBCMode::new(stage, iv).encrypt(&data),
+                StageMode::Cfb => CFBMode::full_block(stage, iv).encrypt(&data),
+                StageMode::Ofb => OFBMode::new(stage, iv).process(&data),
+            };
+        }
+
+        let mut mac = HmacSha256::new(&self.mac_key);
+        mac.update(&nonce);
+        mac.update(&data);
+
+        let mut output = nonce;
+        output.extend_from_slice(&data);
+        output.extend_from_slice(&mac.finalize_reset());
+        output
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Vec<u8> {
+        self.try_decrypt(ciphertext).expect("Decryption failed")
+    }
+
+    fn try_decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        if self.stages.is_empty() {
+            return Err("Cipher not initialized");
+        }
+        if ciphertext.len() < CASCADE_NONCE_LEN + CASCADE_TAG_LEN {
+            return Err("Ciphertext too short");
+        }
+
+        let (nonce, rest) = ciphertext.split_at(CASCADE_NONCE_LEN);
+        let (body, tag) = rest.split_at(rest.len() - CASCADE_TAG_LEN);
+
+        let mut mac = HmacSha256::new(&self.mac_key);
+        mac.update(nonce);
+        mac.update(body);
+        if !mac.verify(tag) {
+            return Err("Cascade authentication failed");
+        }
+
+        let mut data = body.to_vec();
+        for (index, (stage, spec)) in self.stages.iter().zip(self.specs.iter()).enumerate().rev() {
+            let iv = self.stage_iv(index, stage.block_size(), nonce);
+            data = match spec.mode {
+                StageMode::Ctr => CTRMode::new(stage, iv).process(&data),
+                StageMode::Cbc => CBCMode::new(stage, iv).decrypt(&data)?,
+                StageMode::Cfb => CFBMode::full_block(stage, iv).decrypt(&data),
+                StageMode::Ofb => OFBMode::new(stage, iv).process(&data),
+            };
+        }
+        Ok(data)
+    }
+
+    fn wipe(&mut self) {
+        self.stages.clear();
+        self.iv_key.zeroize();
+        self.mac_key.zeroize();
+        self.iv_key.clear();
+        self.mac_key.clear();
+    }
+
+    fn algorithm_id(&self) -> &'static str {
+        "IX-AuthCascade-v1"
+    }
+
+    fn trigger_lockdown(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ciphers::aes::AesCipher;
+    use crate::core::ciphers::serpent::SerpentCipher;
+    use crate::core::ciphers::twofish::TwofishCipher;
+
+    fn cascade() -> AuthenticatedCascade {
+        let mut cascade = AuthenticatedCascade::new();
+        cascade.add_stage::<AesCipher>(32, StageMode::Ctr);
+        cascade.add_stage::<TwofishCipher>(32, StageMode::Cbc);
+        cascade.add_stage::<SerpentCipher>(32, StageMode::Cfb);
+        cascade.add_stage::<AesCipher>(16, StageMode::Ofb);
+        cascade.initialize(b"cascade master key", Some(b"cascade salt"));
+        cascade
+    }
+
+    #[test]
+    fn roundtrip_across_lengths() {
+        let cascade = cascade();
+        for len in [0, 1, 15, 16, 17, 100] {
+            let plaintext = vec![0x5au8; len];
+            let ciphertext = cascade.encrypt(&plaintext);
+            assert_eq!(cascade.try_decrypt(&ciphertext).unwrap(), plaintext);
+        }
+    }
+
+    #[test]
+    fn rejects_tampering_before_decrypting() {
+        let cascade = cascade();
+        let ciphertext = cascade.encrypt(b"layered plaintext");
+        for position in [0, CASCADE_NONCE_LEN, ciphertext.len() - 1] {
+            let mut tampered = ciphertext.clone();
+            tampered[position] ^= 1;
+            assert_eq!(cascade.try_decrypt(&tampered), Err("Cascade authentication failed"));
+        }
+        assert_eq!(cascade.try_decrypt(&ciphertext[..CASCADE_NONCE_LEN]), Err("Ciphertext too short"));
+    }
+
+    #[test]
+    fn other_master_key_fails() {
+        let ciphertext = cascade().encrypt(b"layered plaintext");
+        let mut other = cascade();
+        other.initialize(b"another master key", Some(b"cascade salt"));
+        assert_eq!(other.try_decrypt(&ciphertext), Err("Cascade authentication failed"));
+    }
+}
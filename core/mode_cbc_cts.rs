@@ -0,0 +1,128 @@
+// ix-encryption/core/mode_cbc_cts.rs
+
+//! CBC with ciphertext stealing, variant CBC-CS3 (SP 800-38A addendum).
+//! Length-preserving and unpadded; inputs must be at least one block long.
+//! CS3 always swaps the final two ciphertext blocks, matching Kerberos (RFC 3962).
+
+use crate::core::blockcipher::BlockCipher;
+
+pub struct CBCCS3Mode<'a, C: BlockCipher> {
+    cipher: &'a C,
+    iv: Vec<u8>,
+}
+
+impl<'a, C: BlockCipher> CBCCS3Mode<'a, C> {
+    pub fn new(cipher: &'a C, iv: Vec<u8>) -> Self {
+        assert_eq!(iv.len(), cipher.block_size(), "IV length mismatch");
+        CBCCS3Mode { cipher, iv }
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let block_size = self.cipher.block_size();
+        if plaintext.len() < block_size {
+            return Err("CBC-CS3 input shorter than one block");
+        }
+
+        let mut blocks = Vec::new();
+        let mut previous_block = self.iv.clone();
+        for chunk in plaintext.chunks(block_size) {
+            let block: Vec<u8> = (0..block_size)
+                .map(|i| chunk.get(i).copied().unwrap_or(0) ^ previous_block[i])
+                .collect();
+            previous_block = self.cipher.encrypt_block(&block);
+            blocks.push(previous_block.clone());
+        }
+
+        let n = blocks.len();
+        if n == 1 {
+            return Ok(blocks.pop().unwrap());
+        }
+
+        let tail_len = plaintext.len() - (n - 1) * block_size;
+        let mut ciphertext = Vec::with_capacity(plaintext.len());
+        for block in &blocks[..n - 2] {
+            ciphertext.extend_from_slice(block);
+        }
+        ciphertext.extend_from_slice(&blocks[n - 1]);
+        ciphertext.extend_from_slice(&blocks[n - 2][..tail_len]);
+        Ok(ciphertext)
+    }
+
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let block_size = self.cipher.block_size();
+        if ciphertext.len() < block_size {
+            return Err("CBC-CS3 input shorter than one block");
+        }
+
+        let n = (ciphertext.len() + block_size - 1) / block_size;
+        if n == 1 {
+            return Ok(xor(&self.cipher.decrypt_block(ciphertext), &self.iv));
+        }
+
+        let mut plaintext = Vec::with_capacity(ciphertext.len());
+        let mut previous_block = self.iv.clone();
+        for chunk in ciphertext[..(n - 2) * block_size].chunks(block_size) {
+            plaintext.extend_from_slice(&xor(&self.cipher.decrypt_block(chunk), &previous_block));
+            previous_block = chunk.to_vec();
+        }
+
+        // Undo the swap: the full block in position n-1 is C_n, the partial tail is C_{n-1}*.
+        let last_full = &ciphertext[(n - 2) * block_size..(n - 1) * block_size];
+        let stolen = &ciphertext[(n - 1) * block_size..];
+        let tail_len = stolen.len();
+
+        let z = self.cipher.decrypt_block(last_full);
+        let mut penultimate = stolen.to_vec();
+        penultimate.extend_from_slice(&z[tail_len..]);
+
+        let last_plain = xor(&z[..tail_len], stolen);
+        plaintext.extend_from_slice(&xor(&self.cipher.decrypt_block(&penultimate), &previous_block));
+        plaintext.extend_from_slice(&last_plain);
+        Ok(plaintext)
+    }
+}
+
+fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b.iter()).map(|(&x, &y)| x ^ y).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ciphers::aes::AesCipher;
+    use crate::core::test_util::h;
+
+    /// RFC 3962 Appendix B: AES-128 CBC-CS3 with a zero IV, which is the SP 800-38A addendum's
+    /// CS3 variant. The vectors cover a partial block, exact multiples and several blocks.
+    #[test]
+    fn rfc3962_cbc_cs3_vectors() {
+        let cipher = AesCipher::new(b"chicken teriyaki").unwrap();
+        let cases: [(&[u8], &str); 6] = [
+            (b"I would like the ", "c6353568f2bf8cb4d8a580362da7ff7f97"),
+            (b"I would like the General Gau's ", "fc00783e0efdb2c1d445d4c8eff7ed2297687268d6ecccc0c07b25e25ecfe5"),
+            (b"I would like the General Gau's C", "39312523a78662d5be7fcbcc98ebf5a897687268d6ecccc0c07b25e25ecfe584"),
+            (
+                b"I would like the General Gau's Chicken, please,",
+                "97687268d6ecccc0c07b25e25ecfe584b3fffd940c16a18c1b5549d2f838029e39312523a78662d5be7fcbcc98ebf5",
+            ),
+            (
+                b"I would like the General Gau's Chicken, please, ",
+                "97687268d6ecccc0c07b25e25ecfe5849dad8bbb96c4cdc03bc103e1a194bbd839312523a78662d5be7fcbcc98ebf5a8",
+            ),
+            (
+                b"I would like the General Gau's Chicken, please, and wonton soup.",
+                concat!(
+                    "97687268d6ecccc0c07b25e25ecfe58439312523a78662d5be7fcbcc98ebf5a8",
+                    "4807efe836ee89a526730dbc2f7bc8409dad8bbb96c4cdc03bc103e1a194bbd8",
+                ),
+            ),
+        ];
+
+        for (plaintext, expected) in cases {
+            let mode = CBCCS3Mode::new(&cipher, vec![0u8; 16]);
+            let ciphertext = mode.encrypt(plaintext).unwrap();
+            assert_eq!(ciphertext, h(expected));
+            assert_eq!(mode.decrypt(&ciphertext).unwrap(), plaintext);
+        }
+    }
+}
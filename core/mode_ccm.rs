@@ -0,0 +1,186 @@
+// ix-encryption/core/mode_ccm.rs
+
+//! Counter with CBC-MAC (CCM, SP 800-38C) - AEAD mode common on constrained IoT links.
+
+use subtle::ConstantTimeEq;
+use crate::core::blockcipher::BlockCipher;
+use crate::core::mac::cmac::xor_in_place;
+use crate::core::mode_ctr::CTRMode;
+
+pub struct CCMMode<'a, C: BlockCipher> {
+    cipher: &'a C,
+    nonce: Vec<u8>,
+    aad: Vec<u8>,
+    tag_len: usize,
+}
+
+impl<'a, C: BlockCipher> CCMMode<'a, C> {
+    /// `nonce` must be 7..=13 bytes and `tag_len` one of 4, 6, 8, 10, 12, 14, 16.
+    pub fn new(cipher: &'a C, nonce: Vec<u8>, aad: Vec<u8>, tag_len: usize) -> Self {
+        assert_eq!(cipher.block_size(), 16, "CCM requires a 128-bit block cipher");
+        assert!((7..=13).contains(&nonce.len()), "CCM nonce must be 7 to 13 bytes");
+        assert!(
+            (4..=16).contains(&tag_len) && tag_len % 2 == 0,
+            "CCM tag length must be an even value from 4 to 16"
+        );
+        CCMMode { cipher, nonce, aad, tag_len }
+    }
+
+    /// Encrypt with authentication tag generation
+    pub fn encrypt_and_tag(&self, plaintext: &[u8]) -> Result<(Vec<u8>, Vec<u8>), &'static str> {
+        let mac = self.cbc_mac(plaintext)?;
+        let ciphertext = CTRMode::new(self.cipher, self.counter_block(1)).process(plaintext);
+        Ok((ciphertext, self.mask_tag(&mac)))
+    }
+
+    /// Decrypt with tag verification
+    pub fn decrypt_and_verify(&self, ciphertext: &[u8], tag: &[u8]) -> Option<Vec<u8>> {
+        if tag.len() != self.tag_len {
+            return None;
+        }
+
+        let plaintext = CTRMode::new(self.cipher, self.counter_block(1)).process(ciphertext);
+        let mac = self.cbc_mac(&plaintext).ok()?;
+        let expected = self.mask_tag(&mac);
+
+        if bool::from(expected.ct_eq(tag)) {
+            Some(plaintext)
+        } else {
+            None
+        }
+    }
+
+    /// Size in bytes of the message-length field (q = 15 - n).
+    fn length_field_len(&self) -> usize {
+        15 - self.nonce.len()
+    }
+
+    fn counter_block(&self, index: u64) -> Vec<u8> {
+        let q = self.length_field_len();
+        let mut block = vec![0u8; 16];
+        block[0] = (q - 1) as u8;
+        block[1..1 + self.nonce.len()].copy_from_slice(&self.nonce);
+        let index_bytes = index.to_be_bytes();
+        let take = q.min(8);
+        block[16 - take..].copy_from_slice(&index_bytes[8 - take..]);
+        block
+    }
+
+    fn cbc_mac(&self, payload: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let q = self.length_field_len();
+        if q < 8 && (payload.len() as u64) >> (8 * q) != 0 {
+            return Err("Payload too long for CCM nonce length");
+        }
+
+        let mut b0 = vec![0u8; 16];
+        let adata = if self.aad.is_empty() { 0 } else { 0x40 };
+        b0[0] = adata | ((((self.tag_len - 2) / 2) as u8) << 3) | (q - 1) as u8;
+        b0[1..1 + self.nonce.len()].copy_from_slice(&self.nonce);
+        let len_bytes = (payload.len() as u64).to_be_bytes();
+        let take = q.min(8);
+        b0[16 - take..].copy_from_slice(&len_bytes[8 - take..]);
+
+        let mut y = self.cipher.encrypt_block(&b0);
+
+        if !self.aad.is_empty() {
+            let mut encoded = Self::encode_aad_len(self.aad.len());
+            encoded.extend_from_slice(&self.aad);
+            y = self.absorb(y, &encoded);
+        }
+        y = self.absorb(y, payload);
+
+        y.truncate(self.tag_len);
+        Ok(y)
+    }
+
+    fn absorb(&self, mut y: Vec<u8>, data: &[u8]) -> Vec<u8> {
+        for chunk in data.chunks(16) {
+            xor_in_place(&mut y, chunk);
+            y = self.cipher.encrypt_block(&y);
+        }
+        y
+    }
+
+    fn encode_aad_len(len: usize) -> Vec<u8> {
+        let len = len as u64;
+        if len < 0xff00 {
+            (len as u16).to_be_bytes().to_vec()
+        } else if len <= u32::MAX as u64 {
+            let mut encoded = vec![0xff, 0xfe];
+            encoded.extend_from_slice(&(len as u32).to_be_bytes());
+            encoded
+        } else {
+            let mut encoded = vec![0xff, 0xff];
+            encoded.extend_from_slice(&len.to_be_bytes());
+            encoded
+        }
+    }
+
+    fn mask_tag(&self, mac: &[u8]) -> Vec<u8> {
+        let s0 = self.cipher.encrypt_block(&self.counter_block(0));
+        mac.iter().zip(s0.iter()).map(|(&t, &s)| t ^ s).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ciphers::aes::AesCipher;
+    use crate::core::test_util::h;
+
+    fn check(cipher: &AesCipher, nonce: &str, aad: &str, plaintext: &str, tag_len: usize, expected: &str) {
+        let (ciphertext, tag) = CCMMode::new(cipher, h(nonce), h(aad), tag_len).encrypt_and_tag(&h(plaintext)).unwrap();
+        assert_eq!([ciphertext.clone(), tag.clone()].concat(), h(expected));
+        let opened = CCMMode::new(cipher, h(nonce), h(aad), tag_len).decrypt_and_verify(&ciphertext, &tag);
+        assert_eq!(opened, Some(h(plaintext)));
+
+        let mut bad_tag = tag;
+        bad_tag[0] ^= 1;
+        assert!(CCMMode::new(cipher, h(nonce), h(aad), tag_len).decrypt_and_verify(&ciphertext, &bad_tag).is_none());
+    }
+
+    /// RFC 3610 Section 8, packet vectors #1 and #2.
+    #[test]
+    fn rfc3610_packet_vectors() {
+        let cipher = AesCipher::new(&h("c0c1c2c3c4c5c6c7c8c9cacbcccdcecf")).unwrap();
+        check(
+            &cipher,
+            "00000003020100a0a1a2a3a4a5",
+            "0001020304050607",
+            "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e",
+            8,
+            "588c979a61c663d2f066d0c2c0f989806d5f6b61dac38417e8d12cfdf926e0",
+        );
+        check(
+            &cipher,
+            "00000004030201a0a1a2a3a4a5",
+            "0001020304050607",
+            "08090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+            8,
+            "72c91a36e135f8cf291ca894085c87e3cc15c439c9e43a3ba091d56e10400916",
+        );
+    }
+
+    /// NIST SP 800-38C Appendix C, examples 1 to 3.
+    #[test]
+    fn sp800_38c_examples() {
+        let cipher = AesCipher::new(&h("404142434445464748494a4b4c4d4e4f")).unwrap();
+        check(&cipher, "10111213141516", "0001020304050607", "20212223", 4, "7162015b4dac255d");
+        check(
+            &cipher,
+            "1011121314151617",
+            "000102030405060708090a0b0c0d0e0f",
+            "202122232425262728292a2b2c2d2e2f",
+            6,
+            "d2a1f0e051ea5f62081a7792073d593d1fc64fbfaccd",
+        );
+        check(
+            &cipher,
+            "101112131415161718191a1b",
+            "000102030405060708090a0b0c0d0e0f10111213",
+            "202122232425262728292a2b2c2d2e2f3031323334353637",
+            8,
+            "e3b201a9f5b71a7a9b1ceaeccd97e70b6176aad9a4428aa5484392fbc1b09951",
+        );
+    }
+}
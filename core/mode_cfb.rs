@@ -0,0 +1,103 @@
+// ix-encryption/core/mode_cfb.rs
+
+//! Cipher Feedback (CFB) mode with configurable segment size (CFB-8 through CFB-128), SP 800-38A.
+
+use crate::core::blockcipher::BlockCipher;
+
+pub struct CFBMode<'a, C: BlockCipher> {
+    cipher: &'a C,
+    iv: Vec<u8>,
+    segment_size: usize,
+}
+
+impl<'a, C: BlockCipher> CFBMode<'a, C> {
+    /// `segment_size` is in bytes: 1 for CFB-8, `block_size` for full-block CFB.
+    pub fn new(cipher: &'a C, iv: Vec<u8>, segment_size: usize) -> Self {
+        assert_eq!(iv.len(), cipher.block_size(), "IV length mismatch");
+        assert!(
+            segment_size >= 1 && segment_size <= cipher.block_size(),
+            "Segment size must be between 1 byte and the block size"
+        );
+        CFBMode { cipher, iv, segment_size }
+    }
+
+    /// CFB-8 convenience constructor
+    pub fn cfb8(cipher: &'a C, iv: Vec<u8>) -> Self {
+        Self::new(cipher, iv, 1)
+    }
+
+    /// Full-block CFB convenience constructor (CFB-128 for AES)
+    pub fn full_block(cipher: &'a C, iv: Vec<u8>) -> Self {
+        let block_size = cipher.block_size();
+        Self::new(cipher, iv, block_size)
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        self.process(plaintext, true)
+    }
+
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Vec<u8> {
+        self.process(ciphertext, false)
+    }
+
+    /// A trailing partial segment is processed as a stream, as is common for CFB-128.
+    fn process(&self, input: &[u8], encrypt: bool) -> Vec<u8> {
+        let mut output = Vec::with_capacity(input.len());
+        let mut register = self.iv.clone();
+
+        for segment in input.chunks(self.segment_size) {
+            let keystream = self.cipher.encrypt_block(&register);
+            let processed: Vec<u8> = segment.iter()
+                .zip(keystream.iter())
+                .map(|(&x, &k)| x ^ k)
+                .collect();
+
+            let feedback = if encrypt { &processed[..] } else { segment };
+            register.drain(..feedback.len());
+            register.extend_from_slice(feedback);
+
+            output.extend_from_slice(&processed);
+        }
+
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ciphers::aes::AesCipher;
+    use crate::core::test_util::h;
+
+    fn cipher() -> AesCipher {
+        AesCipher::new(&h("2b7e151628aed2a6abf7158809cf4f3c")).unwrap()
+    }
+
+    const IV: &str = "000102030405060708090a0b0c0d0e0f";
+
+    /// SP 800-38A F.3.7 / F.3.8, CFB8-AES128.
+    #[test]
+    fn sp800_38a_cfb8_aes128() {
+        let cipher = cipher();
+        let plaintext = h("6bc1bee22e409f96e93d7e117393172aae2d");
+        let ciphertext = h("3b79424c9c0dd436bace9e0ed4586a4f32b9");
+        assert_eq!(CFBMode::cfb8(&cipher, h(IV)).encrypt(&plaintext), ciphertext);
+        assert_eq!(CFBMode::cfb8(&cipher, h(IV)).decrypt(&ciphertext), plaintext);
+    }
+
+    /// SP 800-38A F.3.13 / F.3.14, CFB128-AES128.
+    #[test]
+    fn sp800_38a_cfb128_aes128() {
+        let cipher = cipher();
+        let plaintext = h(concat!(
+            "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51",
+            "30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+        ));
+        let ciphertext = h(concat!(
+            "3b3fd92eb72dad20333449f8e83cfb4ac8a64537a0b3a93fcde3cdad9f1ce58b",
+            "26751f67a3cbb140b1808cf187a4f4dfc04b05357c5d1c0eeac4c66f9ff7f2e6",
+        ));
+        assert_eq!(CFBMode::full_block(&cipher, h(IV)).encrypt(&plaintext), ciphertext);
+        assert_eq!(CFBMode::full_block(&cipher, h(IV)).decrypt(&ciphertext), plaintext);
+    }
+}
@@ -1,36 +1,246 @@
 // ix-encryption/core/mode_gcm.rs
 
-//! Authenticated Galois/Counter Mode (GCM) - secure AEAD mode (partial implementation)
+//! Authenticated Galois/Counter Mode (GCM) - secure AEAD mode (NIST SP 800-38D)
+//! 96-bit nonces are used directly; other lengths are hashed into the initial counter.
 
+use subtle::ConstantTimeEq;
 use crate::core::blockcipher::BlockCipher;
-use crate::core::mode_ctr::CTRMode;
 
-/// GCM Mode Struct (partial)
+pub const GCM_TAG_LEN: usize = 16;
+/// Largest plaintext a single GCM invocation may process: 2^32 - 2 blocks.
+const GCM_MAX_TEXT_LEN: u64 = ((1u64 << 32) - 2) * 16;
+
+/// GCM Mode Struct
 pub struct GCMMode<'a, C: BlockCipher> {
     cipher: &'a C,
-    ctr: CTRMode<'a, C>,
+    nonce: Vec<u8>,
     aad: Vec<u8>,
 }
 
 impl<'a, C: BlockCipher> GCMMode<'a, C> {
     pub fn new(cipher: &'a C, nonce: Vec<u8>, aad: Vec<u8>) -> Self {
-        let ctr = CTRMode::new(cipher, nonce);
-        GCMMode { cipher, ctr, aad }
+        assert_eq!(cipher.block_size(), 16, "GCM requires a 128-bit block cipher");
+        assert!(!nonce.is_empty(), "GCM nonce must not be empty");
+        GCMMode { cipher, nonce, aad }
     }
 
-    /// Encrypt with authentication tag generation (partial implementation)
+    /// Encrypt with authentication tag generation
     pub fn encrypt_and_tag(&self, plaintext: &[u8]) -> (Vec<u8>, Vec<u8>) {
-        let ciphertext = self.ctr.process(plaintext);
+        assert!(plaintext.len() as u64 <= GCM_MAX_TEXT_LEN, "Plaintext too long for GCM");
+        let (h, j0) = self.setup();
+        let ciphertext = self.gctr(&j0, plaintext, true);
+        let tag = self.compute_tag(h, &j0, &ciphertext);
+        (ciphertext, tag.to_vec())
+    }
+
+    /// Decrypt with tag verification; `None` if the tag does not match
+    pub fn decrypt_and_verify(&self, ciphertext: &[u8], tag: &[u8]) -> Option<Vec<u8>> {
+        if tag.len() != GCM_TAG_LEN || ciphertext.len() as u64 > GCM_MAX_TEXT_LEN {
+            return None;
+        }
+        let (h, j0) = self.setup();
+        let expected = self.compute_tag(h, &j0, ciphertext);
+        if !bool::from(expected.ct_eq(tag)) {
+            return None;
+        }
+        Some(self.gctr(&j0, ciphertext, true))
+    }
+
+    /// Hash subkey H = E(K, 0^128) and pre-counter block J0.
+    fn setup(&self) -> (u128, [u8; 16]) {
+        let h = u128::from_be_bytes(to_block(&self.cipher.encrypt_block(&[0u8; 16])));
+
+        let mut j0 = [0u8; 16];
+        if self.nonce.len() == 12 {
+            j0[..12].copy_from_slice(&self.nonce);
+            j0[15] = 1;
+        } else {
+            let mut ghash = GHash::new(h);
+            ghash.update_padded(&self.nonce);
+            ghash.update_lengths(0, self.nonce.len() as u64);
+            j0 = ghash.finalize();
+        }
+        (h, j0)
+    }
+
+    /// Counter mode starting at inc32(J0) when `skip_first` is set, incrementing the low 32 bits.
+    fn gctr(&self, j0: &[u8; 16], input: &[u8], skip_first: bool) -> Vec<u8> {
+        let mut counter = *j0;
+        if skip_first {
+            inc32(&mut counter);
+        }
+        let mut output = Vec::with_capacity(input.len());
+        for chunk in input.chunks(16) {
+            let keystream = self.cipher.encrypt_block(&counter);
+            output.extend(chunk.iter().zip(keystream.iter()).map(|(&x, &k)| x ^ k));
+            inc32(&mut counter);
+        }
+        output
+    }
+
+    fn compute_tag(&self, h: u128, j0: &[u8; 16], ciphertext: &[u8]) -> [u8; 16] {
+        let mut ghash = GHash::new(h);
+        ghash.update_padded(&self.aad);
+        ghash.update_padded(ciphertext);
+        ghash.update_lengths(self.aad.len() as u64, ciphertext.len() as u64);
+        let s = ghash.finalize();
+        to_block(&self.gctr(j0, &s, false))
+    }
+}
+
+fn to_block(bytes: &[u8]) -> [u8; 16] {
+    let mut block = [0u8; 16];
+    block.copy_from_slice(&bytes[..16]);
+    block
+}
+
+fn inc32(counter: &mut [u8; 16]) {
+    let low = u32::from_be_bytes([counter[12], counter[13], counter[14], counter[15]]).wrapping_add(1);
+    counter[12..].copy_from_slice(&low.to_be_bytes());
+}
+
+/// GHASH over GF(2^128) with the GCM bit ordering.
+struct GHash {
+    h: u128,
+    acc: u128,
+}
+
+impl GHash {
+    fn new(h: u128) -> Self {
+        GHash { h, acc: 0 }
+    }
+
+    fn update_padded(&mut self, data: &[u8]) {
+        for chunk in data.chunks(16) {
+            let mut block = [0u8; 16];
+            block[..chunk.len()].copy_from_slice(chunk);
+            self.acc = Self::mul(self.acc ^ u128::from_be_bytes(block), self.h);
+        }
+    }
 
-        // Tag generation placeholder (TODO: GHASH implementation)
-        let tag = vec![0u8; self.cipher.block_size()];
+    fn update_lengths(&mut self, aad_len: u64, text_len: u64) {
+        let lengths = ((aad_len as u128 * 8) << 64) | (text_len as u128 * 8);
+        self.acc = Self::mul(self.acc ^ lengths, self.h);
+    }
 
-        (ciphertext, tag)
+    fn finalize(self) -> [u8; 16] {
+        self.acc.to_be_bytes()
     }
 
-    /// Decrypt with tag verification (not implemented)
-    pub fn decrypt_and_verify(&self, _ciphertext: &[u8], _tag: &[u8]) -> Option<Vec<u8>> {
-        // Placeholder for GHASH verification (TODO)
-        None
+    /// Branch-free shift-and-add multiplication (SP 800-38D, Algorithm 1).
+    fn mul(x: u128, y: u128) -> u128 {
+        const R: u128 = 0xE1 << 120;
+        let mut z = 0u128;
+        let mut v = y;
+        for i in 0..128 {
+            let bit = (x >> (127 - i)) & 1;
+            z ^= v & 0u128.wrapping_sub(bit);
+            let lsb = v & 1;
+            v = (v >> 1) ^ (R & 0u128.wrapping_sub(lsb));
+        }
+        z
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ciphers::aes::AesCipher;
+    use crate::core::test_util::h;
+
+    const K128: &str = "feffe9928665731c6d6a8f9467308308";
+    const K256: &str = "feffe9928665731c6d6a8f9467308308feffe9928665731c6d6a8f9467308308";
+    const IV: &str = "cafebabefacedbaddecaf888";
+    const IV8: &str = "cafebabefacedbad";
+    const IV60: &str = "9313225df88406e555909c5aff5269aa6a7a9538534f7da1e4c303d2a318a728c3c0c95156809539fcf0e2429a6b525416aedbf5a0de6a57a637b39b";
+    const P: &str = "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a721c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b391aafd255";
+    const A: &str = "feedfacedeadbeeffeedfacedeadbeefabaddad2";
+
+    /// McGrew and Viega, "The Galois/Counter Mode of Operation", test cases 1-6 (AES-128) and
+    /// 13-18 (AES-256), covering 64-bit and 480-bit IVs as well as the 96-bit fast path.
+    #[test]
+    fn mcgrew_viega_vectors() {
+        let cases = [
+            ("00000000000000000000000000000000", "000000000000000000000000", "", "", "", "58e2fccefa7e3061367f1d57a4e7455a"),
+            ("00000000000000000000000000000000", "000000000000000000000000", "00000000000000000000000000000000", "", "0388dace60b6a392f328c2b971b2fe78", "ab6e47d42cec13bdf53a67b21257bddf"),
+            (
+                K128,
+                IV,
+                P,
+                "",
+                "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091473f5985",
+                "4d5c2af327cd64a62cf35abd2ba6fab4",
+            ),
+            (
+                K128,
+                IV,
+                &P[..120],
+                A,
+                "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091",
+                "5bc94fbc3221a5db94fae95ae7121a47",
+            ),
+            (
+                K128,
+                IV8,
+                &P[..120],
+                A,
+                "61353b4c2806934a777ff51fa22a4755699b2a714fcdc6f83766e5f97b6c742373806900e49f24b22b097544d4896b424989b5e1ebac0f07c23f4598",
+                "3612d2e79e3b0785561be14aaca2fccb",
+            ),
+            (
+                K128,
+                IV60,
+                &P[..120],
+                A,
+                "8ce24998625615b603a033aca13fb894be9112a5c3a211a8ba262a3cca7e2ca701e4a9a4fba43c90ccdcb281d48c7c6fd62875d2aca417034c34aee5",
+                "619cc5aefffe0bfa462af43c1699d050",
+            ),
+            ("0000000000000000000000000000000000000000000000000000000000000000", "000000000000000000000000", "", "", "", "530f8afbc74536b9a963b4f1c4cb738b"),
+            ("0000000000000000000000000000000000000000000000000000000000000000", "000000000000000000000000", "00000000000000000000000000000000", "", "cea7403d4d606b6e074ec5d3baf39d18", "d0d1c8a799996bf0265b98b5d48ab919"),
+            (
+                K256,
+                IV,
+                P,
+                "",
+                "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662898015ad",
+                "b094dac5d93471bdec1a502270e3cc6c",
+            ),
+            (
+                K256,
+                IV,
+                &P[..120],
+                A,
+                "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662",
+                "76fc6ece0f4e1768cddf8853bb2d551b",
+            ),
+            (
+                K256,
+                IV8,
+                &P[..120],
+                A,
+                "c3762df1ca787d32ae47c13bf19844cbaf1ae14d0b976afac52ff7d79bba9de0feb582d33934a4f0954cc2363bc73f7862ac430e64abe499f47c9b1f",
+                "3a337dbf46a792c45e454913fe2ea8f2",
+            ),
+            (
+                K256,
+                IV60,
+                &P[..120],
+                A,
+                "5a8def2f0c9e53f1f75d7853659e2a20eeb2b22aafde6419a058ab4f6f746bf40fc0c3b780f244452da3ebf1c5d82cdea2418997200ef82e44ae7e3f",
+                "a44a8266ee1c8eb0c8b5d4cf5ae9f19a",
+            ),
+        ];
+
+        for (key, iv, plaintext, aad, expected, expected_tag) in cases {
+            let cipher = AesCipher::new(&h(key)).unwrap();
+            let (ciphertext, tag) = GCMMode::new(&cipher, h(iv), h(aad)).encrypt_and_tag(&h(plaintext));
+            assert_eq!((ciphertext.clone(), tag.clone()), (h(expected), h(expected_tag)));
+            let opened = GCMMode::new(&cipher, h(iv), h(aad)).decrypt_and_verify(&ciphertext, &tag);
+            assert_eq!(opened, Some(h(plaintext)));
+
+            let mut bad_tag = tag;
+            bad_tag[0] ^= 1;
+            assert!(GCMMode::new(&cipher, h(iv), h(aad)).decrypt_and_verify(&ciphertext, &bad_tag).is_none());
+        }
     }
 }
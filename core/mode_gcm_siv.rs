@@ -0,0 +1,226 @@
+// ix-encryption/core/mode_gcm_siv.rs
+
+//! AES-GCM-SIV (RFC 8452) - nonce-misuse-resistant AEAD mode.
+//! Repeating a nonce only reveals whether two (nonce, AAD, plaintext) triples were equal;
+//! it does not leak the authentication key or keystream like GCM/ChaCha20-Poly1305 do.
+
+use subtle::ConstantTimeEq;
+use crate::core::ciphers::keyed::KeyedBlockCipher;
+
+pub const GCM_SIV_NONCE_LEN: usize = 12;
+pub const GCM_SIV_TAG_LEN: usize = 16;
+
+/// RFC 8452 caps plaintext and AAD at 2^36 bytes.
+const MAX_INPUT_LEN: u64 = 1 << 36;
+
+/// GCM-SIV mode keyed by a key-generating cipher (AES-128 or AES-256)
+pub struct GCMSIVMode<'a, C: KeyedBlockCipher> {
+    key_generating: &'a C,
+    nonce: Vec<u8>,
+    aad: Vec<u8>,
+}
+
+impl<'a, C: KeyedBlockCipher> GCMSIVMode<'a, C> {
+    pub fn new(key_generating: &'a C, nonce: Vec<u8>, aad: Vec<u8>) -> Self {
+        assert_eq!(key_generating.block_size(), 16, "GCM-SIV requires a 128-bit block cipher");
+        assert!(
+            key_generating.key_len() == 16 || key_generating.key_len() == 32,
+            "GCM-SIV requires a 128- or 256-bit key"
+        );
+        assert_eq!(nonce.len(), GCM_SIV_NONCE_LEN, "GCM-SIV nonce must be 12 bytes");
+        GCMSIVMode { key_generating, nonce, aad }
+    }
+
+    /// Encrypt and return (ciphertext, tag)
+    pub fn encrypt_and_tag(&self, plaintext: &[u8]) -> Result<(Vec<u8>, Vec<u8>), &'static str> {
+        self.check_lengths(plaintext.len())?;
+        let (auth_key, enc_cipher) = self.derive_keys()?;
+
+        let tag = self.compute_tag(&auth_key, &enc_cipher, plaintext);
+        let ciphertext = Self::ctr32(&enc_cipher, &tag, plaintext);
+        Ok((ciphertext, tag))
+    }
+
+    /// Decrypt and verify the tag in constant time. Returns None on authentication failure.
+    pub fn decrypt_and_verify(&self, ciphertext: &[u8], tag: &[u8]) -> Option<Vec<u8>> {
+        if tag.len() != GCM_SIV_TAG_LEN || self.check_lengths(ciphertext.len()).is_err() {
+            return None;
+        }
+        let (auth_key, enc_cipher) = self.derive_keys().ok()?;
+
+        let plaintext = Self::ctr32(&enc_cipher, tag, ciphertext);
+        let expected = self.compute_tag(&auth_key, &enc_cipher, &plaintext);
+
+        if bool::from(expected.ct_eq(tag)) {
+            Some(plaintext)
+        } else {
+            None
+        }
+    }
+
+    fn check_lengths(&self, text_len: usize) -> Result<(), &'static str> {
+        if text_len as u64 > MAX_INPUT_LEN || self.aad.len() as u64 > MAX_INPUT_LEN {
+            return Err("GCM-SIV input exceeds 2^36 bytes");
+        }
+        Ok(())
+    }
+
+    /// Derive the per-nonce message-authentication and message-encryption keys.
+    fn derive_keys(&self) -> Result<([u8; 16], C), &'static str> {
+        let enc_key_len = self.key_generating.key_len();
+        let blocks = 2 + enc_key_len / 8;
+        let mut material = Vec::with_capacity(blocks * 8);
+
+        for counter in 0..blocks as u32 {
+            let mut input = [0u8; 16];
+            input[..4].copy_from_slice(&counter.to_le_bytes());
+            input[4..].copy_from_slice(&self.nonce);
+            let output = self.key_generating.encrypt_block(&input);
+            material.extend_from_slice(&output[..8]);
+        }
+
+        let mut auth_key = [0u8; 16];
+        auth_key.copy_from_slice(&material[..16]);
+        let enc_cipher = C::from_key(&material[16..])?;
+        material.iter_mut().for_each(|b| *b = 0);
+        Ok((auth_key, enc_cipher))
+    }
+
+    fn compute_tag(&self, auth_key: &[u8; 16], enc_cipher: &C, plaintext: &[u8]) -> Vec<u8> {
+        let mut polyval = Polyval::new(auth_key);
+        polyval.update_padded(&self.aad);
+        polyval.update_padded(plaintext);
+
+        let mut length_block = [0u8; 16];
+        length_block[..8].copy_from_slice(&((self.aad.len() as u64) * 8).to_le_bytes());
+        length_block[8..].copy_from_slice(&((plaintext.len() as u64) * 8).to_le_bytes());
+        polyval.update_block(&length_block);
+
+        let mut s = polyval.finalize();
+        for (s_byte, n_byte) in s.iter_mut().zip(self.nonce.iter()) {
+            *s_byte ^= n_byte;
+        }
+        s[15] &= 0x7f;
+        enc_cipher.encrypt_block(&s)
+    }
+
+    /// CTR variant with a 32-bit little-endian counter in the first four bytes.
+    fn ctr32(cipher: &C, tag: &[u8], input: &[u8]) -> Vec<u8> {
+        let mut counter_block = tag.to_vec();
+        counter_block[15] |= 0x80;
+        let mut output = Vec::with_capacity(input.len());
+
+        for chunk in input.chunks(16) {
+            let keystream = cipher.encrypt_block(&counter_block);
+            output.extend(chunk.iter().zip(keystream.iter()).map(|(&x, &k)| x ^ k));
+
+            let counter = u32::from_le_bytes([
+                counter_block[0], counter_block[1], counter_block[2], counter_block[3],
+            ]);
+            counter_block[..4].copy_from_slice(&counter.wrapping_add(1).to_le_bytes());
+        }
+
+        output
+    }
+}
+
+/// POLYVAL universal hash over GF(2^128) with x^128 + x^127 + x^126 + x^121 + 1.
+/// Shared with HCTR2.
+pub(crate) struct Polyval {
+    /// H * x^-128, so each step is a plain field multiplication.
+    h: u128,
+    acc: u128,
+}
+
+impl Polyval {
+    const REDUCTION: u128 = (1 << 127) | (1 << 126) | (1 << 121) | 1;
+
+    pub(crate) fn new(key: &[u8; 16]) -> Self {
+        let mut h = u128::from_le_bytes(*key);
+        for _ in 0..128 {
+            h = Self::mul_x_inv(h);
+        }
+        Polyval { h, acc: 0 }
+    }
+
+    pub(crate) fn update_padded(&mut self, data: &[u8]) {
+        for chunk in data.chunks(16) {
+            let mut block = [0u8; 16];
+            block[..chunk.len()].copy_from_slice(chunk);
+            self.update_block(&block);
+        }
+    }
+
+    pub(crate) fn update_block(&mut self, block: &[u8; 16]) {
+        self.acc = Self::mul(self.acc ^ u128::from_le_bytes(*block), self.h);
+    }
+
+    pub(crate) fn finalize(self) -> [u8; 16] {
+        self.acc.to_le_bytes()
+    }
+
+    /// Constant-time field multiplication (bit i is the coefficient of x^i).
+    fn mul(a: u128, b: u128) -> u128 {
+        let mut result = 0u128;
+        for i in (0..128).rev() {
+            let carry = (result >> 127).wrapping_neg();
+            result = (result << 1) ^ (carry & Self::REDUCTION);
+            let bit = ((b >> i) & 1).wrapping_neg();
+            result ^= a & bit;
+        }
+        result
+    }
+
+    fn mul_x_inv(v: u128) -> u128 {
+        let low = (v & 1).wrapping_neg();
+        let v = v ^ (low & Self::REDUCTION);
+        (v >> 1) | (low & (1 << 127))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ciphers::aes::AesCipher;
+    use crate::core::test_util::h;
+
+    fn check(key: &str, nonce: &str, aad: &str, plaintext: &str, expected: &str) {
+        let cipher = AesCipher::new(&h(key)).unwrap();
+        let (ciphertext, tag) = GCMSIVMode::new(&cipher, h(nonce), h(aad)).encrypt_and_tag(&h(plaintext)).unwrap();
+        assert_eq!([ciphertext.clone(), tag.clone()].concat(), h(expected));
+        let opened = GCMSIVMode::new(&cipher, h(nonce), h(aad)).decrypt_and_verify(&ciphertext, &tag);
+        assert_eq!(opened, Some(h(plaintext)));
+
+        let mut bad_tag = tag;
+        bad_tag[0] ^= 1;
+        assert_eq!(GCMSIVMode::new(&cipher, h(nonce), h(aad)).decrypt_and_verify(&ciphertext, &bad_tag), None);
+    }
+
+    /// RFC 8452 Appendix C.1, AEAD_AES_128_GCM_SIV.
+    #[test]
+    fn rfc8452_aes128_vectors() {
+        let key = "01000000000000000000000000000000";
+        let nonce = "030000000000000000000000";
+        check(key, nonce, "", "", "dc20e2d83f25705bb49e439eca56de25");
+        check(key, nonce, "", "0100000000000000", "b5d839330ac7b786578782fff6013b815b287c22493a364c");
+        check(key, nonce, "", "010000000000000000000000", "7323ea61d05932260047d942a4978db357391a0bc4fdec8b0d106639");
+        check(
+            key,
+            nonce,
+            "",
+            "01000000000000000000000000000000",
+            "743f7c8077ab25f8624e2e948579cf77303aaf90f6fe21199c6068577437a0c4",
+        );
+        check(key, nonce, "01", "0200000000000000", "1e6daba35669f4273b0a1a2560969cdf790d99759abd1508");
+    }
+
+    /// RFC 8452 Appendix C.2, AEAD_AES_256_GCM_SIV.
+    #[test]
+    fn rfc8452_aes256_vectors() {
+        let key = "0100000000000000000000000000000000000000000000000000000000000000";
+        let nonce = "030000000000000000000000";
+        check(key, nonce, "", "", "07f5f4169bbf55a8400cd47ea6fd400f");
+        check(key, nonce, "", "0100000000000000", "c2ef328e5c71c83b843122130f7364b761e0b97427e3df28");
+        check(key, nonce, "01", "0200000000000000", "1de22967237a813291213f267e3b452f02d01ae33e4ec854");
+    }
+}
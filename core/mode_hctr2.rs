@@ -0,0 +1,142 @@
+// ix-encryption/core/mode_hctr2.rs
+
+//! HCTR2 wide-block tweakable encryption (Crowley, Huckleberry, Biggers 2021).
+//! Length-preserving: every ciphertext bit depends on every plaintext bit of the sector and
+//! on the tweak. Built from a 128-bit block cipher, XCTR and POLYVAL.
+
+use crate::core::blockcipher::BlockCipher;
+use crate::core::mac::cmac::xor_in_place;
+use crate::core::mode_gcm_siv::Polyval;
+
+pub const HCTR2_BLOCK_SIZE: usize = 16;
+
+pub struct HCTR2Mode<'a, C: BlockCipher> {
+    cipher: &'a C,
+    hash_key: [u8; 16],
+    l: Vec<u8>,
+}
+
+impl<'a, C: BlockCipher> HCTR2Mode<'a, C> {
+    pub fn new(cipher: &'a C) -> Self {
+        assert_eq!(cipher.block_size(), HCTR2_BLOCK_SIZE, "HCTR2 requires a 128-bit block cipher");
+        let mut hash_key = [0u8; 16];
+        hash_key.copy_from_slice(&cipher.encrypt_block(&le_block(0)));
+        let l = cipher.encrypt_block(&le_block(1));
+        HCTR2Mode { cipher, hash_key, l }
+    }
+
+    /// Encrypt a message of at least 16 bytes in place under `tweak`.
+    pub fn encrypt(&self, tweak: &[u8], data: &mut [u8]) -> Result<(), &'static str> {
+        self.process(tweak, data, true)
+    }
+
+    /// Decrypt a message of at least 16 bytes in place under `tweak`.
+    pub fn decrypt(&self, tweak: &[u8], data: &mut [u8]) -> Result<(), &'static str> {
+        self.process(tweak, data, false)
+    }
+
+    fn process(&self, tweak: &[u8], data: &mut [u8], encrypt: bool) -> Result<(), &'static str> {
+        if data.len() < HCTR2_BLOCK_SIZE {
+            return Err("HCTR2 input shorter than one block");
+        }
+        let (first, bulk) = data.split_at_mut(HCTR2_BLOCK_SIZE);
+
+        let mut mm = first.to_vec();
+        xor_in_place(&mut mm, &self.hash(tweak, bulk));
+        let uu = if encrypt {
+            self.cipher.encrypt_block(&mm)
+        } else {
+            self.cipher.decrypt_block(&mm)
+        };
+
+        let mut s = mm;
+        xor_in_place(&mut s, &uu);
+        xor_in_place(&mut s, &self.l);
+        self.xctr(&s, bulk);
+
+        let mut last = uu;
+        xor_in_place(&mut last, &self.hash(tweak, bulk));
+        first.copy_from_slice(&last);
+        Ok(())
+    }
+
+    /// POLYVAL over the tweak-length block, the zero-padded tweak and the 0x01-padded message.
+    fn hash(&self, tweak: &[u8], message: &[u8]) -> [u8; 16] {
+        let mut polyval = Polyval::new(&self.hash_key);
+
+        let tweak_bits = tweak.len() as u128 * 8;
+        let length_marker = if message.len() % HCTR2_BLOCK_SIZE == 0 { 2 } else { 3 };
+        polyval.update_block(&(2 * tweak_bits + length_marker).to_le_bytes());
+        polyval.update_padded(tweak);
+
+        let full = message.len() - message.len() % HCTR2_BLOCK_SIZE;
+        polyval.update_padded(&message[..full]);
+        if full < message.len() {
+            let mut padded = [0u8; 16];
+            let tail = &message[full..];
+            padded[..tail.len()].copy_from_slice(tail);
+            padded[tail.len()] = 0x01;
+            polyval.update_block(&padded);
+        }
+
+        polyval.finalize()
+    }
+
+    /// XCTR: keystream block i is E(S xor le128(i)), counting from 1.
+    fn xctr(&self, s: &[u8], data: &mut [u8]) {
+        for (i, chunk) in data.chunks_mut(HCTR2_BLOCK_SIZE).enumerate() {
+            let mut input = s.to_vec();
+            xor_in_place(&mut input, &le_block(i as u128 + 1));
+            let keystream = self.cipher.encrypt_block(&input);
+            xor_in_place(chunk, &keystream);
+        }
+    }
+}
+
+fn le_block(value: u128) -> Vec<u8> {
+    value.to_le_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ciphers::aes::AesCipher;
+    use crate::core::test_util::h;
+
+    /// HCTR2-AES-256 with a 32-byte tweak, from an independent implementation of the paper's
+    /// algorithm whose POLYVAL matches RFC 8452 Appendix A. The lengths cover a bare block, a
+    /// one-byte bulk, whole blocks and a partial final block.
+    #[test]
+    fn reference_vectors() {
+        let cipher = AesCipher::new(&h("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f")).unwrap();
+        let hctr2 = HCTR2Mode::new(&cipher);
+        let tweak: Vec<u8> = (0x20..0x40).collect();
+        let cases = [
+            (16, "575f5c88701eaf8383a3264b805c1d7e"),
+            (17, "74ac292799cab20c5a1182e6c802e7e426"),
+            (
+                48,
+                "a45ad00fa13a899c50c99033fcf702bd1655916e0044db8543ac7ee69d21dbf090e8d79a968f617165bf2124fd9844e7",
+            ),
+            (
+                255,
+                concat!(
+                    "97803bfff164cc228096461c0fe0102bdc7eea19e73806fff95d643e3ca4d26ad5a236aa94021415f315160e8816451c",
+                    "0e5c986f522be28cd4e60c80cb1ebdd7a5317a553110cda2e96671a8f90d8d8d70a1a5755e6e1767446e7dd7687f4643",
+                    "6ceb3f0262042ed767e5915fe35c2169e2995f827d4f425f612541f1ab76ace0f0581935801a8e2455567b45e091b9e9",
+                    "b882b3590f598ff76efdefccdbb57ef31eee03a64ec0d0bfe7332f035dd020ef63cd17b09568a3f3d21b6e996a84912c",
+                    "8978477ca18511d1e627416239845d8d278fad2130bd737d8c8999e0eb0b3ab36659463ff7ce24a48e98f3619959ccd6",
+                    "313684f4706c8678a0314e627582cc",
+                ),
+            ),
+        ];
+        for (len, expected) in cases {
+            let plaintext: Vec<u8> = (0..len).map(|i| (i * 7) as u8).collect();
+            let mut data = plaintext.clone();
+            hctr2.encrypt(&tweak, &mut data).unwrap();
+            assert_eq!(data, h(expected));
+            hctr2.decrypt(&tweak, &mut data).unwrap();
+            assert_eq!(data, plaintext);
+        }
+    }
+}
@@ -0,0 +1,232 @@
+// ix-encryption/core/mode_ocb.rs
+
+//! Offset Codebook Mode v3 (OCB3, RFC 7253) - single-pass AEAD mode with 128-bit tags.
+
+use subtle::ConstantTimeEq;
+use crate::core::blockcipher::BlockCipher;
+use crate::core::mac::cmac::{dbl, xor_in_place};
+
+pub const OCB_TAG_LEN: usize = 16;
+pub const OCB_MAX_NONCE_LEN: usize = 15;
+
+pub struct OCBMode<'a, C: BlockCipher> {
+    cipher: &'a C,
+    nonce: Vec<u8>,
+    aad: Vec<u8>,
+    l_star: Vec<u8>,
+    l_dollar: Vec<u8>,
+    l_table: Vec<Vec<u8>>,
+}
+
+impl<'a, C: BlockCipher> OCBMode<'a, C> {
+    pub fn new(cipher: &'a C, nonce: Vec<u8>, aad: Vec<u8>) -> Self {
+        assert_eq!(cipher.block_size(), 16, "OCB requires a 128-bit block cipher");
+        assert!(
+            !nonce.is_empty() && nonce.len() <= OCB_MAX_NONCE_LEN,
+            "OCB nonce must be 1 to 15 bytes"
+        );
+
+        let l_star = cipher.encrypt_block(&[0u8; 16]);
+        let l_dollar = dbl(&l_star);
+        let l_table = vec![dbl(&l_dollar)];
+
+        OCBMode { cipher, nonce, aad, l_star, l_dollar, l_table }
+    }
+
+    /// Encrypt with authentication tag generation
+    pub fn encrypt_and_tag(&mut self, plaintext: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let mut offset = self.initial_offset();
+        let mut checksum = vec![0u8; 16];
+        let mut ciphertext = Vec::with_capacity(plaintext.len());
+
+        let full_blocks = plaintext.len() / 16;
+        for (i, block) in plaintext[..full_blocks * 16].chunks(16).enumerate() {
+            xor_in_place(&mut offset, self.l(ntz(i + 1)));
+            ciphertext.extend_from_slice(&self.whiten(&offset, block, true));
+            xor_in_place(&mut checksum, block);
+        }
+
+        let tail = &plaintext[full_blocks * 16..];
+        if !tail.is_empty() {
+            xor_in_place(&mut offset, &self.l_star);
+            let pad = self.cipher.encrypt_block(&offset);
+            ciphertext.extend(tail.iter().zip(pad.iter()).map(|(&p, &k)| p ^ k));
+            xor_in_place(&mut checksum, &pad_block(tail));
+        }
+
+        let tag = self.finish_tag(&checksum, &offset);
+        (ciphertext, tag)
+    }
+
+    /// Decrypt with tag verification
+    pub fn decrypt_and_verify(&mut self, ciphertext: &[u8], tag: &[u8]) -> Option<Vec<u8>> {
+        if tag.len() != OCB_TAG_LEN {
+            return None;
+        }
+
+        let mut offset = self.initial_offset();
+        let mut checksum = vec![0u8; 16];
+        let mut plaintext = Vec::with_capacity(ciphertext.len());
+
+        let full_blocks = ciphertext.len() / 16;
+        for (i, block) in ciphertext[..full_blocks * 16].chunks(16).enumerate() {
+            xor_in_place(&mut offset, self.l(ntz(i + 1)));
+            let decrypted = self.whiten(&offset, block, false);
+            xor_in_place(&mut checksum, &decrypted);
+            plaintext.extend_from_slice(&decrypted);
+        }
+
+        let tail = &ciphertext[full_blocks * 16..];
+        if !tail.is_empty() {
+            xor_in_place(&mut offset, &self.l_star);
+            let pad = self.cipher.encrypt_block(&offset);
+            let decrypted: Vec<u8> = tail.iter().zip(pad.iter()).map(|(&c, &k)| c ^ k).collect();
+            xor_in_place(&mut checksum, &pad_block(&decrypted));
+            plaintext.extend_from_slice(&decrypted);
+        }
+
+        let expected = self.finish_tag(&checksum, &offset);
+        if bool::from(expected.ct_eq(tag)) {
+            Some(plaintext)
+        } else {
+            None
+        }
+    }
+
+    /// L_i, extending the doubling table on demand.
+    fn l(&mut self, i: usize) -> &[u8] {
+        while self.l_table.len() <= i {
+            let next = dbl(self.l_table.last().unwrap());
+            self.l_table.push(next);
+        }
+        &self.l_table[i]
+    }
+
+    fn whiten(&self, offset: &[u8], block: &[u8], encrypt: bool) -> Vec<u8> {
+        let mut input = block.to_vec();
+        xor_in_place(&mut input, offset);
+        let mut output = if encrypt {
+            self.cipher.encrypt_block(&input)
+        } else {
+            self.cipher.decrypt_block(&input)
+        };
+        xor_in_place(&mut output, offset);
+        output
+    }
+
+    /// Offset_0 derived from the nonce via Ktop and Stretch.
+    fn initial_offset(&self) -> Vec<u8> {
+        let mut nonce_block = [0u8; 16];
+        nonce_block[0] = (((OCB_TAG_LEN * 8) % 128) as u8) << 1;
+        nonce_block[16 - self.nonce.len() - 1] |= 0x01;
+        nonce_block[16 - self.nonce.len()..].copy_from_slice(&self.nonce);
+
+        let bottom = (nonce_block[15] & 0x3f) as usize;
+        nonce_block[15] &= 0xc0;
+        let ktop = self.cipher.encrypt_block(&nonce_block);
+
+        let mut stretch = ktop.clone();
+        stretch.extend((0..8).map(|i| ktop[i] ^ ktop[i + 1]));
+
+        let byte_shift = bottom / 8;
+        let bit_shift = bottom % 8;
+        (0..16)
+            .map(|i| {
+                let hi = stretch[i + byte_shift] << bit_shift;
+                let lo = if bit_shift == 0 { 0 } else { stretch[i + byte_shift + 1] >> (8 - bit_shift) };
+                hi | lo
+            })
+            .collect()
+    }
+
+    fn finish_tag(&mut self, checksum: &[u8], offset: &[u8]) -> Vec<u8> {
+        let mut input = checksum.to_vec();
+        xor_in_place(&mut input, offset);
+        xor_in_place(&mut input, &self.l_dollar);
+        let mut tag = self.cipher.encrypt_block(&input);
+        let hash = self.hash_aad();
+        xor_in_place(&mut tag, &hash);
+        tag.truncate(OCB_TAG_LEN);
+        tag
+    }
+
+    fn hash_aad(&mut self) -> Vec<u8> {
+        let aad = std::mem::take(&mut self.aad);
+        let mut sum = vec![0u8; 16];
+        let mut offset = vec![0u8; 16];
+
+        let full_blocks = aad.len() / 16;
+        for (i, block) in aad[..full_blocks * 16].chunks(16).enumerate() {
+            xor_in_place(&mut offset, self.l(ntz(i + 1)));
+            let mut input = block.to_vec();
+            xor_in_place(&mut input, &offset);
+            xor_in_place(&mut sum, &self.cipher.encrypt_block(&input));
+        }
+
+        let tail = &aad[full_blocks * 16..];
+        if !tail.is_empty() {
+            xor_in_place(&mut offset, &self.l_star);
+            let mut input = pad_block(tail);
+            xor_in_place(&mut input, &offset);
+            xor_in_place(&mut sum, &self.cipher.encrypt_block(&input));
+        }
+
+        self.aad = aad;
+        sum
+    }
+}
+
+/// Number of trailing zero bits of a block index.
+fn ntz(i: usize) -> usize {
+    i.trailing_zeros() as usize
+}
+
+/// Partial block followed by a single 1 bit and zero fill.
+fn pad_block(partial: &[u8]) -> Vec<u8> {
+    let mut block = vec![0u8; 16];
+    block[..partial.len()].copy_from_slice(partial);
+    block[partial.len()] = 0x80;
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ciphers::aes::AesCipher;
+    use crate::core::test_util::h;
+
+    /// RFC 7253 Appendix A, AES-128 with 128-bit tags.
+    #[test]
+    fn rfc7253_sample_results() {
+        let cipher = AesCipher::new(&h("000102030405060708090a0b0c0d0e0f")).unwrap();
+        let cases = [
+            ("bbaa99887766554433221100", "", "", "785407bfffc8ad9edcc5520ac9111ee6"),
+            ("bbaa99887766554433221101", "0001020304050607", "0001020304050607", "6820b3657b6f615a5725bda0d3b4eb3a257c9af1f8f03009"),
+            ("bbaa99887766554433221102", "0001020304050607", "", "81017f8203f081277152fade694a0a00"),
+            ("bbaa99887766554433221103", "", "0001020304050607", "45dd69f8f5aae72414054cd1f35d82760b2cd00d2f99bfa9"),
+            (
+                "bbaa99887766554433221104",
+                "000102030405060708090a0b0c0d0e0f",
+                "000102030405060708090a0b0c0d0e0f",
+                "571d535b60b277188be5147170a9a22c3ad7a4ff3835b8c5701c1ccec8fc3358",
+            ),
+            (
+                "bbaa9988776655443322110d",
+                "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f2021222324252627",
+                "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f2021222324252627",
+                "d5ca91748410c1751ff8a2f618255b68a0a12e093ff454606e59f9c1d0ddc54b65e8628e568bad7aed07ba06a4a69483a7035490c5769e60",
+            ),
+        ];
+
+        for (nonce, aad, plaintext, expected) in cases {
+            let (ciphertext, tag) = OCBMode::new(&cipher, h(nonce), h(aad)).encrypt_and_tag(&h(plaintext));
+            assert_eq!([ciphertext.clone(), tag.clone()].concat(), h(expected));
+            let opened = OCBMode::new(&cipher, h(nonce), h(aad)).decrypt_and_verify(&ciphertext, &tag);
+            assert_eq!(opened, Some(h(plaintext)));
+
+            let mut bad_tag = tag;
+            bad_tag[0] ^= 1;
+            assert!(OCBMode::new(&cipher, h(nonce), h(aad)).decrypt_and_verify(&ciphertext, &bad_tag).is_none());
+        }
+    }
+}
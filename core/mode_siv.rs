@@ -0,0 +1,145 @@
+// ix-encryption/core/mode_siv.rs
+
+//! Synthetic Initialization Vector (SIV) mode, RFC 5297 - deterministic/nonce-misuse-resistant AEAD.
+//! With no nonce in the associated data SIV is fully deterministic, which is the intended
+//! construction for key wrapping.
+
+use subtle::ConstantTimeEq;
+use crate::core::blockcipher::BlockCipher;
+use crate::core::mac::cmac::{cmac, dbl, xor_in_place};
+use crate::core::mode_ctr::CTRMode;
+
+pub const SIV_TAG_LEN: usize = 16;
+
+/// SIV mode over two independently keyed 128-bit block ciphers (K1 for S2V, K2 for CTR)
+pub struct SIVMode<'a, C: BlockCipher> {
+    mac_cipher: &'a C,
+    ctr_cipher: &'a C,
+}
+
+impl<'a, C: BlockCipher> SIVMode<'a, C> {
+    pub fn new(mac_cipher: &'a C, ctr_cipher: &'a C) -> Self {
+        assert_eq!(mac_cipher.block_size(), 16, "SIV requires a 128-bit block cipher");
+        assert_eq!(ctr_cipher.block_size(), 16, "SIV requires a 128-bit block cipher");
+        SIVMode { mac_cipher, ctr_cipher }
+    }
+
+    /// Encrypt under a vector of associated data components (a nonce, if any, goes last).
+    /// Returns V || C.
+    pub fn encrypt(&self, associated_data: &[&[u8]], plaintext: &[u8]) -> Vec<u8> {
+        let v = self.s2v(associated_data, plaintext);
+        let ciphertext = CTRMode::new(self.ctr_cipher, Self::ctr_iv(&v)).process(plaintext);
+
+        let mut output = v;
+        output.extend_from_slice(&ciphertext);
+        output
+    }
+
+    /// Decrypt V || C and verify the synthetic IV in constant time.
+    pub fn decrypt(&self, associated_data: &[&[u8]], ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        if ciphertext.len() < SIV_TAG_LEN {
+            return Err("Ciphertext shorter than SIV tag");
+        }
+        let (v, body) = ciphertext.split_at(SIV_TAG_LEN);
+        let plaintext = CTRMode::new(self.ctr_cipher, Self::ctr_iv(v)).process(body);
+
+        let expected = self.s2v(associated_data, &plaintext);
+        if bool::from(expected.ct_eq(v)) {
+            Ok(plaintext)
+        } else {
+            Err("SIV authentication failed")
+        }
+    }
+
+    /// Deterministically wrap key material (no nonce component).
+    pub fn wrap_key(&self, header: &[u8], key: &[u8]) -> Vec<u8> {
+        self.encrypt(&[header], key)
+    }
+
+    /// Unwrap key material produced by `wrap_key`.
+    pub fn unwrap_key(&self, header: &[u8], wrapped: &[u8]) -> Result<Vec<u8>, &'static str> {
+        self.decrypt(&[header], wrapped)
+    }
+
+    /// Clear bits 31 and 63 of V to form the CTR initial counter block.
+    fn ctr_iv(v: &[u8]) -> Vec<u8> {
+        let mut q = v.to_vec();
+        q[8] &= 0x7f;
+        q[12] &= 0x7f;
+        q
+    }
+
+    fn s2v(&self, associated_data: &[&[u8]], plaintext: &[u8]) -> Vec<u8> {
+        let mut d = self.cmac(&[0u8; 16]);
+        for component in associated_data {
+            d = dbl(&d);
+            xor_in_place(&mut d, &self.cmac(component));
+        }
+
+        if plaintext.len() >= 16 {
+            let mut t = plaintext.to_vec();
+            let offset = t.len() - 16;
+            xor_in_place(&mut t[offset..], &d);
+            self.cmac(&t)
+        } else {
+            let mut t = dbl(&d);
+            let mut padded = [0u8; 16];
+            padded[..plaintext.len()].copy_from_slice(plaintext);
+            padded[plaintext.len()] = 0x80;
+            xor_in_place(&mut t, &padded);
+            self.cmac(&t)
+        }
+    }
+
+    fn cmac(&self, message: &[u8]) -> Vec<u8> {
+        cmac(self.mac_cipher, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ciphers::aes::AesCipher;
+    use crate::core::test_util::h;
+
+    /// RFC 5297 Appendix A.1, deterministic authenticated encryption.
+    #[test]
+    fn rfc5297_deterministic_vector() {
+        let mac_cipher = AesCipher::new(&h("fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0")).unwrap();
+        let ctr_cipher = AesCipher::new(&h("f0f1f2f3f4f5f6f7f8f9fafbfcfdfeff")).unwrap();
+        let siv = SIVMode::new(&mac_cipher, &ctr_cipher);
+        let header = h("101112131415161718191a1b1c1d1e1f2021222324252627");
+        let key = h("112233445566778899aabbccddee");
+
+        let wrapped = siv.wrap_key(&header, &key);
+        assert_eq!(wrapped, h("85632d07c6e8f37f950acd320a2ecc9340c02b9690c4dc04daef7f6afe5c"));
+        assert_eq!(siv.unwrap_key(&header, &wrapped).unwrap(), key);
+
+        let mut tampered = wrapped;
+        tampered[SIV_TAG_LEN] ^= 1;
+        assert_eq!(siv.unwrap_key(&header, &tampered), Err("SIV authentication failed"));
+    }
+
+    /// RFC 5297 Appendix A.2, nonce-based authenticated encryption with two AD components.
+    #[test]
+    fn rfc5297_nonce_based_vector() {
+        let mac_cipher = AesCipher::new(&h("7f7e7d7c7b7a79787776757473727170")).unwrap();
+        let ctr_cipher = AesCipher::new(&h("404142434445464748494a4b4c4d4e4f")).unwrap();
+        let siv = SIVMode::new(&mac_cipher, &ctr_cipher);
+        let ad1 = h("00112233445566778899aabbccddeeffdeaddadadeaddadaffeeddccbbaa99887766554433221100");
+        let ad2 = h("102030405060708090a0");
+        let nonce = h("09f911029d74e35bd84156c5635688c0");
+        let plaintext = h("7468697320697320736f6d6520706c61696e7465787420746f20656e6372797074207573696e67205349562d414553");
+
+        let ciphertext = siv.encrypt(&[&ad1, &ad2, &nonce], &plaintext);
+        assert_eq!(
+            ciphertext,
+            h(concat!(
+                "7bdb6e3b432667eb06f4d14bff2fbd0f",
+                "cb900f2fddbe404326601965c889bf17dba77ceb094fa663b7a3f748ba8af829ea64ad544a272e9c485b62a3fd5c0d",
+            ))
+        );
+        assert_eq!(siv.decrypt(&[&ad1, &ad2, &nonce], &ciphertext).unwrap(), plaintext);
+        assert!(siv.decrypt(&[&ad1, &nonce], &ciphertext).is_err());
+    }
+}
@@ -0,0 +1,221 @@
+// ix-encryption/core/mode_xts.rs
+
+//! XTS mode (IEEE 1619 / SP 800-38E) for sector-based disk and block-device encryption.
+//! Each sector is encrypted in place under a tweak derived from its sector number;
+//! partial final blocks are handled with ciphertext stealing, so output length equals input length.
+
+use subtle::ConstantTimeEq;
+use crate::core::blockcipher::BlockCipher;
+use crate::core::mac::cmac::xor_in_place;
+
+pub const XTS_BLOCK_SIZE: usize = 16;
+
+/// XTS mode over a data-unit cipher (K1) and a tweak cipher (K2), which must use independent keys
+pub struct XTSMode<'a, C: BlockCipher> {
+    data_cipher: &'a C,
+    tweak_cipher: &'a C,
+}
+
+impl<'a, C: BlockCipher> XTSMode<'a, C> {
+    /// Fails when K1 and K2 are the same key (IEEE 1619-2018 5.1), detected by comparing the
+    /// encryption of a probe block under both ciphers.
+    pub fn new(data_cipher: &'a C, tweak_cipher: &'a C) -> Result<Self, &'static str> {
+        if data_cipher.block_size() != XTS_BLOCK_SIZE || tweak_cipher.block_size() != XTS_BLOCK_SIZE {
+            return Err("XTS requires a 128-bit block cipher");
+        }
+        let probe = [0u8; XTS_BLOCK_SIZE];
+        if bool::from(data_cipher.encrypt_block(&probe).ct_eq(&tweak_cipher.encrypt_block(&probe))) {
+            return Err("XTS data and tweak keys must differ");
+        }
+        Ok(XTSMode { data_cipher, tweak_cipher })
+    }
+
+    /// Encrypt one sector in place. The sector must be at least one block long.
+    pub fn encrypt_sector(&self, sector_number: u128, sector: &mut [u8]) -> Result<(), &'static str> {
+        self.process_sector(sector_number, sector, true)
+    }
+
+    /// Decrypt one sector in place.
+    pub fn decrypt_sector(&self, sector_number: u128, sector: &mut [u8]) -> Result<(), &'static str> {
+        self.process_sector(sector_number, sector, false)
+    }
+
+    /// Encrypt consecutive fixed-size sectors in place, starting at `first_sector`.
+    /// A shorter trailing sector is allowed as long as it holds at least one block.
+    pub fn encrypt_sectors(&self, first_sector: u128, sector_size: usize, buffer: &mut [u8]) -> Result<(), &'static str> {
+        check_sectors(first_sector, sector_size, buffer.len())?;
+        for (i, sector) in buffer.chunks_mut(sector_size).enumerate() {
+            self.encrypt_sector(first_sector + i as u128, sector)?;
+        }
+        Ok(())
+    }
+
+    /// Decrypt consecutive fixed-size sectors in place, starting at `first_sector`.
+    pub fn decrypt_sectors(&self, first_sector: u128, sector_size: usize, buffer: &mut [u8]) -> Result<(), &'static str> {
+        check_sectors(first_sector, sector_size, buffer.len())?;
+        for (i, sector) in buffer.chunks_mut(sector_size).enumerate() {
+            self.decrypt_sector(first_sector + i as u128, sector)?;
+        }
+        Ok(())
+    }
+
+    fn process_sector(&self, sector_number: u128, sector: &mut [u8], encrypt: bool) -> Result<(), &'static str> {
+        if sector.len() < XTS_BLOCK_SIZE {
+            return Err("XTS sector shorter than one block");
+        }
+
+        let mut tweak = self.tweak_cipher.encrypt_block(&sector_number.to_le_bytes());
+        let full_blocks = sector.len() / XTS_BLOCK_SIZE;
+        let remainder = sector.len() % XTS_BLOCK_SIZE;
+        // With stealing, the last full block is handled together with the partial tail.
+        let plain_blocks = if remainder == 0 { full_blocks } else { full_blocks - 1 };
+
+        for block in sector[..plain_blocks * XTS_BLOCK_SIZE].chunks_mut(XTS_BLOCK_SIZE) {
+            let output = self.xex(&tweak, block, encrypt);
+            block.copy_from_slice(&output);
+            tweak = mul_alpha(&tweak);
+        }
+
+        if remainder == 0 {
+            return Ok(());
+        }
+
+        let start = plain_blocks * XTS_BLOCK_SIZE;
+        let (last_full, tail) = sector[start..].split_at_mut(XTS_BLOCK_SIZE);
+        let next_tweak = mul_alpha(&tweak);
+        // Encryption uses T_{m-1} then T_m; decryption reverses the order.
+        let (first_tweak, second_tweak) = if encrypt { (&tweak, &next_tweak) } else { (&next_tweak, &tweak) };
+
+        let stolen = self.xex(first_tweak, last_full, encrypt);
+        let mut combined = tail.to_vec();
+        combined.extend_from_slice(&stolen[remainder..]);
+        tail.copy_from_slice(&stolen[..remainder]);
+
+        let output = self.xex(second_tweak, &combined, encrypt);
+        last_full.copy_from_slice(&output);
+        Ok(())
+    }
+
+    fn xex(&self, tweak: &[u8], block: &[u8], encrypt: bool) -> Vec<u8> {
+        let mut input = block.to_vec();
+        xor_in_place(&mut input, tweak);
+        let mut output = if encrypt {
+            self.data_cipher.encrypt_block(&input)
+        } else {
+            self.data_cipher.decrypt_block(&input)
+        };
+        xor_in_place(&mut output, tweak);
+        output
+    }
+}
+
+/// Validates a multi-sector buffer before any sector is touched, so a bad layout never leaves
+/// the buffer partly transformed.
+fn check_sectors(first_sector: u128, sector_size: usize, len: usize) -> Result<(), &'static str> {
+    if sector_size == 0 {
+        return Err("XTS sector size must be nonzero");
+    }
+    if len == 0 {
+        return Ok(());
+    }
+    let tail = len % sector_size;
+    if sector_size < XTS_BLOCK_SIZE || (tail != 0 && tail < XTS_BLOCK_SIZE) {
+        return Err("XTS sector shorter than one block");
+    }
+    let sectors = len.div_ceil(sector_size) as u128;
+    first_sector.checked_add(sectors - 1).ok_or("XTS sector number overflow")?;
+    Ok(())
+}
+
+/// Multiply the tweak by alpha in GF(2^128), little-endian byte order.
+fn mul_alpha(tweak: &[u8]) -> Vec<u8> {
+    let mut out = vec![0u8; XTS_BLOCK_SIZE];
+    let carry = tweak[15] >> 7;
+    for i in (1..XTS_BLOCK_SIZE).rev() {
+        out[i] = (tweak[i] << 1) | (tweak[i - 1] >> 7);
+    }
+    out[0] = (tweak[0] << 1) ^ (0x87 & carry.wrapping_neg());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ciphers::aes::AesCipher;
+    use crate::core::test_util::h;
+
+    fn check(key1: &str, key2: &str, sector_number: u128, plaintext: &[u8], expected: &str) {
+        let data_cipher = AesCipher::new(&h(key1)).unwrap();
+        let tweak_cipher = AesCipher::new(&h(key2)).unwrap();
+        let xts = XTSMode::new(&data_cipher, &tweak_cipher).unwrap();
+
+        let mut sector = plaintext.to_vec();
+        xts.encrypt_sector(sector_number, &mut sector).unwrap();
+        assert_eq!(sector, h(expected));
+        xts.decrypt_sector(sector_number, &mut sector).unwrap();
+        assert_eq!(sector, plaintext);
+    }
+
+    /// IEEE 1619-2007 Annex B, XTS-AES-128 vectors 2 and 3 (vector 1 uses equal keys, which
+    /// `XTSMode::new` refuses).
+    #[test]
+    fn ieee1619_full_block_vectors() {
+        let plaintext = [0x44u8; 32];
+        check(
+            "11111111111111111111111111111111",
+            "22222222222222222222222222222222",
+            0x3333333333,
+            &plaintext,
+            "c454185e6a16936e39334038acef838bfb186fff7480adc4289382ecd6d394f0",
+        );
+        check(
+            "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0",
+            "22222222222222222222222222222222",
+            0x3333333333,
+            &plaintext,
+            "af85336b597afc1a900b2eb21ec949d292df4c047e0b21532186a5971a227a89",
+        );
+    }
+
+    /// IEEE 1619-2007 Annex B, XTS-AES-128 vectors 15 to 18: data units of 17 to 20 bytes,
+    /// which exercise ciphertext stealing.
+    #[test]
+    fn ieee1619_ciphertext_stealing_vectors() {
+        let cases = [
+            (17, "6c1625db4671522d3d7599601de7ca09ed"),
+            (18, "d069444b7a7e0cab09e24447d24deb1fedbf"),
+            (19, "e5df1351c0544ba1350b3363cd8ef4beedbf9d"),
+            (20, "9d84c813f719aa2c7be3f66171c7c5c2edbf9dac"),
+        ];
+        for (len, expected) in cases {
+            let plaintext: Vec<u8> = (0..len).collect();
+            check(
+                "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0",
+                "bfbebdbcbbbab9b8b7b6b5b4b3b2b1b0",
+                0x123456789a,
+                &plaintext,
+                expected,
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_bad_sector_layouts_before_writing() {
+        let data_cipher = AesCipher::new(&[1u8; 16]).unwrap();
+        let tweak_cipher = AesCipher::new(&[2u8; 16]).unwrap();
+        let xts = XTSMode::new(&data_cipher, &tweak_cipher).unwrap();
+
+        let mut buffer = vec![7u8; 64 + 8];
+        assert_eq!(xts.encrypt_sectors(0, 64, &mut buffer), Err("XTS sector shorter than one block"));
+        assert_eq!(buffer, vec![7u8; 64 + 8]);
+
+        let mut buffer = vec![7u8; 64];
+        assert_eq!(xts.encrypt_sectors(u128::MAX, 32, &mut buffer), Err("XTS sector number overflow"));
+        assert_eq!(buffer, vec![7u8; 64]);
+        assert_eq!(xts.encrypt_sectors(0, 0, &mut buffer), Err("XTS sector size must be nonzero"));
+
+        xts.encrypt_sectors(u128::MAX - 1, 32, &mut buffer).unwrap();
+        xts.decrypt_sectors(u128::MAX - 1, 32, &mut buffer).unwrap();
+        assert_eq!(buffer, vec![7u8; 64]);
+    }
+}
@@ -2,29 +2,74 @@
 
 //! Multiplexer to combine multiple IXCipherCore implementations into one unified hybrid cipher.
 //! Enables dynamic selection and layered encryption for defense in depth.
+//!
+//! Two keying versions exist. `IX-Multiplexer-v1` splits the key into equal consecutive parts,
+//! one per layer, and stays the default so existing ciphertexts keep decrypting.
+//! `IX-Multiplexer-v2` derives an independent HKDF key for every layer at that layer's native
+//! key length, so layers never share key bytes and the master key length does not matter.
 
+use zeroize::Zeroize;
 use crate::core::IXCipherCore;
+use crate::core::kdf::key_hierarchy::KeyHierarchy;
+
+/// Layer key length used by `IX-Multiplexer-v2` when none is given.
+pub const DEFAULT_LAYER_KEY_LEN: usize = 32;
+
+/// How per-layer keys are obtained from the key passed to `initialize`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayerKeying {
+    /// v1: equal consecutive slices of the key.
+    Split,
+    /// v2: independent HKDF-derived keys of each layer's native length.
+    Derived,
+}
+
+struct Layer {
+    cipher: Box<dyn IXCipherCore>,
+    key_len: usize,
+}
 
 pub struct IXCipherMultiplexer {
-    ciphers: Vec<Box<dyn IXCipherCore>>,
+    keying: LayerKeying,
+    layers: Vec<Layer>,
 }
 
 impl IXCipherMultiplexer {
+    /// v1 multiplexer that splits the key between layers.
     pub fn new() -> Self {
+        Self::with_keying(LayerKeying::Split)
+    }
+
+    /// v2 multiplexer with independently derived layer keys.
+    pub fn new_derived() -> Self {
+        Self::with_keying(LayerKeying::Derived)
+    }
+
+    pub fn with_keying(keying: LayerKeying) -> Self {
         Self {
-            ciphers: Vec::new(),
+            keying,
+            layers: Vec::new(),
         }
     }
 
+    pub fn keying(&self) -> LayerKeying {
+        self.keying
+    }
+
     /// Add a cipher implementation to the multiplexer
     pub fn add_cipher(&mut self, cipher: Box<dyn IXCipherCore>) {
-        self.ciphers.push(cipher);
+        self.add_cipher_with_key_len(cipher, DEFAULT_LAYER_KEY_LEN);
+    }
+
+    /// Add a cipher together with its native key length, used by `LayerKeying::Derived`.
+    pub fn add_cipher_with_key_len(&mut self, cipher: Box<dyn IXCipherCore>, key_len: usize) {
+        self.layers.push(Layer { cipher, key_len });
     }
 
     /// Trigger lockdown on all ciphers
     pub fn trigger_lockdown_all(&self) -> bool {
-        for cipher in &self.ciphers {
-            if cipher.trigger_lockdown() {
+        for layer in &self.layers {
+            if layer.cipher.trigger_lockdown() {
                 return true;
             }
         }
@@ -34,42 +79,63 @@ impl IXCipherMultiplexer {
 
 impl IXCipherCore for IXCipherMultiplexer {
     fn initialize(&mut self, key: &[u8], salt: Option<&[u8]>) {
-        // Split key per cipher equally for initialization
-        let part_len = key.len() / self.ciphers.len().max(1);
-        for (i, cipher) in self.ciphers.iter_mut().enumerate() {
-            let start = i * part_len;
-            let end = start + part_len;
-            let part_key = &key[start..end.min(key.len())];
-            cipher.initialize(part_key, salt);
+        match self.keying {
+            LayerKeying::Split => {
+                // Split key per cipher equally for initialization
+                let part_len = key.len() / self.layers.len().max(1);
+                for (i, layer) in self.layers.iter_mut().enumerate() {
+                    let start = i * part_len;
+                    let end = start + part_len;
+                    layer.cipher.initialize(&key[start..end.min(key.len())], salt);
+                }
+            }
+            LayerKeying::Derived => {
+                let tree = KeyHierarchy::from_master(key).child("multiplexer");
+                for (i, layer) in self.layers.iter_mut().enumerate() {
+                    let label = format!("layer:{}:{}", i, layer.cipher.algorithm_id());
+                    let mut layer_key = tree
+                        .purpose_key(&label, layer.key_len)
+                        .expect("Layer key length within HKDF limits");
+                    layer.cipher.initialize(&layer_key, salt);
+                    layer_key.zeroize();
+                }
+            }
         }
     }
 
     fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
         // Layered encryption: encrypt through each cipher in order
         let mut data = plaintext.to_vec();
-        for cipher in &self.ciphers {
-            data = cipher.encrypt(&data);
+        for layer in &self.layers {
+            data = layer.cipher.encrypt(&data);
         }
         data
     }
 
     fn decrypt(&self, ciphertext: &[u8]) -> Vec<u8> {
+        self.try_decrypt(ciphertext).expect("Decryption failed")
+    }
+
+    fn try_decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
         // Reverse layered decryption
         let mut data = ciphertext.to_vec();
-        for cipher in self.ciphers.iter().rev() {
-            data = cipher.decrypt(&data);
+        for layer in self.layers.iter().rev() {
+            data = layer.cipher.try_decrypt(&data)?;
         }
-        data
+        Ok(data)
     }
 
     fn wipe(&mut self) {
-        for cipher in &mut self.ciphers {
-            cipher.wipe();
+        for layer in &mut self.layers {
+            layer.cipher.wipe();
         }
     }
 
     fn algorithm_id(&self) -> &'static str {
-        "IX-Multiplexer-v1"
+        match self.keying {
+            LayerKeying::Split => "IX-Multiplexer-v1",
+            LayerKeying::Derived => "IX-Multiplexer-v2",
+        }
     }
 
     fn trigger_lockdown(&self) -> bool {
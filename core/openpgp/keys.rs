@@ -0,0 +1,301 @@
+// ix-encryption/core/openpgp/keys.rs
+
+//! Version 6 OpenPGP encryption keys (RFC 9580 §5.5): X25519 (algorithm 25) and the draft
+//! ML-KEM-768+X25519 composite (algorithm 35, draft-ietf-openpgp-pqc) backed by `LatticeKEM`.
+//! The composite is only built with the `ml_kem` feature, for a `LatticeKEM` that is a real
+//! FIPS 203 ML-KEM-768; the placeholder backend's keys and ciphertexts have the wrong sizes.
+//! Without it, algorithm 35 keys are skipped as unsupported.
+//!
+//! Key packets are read out of certificates and transferable secret keys without checking
+//! self-signatures; callers must authenticate certificates by other means. Keys written here are
+//! bare key packets without binding signatures.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+use sha2::{Digest, Sha256};
+use x25519_dalek::{PublicKey as X25519Public, StaticSecret};
+use zeroize::Zeroize;
+use crate::core::openpgp::packet::{
+    parse_packets, write_packet, TAG_PUBLIC_KEY, TAG_PUBLIC_SUBKEY, TAG_SECRET_KEY, TAG_SECRET_SUBKEY,
+};
+#[cfg(feature = "ml_kem")]
+use crate::core::postquantum::lattice_kem::LatticeKEM;
+
+pub const ALGO_X25519: u8 = 25;
+pub const ALGO_ML_KEM_768_X25519: u8 = 35;
+
+pub const ML_KEM_768_PUBLIC_LEN: usize = 1184;
+pub const ML_KEM_768_CIPHERTEXT_LEN: usize = 1088;
+
+const KEY_VERSION: u8 = 6;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PublicKeyMaterial {
+    X25519([u8; 32]),
+    #[cfg(feature = "ml_kem")]
+    MlKem768X25519 { x25519: [u8; 32], ml_kem: Vec<u8> },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicKey {
+    pub created: u32,
+    pub material: PublicKeyMaterial,
+}
+
+pub enum SecretKeyMaterial {
+    X25519([u8; 32]),
+    #[cfg(feature = "ml_kem")]
+    MlKem768X25519 { x25519: [u8; 32], ml_kem: LatticeKEM },
+}
+
+pub struct SecretKey {
+    pub created: u32,
+    pub material: SecretKeyMaterial,
+}
+
+fn now() -> u32 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as u32).unwrap_or(0)
+}
+
+fn random_x25519() -> Result<[u8; 32], &'static str> {
+    let mut secret = [0u8; 32];
+    getrandom::getrandom(&mut secret).map_err(|_| "Key generation failed")?;
+    Ok(secret)
+}
+
+impl PublicKey {
+    pub fn algorithm(&self) -> u8 {
+        match self.material {
+            PublicKeyMaterial::X25519(_) => ALGO_X25519,
+            #[cfg(feature = "ml_kem")]
+            PublicKeyMaterial::MlKem768X25519 { .. } => ALGO_ML_KEM_768_X25519,
+        }
+    }
+
+    /// Version, creation time, algorithm, material length and material.
+    pub fn to_body(&self) -> Vec<u8> {
+        let material = match &self.material {
+            PublicKeyMaterial::X25519(x25519) => x25519.to_vec(),
+            #[cfg(feature = "ml_kem")]
+            PublicKeyMaterial::MlKem768X25519 { x25519, ml_kem } => [&x25519[..], ml_kem].concat(),
+        };
+        let mut body = vec![KEY_VERSION];
+        body.extend_from_slice(&self.created.to_be_bytes());
+        body.push(self.algorithm());
+        body.extend_from_slice(&(material.len() as u32).to_be_bytes());
+        body.extend_from_slice(&material);
+        body
+    }
+
+    /// Parse the public part of a key packet body, returning the key and the bytes consumed.
+    pub fn parse(body: &[u8]) -> Result<(Self, usize), &'static str> {
+        if body.len() < 10 {
+            return Err("Truncated key packet");
+        }
+        if body[0] != KEY_VERSION {
+            return Err("Unsupported key packet version");
+        }
+        let created = u32::from_be_bytes(body[1..5].try_into().unwrap());
+        let material_len = u32::from_be_bytes(body[6..10].try_into().unwrap()) as usize;
+        let material = body.get(10..10 + material_len).ok_or("Truncated key material")?;
+
+        let material = match (body[5], material.len()) {
+            (ALGO_X25519, 32) => PublicKeyMaterial::X25519(material.try_into().unwrap()),
+            #[cfg(feature = "ml_kem")]
+            (ALGO_ML_KEM_768_X25519, len) if len == 32 + ML_KEM_768_PUBLIC_LEN => PublicKeyMaterial::MlKem768X25519 {
+                x25519: material[..32].try_into().unwrap(),
+                ml_kem: material[32..].to_vec(),
+            },
+            #[cfg(feature = "ml_kem")]
+            (ALGO_ML_KEM_768_X25519, _) => return Err("Invalid key material length"),
+            (ALGO_X25519, _) => return Err("Invalid key material length"),
+            _ => return Err("Unsupported public key algorithm"),
+        };
+        Ok((Self { created, material }, 10 + material_len))
+    }
+
+    /// v6 fingerprint: SHA-256 over 0x9B, a four-octet length and the key body.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        let body = self.to_body();
+        let mut hasher = Sha256::new();
+        hasher.update([0x9b]);
+        hasher.update((body.len() as u32).to_be_bytes());
+        hasher.update(&body);
+        hasher.finalize().into()
+    }
+
+    pub fn to_packet(&self, subkey: bool) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_packet(&mut out, if subkey { TAG_PUBLIC_SUBKEY } else { TAG_PUBLIC_KEY }, &self.to_body());
+        out
+    }
+}
+
+impl SecretKey {
+    pub fn generate_x25519() -> Result<Self, &'static str> {
+        Ok(Self { created: now(), material: SecretKeyMaterial::X25519(random_x25519()?) })
+    }
+
+    #[cfg(feature = "ml_kem")]
+    pub fn generate_ml_kem_768_x25519() -> Result<Self, &'static str> {
+        let ml_kem = LatticeKEM::keypair();
+        if ml_kem.public_key.len() != ML_KEM_768_PUBLIC_LEN {
+            return Err("LatticeKEM does not produce ML-KEM-768 sized keys");
+        }
+        Ok(Self {
+            created: now(),
+            material: SecretKeyMaterial::MlKem768X25519 { x25519: random_x25519()?, ml_kem },
+        })
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        let x25519_public = |secret: &[u8; 32]| X25519Public::from(&StaticSecret::from(*secret)).to_bytes();
+        let material = match &self.material {
+            SecretKeyMaterial::X25519(secret) => PublicKeyMaterial::X25519(x25519_public(secret)),
+            #[cfg(feature = "ml_kem")]
+            SecretKeyMaterial::MlKem768X25519 { x25519, ml_kem } => PublicKeyMaterial::MlKem768X25519 {
+                x25519: x25519_public(x25519),
+                ml_kem: ml_kem.public_key.clone(),
+            },
+        };
+        PublicKey { created: self.created, material }
+    }
+
+    pub fn fingerprint(&self) -> [u8; 32] {
+        self.public_key().fingerprint()
+    }
+
+    /// Unprotected secret key packet (S2K usage 0). For the composite algorithm the secret
+    /// material is the X25519 scalar followed by the `LatticeKEM` secret key.
+    pub fn to_packet(&self, subkey: bool) -> Vec<u8> {
+        let mut body = self.public_key().to_body();
+        body.push(0);
+        match &self.material {
+            SecretKeyMaterial::X25519(secret) => body.extend_from_slice(secret),
+            #[cfg(feature = "ml_kem")]
+            SecretKeyMaterial::MlKem768X25519 { x25519, ml_kem } => {
+                body.extend_from_slice(x25519);
+                body.extend_from_slice(&ml_kem.secret_key);
+            }
+        }
+        let mut out = Vec::new();
+        write_packet(&mut out, if subkey { TAG_SECRET_SUBKEY } else { TAG_SECRET_KEY }, &body);
+        body.zeroize();
+        out
+    }
+
+    pub fn parse(body: &[u8]) -> Result<Self, &'static str> {
+        let (public, offset) = PublicKey::parse(body)?;
+        match body.get(offset) {
+            Some(0) => {}
+            Some(_) => return Err("Protected secret keys are not supported"),
+            None => return Err("Truncated secret key packet"),
+        }
+        let secret = &body[offset + 1..];
+        if secret.len() < 32 {
+            return Err("Truncated secret key material");
+        }
+        let x25519: [u8; 32] = secret[..32].try_into().unwrap();
+
+        let key = match public.material {
+            PublicKeyMaterial::X25519(_) if secret.len() == 32 => Self { created: public.created, material: SecretKeyMaterial::X25519(x25519) },
+            #[cfg(feature = "ml_kem")]
+            PublicKeyMaterial::MlKem768X25519 { ml_kem, .. } => Self {
+                created: public.created,
+                material: SecretKeyMaterial::MlKem768X25519 {
+                    x25519,
+                    ml_kem: LatticeKEM { public_key: ml_kem, secret_key: secret[32..].to_vec() },
+                },
+            },
+            _ => return Err("Invalid secret key material length"),
+        };
+        if key.public_key().to_body() != body[..offset] {
+            return Err("Secret key does not match its public key");
+        }
+        Ok(key)
+    }
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        match &mut self.material {
+            SecretKeyMaterial::X25519(secret) => secret.zeroize(),
+            #[cfg(feature = "ml_kem")]
+            SecretKeyMaterial::MlKem768X25519 { x25519, ml_kem } => {
+                x25519.zeroize();
+                ml_kem.secret_key.zeroize();
+            }
+        }
+    }
+}
+
+/// Collect the supported v6 encryption keys from a certificate, skipping other key packets.
+pub fn read_public_keys(data: &[u8]) -> Result<Vec<PublicKey>, &'static str> {
+    let mut keys = Vec::new();
+    for packet in parse_packets(data)? {
+        if matches!(packet.tag, TAG_PUBLIC_KEY | TAG_PUBLIC_SUBKEY | TAG_SECRET_KEY | TAG_SECRET_SUBKEY) {
+            if let Ok((key, _)) = PublicKey::parse(&packet.body) {
+                keys.push(key);
+            }
+        }
+    }
+    Ok(keys)
+}
+
+fn is_supported_algorithm(algorithm: u8) -> bool {
+    match algorithm {
+        ALGO_X25519 => true,
+        #[cfg(feature = "ml_kem")]
+        ALGO_ML_KEM_768_X25519 => true,
+        _ => false,
+    }
+}
+
+/// Collect the supported unprotected v6 encryption keys from a transferable secret key.
+/// Other versions and algorithms, and passphrase-protected keys, are skipped.
+pub fn read_secret_keys(data: &[u8]) -> Result<Vec<SecretKey>, &'static str> {
+    let mut keys = Vec::new();
+    for packet in parse_packets(data)? {
+        let body = &packet.body;
+        let supported = body.first() == Some(&KEY_VERSION) && body.get(5).copied().is_some_and(is_supported_algorithm);
+        if !matches!(packet.tag, TAG_SECRET_KEY | TAG_SECRET_SUBKEY) || !supported {
+            continue;
+        }
+        let (_, offset) = PublicKey::parse(body)?;
+        if body.get(offset).is_some_and(|&usage| usage != 0) {
+            continue;
+        }
+        keys.push(SecretKey::parse(body)?);
+    }
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn secret_key_roundtrip() {
+        let key = SecretKey::generate_x25519().unwrap();
+        let keys = read_secret_keys(&key.to_packet(true)).unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].fingerprint(), key.fingerprint());
+        assert_eq!(read_public_keys(&key.public_key().to_packet(true)).unwrap()[0].fingerprint(), key.fingerprint());
+    }
+
+    #[test]
+    fn skips_protected_secret_keys() {
+        let protected = SecretKey::generate_x25519().unwrap();
+        let mut body = protected.public_key().to_body();
+        body.push(254);
+        body.extend_from_slice(&[0; 48]);
+        let mut data = Vec::new();
+        write_packet(&mut data, TAG_SECRET_KEY, &body);
+        let plain = SecretKey::generate_x25519().unwrap();
+        data.extend_from_slice(&plain.to_packet(true));
+
+        let keys = read_secret_keys(&data).unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].fingerprint(), plain.fingerprint());
+        assert!(SecretKey::parse(&body).is_err());
+    }
+}
@@ -0,0 +1,756 @@
+// ix-encryption/core/openpgp/message.rs
+
+//! OpenPGP message encryption (RFC 9580): v6 PKESK and SKESK session key packets, v2 SEIPD
+//! chunked AEAD encryption with AES-256, and literal and compressed data packets.
+//!
+//! PKESK recipients are X25519 or, with the `ml_kem` feature, the draft ML-KEM-768+X25519
+//! composite; SKESK recipients use
+//! Argon2 S2K. Legacy v4 SKESK and v1 SEIPD (CFB with MDC) are read but never written, so that
+//! `gpg --symmetric` output can be decrypted. Signature, one-pass signature, marker and padding
+//! packets are skipped without verification.
+
+use std::io::{Read, Write};
+use flate2::read::{DeflateDecoder, ZlibDecoder};
+use flate2::write::{DeflateEncoder, ZlibEncoder};
+use sha1::{Digest, Sha1};
+#[cfg(feature = "ml_kem")]
+use sha3::Sha3_256;
+use subtle::ConstantTimeEq;
+use x25519_dalek::{EphemeralSecret, PublicKey as X25519Public, StaticSecret};
+use zeroize::Zeroize;
+use crate::core::ciphers::aes::AesCipher;
+use crate::core::kdf::hkdf::{hkdf, HkdfHash};
+use crate::core::key_wrap::KeyWrap;
+use crate::core::mode_cfb::CFBMode;
+use crate::core::mode_eax::EAXMode;
+use crate::core::mode_gcm::GCMMode;
+use crate::core::mode_ocb::OCBMode;
+use crate::core::openpgp::keys::{PublicKey, PublicKeyMaterial, SecretKey, SecretKeyMaterial, ALGO_X25519};
+#[cfg(feature = "ml_kem")]
+use crate::core::openpgp::keys::{ALGO_ML_KEM_768_X25519, ML_KEM_768_CIPHERTEXT_LEN};
+use crate::core::openpgp::packet::{
+    parse_packets, write_packet, Packet, S2k, TAG_COMPRESSED, TAG_LITERAL, TAG_MARKER, TAG_MDC,
+    TAG_ONE_PASS_SIGNATURE, TAG_PADDING, TAG_PKESK, TAG_SEIPD, TAG_SIGNATURE, TAG_SKESK,
+};
+#[cfg(feature = "ml_kem")]
+use crate::core::postquantum::lattice_kem::LatticeKEM;
+
+// Symmetric algorithm IDs
+pub const CIPHER_AES128: u8 = 7;
+pub const CIPHER_AES192: u8 = 8;
+pub const CIPHER_AES256: u8 = 9;
+
+pub const AEAD_TAG_LEN: usize = 16;
+/// Chunk size octet `c` gives 2^(c+6)-byte chunks; 10 is 64 KiB.
+pub const DEFAULT_CHUNK_SIZE_OCTET: u8 = 10;
+const MAX_CHUNK_SIZE_OCTET: u8 = 16;
+const MAX_DECOMPRESSED_LEN: usize = 1 << 30;
+const MAX_COMPRESSION_DEPTH: u8 = 4;
+
+const X25519_KEK_INFO: &[u8] = b"OpenPGP X25519";
+#[cfg(feature = "ml_kem")]
+const COMPOSITE_DOMAIN: &[u8] = b"OpenPGPCompositeKDFv1";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AeadAlgorithm {
+    Eax,
+    Ocb,
+    Gcm,
+}
+
+impl AeadAlgorithm {
+    pub fn id(self) -> u8 {
+        match self {
+            AeadAlgorithm::Eax => 1,
+            AeadAlgorithm::Ocb => 2,
+            AeadAlgorithm::Gcm => 3,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Result<Self, &'static str> {
+        match id {
+            1 => Ok(AeadAlgorithm::Eax),
+            2 => Ok(AeadAlgorithm::Ocb),
+            3 => Ok(AeadAlgorithm::Gcm),
+            _ => Err("Unsupported OpenPGP AEAD algorithm"),
+        }
+    }
+
+    pub fn nonce_len(self) -> usize {
+        match self {
+            AeadAlgorithm::Eax => 16,
+            AeadAlgorithm::Ocb => 15,
+            AeadAlgorithm::Gcm => 12,
+        }
+    }
+
+    fn seal(self, cipher: &AesCipher, nonce: &[u8], aad: &[u8], plaintext: &[u8]) -> Vec<u8> {
+        let (mut ciphertext, tag) = match self {
+            AeadAlgorithm::Eax => EAXMode::new(cipher, nonce.to_vec(), aad.to_vec()).encrypt_and_tag(plaintext),
+            AeadAlgorithm::Ocb => OCBMode::new(cipher, nonce.to_vec(), aad.to_vec()).encrypt_and_tag(plaintext),
+            AeadAlgorithm::Gcm => GCMMode::new(cipher, nonce.to_vec(), aad.to_vec()).encrypt_and_tag(plaintext),
+        };
+        ciphertext.extend_from_slice(&tag);
+        ciphertext
+    }
+
+    /// `sealed` is ciphertext followed by the tag; `None` if authentication fails.
+    fn open(self, cipher: &AesCipher, nonce: &[u8], aad: &[u8], sealed: &[u8]) -> Option<Vec<u8>> {
+        let split = sealed.len().checked_sub(AEAD_TAG_LEN)?;
+        let (ciphertext, tag) = sealed.split_at(split);
+        match self {
+            AeadAlgorithm::Eax => EAXMode::new(cipher, nonce.to_vec(), aad.to_vec()).decrypt_and_verify(ciphertext, tag),
+            AeadAlgorithm::Ocb => OCBMode::new(cipher, nonce.to_vec(), aad.to_vec()).decrypt_and_verify(ciphertext, tag),
+            AeadAlgorithm::Gcm => GCMMode::new(cipher, nonce.to_vec(), aad.to_vec()).decrypt_and_verify(ciphertext, tag),
+        }
+    }
+}
+
+/// Compression applied inside the encrypted data; `Uncompressed` writes no Compressed Data packet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    Uncompressed,
+    Zip,
+    Zlib,
+}
+
+pub struct EncryptOptions {
+    pub aead: AeadAlgorithm,
+    pub chunk_size_octet: u8,
+    pub compression: Compression,
+}
+
+impl Default for EncryptOptions {
+    fn default() -> Self {
+        Self { aead: AeadAlgorithm::Ocb, chunk_size_octet: DEFAULT_CHUNK_SIZE_OCTET, compression: Compression::Uncompressed }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LiteralData {
+    /// `b'b'` binary, `b'u'` UTF-8 text or `b't'` text.
+    pub format: u8,
+    pub file_name: Vec<u8>,
+    pub date: u32,
+    pub data: Vec<u8>,
+}
+
+impl LiteralData {
+    pub fn binary(data: &[u8]) -> Self {
+        Self { format: b'b', file_name: Vec::new(), date: 0, data: data.to_vec() }
+    }
+
+    fn encode(&self) -> Result<Vec<u8>, &'static str> {
+        if self.file_name.len() > 255 {
+            return Err("Literal file name too long");
+        }
+        let mut body = vec![self.format, self.file_name.len() as u8];
+        body.extend_from_slice(&self.file_name);
+        body.extend_from_slice(&self.date.to_be_bytes());
+        body.extend_from_slice(&self.data);
+        Ok(body)
+    }
+
+    fn parse(body: &[u8]) -> Result<Self, &'static str> {
+        let name_len = *body.get(1).ok_or("Truncated literal data packet")? as usize;
+        let date = body.get(2 + name_len..6 + name_len).ok_or("Truncated literal data packet")?;
+        Ok(Self {
+            format: body[0],
+            file_name: body[2..2 + name_len].to_vec(),
+            date: u32::from_be_bytes(date.try_into().unwrap()),
+            data: body[6 + name_len..].to_vec(),
+        })
+    }
+}
+
+pub enum Recipient<'a> {
+    /// PKESK naming the recipient key by fingerprint.
+    Key(&'a PublicKey),
+    /// PKESK with the recipient key identity omitted.
+    AnonymousKey(&'a PublicKey),
+    Passphrase(&'a [u8]),
+}
+
+pub enum Decryptor<'a> {
+    Keys(&'a [SecretKey]),
+    Passphrase(&'a [u8]),
+}
+
+/// A decrypted session key; the cipher is only known for legacy v4 SKESK packets.
+struct SessionKey {
+    cipher: Option<u8>,
+    key: Vec<u8>,
+}
+
+impl Drop for SessionKey {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+fn key_len(cipher: u8) -> Result<usize, &'static str> {
+    match cipher {
+        CIPHER_AES128 => Ok(16),
+        CIPHER_AES192 => Ok(24),
+        CIPHER_AES256 => Ok(32),
+        _ => Err("Unsupported OpenPGP symmetric cipher"),
+    }
+}
+
+fn random<const N: usize>() -> Result<[u8; N], &'static str> {
+    let mut bytes = [0u8; N];
+    getrandom::getrandom(&mut bytes).map_err(|_| "Random generation failed")?;
+    Ok(bytes)
+}
+
+fn wrap(kek: &[u8], key: &[u8]) -> Result<Vec<u8>, &'static str> {
+    KeyWrap::new(&AesCipher::new(kek)?).wrap(key)
+}
+
+fn unwrap(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>, &'static str> {
+    KeyWrap::new(&AesCipher::new(kek)?).unwrap(wrapped)
+}
+
+/// Encrypt `literal` to every recipient. The session key is always AES-256.
+pub fn encrypt(recipients: &[Recipient], literal: &LiteralData, options: &EncryptOptions) -> Result<Vec<u8>, &'static str> {
+    if recipients.is_empty() {
+        return Err("At least one recipient is required");
+    }
+    if options.chunk_size_octet > MAX_CHUNK_SIZE_OCTET {
+        return Err("Chunk size octet must be at most 16");
+    }
+
+    let mut session_key: [u8; 32] = random()?;
+    let result = encrypt_with_session_key(recipients, literal, options, &session_key);
+    session_key.zeroize();
+    result
+}
+
+fn encrypt_with_session_key(
+    recipients: &[Recipient],
+    literal: &LiteralData,
+    options: &EncryptOptions,
+    session_key: &[u8],
+) -> Result<Vec<u8>, &'static str> {
+    let mut out = Vec::new();
+    for recipient in recipients {
+        match recipient {
+            Recipient::Key(key) => write_packet(&mut out, TAG_PKESK, &pkesk(key, true, session_key)?),
+            Recipient::AnonymousKey(key) => write_packet(&mut out, TAG_PKESK, &pkesk(key, false, session_key)?),
+            Recipient::Passphrase(passphrase) => write_packet(&mut out, TAG_SKESK, &skesk(passphrase, options.aead, session_key)?),
+        }
+    }
+
+    let mut inner = Vec::new();
+    write_packet(&mut inner, TAG_LITERAL, &literal.encode()?);
+    if options.compression != Compression::Uncompressed {
+        let compressed = compress(options.compression, &inner)?;
+        inner.clear();
+        write_packet(&mut inner, TAG_COMPRESSED, &compressed);
+    }
+
+    write_packet(&mut out, TAG_SEIPD, &seipd_encrypt(session_key, options, &inner)?);
+    Ok(out)
+}
+
+/// Decrypt a binary OpenPGP message and return its literal data.
+pub fn decrypt(decryptor: &Decryptor, message: &[u8]) -> Result<LiteralData, &'static str> {
+    let packets = parse_packets(message)?;
+    let (seipd, session_packets) = packets.split_last().ok_or("Empty OpenPGP message")?;
+    if seipd.tag != TAG_SEIPD {
+        return Err("OpenPGP message must end with an encrypted data packet");
+    }
+
+    // A session key packet that fails to open may just be addressed to someone else, so keep
+    // trying and report the first error only once no packet opens.
+    let mut session_key = None;
+    let mut first_error = None;
+    for packet in session_packets {
+        match packet.tag {
+            TAG_PKESK | TAG_SKESK if session_key.is_none() => match open_session_packet(decryptor, packet) {
+                Ok(opened) => session_key = opened,
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            },
+            TAG_PKESK | TAG_SKESK | TAG_MARKER | TAG_PADDING => {}
+            _ => return Err("Unexpected packet before encrypted data"),
+        }
+    }
+    let session_key = session_key.ok_or(first_error.unwrap_or("No session key packet matches the decryptor"))?;
+
+    let mut inner = seipd_decrypt(&session_key, &seipd.body)?;
+    let literal = read_literal(&inner, 0);
+    inner.zeroize();
+    literal
+}
+
+fn open_session_packet(decryptor: &Decryptor, packet: &Packet) -> Result<Option<SessionKey>, &'static str> {
+    match (decryptor, packet.tag) {
+        (Decryptor::Keys(keys), TAG_PKESK) => Ok(open_pkesk(keys, &packet.body)?.map(|key| SessionKey { cipher: None, key })),
+        (Decryptor::Passphrase(passphrase), TAG_SKESK) => open_skesk(passphrase, &packet.body),
+        _ => Ok(None),
+    }
+}
+
+fn read_literal(data: &[u8], depth: u8) -> Result<LiteralData, &'static str> {
+    let mut literal = None;
+    for packet in parse_packets(data)? {
+        match packet.tag {
+            TAG_LITERAL if literal.is_none() => literal = Some(LiteralData::parse(&packet.body)?),
+            TAG_COMPRESSED if literal.is_none() && depth < MAX_COMPRESSION_DEPTH => {
+                literal = Some(read_literal(&decompress(&packet.body)?, depth + 1)?);
+            }
+            TAG_SIGNATURE | TAG_ONE_PASS_SIGNATURE | TAG_MARKER | TAG_PADDING => {}
+            _ => return Err("Unexpected packet in encrypted data"),
+        }
+    }
+    literal.ok_or("No literal data in OpenPGP message")
+}
+
+fn compress(compression: Compression, data: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let result = match compression {
+        Compression::Uncompressed => Ok([&[0][..], data].concat()),
+        Compression::Zip => {
+            let mut encoder = DeflateEncoder::new(vec![1], flate2::Compression::default());
+            encoder.write_all(data).and_then(|_| encoder.finish())
+        }
+        Compression::Zlib => {
+            let mut encoder = ZlibEncoder::new(vec![2], flate2::Compression::default());
+            encoder.write_all(data).and_then(|_| encoder.finish())
+        }
+    };
+    result.map_err(|_| "Compression failed")
+}
+
+fn decompress(body: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let (&algorithm, data) = body.split_first().ok_or("Truncated compressed data packet")?;
+    let limit = MAX_DECOMPRESSED_LEN as u64 + 1;
+    let mut out = Vec::new();
+    let result = match algorithm {
+        0 => return Ok(data.to_vec()),
+        1 => DeflateDecoder::new(data).take(limit).read_to_end(&mut out),
+        2 => ZlibDecoder::new(data).take(limit).read_to_end(&mut out),
+        _ => return Err("Unsupported compression algorithm"),
+    };
+    result.map_err(|_| "Invalid compressed data")?;
+    if out.len() > MAX_DECOMPRESSED_LEN {
+        return Err("Decompressed data too large");
+    }
+    Ok(out)
+}
+
+// ---- Public-key encrypted session keys ----
+
+fn x25519_kek(ephemeral: &[u8], recipient: &[u8], shared: &[u8]) -> Result<Vec<u8>, &'static str> {
+    hkdf(HkdfHash::Sha256, None, &[ephemeral, recipient, shared].concat(), X25519_KEK_INFO, 16)
+}
+
+/// Composite KEK: SHA3-256 over both key shares, the X25519 transcript, the algorithm ID and the
+/// length-suffixed domain separator.
+#[cfg(feature = "ml_kem")]
+fn composite_kek(ml_kem_shared: &[u8], x25519_shared: &[u8], ephemeral: &[u8], recipient: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha3_256::new();
+    for part in [ml_kem_shared, x25519_shared, ephemeral, recipient, &[ALGO_ML_KEM_768_X25519], COMPOSITE_DOMAIN] {
+        hasher.update(part);
+    }
+    hasher.update([COMPOSITE_DOMAIN.len() as u8]);
+    hasher.finalize().to_vec()
+}
+
+fn x25519_share(recipient: &[u8; 32]) -> Result<([u8; 32], [u8; 32]), &'static str> {
+    let ephemeral = EphemeralSecret::random_from_rng(rand::rngs::OsRng);
+    let share = X25519Public::from(&ephemeral).to_bytes();
+    let shared = ephemeral.diffie_hellman(&X25519Public::from(*recipient));
+    if !shared.was_contributory() {
+        return Err("Low-order X25519 recipient");
+    }
+    Ok((share, shared.to_bytes()))
+}
+
+fn x25519_shared(secret: &[u8; 32], share: &[u8]) -> Result<[u8; 32], &'static str> {
+    let share: [u8; 32] = share.try_into().map_err(|_| "Invalid X25519 share")?;
+    let shared = StaticSecret::from(*secret).diffie_hellman(&X25519Public::from(share));
+    if !shared.was_contributory() {
+        return Err("Low-order X25519 share");
+    }
+    Ok(shared.to_bytes())
+}
+
+/// v6 PKESK: the wrapped session key carries neither an algorithm octet nor a checksum.
+fn pkesk(key: &PublicKey, identify: bool, session_key: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let mut body = vec![6];
+    if identify {
+        body.extend_from_slice(&[33, 6]);
+        body.extend_from_slice(&key.fingerprint());
+    } else {
+        body.push(0);
+    }
+    body.push(key.algorithm());
+
+    match &key.material {
+        PublicKeyMaterial::X25519(recipient) => {
+            let (share, mut shared) = x25519_share(recipient)?;
+            let kek = x25519_kek(&share, recipient, &shared);
+            shared.zeroize();
+            let mut kek = kek?;
+            let wrapped = wrap(&kek, session_key);
+            kek.zeroize();
+            let wrapped = wrapped?;
+
+            body.extend_from_slice(&share);
+            body.push(wrapped.len() as u8);
+            body.extend_from_slice(&wrapped);
+        }
+        #[cfg(feature = "ml_kem")]
+        PublicKeyMaterial::MlKem768X25519 { x25519, ml_kem } => {
+            let (share, mut shared) = x25519_share(x25519)?;
+            let (kem_ciphertext, mut kem_shared) = LatticeKEM::keypair().encapsulate(ml_kem);
+            let mut kek = composite_kek(&kem_shared, &shared, &share, x25519);
+            shared.zeroize();
+            kem_shared.zeroize();
+            let wrapped = wrap(&kek, session_key);
+            kek.zeroize();
+            let wrapped = wrapped?;
+            if kem_ciphertext.len() != ML_KEM_768_CIPHERTEXT_LEN {
+                return Err("LatticeKEM does not produce ML-KEM-768 sized ciphertexts");
+            }
+
+            body.extend_from_slice(&share);
+            body.extend_from_slice(&kem_ciphertext);
+            body.push(wrapped.len() as u8);
+            body.extend_from_slice(&wrapped);
+        }
+    }
+    Ok(body)
+}
+
+/// Session key from the first v6 PKESK addressed to one of `keys`; other versions are skipped.
+fn open_pkesk(keys: &[SecretKey], body: &[u8]) -> Result<Option<Vec<u8>>, &'static str> {
+    if body.first() != Some(&6) {
+        return Ok(None);
+    }
+    let id_len = *body.get(1).ok_or("Truncated PKESK packet")? as usize;
+    let fingerprint = match id_len {
+        0 => None,
+        33 if body.get(2) == Some(&6) => Some(body.get(3..35).ok_or("Truncated PKESK packet")?),
+        _ => return Ok(None),
+    };
+    let algorithm = *body.get(2 + id_len).ok_or("Truncated PKESK packet")?;
+    let fields = &body[3 + id_len..];
+
+    let candidates = keys.iter().filter(|key| {
+        key.public_key().algorithm() == algorithm && fingerprint.is_none_or(|fp| key.fingerprint()[..] == *fp)
+    });
+    for key in candidates {
+        // An anonymous PKESK is tried against every key, so unwrap failures are not fatal.
+        match decrypt_pkesk_fields(key, fields) {
+            Ok(session_key) => return Ok(Some(session_key)),
+            Err(e) if fingerprint.is_some() => return Err(e),
+            Err(_) => {}
+        }
+    }
+    Ok(None)
+}
+
+fn decrypt_pkesk_fields(key: &SecretKey, fields: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let wrapped_at = |offset: usize| -> Result<&[u8], &'static str> {
+        let len = *fields.get(offset).ok_or("Truncated PKESK packet")? as usize;
+        match fields.len() == offset + 1 + len {
+            true => Ok(&fields[offset + 1..]),
+            false => Err("Invalid PKESK wrapped key length"),
+        }
+    };
+
+    match &key.material {
+        SecretKeyMaterial::X25519(secret) => {
+            let wrapped = wrapped_at(32)?;
+            let share = &fields[..32];
+            let mut shared = x25519_shared(secret, share)?;
+            let recipient = X25519Public::from(&StaticSecret::from(*secret)).to_bytes();
+            let kek = x25519_kek(share, &recipient, &shared);
+            shared.zeroize();
+            let mut kek = kek?;
+            let session_key = unwrap(&kek, wrapped);
+            kek.zeroize();
+            session_key
+        }
+        #[cfg(feature = "ml_kem")]
+        SecretKeyMaterial::MlKem768X25519 { x25519, ml_kem } => {
+            let wrapped = wrapped_at(32 + ML_KEM_768_CIPHERTEXT_LEN)?;
+            let share = &fields[..32];
+            let mut shared = x25519_shared(x25519, share)?;
+            let mut kem_shared = ml_kem.decapsulate(&fields[32..32 + ML_KEM_768_CIPHERTEXT_LEN]);
+            let recipient = X25519Public::from(&StaticSecret::from(*x25519)).to_bytes();
+            let mut kek = composite_kek(&kem_shared, &shared, share, &recipient);
+            shared.zeroize();
+            kem_shared.zeroize();
+            let session_key = unwrap(&kek, wrapped);
+            kek.zeroize();
+            session_key
+        }
+    }
+}
+
+// ---- Symmetric-key encrypted session keys ----
+
+/// v6 SKESK: the S2K output is expanded with HKDF into an AEAD key for the session key.
+fn skesk(passphrase: &[u8], aead: AeadAlgorithm, session_key: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let s2k = S2k::argon2()?;
+    let encoded_s2k = s2k.encode();
+    let mut iv = vec![0u8; aead.nonce_len()];
+    getrandom::getrandom(&mut iv).map_err(|_| "Random generation failed")?;
+    let info = skesk_info(CIPHER_AES256, aead);
+
+    let mut body = vec![6, (3 + encoded_s2k.len() + iv.len()) as u8, CIPHER_AES256, aead.id(), encoded_s2k.len() as u8];
+    body.extend_from_slice(&encoded_s2k);
+    body.extend_from_slice(&iv);
+
+    let kek = skesk_kek(passphrase, &s2k, &info, 32)?;
+    body.extend_from_slice(&aead.seal(&kek, &iv, &info, session_key));
+    Ok(body)
+}
+
+fn skesk_info(cipher: u8, aead: AeadAlgorithm) -> [u8; 4] {
+    [0xc3, 6, cipher, aead.id()]
+}
+
+fn skesk_kek(passphrase: &[u8], s2k: &S2k, info: &[u8], key_len: usize) -> Result<AesCipher, &'static str> {
+    let mut ikm = s2k.derive(passphrase, key_len)?;
+    let kek = hkdf(HkdfHash::Sha256, None, &ikm, info, key_len);
+    ikm.zeroize();
+    let mut kek = kek?;
+    let cipher = AesCipher::new(&kek);
+    kek.zeroize();
+    cipher
+}
+
+/// Session key from a v6 or legacy v4 SKESK; `None` if the passphrase does not open it.
+fn open_skesk(passphrase: &[u8], body: &[u8]) -> Result<Option<SessionKey>, &'static str> {
+    match body.first() {
+        Some(6) => {
+            let header = body.get(..5).ok_or("Truncated SKESK packet")?;
+            let (cipher, aead, s2k_len) = (header[2], AeadAlgorithm::from_id(header[3])?, header[4] as usize);
+            let iv_end = 5 + s2k_len + aead.nonce_len();
+            if header[1] as usize != iv_end - 2 || body.len() < iv_end {
+                return Err("Malformed SKESK packet");
+            }
+            let (s2k, consumed) = S2k::decode(&body[5..5 + s2k_len])?;
+            if consumed != s2k_len {
+                return Err("Malformed SKESK packet");
+            }
+
+            let key_len = key_len(cipher)?;
+            let info = skesk_info(cipher, aead);
+            let kek = skesk_kek(passphrase, &s2k, &info, key_len)?;
+            Ok(aead
+                .open(&kek, &body[5 + s2k_len..iv_end], &info, &body[iv_end..])
+                .filter(|key| key.len() == key_len)
+                .map(|key| SessionKey { cipher: None, key }))
+        }
+        Some(4) => {
+            let cipher = *body.get(1).ok_or("Truncated SKESK packet")?;
+            let (s2k, s2k_len) = S2k::decode(&body[2..])?;
+            let mut key = s2k.derive(passphrase, key_len(cipher)?)?;
+            let encrypted = &body[2 + s2k_len..];
+            if encrypted.is_empty() {
+                return Ok(Some(SessionKey { cipher: Some(cipher), key }));
+            }
+
+            // An encrypted session key is the cipher octet and key, CFB-encrypted with a zero IV.
+            let kek = AesCipher::new(&key);
+            key.zeroize();
+            let mut decrypted = CFBMode::full_block(&kek?, vec![0u8; 16]).decrypt(encrypted);
+            let session = SessionKey { cipher: Some(decrypted[0]), key: decrypted[1..].to_vec() };
+            decrypted.zeroize();
+            match key_len(session.cipher.unwrap_or_default()) {
+                Ok(len) if len == session.key.len() => Ok(Some(session)),
+                _ => Ok(None),
+            }
+        }
+        _ => Ok(None),
+    }
+}
+
+// ---- Symmetrically encrypted integrity protected data ----
+
+fn chunk_nonce(iv: &[u8], index: u64) -> Vec<u8> {
+    [iv, &index.to_be_bytes()].concat()
+}
+
+/// Message key and IV from HKDF-SHA256 over the session key, salted with the packet's salt.
+fn message_key(session_key: &[u8], salt: &[u8], info: &[u8], aead: AeadAlgorithm) -> Result<(AesCipher, Vec<u8>), &'static str> {
+    let mut okm = hkdf(HkdfHash::Sha256, Some(salt), session_key, info, session_key.len() + aead.nonce_len() - 8)?;
+    let cipher = AesCipher::new(&okm[..session_key.len()]);
+    let iv = okm[session_key.len()..].to_vec();
+    okm.zeroize();
+    Ok((cipher?, iv))
+}
+
+fn seipd_encrypt(session_key: &[u8], options: &EncryptOptions, plaintext: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let aead = options.aead;
+    let info = [0xd2, 2, CIPHER_AES256, aead.id(), options.chunk_size_octet];
+    let salt: [u8; 32] = random()?;
+    let (cipher, iv) = message_key(session_key, &salt, &info, aead)?;
+
+    let mut body = info[1..].to_vec();
+    body.extend_from_slice(&salt);
+    let mut index = 0u64;
+    for chunk in plaintext.chunks(1 << (options.chunk_size_octet + 6)) {
+        body.extend_from_slice(&aead.seal(&cipher, &chunk_nonce(&iv, index), &info, chunk));
+        index += 1;
+    }
+
+    // The final tag authenticates the total plaintext length, detecting truncation.
+    let final_aad = [&info[..], &(plaintext.len() as u64).to_be_bytes()].concat();
+    body.extend_from_slice(&aead.seal(&cipher, &chunk_nonce(&iv, index), &final_aad, &[]));
+    Ok(body)
+}
+
+fn seipd_decrypt(session_key: &SessionKey, body: &[u8]) -> Result<Vec<u8>, &'static str> {
+    match body.first() {
+        Some(2) => seipd_v2_decrypt(&session_key.key, body),
+        Some(1) => seipd_v1_decrypt(session_key, &body[1..]),
+        _ => Err("Unsupported encrypted data packet version"),
+    }
+}
+
+fn seipd_v2_decrypt(session_key: &[u8], body: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let header = body.get(..36).ok_or("Truncated encrypted data packet")?;
+    let (cipher, aead, chunk_size_octet) = (header[1], AeadAlgorithm::from_id(header[2])?, header[3]);
+    if key_len(cipher)? != session_key.len() {
+        return Err("Session key does not match the data cipher");
+    }
+    if chunk_size_octet > MAX_CHUNK_SIZE_OCTET {
+        return Err("Invalid chunk size octet");
+    }
+    let info = [0xd2, 2, cipher, aead.id(), chunk_size_octet];
+    let (cipher, iv) = message_key(session_key, &header[4..], &info, aead)?;
+
+    let data = &body[36..];
+    let split = data.len().checked_sub(AEAD_TAG_LEN).ok_or("Truncated encrypted data packet")?;
+    let (chunks, final_tag) = data.split_at(split);
+
+    let mut plaintext = Vec::with_capacity(chunks.len());
+    let mut index = 0u64;
+    for chunk in chunks.chunks((1 << (chunk_size_octet + 6)) + AEAD_TAG_LEN) {
+        match aead.open(&cipher, &chunk_nonce(&iv, index), &info, chunk) {
+            Some(decrypted) => plaintext.extend_from_slice(&decrypted),
+            None => {
+                plaintext.zeroize();
+                return Err("OpenPGP AEAD chunk authentication failed");
+            }
+        }
+        index += 1;
+    }
+
+    let final_aad = [&info[..], &(plaintext.len() as u64).to_be_bytes()].concat();
+    if aead.open(&cipher, &chunk_nonce(&iv, index), &final_aad, final_tag).is_none() {
+        plaintext.zeroize();
+        return Err("OpenPGP AEAD final tag authentication failed");
+    }
+    Ok(plaintext)
+}
+
+/// Legacy v1 SEIPD: CFB with a zero IV over a random prefix, the data and a trailing MDC packet
+/// holding SHA-1 of everything before the hash.
+fn seipd_v1_decrypt(session_key: &SessionKey, data: &[u8]) -> Result<Vec<u8>, &'static str> {
+    let cipher = session_key.cipher.ok_or("v1 encrypted data requires a v4 SKESK session key")?;
+    if key_len(cipher)? != session_key.key.len() {
+        return Err("Session key does not match the data cipher");
+    }
+    let mut plaintext = CFBMode::full_block(&AesCipher::new(&session_key.key)?, vec![0u8; 16]).decrypt(data);
+
+    let mdc_at = plaintext.len().checked_sub(22).filter(|&at| at >= 18).ok_or("Truncated encrypted data packet")?;
+    let expected = Sha1::digest(&plaintext[..mdc_at + 2]);
+    let valid = plaintext[mdc_at..mdc_at + 2] == [0xc0 | TAG_MDC, 20] && bool::from(expected.ct_eq(&plaintext[mdc_at + 2..]));
+    if !valid {
+        plaintext.zeroize();
+        return Err("Modification detection code mismatch");
+    }
+
+    let inner = plaintext[18..mdc_at].to_vec();
+    plaintext.zeroize();
+    Ok(inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use crate::core::openpgp::keys::read_secret_keys;
+    use crate::core::test_util::h;
+
+    // RFC 9580 Appendix A.4: the sample v6 transferable secret key (Ed25519 primary, X25519 subkey)
+    const SAMPLE_SECRET_KEY: &str = concat!(
+        "xUsGY4d/4xsAAAAg+U2nu0jWCmHlZ3BqZYfQMxmZu52JGggkLq2EVD34laMAGXKB",
+        "exK+cH6NX1hs5hNhIB00TrJmosgv3mg1ditlsLfCsQYfGwoAAABCBYJjh3/jAwsJ",
+        "BwUVCg4IDAIWAAKbAwIeCSIhBssYbE8GCaaX5NUt+mxyKwwfHifBilZwj2Ul7Ce6",
+        "2azJBScJAgcCAAAAAK0oIBA+LX0ifsDm185Ecds2v8lwgyU2kCcUmKfvBXbAf6rh",
+        "RYWzuQOwEn7E/aLwIwRaLsdry0+VcallHhSu4RN6HWaEQsiPlR4zxP/TP7mhfVEe",
+        "7XWPxtnMUMtf15OyA51YBMdLBmOHf+MZAAAAIIaTJINn+eUBXbki+PSAld2nhJh/",
+        "LVmFsS+60WyvXkQ1AE1gCk95TUR3XFeibg/u/tVY6a//1q0NWC1X+yui3O24wpsG",
+        "GBsKAAAALAWCY4d/4wKbDCIhBssYbE8GCaaX5NUt+mxyKwwfHifBilZwj2Ul7Ce6",
+        "2azJAAAAAAQBIKbpGG2dWTX8j+VjFM21J0hqWlEg+bdiojWnKfA5AQpWUWtnNwDE",
+        "M0g12vYxoWM8Y81W+bHBw805I8kWVkXU6vFOi+HWvv/ira7ofJu16NnoUkhclkUr",
+        "k0mXubZvyl4GBg==",
+    );
+    // RFC 9580 Appendix A.8: X25519 PKESK with an AEAD-OCB SEIPD to the sample key
+    const SAMPLE_X25519_MESSAGE: &str = concat!(
+        "wV0GIQYSyD8ecG9jCP4VGkF3Q6HwM3kOk+mXhIjR2zeNqZMIhRmHzxjV8bU/gXzO",
+        "WgBM85PMiVi93AZfJfhK9QmxfdNnZBjeo1VDeVZheQHgaVf7yopqR6W1FT6NOrfS",
+        "aQIHAgZhZBZTW+CwcW1g4FKlbExAf56zaw76/prQoN+bAzxpohup69LA7JW/Vp0l",
+        "yZnuSj3hcFj0DfqLTGgr4/u717J+sPWbtQBfgMfG9AOIwwrUBqsFE9zW+f1zdlYo",
+        "bhF30A+IitsxxA==",
+    );
+    // RFC 9580 Appendices A.9 to A.11: v6 SKESK and v2 SEIPD with EAX, OCB and GCM, passphrase "password"
+    const SAMPLE_EAX_MESSAGE: &str = concat!(
+        "w0AGHgcBCwMIpa5XnR/F2Cv/aSJPkZmTs1Bvo7WaanPP+MXvxfQcV/tU4cImgV14",
+        "KPX5LEVOtl6+AKtZhsaObnxV0mkCBwEGn/kOOzIZZPOkKRPI3MZhkyUBUifvt+rq",
+        "pJ8EwuZ0F11KPSJu1q/LnKmsEiwUcOEcY9TAqyQcapOK1Iv5mlqZuQu6gyXeYQR1",
+        "QCWKt5Wala0FHdqW6xVDHf719eIlXKeCYVRuM5o=",
+    );
+    const SAMPLE_OCB_MESSAGE: &str = concat!(
+        "wz8GHQcCCwMIVqKY0vXjZFP/z8xcEWZO2520JZDX3EawckG2EsOBLP/76gDyNHsl",
+        "ZBEj+IeuYNT9YU4IN9gZ02zSaQIHAgYgpmH3MfyaMDK1YjMmAn46XY21dI6+/wsM",
+        "WRDQns3WQf+f04VidYA1vEl1TOG/P/+n2tCjuBBPUTPPQqQQCoPu9MobSAGohGv0",
+        "K82nyM6dZeIS8wHLzZj9yt5pSod61CRzI/boVw==",
+    );
+    const SAMPLE_GCM_MESSAGE: &str = concat!(
+        "wzwGGgcDCwMI6dOXhbIHAAj/tC58SD70iERXyzcmubPbn/d25fTZpAlS4kRymIUa",
+        "v/91Jt8t1VRBdXmneZ/SaQIHAwb8uUSQvLmLvcnRBsYJAmaUD3LontwhtVlrFXax",
+        "Ae0Pn/xvxtZbv9JNzQeQlm5tHoWjAFN4TLHYtqBpnvEhVaeyrWJYUxtXZR/Xd3kS",
+        "+pXjXZtAIW9ppMJI2yj/QzHxYykHOZ5v+Q==",
+    );
+
+    fn base64(encoded: &str) -> Vec<u8> {
+        STANDARD.decode(encoded).unwrap()
+    }
+
+    #[test]
+    fn rfc9580_x25519_sample() {
+        let keys = read_secret_keys(&base64(SAMPLE_SECRET_KEY)).unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].fingerprint().to_vec(), h("12c83f1e706f6308fe151a417743a1f033790e93e9978488d1db378da9930885"));
+        let literal = decrypt(&Decryptor::Keys(&keys), &base64(SAMPLE_X25519_MESSAGE)).unwrap();
+        assert_eq!(literal.data, b"Hello, world!");
+    }
+
+    #[test]
+    fn rfc9580_passphrase_samples() {
+        for message in [SAMPLE_EAX_MESSAGE, SAMPLE_OCB_MESSAGE, SAMPLE_GCM_MESSAGE] {
+            let literal = decrypt(&Decryptor::Passphrase(b"password"), &base64(message)).unwrap();
+            assert_eq!(literal.data, b"Hello, world!");
+            assert!(decrypt(&Decryptor::Passphrase(b"wrong"), &base64(message)).is_err());
+        }
+    }
+
+    #[test]
+    fn skips_session_packets_that_fail_to_open() {
+        let recipients = [Recipient::Passphrase(b"first".as_slice()), Recipient::Passphrase(b"second".as_slice())];
+        let message = encrypt(&recipients, &LiteralData::binary(b"data"), &EncryptOptions::default()).unwrap();
+        assert_eq!(decrypt(&Decryptor::Passphrase(b"second"), &message).unwrap().data, b"data");
+        assert!(decrypt(&Decryptor::Passphrase(b"third"), &message).is_err());
+    }
+}
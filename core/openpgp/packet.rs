@@ -0,0 +1,219 @@
+// ix-encryption/core/openpgp/packet.rs
+
+//! OpenPGP packet framing (RFC 9580 §4) and string-to-key specifiers (§3.7).
+//! Packets are written in the new format with definite lengths; the reader also accepts
+//! legacy-format headers and partial body lengths on data packets.
+
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+use crate::core::byte_reader::ByteReader;
+use crate::core::kdf::password::PasswordKdf;
+
+pub const TAG_PKESK: u8 = 1;
+pub const TAG_SIGNATURE: u8 = 2;
+pub const TAG_SKESK: u8 = 3;
+pub const TAG_ONE_PASS_SIGNATURE: u8 = 4;
+pub const TAG_SECRET_KEY: u8 = 5;
+pub const TAG_PUBLIC_KEY: u8 = 6;
+pub const TAG_SECRET_SUBKEY: u8 = 7;
+pub const TAG_COMPRESSED: u8 = 8;
+pub const TAG_MARKER: u8 = 10;
+pub const TAG_LITERAL: u8 = 11;
+pub const TAG_PUBLIC_SUBKEY: u8 = 14;
+pub const TAG_SEIPD: u8 = 18;
+pub const TAG_MDC: u8 = 19;
+pub const TAG_PADDING: u8 = 21;
+
+// Hash algorithm IDs usable in S2K
+pub const HASH_SHA1: u8 = 2;
+pub const HASH_SHA256: u8 = 8;
+pub const HASH_SHA512: u8 = 10;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Packet {
+    pub tag: u8,
+    pub body: Vec<u8>,
+}
+
+/// Append one new-format packet with a definite length.
+pub fn write_packet(out: &mut Vec<u8>, tag: u8, body: &[u8]) {
+    out.push(0xc0 | tag);
+    match body.len() {
+        len @ 0..=191 => out.push(len as u8),
+        len @ 192..=8383 => {
+            let len = len - 192;
+            out.extend_from_slice(&[((len >> 8) + 192) as u8, len as u8]);
+        }
+        len => {
+            out.push(0xff);
+            out.extend_from_slice(&(len as u32).to_be_bytes());
+        }
+    }
+    out.extend_from_slice(body);
+}
+
+/// New-format length, and whether it is a partial body length.
+fn read_new_length(reader: &mut ByteReader) -> Result<(usize, bool), &'static str> {
+    let first = reader.take_u8()? as usize;
+    Ok(match first {
+        0..=191 => (first, false),
+        192..=223 => (((first - 192) << 8) + reader.take_u8()? as usize + 192, false),
+        224..=254 => (1 << (first & 0x1f), true),
+        _ => (reader.take_u32()? as usize, false),
+    })
+}
+
+/// Split a packet sequence into packets, joining partial body chunks.
+pub fn parse_packets(data: &[u8]) -> Result<Vec<Packet>, &'static str> {
+    let mut packets = Vec::new();
+    let mut reader = ByteReader::new(data, "Truncated OpenPGP packet");
+    while !reader.is_empty() {
+        let header = reader.take_u8()?;
+        if header & 0x80 == 0 {
+            return Err("Invalid OpenPGP packet header");
+        }
+
+        let (tag, body) = if header & 0x40 != 0 {
+            let tag = header & 0x3f;
+            let mut body = Vec::new();
+            loop {
+                let (len, partial) = read_new_length(&mut reader)?;
+                body.extend_from_slice(reader.take(len)?);
+                if !partial {
+                    break;
+                }
+                if !matches!(tag, TAG_COMPRESSED | TAG_LITERAL | TAG_SEIPD) {
+                    return Err("Partial lengths are only allowed on data packets");
+                }
+            }
+            (tag, body)
+        } else {
+            let tag = (header >> 2) & 0x0f;
+            let len = match header & 0x03 {
+                0 => reader.take_u8()? as usize,
+                1 => reader.take_u16()? as usize,
+                2 => reader.take_u32()? as usize,
+                _ => reader.remaining().len(),
+            };
+            (tag, reader.take(len)?.to_vec())
+        };
+        packets.push(Packet { tag, body });
+    }
+    Ok(packets)
+}
+
+/// Largest Argon2 S2K memory exponent accepted: 2^20 KiB, the `MAX_ARGON2_MEMORY_KIB` limit.
+const MAX_S2K_LOG2_MEMORY_KIB: u8 = 20;
+
+/// String-to-key specifier. Argon2 is the only form written for new messages; the others are
+/// read for interoperability with existing OpenPGP implementations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum S2k {
+    Simple { hash: u8 },
+    Salted { hash: u8, salt: [u8; 8] },
+    IteratedSalted { hash: u8, salt: [u8; 8], count: u8 },
+    Argon2 { salt: [u8; 16], passes: u8, parallelism: u8, log2_memory_kib: u8 },
+}
+
+impl S2k {
+    /// Argon2 with RFC 9580's second recommended parameter set (t=3, p=4, 64 MiB).
+    pub fn argon2() -> Result<Self, &'static str> {
+        let mut salt = [0u8; 16];
+        getrandom::getrandom(&mut salt).map_err(|_| "Salt generation failed")?;
+        Ok(S2k::Argon2 { salt, passes: 3, parallelism: 4, log2_memory_kib: 16 })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            S2k::Simple { hash } => vec![0, *hash],
+            S2k::Salted { hash, salt } => [&[1, *hash][..], salt].concat(),
+            S2k::IteratedSalted { hash, salt, count } => [&[3, *hash][..], salt, &[*count]].concat(),
+            S2k::Argon2 { salt, passes, parallelism, log2_memory_kib } => {
+                [&[4][..], salt, &[*passes, *parallelism, *log2_memory_kib]].concat()
+            }
+        }
+    }
+
+    /// Parse a specifier, returning it and the number of bytes consumed.
+    pub fn decode(data: &[u8]) -> Result<(Self, usize), &'static str> {
+        let field = |range: std::ops::Range<usize>| data.get(range).ok_or("Truncated S2K specifier");
+        match data.first() {
+            Some(0) => Ok((S2k::Simple { hash: field(1..2)?[0] }, 2)),
+            Some(1) => Ok((S2k::Salted { hash: field(1..2)?[0], salt: field(2..10)?.try_into().unwrap() }, 10)),
+            Some(3) => Ok((
+                S2k::IteratedSalted { hash: field(1..2)?[0], salt: field(2..10)?.try_into().unwrap(), count: field(10..11)?[0] },
+                11,
+            )),
+            Some(4) => {
+                let params = field(17..20)?;
+                Ok((
+                    S2k::Argon2 {
+                        salt: field(1..17)?.try_into().unwrap(),
+                        passes: params[0],
+                        parallelism: params[1],
+                        log2_memory_kib: params[2],
+                    },
+                    20,
+                ))
+            }
+            _ => Err("Unsupported S2K specifier"),
+        }
+    }
+
+    pub fn derive(&self, passphrase: &[u8], key_len: usize) -> Result<Vec<u8>, &'static str> {
+        match *self {
+            S2k::Argon2 { salt, passes, parallelism, log2_memory_kib } => {
+                if log2_memory_kib > MAX_S2K_LOG2_MEMORY_KIB {
+                    return Err("Argon2 memory parameter too large");
+                }
+                let kdf = PasswordKdf::Argon2id {
+                    memory_kib: 1 << log2_memory_kib,
+                    iterations: passes as u32,
+                    parallelism: parallelism as u32,
+                };
+                kdf.validate()?;
+                kdf.derive(passphrase, &salt, key_len)
+            }
+            S2k::Simple { hash } => hashed_s2k(hash, passphrase, key_len, 0),
+            S2k::Salted { hash, salt } => hashed_s2k(hash, &[&salt[..], passphrase].concat(), key_len, 0),
+            S2k::IteratedSalted { hash, salt, count } => {
+                let octets = (16usize + (count as usize & 15)) << ((count >> 4) + 6);
+                hashed_s2k(hash, &[&salt[..], passphrase].concat(), key_len, octets)
+            }
+        }
+    }
+}
+
+/// Hash `input` repeated to `octets` (at least once), using one context per output block,
+/// each preloaded with one more zero octet than the last.
+fn hashed_s2k(hash: u8, input: &[u8], key_len: usize, octets: usize) -> Result<Vec<u8>, &'static str> {
+    fn run<D: Digest>(input: &[u8], key_len: usize, octets: usize) -> Vec<u8> {
+        let total = octets.max(input.len());
+        let mut output = Vec::with_capacity(key_len);
+        let mut preload = 0;
+        while output.len() < key_len {
+            let mut digest = D::new();
+            digest.update(vec![0u8; preload]);
+            let mut remaining = total;
+            while remaining >= input.len() {
+                digest.update(input);
+                remaining -= input.len();
+            }
+            digest.update(&input[..remaining]);
+            output.extend_from_slice(&digest.finalize());
+            preload += 1;
+        }
+        output.truncate(key_len);
+        output
+    }
+
+    if input.is_empty() {
+        return Err("Empty S2K input");
+    }
+    match hash {
+        HASH_SHA1 => Ok(run::<Sha1>(input, key_len, octets)),
+        HASH_SHA256 => Ok(run::<Sha256>(input, key_len, octets)),
+        HASH_SHA512 => Ok(run::<Sha512>(input, key_len, octets)),
+        _ => Err("Unsupported S2K hash algorithm"),
+    }
+}
@@ -0,0 +1,136 @@
+// ix-encryption/core/password_cipher.rs
+
+//! Password-based encryption over any `IXCipherCore`.
+//! A fresh salt is drawn per message; the password KDF output is split via HKDF into the
+//! cipher key and a header-authentication key. The header records the KDF and its cost
+//! parameters so decryption needs only the password, and a random IV that is handed to the
+//! inner cipher as its `initialize` salt (the nonce of a standard `ChaChaQuantum`).
+//!
+//! Layout: "IXPW" | version | kdf params | salt len | salt | key len (u16) | iv len | iv
+//!         | header MAC (32) | body
+
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+use crate::core::IXCipherCore;
+use crate::core::kdf::hkdf::{hkdf_expand, HkdfHash};
+use crate::core::kdf::password::PasswordKdf;
+use crate::core::mac::hmac::HmacSha256;
+use crate::core::mac::mac_core::Mac;
+
+const MAGIC: &[u8; 4] = b"IXPW";
+const VERSION: u8 = 2;
+const SALT_LEN: usize = 16;
+const IV_LEN: usize = 12;
+const HEADER_MAC_LEN: usize = 32;
+
+pub struct PasswordCipher<C: IXCipherCore> {
+    inner: C,
+    kdf: PasswordKdf,
+    key_len: usize,
+}
+
+impl<C: IXCipherCore> PasswordCipher<C> {
+    /// `key_len` is the key size the inner cipher expects (32 for `ChaChaQuantum`).
+    pub fn new(inner: C, kdf: PasswordKdf, key_len: usize) -> Self {
+        assert!(key_len > 0 && key_len <= u16::MAX as usize, "Invalid key length");
+        Self { inner, kdf, key_len }
+    }
+
+    pub fn encrypt(&mut self, password: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        self.kdf.validate()?;
+        let mut salt = [0u8; SALT_LEN];
+        getrandom::getrandom(&mut salt).map_err(|_| "Salt generation failed")?;
+        let mut iv = [0u8; IV_LEN];
+        getrandom::getrandom(&mut iv).map_err(|_| "IV generation failed")?;
+
+        let mut header = MAGIC.to_vec();
+        header.push(VERSION);
+        header.extend_from_slice(&self.kdf.encode());
+        header.push(SALT_LEN as u8);
+        header.extend_from_slice(&salt);
+        header.extend_from_slice(&(self.key_len as u16).to_be_bytes());
+        header.push(IV_LEN as u8);
+        header.extend_from_slice(&iv);
+
+        let (mut cipher_key, mut mac_key) = Self::derive_keys(&self.kdf, password, &salt, self.key_len)?;
+        let header_mac = HmacSha256::new(&mac_key).compute(&header);
+        self.inner.initialize(&cipher_key, Some(&iv));
+        cipher_key.zeroize();
+        mac_key.zeroize();
+
+        let mut output = header;
+        output.extend_from_slice(&header_mac);
+        output.extend_from_slice(&self.inner.encrypt(plaintext));
+        Ok(output)
+    }
+
+    /// Decrypt with the password. A wrong password or a tampered header is reported as an
+    /// error before the inner cipher is touched, and a tampered body as an error from it.
+    pub fn decrypt(&mut self, password: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let (kdf, salt, key_len, iv, header_len) = Self::parse_header(ciphertext)?;
+        if key_len != self.key_len {
+            return Err("Key length does not match the inner cipher");
+        }
+        let header = &ciphertext[..header_len];
+        let header_mac = ciphertext
+            .get(header_len..header_len + HEADER_MAC_LEN)
+            .ok_or("Truncated password header")?;
+
+        let (mut cipher_key, mut mac_key) = Self::derive_keys(&kdf, password, salt, key_len)?;
+        let expected = HmacSha256::new(&mac_key).compute(header);
+        mac_key.zeroize();
+        if !bool::from(expected.ct_eq(header_mac)) {
+            cipher_key.zeroize();
+            return Err("Wrong password or corrupted header");
+        }
+
+        self.inner.initialize(&cipher_key, Some(iv));
+        cipher_key.zeroize();
+        self.inner.try_decrypt(&ciphertext[header_len + HEADER_MAC_LEN..])
+    }
+
+    /// Read the KDF parameters from a ciphertext without the password (e.g. for inspection).
+    pub fn header_kdf(ciphertext: &[u8]) -> Result<PasswordKdf, &'static str> {
+        Self::parse_header(ciphertext).map(|(kdf, _, _, _, _)| kdf)
+    }
+
+    pub fn wipe(&mut self) {
+        self.inner.wipe();
+    }
+
+    /// Parse the header; the KDF parameters are bounds-checked by `PasswordKdf::decode`.
+    fn parse_header(data: &[u8]) -> Result<(PasswordKdf, &[u8], usize, &[u8], usize), &'static str> {
+        if data.len() < MAGIC.len() + 1 || &data[..4] != MAGIC {
+            return Err("Not a password-encrypted message");
+        }
+        if data[4] != VERSION {
+            return Err("Unsupported password header version");
+        }
+
+        let (kdf, consumed) = PasswordKdf::decode(&data[5..])?;
+        let mut offset = 5 + consumed;
+        let salt_len = *data.get(offset).ok_or("Truncated password header")? as usize;
+        offset += 1;
+        let salt = data.get(offset..offset + salt_len).ok_or("Truncated password header")?;
+        offset += salt_len;
+        let key_len = data
+            .get(offset..offset + 2)
+            .map(|b| u16::from_be_bytes([b[0], b[1]]) as usize)
+            .ok_or("Truncated password header")?;
+        offset += 2;
+        let iv_len = *data.get(offset).ok_or("Truncated password header")? as usize;
+        offset += 1;
+        let iv = data.get(offset..offset + iv_len).ok_or("Truncated password header")?;
+        offset += iv_len;
+
+        Ok((kdf, salt, key_len, iv, offset))
+    }
+
+    fn derive_keys(kdf: &PasswordKdf, password: &[u8], salt: &[u8], key_len: usize) -> Result<(Vec<u8>, Vec<u8>), &'static str> {
+        let mut master = kdf.derive(password, salt, 32)?;
+        let cipher_key = hkdf_expand(HkdfHash::Sha256, &master, b"IX-PasswordCipher-v1 cipher key", key_len)?;
+        let mac_key = hkdf_expand(HkdfHash::Sha256, &master, b"IX-PasswordCipher-v1 header mac", 32)?;
+        master.zeroize();
+        Ok((cipher_key, mac_key))
+    }
+}
@@ -0,0 +1,272 @@
+// ix-encryption/core/pkcs11/ffi.rs
+
+//! Minimal Cryptoki (PKCS#11 v2.40) C bindings: the types, constants and function list used by
+//! the token session layer. The module library is loaded at runtime, so no vendor headers or
+//! link-time dependency are needed. Structures use the platform's default packing except on
+//! Windows, where Cryptoki mandates 1-byte packing.
+
+#![allow(non_camel_case_types, non_snake_case)]
+
+use std::os::raw::{c_ulong, c_void};
+
+pub type CK_BYTE = u8;
+pub type CK_BBOOL = u8;
+pub type CK_ULONG = c_ulong;
+pub type CK_RV = CK_ULONG;
+pub type CK_FLAGS = CK_ULONG;
+pub type CK_SLOT_ID = CK_ULONG;
+pub type CK_SESSION_HANDLE = CK_ULONG;
+pub type CK_OBJECT_HANDLE = CK_ULONG;
+pub type CK_OBJECT_CLASS = CK_ULONG;
+pub type CK_KEY_TYPE = CK_ULONG;
+pub type CK_ATTRIBUTE_TYPE = CK_ULONG;
+pub type CK_MECHANISM_TYPE = CK_ULONG;
+pub type CK_USER_TYPE = CK_ULONG;
+pub type CK_VOID_PTR = *mut c_void;
+
+pub const CK_TRUE: CK_BBOOL = 1;
+pub const CK_FALSE: CK_BBOOL = 0;
+pub const CK_INVALID_HANDLE: CK_ULONG = 0;
+
+// Return values
+pub const CKR_OK: CK_RV = 0x000;
+pub const CKR_HOST_MEMORY: CK_RV = 0x002;
+pub const CKR_GENERAL_ERROR: CK_RV = 0x005;
+pub const CKR_FUNCTION_FAILED: CK_RV = 0x006;
+pub const CKR_ARGUMENTS_BAD: CK_RV = 0x007;
+pub const CKR_ATTRIBUTE_VALUE_INVALID: CK_RV = 0x013;
+pub const CKR_DATA_LEN_RANGE: CK_RV = 0x021;
+pub const CKR_DEVICE_ERROR: CK_RV = 0x030;
+pub const CKR_AEAD_DECRYPT_FAILED: CK_RV = 0x035;
+pub const CKR_ENCRYPTED_DATA_INVALID: CK_RV = 0x040;
+pub const CKR_ENCRYPTED_DATA_LEN_RANGE: CK_RV = 0x041;
+pub const CKR_KEY_HANDLE_INVALID: CK_RV = 0x060;
+pub const CKR_KEY_FUNCTION_NOT_PERMITTED: CK_RV = 0x068;
+pub const CKR_KEY_UNEXTRACTABLE: CK_RV = 0x06A;
+pub const CKR_MECHANISM_INVALID: CK_RV = 0x070;
+pub const CKR_MECHANISM_PARAM_INVALID: CK_RV = 0x071;
+pub const CKR_PIN_INCORRECT: CK_RV = 0x0A0;
+pub const CKR_PIN_LOCKED: CK_RV = 0x0A4;
+pub const CKR_SESSION_HANDLE_INVALID: CK_RV = 0x0B3;
+pub const CKR_TEMPLATE_INCOMPLETE: CK_RV = 0x0D0;
+pub const CKR_TEMPLATE_INCONSISTENT: CK_RV = 0x0D1;
+pub const CKR_TOKEN_NOT_PRESENT: CK_RV = 0x0E0;
+pub const CKR_UNWRAPPING_KEY_HANDLE_INVALID: CK_RV = 0x0F0;
+pub const CKR_USER_ALREADY_LOGGED_IN: CK_RV = 0x100;
+pub const CKR_USER_NOT_LOGGED_IN: CK_RV = 0x101;
+pub const CKR_WRAPPED_KEY_INVALID: CK_RV = 0x110;
+pub const CKR_WRAPPING_KEY_HANDLE_INVALID: CK_RV = 0x113;
+pub const CKR_BUFFER_TOO_SMALL: CK_RV = 0x150;
+pub const CKR_CRYPTOKI_NOT_INITIALIZED: CK_RV = 0x190;
+pub const CKR_CRYPTOKI_ALREADY_INITIALIZED: CK_RV = 0x191;
+
+// Flags
+pub const CKF_TOKEN_PRESENT: CK_FLAGS = 0x1;
+pub const CKF_RW_SESSION: CK_FLAGS = 0x2;
+pub const CKF_SERIAL_SESSION: CK_FLAGS = 0x4;
+pub const CKF_OS_LOCKING_OK: CK_FLAGS = 0x2;
+
+pub const CKU_USER: CK_USER_TYPE = 1;
+
+// Object classes and key types
+pub const CKO_SECRET_KEY: CK_OBJECT_CLASS = 0x4;
+pub const CKK_AES: CK_KEY_TYPE = 0x1F;
+
+// Attributes
+pub const CKA_CLASS: CK_ATTRIBUTE_TYPE = 0x000;
+pub const CKA_TOKEN: CK_ATTRIBUTE_TYPE = 0x001;
+pub const CKA_PRIVATE: CK_ATTRIBUTE_TYPE = 0x002;
+pub const CKA_LABEL: CK_ATTRIBUTE_TYPE = 0x003;
+pub const CKA_KEY_TYPE: CK_ATTRIBUTE_TYPE = 0x100;
+pub const CKA_ID: CK_ATTRIBUTE_TYPE = 0x102;
+pub const CKA_SENSITIVE: CK_ATTRIBUTE_TYPE = 0x103;
+pub const CKA_ENCRYPT: CK_ATTRIBUTE_TYPE = 0x104;
+pub const CKA_DECRYPT: CK_ATTRIBUTE_TYPE = 0x105;
+pub const CKA_WRAP: CK_ATTRIBUTE_TYPE = 0x106;
+pub const CKA_UNWRAP: CK_ATTRIBUTE_TYPE = 0x107;
+pub const CKA_VALUE_LEN: CK_ATTRIBUTE_TYPE = 0x161;
+pub const CKA_EXTRACTABLE: CK_ATTRIBUTE_TYPE = 0x162;
+
+// Mechanisms
+pub const CKM_AES_KEY_GEN: CK_MECHANISM_TYPE = 0x1080;
+pub const CKM_AES_GCM: CK_MECHANISM_TYPE = 0x1087;
+pub const CKM_AES_KEY_WRAP: CK_MECHANISM_TYPE = 0x2109;
+pub const CKM_AES_KEY_WRAP_PAD: CK_MECHANISM_TYPE = 0x210A;
+
+#[repr(C)]
+#[cfg_attr(windows, repr(packed))]
+#[derive(Clone, Copy, Default)]
+pub struct CK_VERSION {
+    pub major: CK_BYTE,
+    pub minor: CK_BYTE,
+}
+
+#[repr(C)]
+#[cfg_attr(windows, repr(packed))]
+pub struct CK_ATTRIBUTE {
+    pub type_: CK_ATTRIBUTE_TYPE,
+    pub pValue: CK_VOID_PTR,
+    pub ulValueLen: CK_ULONG,
+}
+
+#[repr(C)]
+#[cfg_attr(windows, repr(packed))]
+pub struct CK_MECHANISM {
+    pub mechanism: CK_MECHANISM_TYPE,
+    pub pParameter: CK_VOID_PTR,
+    pub ulParameterLen: CK_ULONG,
+}
+
+#[repr(C)]
+#[cfg_attr(windows, repr(packed))]
+pub struct CK_GCM_PARAMS {
+    pub pIv: *mut CK_BYTE,
+    pub ulIvLen: CK_ULONG,
+    pub ulIvBits: CK_ULONG,
+    pub pAAD: *mut CK_BYTE,
+    pub ulAADLen: CK_ULONG,
+    pub ulTagBits: CK_ULONG,
+}
+
+#[repr(C)]
+#[cfg_attr(windows, repr(packed))]
+pub struct CK_C_INITIALIZE_ARGS {
+    pub CreateMutex: CK_VOID_PTR,
+    pub DestroyMutex: CK_VOID_PTR,
+    pub LockMutex: CK_VOID_PTR,
+    pub UnlockMutex: CK_VOID_PTR,
+    pub flags: CK_FLAGS,
+    pub pReserved: CK_VOID_PTR,
+}
+
+#[repr(C)]
+#[cfg_attr(windows, repr(packed))]
+pub struct CK_TOKEN_INFO {
+    pub label: [CK_BYTE; 32],
+    pub manufacturerID: [CK_BYTE; 32],
+    pub model: [CK_BYTE; 16],
+    pub serialNumber: [CK_BYTE; 16],
+    pub flags: CK_FLAGS,
+    pub ulMaxSessionCount: CK_ULONG,
+    pub ulSessionCount: CK_ULONG,
+    pub ulMaxRwSessionCount: CK_ULONG,
+    pub ulRwSessionCount: CK_ULONG,
+    pub ulMaxPinLen: CK_ULONG,
+    pub ulMinPinLen: CK_ULONG,
+    pub ulTotalPublicMemory: CK_ULONG,
+    pub ulFreePublicMemory: CK_ULONG,
+    pub ulTotalPrivateMemory: CK_ULONG,
+    pub ulFreePrivateMemory: CK_ULONG,
+    pub hardwareVersion: CK_VERSION,
+    pub firmwareVersion: CK_VERSION,
+    pub utcTime: [CK_BYTE; 16],
+}
+
+/// Placeholder for function-list entries this crate never calls.
+pub type CK_UNUSED = Option<unsafe extern "C" fn()>;
+
+pub type C_GetFunctionList = unsafe extern "C" fn(*mut *mut CK_FUNCTION_LIST) -> CK_RV;
+
+/// `CK_FUNCTION_LIST`; entry order is fixed by the standard and must not change.
+#[repr(C)]
+#[cfg_attr(windows, repr(packed))]
+pub struct CK_FUNCTION_LIST {
+    pub version: CK_VERSION,
+    pub C_Initialize: Option<unsafe extern "C" fn(CK_VOID_PTR) -> CK_RV>,
+    pub C_Finalize: Option<unsafe extern "C" fn(CK_VOID_PTR) -> CK_RV>,
+    pub C_GetInfo: CK_UNUSED,
+    pub C_GetFunctionList: CK_UNUSED,
+    pub C_GetSlotList: Option<unsafe extern "C" fn(CK_BBOOL, *mut CK_SLOT_ID, *mut CK_ULONG) -> CK_RV>,
+    pub C_GetSlotInfo: CK_UNUSED,
+    pub C_GetTokenInfo: Option<unsafe extern "C" fn(CK_SLOT_ID, *mut CK_TOKEN_INFO) -> CK_RV>,
+    pub C_GetMechanismList: CK_UNUSED,
+    pub C_GetMechanismInfo: CK_UNUSED,
+    pub C_InitToken: CK_UNUSED,
+    pub C_InitPIN: CK_UNUSED,
+    pub C_SetPIN: CK_UNUSED,
+    pub C_OpenSession: Option<unsafe extern "C" fn(CK_SLOT_ID, CK_FLAGS, CK_VOID_PTR, CK_VOID_PTR, *mut CK_SESSION_HANDLE) -> CK_RV>,
+    pub C_CloseSession: Option<unsafe extern "C" fn(CK_SESSION_HANDLE) -> CK_RV>,
+    pub C_CloseAllSessions: CK_UNUSED,
+    pub C_GetSessionInfo: CK_UNUSED,
+    pub C_GetOperationState: CK_UNUSED,
+    pub C_SetOperationState: CK_UNUSED,
+    pub C_Login: Option<unsafe extern "C" fn(CK_SESSION_HANDLE, CK_USER_TYPE, *const CK_BYTE, CK_ULONG) -> CK_RV>,
+    pub C_Logout: CK_UNUSED,
+    pub C_CreateObject: CK_UNUSED,
+    pub C_CopyObject: CK_UNUSED,
+    pub C_DestroyObject: Option<unsafe extern "C" fn(CK_SESSION_HANDLE, CK_OBJECT_HANDLE) -> CK_RV>,
+    pub C_GetObjectSize: CK_UNUSED,
+    pub C_GetAttributeValue: Option<unsafe extern "C" fn(CK_SESSION_HANDLE, CK_OBJECT_HANDLE, *mut CK_ATTRIBUTE, CK_ULONG) -> CK_RV>,
+    pub C_SetAttributeValue: CK_UNUSED,
+    pub C_FindObjectsInit: Option<unsafe extern "C" fn(CK_SESSION_HANDLE, *mut CK_ATTRIBUTE, CK_ULONG) -> CK_RV>,
+    pub C_FindObjects: Option<unsafe extern "C" fn(CK_SESSION_HANDLE, *mut CK_OBJECT_HANDLE, CK_ULONG, *mut CK_ULONG) -> CK_RV>,
+    pub C_FindObjectsFinal: Option<unsafe extern "C" fn(CK_SESSION_HANDLE) -> CK_RV>,
+    pub C_EncryptInit: Option<unsafe extern "C" fn(CK_SESSION_HANDLE, *mut CK_MECHANISM, CK_OBJECT_HANDLE) -> CK_RV>,
+    pub C_Encrypt: Option<unsafe extern "C" fn(CK_SESSION_HANDLE, *const CK_BYTE, CK_ULONG, *mut CK_BYTE, *mut CK_ULONG) -> CK_RV>,
+    pub C_EncryptUpdate: CK_UNUSED,
+    pub C_EncryptFinal: CK_UNUSED,
+    pub C_DecryptInit: Option<unsafe extern "C" fn(CK_SESSION_HANDLE, *mut CK_MECHANISM, CK_OBJECT_HANDLE) -> CK_RV>,
+    pub C_Decrypt: Option<unsafe extern "C" fn(CK_SESSION_HANDLE, *const CK_BYTE, CK_ULONG, *mut CK_BYTE, *mut CK_ULONG) -> CK_RV>,
+    pub C_DecryptUpdate: CK_UNUSED,
+    pub C_DecryptFinal: CK_UNUSED,
+    pub C_DigestInit: CK_UNUSED,
+    pub C_Digest: CK_UNUSED,
+    pub C_DigestUpdate: CK_UNUSED,
+    pub C_DigestKey: CK_UNUSED,
+    pub C_DigestFinal: CK_UNUSED,
+    pub C_SignInit: CK_UNUSED,
+    pub C_Sign: CK_UNUSED,
+    pub C_SignUpdate: CK_UNUSED,
+    pub C_SignFinal: CK_UNUSED,
+    pub C_SignRecoverInit: CK_UNUSED,
+    pub C_SignRecover: CK_UNUSED,
+    pub C_VerifyInit: CK_UNUSED,
+    pub C_Verify: CK_UNUSED,
+    pub C_VerifyUpdate: CK_UNUSED,
+    pub C_VerifyFinal: CK_UNUSED,
+    pub C_VerifyRecoverInit: CK_UNUSED,
+    pub C_VerifyRecover: CK_UNUSED,
+    pub C_DigestEncryptUpdate: CK_UNUSED,
+    pub C_DecryptDigestUpdate: CK_UNUSED,
+    pub C_SignEncryptUpdate: CK_UNUSED,
+    pub C_DecryptVerifyUpdate: CK_UNUSED,
+    pub C_GenerateKey: Option<unsafe extern "C" fn(CK_SESSION_HANDLE, *mut CK_MECHANISM, *mut CK_ATTRIBUTE, CK_ULONG, *mut CK_OBJECT_HANDLE) -> CK_RV>,
+    pub C_GenerateKeyPair: CK_UNUSED,
+    pub C_WrapKey: Option<unsafe extern "C" fn(CK_SESSION_HANDLE, *mut CK_MECHANISM, CK_OBJECT_HANDLE, CK_OBJECT_HANDLE, *mut CK_BYTE, *mut CK_ULONG) -> CK_RV>,
+    pub C_UnwrapKey: Option<
+        unsafe extern "C" fn(CK_SESSION_HANDLE, *mut CK_MECHANISM, CK_OBJECT_HANDLE, *const CK_BYTE, CK_ULONG, *mut CK_ATTRIBUTE, CK_ULONG, *mut CK_OBJECT_HANDLE) -> CK_RV,
+    >,
+    pub C_DeriveKey: CK_UNUSED,
+    pub C_SeedRandom: CK_UNUSED,
+    pub C_GenerateRandom: Option<unsafe extern "C" fn(CK_SESSION_HANDLE, *mut CK_BYTE, CK_ULONG) -> CK_RV>,
+    pub C_GetFunctionStatus: CK_UNUSED,
+    pub C_CancelFunction: CK_UNUSED,
+    pub C_WaitForSlotEvent: CK_UNUSED,
+}
+
+/// Map a Cryptoki return value to a static error message.
+pub fn rv_error(rv: CK_RV) -> &'static str {
+    match rv {
+        CKR_HOST_MEMORY => "PKCS#11: host memory exhausted",
+        CKR_ARGUMENTS_BAD => "PKCS#11: bad arguments",
+        CKR_ATTRIBUTE_VALUE_INVALID => "PKCS#11: invalid attribute value",
+        CKR_DATA_LEN_RANGE => "PKCS#11: data length out of range",
+        CKR_DEVICE_ERROR => "PKCS#11: device error",
+        CKR_AEAD_DECRYPT_FAILED | CKR_ENCRYPTED_DATA_INVALID | CKR_ENCRYPTED_DATA_LEN_RANGE => "PKCS#11: decryption failed",
+        CKR_KEY_HANDLE_INVALID | CKR_WRAPPING_KEY_HANDLE_INVALID | CKR_UNWRAPPING_KEY_HANDLE_INVALID => "PKCS#11: invalid key handle",
+        CKR_KEY_FUNCTION_NOT_PERMITTED => "PKCS#11: key usage not permitted",
+        CKR_KEY_UNEXTRACTABLE => "PKCS#11: key is not extractable",
+        CKR_MECHANISM_INVALID | CKR_MECHANISM_PARAM_INVALID => "PKCS#11: mechanism not supported",
+        CKR_PIN_INCORRECT => "PKCS#11: incorrect PIN",
+        CKR_PIN_LOCKED => "PKCS#11: PIN locked",
+        CKR_SESSION_HANDLE_INVALID => "PKCS#11: invalid session",
+        CKR_TEMPLATE_INCOMPLETE | CKR_TEMPLATE_INCONSISTENT => "PKCS#11: invalid key template",
+        CKR_TOKEN_NOT_PRESENT => "PKCS#11: token not present",
+        CKR_USER_NOT_LOGGED_IN => "PKCS#11: user not logged in",
+        CKR_WRAPPED_KEY_INVALID => "PKCS#11: invalid wrapped key",
+        CKR_BUFFER_TOO_SMALL => "PKCS#11: buffer too small",
+        CKR_CRYPTOKI_NOT_INITIALIZED => "PKCS#11: library not initialized",
+        CKR_GENERAL_ERROR | CKR_FUNCTION_FAILED => "PKCS#11: function failed",
+        _ => "PKCS#11: unexpected error",
+    }
+}
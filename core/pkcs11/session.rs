@@ -0,0 +1,504 @@
+// ix-encryption/core/pkcs11/session.rs
+
+//! PKCS#11 token access: load a Cryptoki module (e.g. SoftHSM2's `libsofthsm2.so` or a vendor
+//! HSM library), pick a slot by token label, log in and operate on AES keys that stay on the
+//! token. Keys are found by `CKA_LABEL` and optionally `CKA_ID`; generated and unwrapped keys
+//! are persistent, sensitive and non-extractable unless the template says otherwise.
+//!
+//! Cryptoki operations are multi-call (`C_EncryptInit` then `C_Encrypt`), so each session
+//! serializes its operations; open one session per thread for parallelism.
+//!
+//! Local testing with SoftHSM2:
+//!     softhsm2-util --init-token --free --label ix-test --pin 1234 --so-pin 5678
+//!     Pkcs11Module::load("/usr/lib/softhsm/libsofthsm2.so")?.find_slot("ix-test")
+//! The `#[ignore]`d tests here and in `token_cipher` run against such a token with
+//! `cargo test -- --ignored`; they need `SOFTHSM2_CONF` (and `SOFTHSM2_MODULE` if the library
+//! lives elsewhere) and fail without them.
+
+use std::ffi::c_void;
+use std::path::Path;
+use std::ptr;
+use std::sync::{Arc, Mutex};
+use crate::core::pkcs11::ffi::*;
+
+const GCM_IV_LEN: usize = 12;
+const GCM_TAG_BITS: CK_ULONG = 128;
+const FIND_BATCH: usize = 16;
+
+/// Handle of a key object on the token; valid for the module it was obtained from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyHandle(pub CK_OBJECT_HANDLE);
+
+/// Attributes for generated or unwrapped AES keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyTemplate {
+    /// `CKA_ENCRYPT` / `CKA_DECRYPT`.
+    pub encrypt: bool,
+    /// `CKA_WRAP` / `CKA_UNWRAP`.
+    pub wrap: bool,
+    /// `CKA_EXTRACTABLE`: whether the key may itself be wrapped for export.
+    pub extractable: bool,
+}
+
+impl KeyTemplate {
+    pub const ENCRYPTION: KeyTemplate = KeyTemplate { encrypt: true, wrap: false, extractable: false };
+    pub const WRAPPING: KeyTemplate = KeyTemplate { encrypt: false, wrap: true, extractable: false };
+}
+
+fn check(rv: CK_RV) -> Result<(), &'static str> {
+    match rv {
+        CKR_OK => Ok(()),
+        rv => Err(rv_error(rv)),
+    }
+}
+
+fn attribute<T>(type_: CK_ATTRIBUTE_TYPE, value: &T) -> CK_ATTRIBUTE {
+    CK_ATTRIBUTE { type_, pValue: value as *const T as *mut c_void, ulValueLen: std::mem::size_of::<T>() as CK_ULONG }
+}
+
+fn bytes_attribute(type_: CK_ATTRIBUTE_TYPE, value: &[u8]) -> CK_ATTRIBUTE {
+    CK_ATTRIBUTE { type_, pValue: value.as_ptr() as *mut c_void, ulValueLen: value.len() as CK_ULONG }
+}
+
+fn bool_value(flag: bool) -> CK_BBOOL {
+    if flag { CK_TRUE } else { CK_FALSE }
+}
+
+/// A loaded and initialized Cryptoki library.
+pub struct Pkcs11Module {
+    functions: *const CK_FUNCTION_LIST,
+    /// False when another component had already initialized the library; it finalizes it then.
+    owns_initialization: bool,
+    _library: libloading::Library,
+}
+
+// The library is initialized with CKF_OS_LOCKING_OK, which makes its entry points thread-safe.
+unsafe impl Send for Pkcs11Module {}
+unsafe impl Sync for Pkcs11Module {}
+
+impl Pkcs11Module {
+    pub fn load(path: impl AsRef<Path>) -> Result<Arc<Self>, &'static str> {
+        // SAFETY: loading a Cryptoki module runs its initializers; the caller chooses the path.
+        let library = unsafe { libloading::Library::new(path.as_ref()) }.map_err(|_| "Cannot load PKCS#11 module")?;
+        let mut functions: *mut CK_FUNCTION_LIST = ptr::null_mut();
+        // SAFETY: C_GetFunctionList has this signature in every Cryptoki version.
+        unsafe {
+            let get_function_list = library
+                .get::<C_GetFunctionList>(b"C_GetFunctionList\0")
+                .map_err(|_| "Not a PKCS#11 module")?;
+            check(get_function_list(&mut functions))?;
+        }
+        if functions.is_null() {
+            return Err("Not a PKCS#11 module");
+        }
+
+        let mut module = Self { functions, owns_initialization: false, _library: library };
+        let mut args = CK_C_INITIALIZE_ARGS {
+            CreateMutex: ptr::null_mut(),
+            DestroyMutex: ptr::null_mut(),
+            LockMutex: ptr::null_mut(),
+            UnlockMutex: ptr::null_mut(),
+            flags: CKF_OS_LOCKING_OK,
+            pReserved: ptr::null_mut(),
+        };
+        let initialize = module.functions().C_Initialize.ok_or("PKCS#11: C_Initialize missing")?;
+        // SAFETY: args outlives the call.
+        match unsafe { initialize(&mut args as *mut CK_C_INITIALIZE_ARGS as CK_VOID_PTR) } {
+            CKR_OK => module.owns_initialization = true,
+            CKR_CRYPTOKI_ALREADY_INITIALIZED => {}
+            rv => return Err(rv_error(rv)),
+        }
+        Ok(Arc::new(module))
+    }
+
+    fn functions(&self) -> &CK_FUNCTION_LIST {
+        // SAFETY: the list is static data inside the library, which lives as long as self.
+        unsafe { &*self.functions }
+    }
+
+    /// Slots that currently hold a token.
+    pub fn slots(&self) -> Result<Vec<CK_SLOT_ID>, &'static str> {
+        let get_slot_list = self.functions().C_GetSlotList.ok_or("PKCS#11: C_GetSlotList missing")?;
+        let mut count: CK_ULONG = 0;
+        // SAFETY: a null list pointer asks for the count only.
+        check(unsafe { get_slot_list(CK_TRUE, ptr::null_mut(), &mut count) })?;
+        let mut slots = vec![0 as CK_SLOT_ID; count as usize];
+        // SAFETY: slots has room for count entries.
+        check(unsafe { get_slot_list(CK_TRUE, slots.as_mut_ptr(), &mut count) })?;
+        slots.truncate(count as usize);
+        Ok(slots)
+    }
+
+    /// The slot whose token label (blank-padded in `CK_TOKEN_INFO`) equals `label`.
+    pub fn find_slot(&self, label: &str) -> Result<CK_SLOT_ID, &'static str> {
+        let get_token_info = self.functions().C_GetTokenInfo.ok_or("PKCS#11: C_GetTokenInfo missing")?;
+        for slot in self.slots()? {
+            // SAFETY: CK_TOKEN_INFO is plain data, so all-zero is a valid value to overwrite.
+            let mut info: CK_TOKEN_INFO = unsafe { std::mem::zeroed() };
+            // SAFETY: info is a valid out-pointer.
+            check(unsafe { get_token_info(slot, &mut info) })?;
+            let token_label = info.label;
+            if token_label.trim_ascii_end() == label.as_bytes() {
+                return Ok(slot);
+            }
+        }
+        Err("No PKCS#11 token with this label")
+    }
+
+    /// Open a read-write session on `slot`, logging in as the user when `pin` is given.
+    pub fn open_session(self: &Arc<Self>, slot: CK_SLOT_ID, pin: Option<&[u8]>) -> Result<Pkcs11Session, &'static str> {
+        let open_session = self.functions().C_OpenSession.ok_or("PKCS#11: C_OpenSession missing")?;
+        let mut handle: CK_SESSION_HANDLE = CK_INVALID_HANDLE;
+        // SAFETY: no notification callback is registered.
+        check(unsafe { open_session(slot, CKF_SERIAL_SESSION | CKF_RW_SESSION, ptr::null_mut(), ptr::null_mut(), &mut handle) })?;
+        let session = Pkcs11Session { module: Arc::clone(self), handle, operation: Mutex::new(()) };
+
+        if let Some(pin) = pin {
+            let login = self.functions().C_Login.ok_or("PKCS#11: C_Login missing")?;
+            // SAFETY: pin is valid for pin.len() bytes.
+            match unsafe { login(handle, CKU_USER, pin.as_ptr(), pin.len() as CK_ULONG) } {
+                CKR_OK | CKR_USER_ALREADY_LOGGED_IN => {}
+                rv => return Err(rv_error(rv)),
+            }
+        }
+        Ok(session)
+    }
+}
+
+impl Drop for Pkcs11Module {
+    fn drop(&mut self) {
+        if let (true, Some(finalize)) = (self.owns_initialization, self.functions().C_Finalize) {
+            // SAFETY: every session holds an Arc to the module, so none is still open.
+            unsafe { finalize(ptr::null_mut()) };
+        }
+    }
+}
+
+/// A logged-in session on one token.
+pub struct Pkcs11Session {
+    module: Arc<Pkcs11Module>,
+    handle: CK_SESSION_HANDLE,
+    operation: Mutex<()>,
+}
+
+impl Pkcs11Session {
+    fn functions(&self) -> &CK_FUNCTION_LIST {
+        self.module.functions()
+    }
+
+    /// All secret keys with `label` and, if given, `id`.
+    pub fn find_keys(&self, label: &str, id: Option<&[u8]>) -> Result<Vec<KeyHandle>, &'static str> {
+        let functions = self.functions();
+        let find_init = functions.C_FindObjectsInit.ok_or("PKCS#11: C_FindObjectsInit missing")?;
+        let find = functions.C_FindObjects.ok_or("PKCS#11: C_FindObjects missing")?;
+        let find_final = functions.C_FindObjectsFinal.ok_or("PKCS#11: C_FindObjectsFinal missing")?;
+
+        let class = CKO_SECRET_KEY;
+        let mut template = vec![attribute(CKA_CLASS, &class), bytes_attribute(CKA_LABEL, label.as_bytes())];
+        if let Some(id) = id {
+            template.push(bytes_attribute(CKA_ID, id));
+        }
+
+        let _guard = self.operation.lock().map_err(|_| "PKCS#11 session poisoned")?;
+        // SAFETY: the template and its values outlive the search.
+        check(unsafe { find_init(self.handle, template.as_mut_ptr(), template.len() as CK_ULONG) })?;
+        let mut keys = Vec::new();
+        let result = loop {
+            let mut batch = [CK_INVALID_HANDLE; FIND_BATCH];
+            let mut found: CK_ULONG = 0;
+            // SAFETY: batch has room for FIND_BATCH handles.
+            if let Err(e) = check(unsafe { find(self.handle, batch.as_mut_ptr(), FIND_BATCH as CK_ULONG, &mut found) }) {
+                break Err(e);
+            }
+            keys.extend(batch[..found as usize].iter().map(|&handle| KeyHandle(handle)));
+            if (found as usize) < FIND_BATCH {
+                break Ok(keys);
+            }
+        };
+        // SAFETY: ends the search started above.
+        check(unsafe { find_final(self.handle) })?;
+        result
+    }
+
+    /// The single secret key with `label` (and `id`, if given).
+    pub fn find_key(&self, label: &str, id: Option<&[u8]>) -> Result<KeyHandle, &'static str> {
+        match self.find_keys(label, id)?.as_slice() {
+            [key] => Ok(*key),
+            [] => Err("No PKCS#11 key with this label"),
+            _ => Err("Several PKCS#11 keys share this label"),
+        }
+    }
+
+    /// The `CKA_ID` of a key.
+    pub fn key_id(&self, key: KeyHandle) -> Result<Vec<u8>, &'static str> {
+        let get_attribute = self.functions().C_GetAttributeValue.ok_or("PKCS#11: C_GetAttributeValue missing")?;
+        let mut template = [CK_ATTRIBUTE { type_: CKA_ID, pValue: ptr::null_mut(), ulValueLen: 0 }];
+        // SAFETY: a null value pointer asks for the length only.
+        check(unsafe { get_attribute(self.handle, key.0, template.as_mut_ptr(), 1) })?;
+        let mut id = vec![0u8; template[0].ulValueLen as usize];
+        template[0].pValue = id.as_mut_ptr() as *mut c_void;
+        // SAFETY: id has room for the reported length.
+        check(unsafe { get_attribute(self.handle, key.0, template.as_mut_ptr(), 1) })?;
+        id.truncate(template[0].ulValueLen as usize);
+        Ok(id)
+    }
+
+    /// Attributes shared by generated and unwrapped keys. `values` holds CK_TRUE and the
+    /// encrypt, wrap and extractable flags; the attributes point into it.
+    fn key_template(label: &str, id: &[u8], values: &[CK_BBOOL; 4], class: &CK_ULONG, key_type: &CK_ULONG) -> Vec<CK_ATTRIBUTE> {
+        let [yes, encrypt, wrap, extractable] = values;
+        vec![
+            attribute(CKA_CLASS, class),
+            attribute(CKA_KEY_TYPE, key_type),
+            attribute(CKA_TOKEN, yes),
+            attribute(CKA_PRIVATE, yes),
+            attribute(CKA_SENSITIVE, yes),
+            attribute(CKA_EXTRACTABLE, extractable),
+            attribute(CKA_ENCRYPT, encrypt),
+            attribute(CKA_DECRYPT, encrypt),
+            attribute(CKA_WRAP, wrap),
+            attribute(CKA_UNWRAP, wrap),
+            bytes_attribute(CKA_LABEL, label.as_bytes()),
+            bytes_attribute(CKA_ID, id),
+        ]
+    }
+
+    /// Generate a persistent AES key of `key_len` bytes (16, 24 or 32) on the token.
+    pub fn generate_aes_key(&self, label: &str, id: &[u8], key_len: usize, template: KeyTemplate) -> Result<KeyHandle, &'static str> {
+        if ![16, 24, 32].contains(&key_len) {
+            return Err("AES keys must be 16, 24 or 32 bytes");
+        }
+        let generate = self.functions().C_GenerateKey.ok_or("PKCS#11: C_GenerateKey missing")?;
+        let values = [CK_TRUE, bool_value(template.encrypt), bool_value(template.wrap), bool_value(template.extractable)];
+        let (class, key_type, value_len) = (CKO_SECRET_KEY, CKK_AES, key_len as CK_ULONG);
+        let mut attributes = Self::key_template(label, id, &values, &class, &key_type);
+        attributes.push(attribute(CKA_VALUE_LEN, &value_len));
+        let mut mechanism = CK_MECHANISM { mechanism: CKM_AES_KEY_GEN, pParameter: ptr::null_mut(), ulParameterLen: 0 };
+
+        let mut key = CK_INVALID_HANDLE;
+        let _guard = self.operation.lock().map_err(|_| "PKCS#11 session poisoned")?;
+        // SAFETY: mechanism, template and values outlive the call.
+        check(unsafe { generate(self.handle, &mut mechanism, attributes.as_mut_ptr(), attributes.len() as CK_ULONG, &mut key) })?;
+        Ok(KeyHandle(key))
+    }
+
+    pub fn destroy_key(&self, key: KeyHandle) -> Result<(), &'static str> {
+        let destroy = self.functions().C_DestroyObject.ok_or("PKCS#11: C_DestroyObject missing")?;
+        let _guard = self.operation.lock().map_err(|_| "PKCS#11 session poisoned")?;
+        // SAFETY: plain handle arguments.
+        check(unsafe { destroy(self.handle, key.0) })
+    }
+
+    /// Random bytes from the token's generator.
+    pub fn generate_random(&self, len: usize) -> Result<Vec<u8>, &'static str> {
+        let generate_random = self.functions().C_GenerateRandom.ok_or("PKCS#11: C_GenerateRandom missing")?;
+        let mut bytes = vec![0u8; len];
+        let _guard = self.operation.lock().map_err(|_| "PKCS#11 session poisoned")?;
+        // SAFETY: bytes has room for len bytes.
+        check(unsafe { generate_random(self.handle, bytes.as_mut_ptr(), len as CK_ULONG) })?;
+        Ok(bytes)
+    }
+
+    /// Run a single-part `C_Encrypt` or `C_Decrypt` with a length query first.
+    fn single_part(
+        &self,
+        encrypt: bool,
+        mechanism: &mut CK_MECHANISM,
+        key: KeyHandle,
+        input: &[u8],
+    ) -> Result<Vec<u8>, &'static str> {
+        let functions = self.functions();
+        let (init, run) = match encrypt {
+            true => (functions.C_EncryptInit, functions.C_Encrypt),
+            false => (functions.C_DecryptInit, functions.C_Decrypt),
+        };
+        let (init, run) = (init.ok_or("PKCS#11: cipher functions missing")?, run.ok_or("PKCS#11: cipher functions missing")?);
+
+        let _guard = self.operation.lock().map_err(|_| "PKCS#11 session poisoned")?;
+        // SAFETY: the mechanism and its parameters outlive the operation, which ends with the
+        // second run call (or an error from either call).
+        unsafe {
+            check(init(self.handle, mechanism, key.0))?;
+            let mut len: CK_ULONG = 0;
+            check(run(self.handle, input.as_ptr(), input.len() as CK_ULONG, ptr::null_mut(), &mut len))?;
+            let mut output = vec![0u8; len as usize];
+            check(run(self.handle, input.as_ptr(), input.len() as CK_ULONG, output.as_mut_ptr(), &mut len))?;
+            output.truncate(len as usize);
+            Ok(output)
+        }
+    }
+
+    /// AES-GCM on the token with a 96-bit IV and 128-bit tag. Returns ciphertext || tag.
+    pub fn encrypt_gcm(&self, key: KeyHandle, iv: &[u8; GCM_IV_LEN], aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let mut params = gcm_params(iv, aad);
+        self.single_part(true, &mut gcm_mechanism(&mut params), key, plaintext)
+    }
+
+    /// Inverse of `encrypt_gcm`; fails if the tag does not verify.
+    pub fn decrypt_gcm(&self, key: KeyHandle, iv: &[u8; GCM_IV_LEN], aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let mut params = gcm_params(iv, aad);
+        self.single_part(false, &mut gcm_mechanism(&mut params), key, sealed)
+    }
+
+    /// Export `key` wrapped under `wrapping_key` with AES Key Wrap (RFC 3394, `CKM_AES_KEY_WRAP`).
+    /// AES key values are a multiple of 8 bytes, so no padding variant is needed; the padded
+    /// mechanisms are avoided because tokens disagree on what `CKM_AES_KEY_WRAP_PAD` means.
+    /// `key` must be extractable; its value never appears in host memory unwrapped.
+    pub fn wrap_key(&self, wrapping_key: KeyHandle, key: KeyHandle) -> Result<Vec<u8>, &'static str> {
+        let wrap = self.functions().C_WrapKey.ok_or("PKCS#11: C_WrapKey missing")?;
+        let mut mechanism = CK_MECHANISM { mechanism: CKM_AES_KEY_WRAP, pParameter: ptr::null_mut(), ulParameterLen: 0 };
+
+        let _guard = self.operation.lock().map_err(|_| "PKCS#11 session poisoned")?;
+        // SAFETY: a null output pointer asks for the length; the second call fills wrapped.
+        unsafe {
+            let mut len: CK_ULONG = 0;
+            check(wrap(self.handle, &mut mechanism, wrapping_key.0, key.0, ptr::null_mut(), &mut len))?;
+            let mut wrapped = vec![0u8; len as usize];
+            check(wrap(self.handle, &mut mechanism, wrapping_key.0, key.0, wrapped.as_mut_ptr(), &mut len))?;
+            wrapped.truncate(len as usize);
+            Ok(wrapped)
+        }
+    }
+
+    /// Import an RFC 3394-wrapped AES key as a new persistent token key.
+    pub fn unwrap_key(&self, unwrapping_key: KeyHandle, wrapped: &[u8], label: &str, id: &[u8], template: KeyTemplate) -> Result<KeyHandle, &'static str> {
+        let unwrap = self.functions().C_UnwrapKey.ok_or("PKCS#11: C_UnwrapKey missing")?;
+        let mut mechanism = CK_MECHANISM { mechanism: CKM_AES_KEY_WRAP, pParameter: ptr::null_mut(), ulParameterLen: 0 };
+        let values = [CK_TRUE, bool_value(template.encrypt), bool_value(template.wrap), bool_value(template.extractable)];
+        let (class, key_type) = (CKO_SECRET_KEY, CKK_AES);
+        let mut attributes = Self::key_template(label, id, &values, &class, &key_type);
+
+        let mut key = CK_INVALID_HANDLE;
+        let _guard = self.operation.lock().map_err(|_| "PKCS#11 session poisoned")?;
+        // SAFETY: mechanism, wrapped and the template outlive the call.
+        check(unsafe {
+            unwrap(
+                self.handle,
+                &mut mechanism,
+                unwrapping_key.0,
+                wrapped.as_ptr(),
+                wrapped.len() as CK_ULONG,
+                attributes.as_mut_ptr(),
+                attributes.len() as CK_ULONG,
+                &mut key,
+            )
+        })?;
+        Ok(KeyHandle(key))
+    }
+}
+
+impl Drop for Pkcs11Session {
+    fn drop(&mut self) {
+        if let Some(close) = self.functions().C_CloseSession {
+            // SAFETY: the handle came from C_OpenSession and is closed once.
+            unsafe { close(self.handle) };
+        }
+    }
+}
+
+fn gcm_params(iv: &[u8; GCM_IV_LEN], aad: &[u8]) -> CK_GCM_PARAMS {
+    CK_GCM_PARAMS {
+        pIv: iv.as_ptr() as *mut CK_BYTE,
+        ulIvLen: GCM_IV_LEN as CK_ULONG,
+        ulIvBits: (GCM_IV_LEN * 8) as CK_ULONG,
+        pAAD: if aad.is_empty() { ptr::null_mut() } else { aad.as_ptr() as *mut CK_BYTE },
+        ulAADLen: aad.len() as CK_ULONG,
+        ulTagBits: GCM_TAG_BITS,
+    }
+}
+
+fn gcm_mechanism(params: &mut CK_GCM_PARAMS) -> CK_MECHANISM {
+    CK_MECHANISM {
+        mechanism: CKM_AES_GCM,
+        pParameter: params as *mut CK_GCM_PARAMS as CK_VOID_PTR,
+        ulParameterLen: std::mem::size_of::<CK_GCM_PARAMS>() as CK_ULONG,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    const TOKEN_LABEL: &str = "ix-test";
+    const TOKEN_PIN: &[u8] = b"1234";
+
+    /// Session on a SoftHSM2 token initialised as in the module docs. The module path comes
+    /// from `SOFTHSM2_MODULE`, defaulting to the Debian location. Panics if SoftHSM2 is not
+    /// set up, so an ignored test run without it fails rather than passing vacuously.
+    pub(crate) fn softhsm_session() -> Pkcs11Session {
+        if std::env::var_os("SOFTHSM2_CONF").is_none() {
+            panic!("SoftHSM2 tests need SOFTHSM2_CONF pointing at a config with an {TOKEN_LABEL} token");
+        }
+        let path = std::env::var("SOFTHSM2_MODULE").unwrap_or_else(|_| "/usr/lib/softhsm/libsofthsm2.so".into());
+        let module = Pkcs11Module::load(&path).unwrap_or_else(|e| panic!("Cannot load SoftHSM2 module {path} (set SOFTHSM2_MODULE): {e}"));
+        let slot = module.find_slot(TOKEN_LABEL).unwrap_or_else(|e| panic!("No {TOKEN_LABEL} token: {e}"));
+        module.open_session(slot, Some(TOKEN_PIN)).unwrap()
+    }
+
+    /// A label unique to this run, whose keys are destroyed on drop even if the test fails,
+    /// so leftovers never make a later run's labels ambiguous.
+    pub(crate) struct TestLabel<'a> {
+        session: &'a Pkcs11Session,
+        pub(crate) label: String,
+    }
+
+    impl<'a> TestLabel<'a> {
+        pub(crate) fn new(session: &'a Pkcs11Session, prefix: &str) -> Self {
+            let suffix = u64::from_be_bytes(session.generate_random(8).unwrap().try_into().unwrap());
+            Self { session, label: format!("{prefix}-{suffix:016x}") }
+        }
+    }
+
+    impl Drop for TestLabel<'_> {
+        fn drop(&mut self) {
+            for key in self.session.find_keys(&self.label, None).unwrap_or_default() {
+                let _ = self.session.destroy_key(key);
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "needs SoftHSM2 (SOFTHSM2_CONF) with an ix-test token"]
+    fn softhsm_gcm_roundtrip() {
+        let session = softhsm_session();
+        let gcm = TestLabel::new(&session, "ix-test-gcm");
+        let key = session.generate_aes_key(&gcm.label, b"gcm", 32, KeyTemplate::ENCRYPTION).unwrap();
+        assert_eq!(session.find_key(&gcm.label, Some(b"gcm")).unwrap(), key);
+
+        let iv = [7u8; GCM_IV_LEN];
+        let mut sealed = session.encrypt_gcm(key, &iv, b"aad", b"token data").unwrap();
+        assert_eq!(session.decrypt_gcm(key, &iv, b"aad", &sealed).unwrap(), b"token data");
+        sealed[0] ^= 1;
+        assert!(session.decrypt_gcm(key, &iv, b"aad", &sealed).is_err());
+        session.destroy_key(key).unwrap();
+        assert!(session.find_keys(&gcm.label, None).unwrap().is_empty());
+    }
+
+    #[test]
+    #[ignore = "needs SoftHSM2 (SOFTHSM2_CONF) with an ix-test token"]
+    fn softhsm_wrap_unwrap() {
+        let session = softhsm_session();
+        let labels = TestLabel::new(&session, "ix-test-wrap");
+        let label = labels.label.as_str();
+        let kek = session.generate_aes_key(label, b"kek", 32, KeyTemplate::WRAPPING).unwrap();
+        let sealed_only = session.generate_aes_key(label, b"fixed", 32, KeyTemplate::ENCRYPTION).unwrap();
+        assert!(session.wrap_key(kek, sealed_only).is_err());
+
+        let template = KeyTemplate { encrypt: true, wrap: false, extractable: true };
+        let key = session.generate_aes_key(label, b"export", 32, template).unwrap();
+        let wrapped = session.wrap_key(kek, key).unwrap();
+        assert_eq!(wrapped.len(), 32 + 8);
+
+        let imported = session.unwrap_key(kek, &wrapped, label, b"imp", KeyTemplate::ENCRYPTION).unwrap();
+        assert_eq!(session.key_id(imported).unwrap(), b"imp");
+        let iv = [9u8; GCM_IV_LEN];
+        let sealed = session.encrypt_gcm(key, &iv, b"", b"same key").unwrap();
+        assert_eq!(session.decrypt_gcm(imported, &iv, b"", &sealed).unwrap(), b"same key");
+
+        let mut tampered = wrapped.clone();
+        tampered[5] ^= 1;
+        assert!(session.unwrap_key(kek, &tampered, label, b"bad", KeyTemplate::ENCRYPTION).is_err());
+        assert_eq!(session.find_keys(label, None).unwrap().len(), 4);
+        assert_eq!(session.find_key(label, None), Err("Several PKCS#11 keys share this label"));
+    }
+}
@@ -0,0 +1,207 @@
+// ix-encryption/core/pkcs11/token_cipher.rs
+
+//! Token-held keys behind the crate's interfaces. `Pkcs11Cipher` is an `IXCipherCore` whose
+//! AES key lives on a PKCS#11 token; `Pkcs11Provider` is an envelope `KeyEncryptionProvider`
+//! whose KEKs live on the token. Key material never enters host memory; only AES-GCM inputs
+//! and outputs cross the Cryptoki boundary.
+
+use std::sync::Arc;
+use crate::core::IXCipherCore;
+use crate::core::envelope::provider::{wrap_aad, KeyEncryptionProvider, WrappedKey};
+use crate::core::pkcs11::session::{KeyHandle, KeyTemplate, Pkcs11Session};
+
+const IV_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// AES-GCM with a token key and a random 96-bit IV from the token's generator.
+/// Output layout: iv || ciphertext || tag.
+pub struct Pkcs11Cipher {
+    session: Arc<Pkcs11Session>,
+    key: Option<KeyHandle>,
+}
+
+impl Pkcs11Cipher {
+    /// Uninitialized cipher; `initialize` takes the token key's label in place of key bytes.
+    pub fn new(session: Arc<Pkcs11Session>) -> Self {
+        Self { session, key: None }
+    }
+
+    /// Cipher bound to the token key with `label`.
+    pub fn with_label(session: Arc<Pkcs11Session>, label: &str) -> Result<Self, &'static str> {
+        let key = session.find_key(label, None)?;
+        Ok(Self { session, key: Some(key) })
+    }
+
+    pub fn seal(&self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let key = self.key.ok_or("Cipher not initialized")?;
+        let iv: [u8; IV_LEN] = self.session.generate_random(IV_LEN)?.try_into().unwrap();
+        let sealed = self.session.encrypt_gcm(key, &iv, aad, plaintext)?;
+
+        let mut output = iv.to_vec();
+        output.extend_from_slice(&sealed);
+        Ok(output)
+    }
+
+    pub fn open(&self, aad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let key = self.key.ok_or("Cipher not initialized")?;
+        if ciphertext.len() < IV_LEN + TAG_LEN {
+            return Err("Ciphertext too short");
+        }
+        let (iv, sealed) = ciphertext.split_at(IV_LEN);
+        self.session.decrypt_gcm(key, iv.try_into().unwrap(), aad, sealed)
+    }
+}
+
+impl IXCipherCore for Pkcs11Cipher {
+    /// `key` is the UTF-8 label of the token key, not key material.
+    fn initialize(&mut self, key: &[u8], _salt: Option<&[u8]>) {
+        let label = std::str::from_utf8(key).expect("Token key label must be UTF-8");
+        self.key = Some(self.session.find_key(label, None).expect("Token key lookup failed"));
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        self.seal(&[], plaintext).expect("Encryption failed")
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Vec<u8> {
+        self.open(&[], ciphertext).expect("Decryption failed")
+    }
+
+    fn try_decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        self.open(&[], ciphertext)
+    }
+
+    /// Forgets the key handle; the key itself stays on the token.
+    fn wipe(&mut self) {
+        self.key = None;
+    }
+
+    fn algorithm_id(&self) -> &'static str {
+        "IX-PKCS11-AES-GCM-v1"
+    }
+
+    fn trigger_lockdown(&self) -> bool {
+        false
+    }
+}
+
+/// Envelope KEKs held on a token. Version `n` of KEK `key_id` is the AES-256 key with
+/// `CKA_LABEL` = key_id and `CKA_ID` = n as a big-endian u32. Data keys are sealed with
+/// AES-GCM on the token. Wrapped key: iv (12) | ciphertext || tag.
+pub struct Pkcs11Provider {
+    session: Arc<Pkcs11Session>,
+}
+
+impl Pkcs11Provider {
+    pub const PROVIDER_ID: &'static str = "pkcs11";
+
+    pub fn new(session: Arc<Pkcs11Session>) -> Self {
+        Self { session }
+    }
+
+    fn latest_version(&self, key_id: &str) -> Result<Option<u32>, &'static str> {
+        let mut latest = None;
+        for key in self.session.find_keys(key_id, None)? {
+            if let Ok(id) = <[u8; 4]>::try_from(self.session.key_id(key)?) {
+                latest = latest.max(Some(u32::from_be_bytes(id)));
+            }
+        }
+        Ok(latest)
+    }
+
+    /// Generate the next KEK version of `key_id` on the token (version 1 if none exist).
+    pub fn generate_kek(&self, key_id: &str) -> Result<u32, &'static str> {
+        let version = match self.latest_version(key_id)? {
+            Some(latest) => latest.checked_add(1).ok_or("Version overflow")?,
+            None => 1,
+        };
+        self.session.generate_aes_key(key_id, &version.to_be_bytes(), 32, KeyTemplate::ENCRYPTION)?;
+        Ok(version)
+    }
+}
+
+impl KeyEncryptionProvider for Pkcs11Provider {
+    fn provider_id(&self) -> &str {
+        Self::PROVIDER_ID
+    }
+
+    fn wrap(&self, key_id: &str, data_key: &[u8], context: &[u8]) -> Result<WrappedKey, &'static str> {
+        if key_id.len() > 255 {
+            return Err("Key id too long");
+        }
+        let version = self.latest_version(key_id)?.ok_or("No PKCS#11 KEK with this label")?;
+        let kek = self.session.find_key(key_id, Some(&version.to_be_bytes()))?;
+        let iv: [u8; IV_LEN] = self.session.generate_random(IV_LEN)?.try_into().unwrap();
+        let sealed = self.session.encrypt_gcm(kek, &iv, &wrap_aad(key_id, version, context), data_key)?;
+
+        let mut ciphertext = iv.to_vec();
+        ciphertext.extend_from_slice(&sealed);
+        Ok(WrappedKey { key_id: key_id.to_string(), kek_version: version, ciphertext })
+    }
+
+    fn unwrap(&self, wrapped: &WrappedKey, context: &[u8]) -> Result<Vec<u8>, &'static str> {
+        if wrapped.ciphertext.len() < IV_LEN + TAG_LEN {
+            return Err("Wrapped key too short");
+        }
+        let kek = self.session.find_key(&wrapped.key_id, Some(&wrapped.kek_version.to_be_bytes()))?;
+        let (iv, sealed) = wrapped.ciphertext.split_at(IV_LEN);
+        self.session
+            .decrypt_gcm(kek, iv.try_into().unwrap(), &wrap_aad(&wrapped.key_id, wrapped.kek_version, context), sealed)
+            .map_err(|_| "Data key unwrap failed")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::envelope::envelope_format::{decrypt, encrypt, DataCipher};
+    use crate::core::pkcs11::session::tests::{softhsm_session, TestLabel};
+
+    #[test]
+    #[ignore = "needs SoftHSM2 (SOFTHSM2_CONF) with an ix-test token"]
+    fn softhsm_cipher() {
+        let session = Arc::new(softhsm_session());
+        let key = TestLabel::new(&session, "ix-test-cipher");
+        session.generate_aes_key(&key.label, b"", 32, KeyTemplate::ENCRYPTION).unwrap();
+
+        let cipher = Pkcs11Cipher::with_label(session.clone(), &key.label).unwrap();
+        let sealed = cipher.seal(b"aad", b"token data").unwrap();
+        assert_eq!(sealed.len(), IV_LEN + 10 + TAG_LEN);
+        assert_eq!(cipher.open(b"aad", &sealed).unwrap(), b"token data");
+        assert!(cipher.open(b"other", &sealed).is_err());
+        assert_ne!(cipher.seal(b"aad", b"token data").unwrap(), sealed);
+
+        let mut core = Pkcs11Cipher::new(session.clone());
+        assert_eq!(core.try_decrypt(&sealed), Err("Cipher not initialized"));
+        core.initialize(key.label.as_bytes(), None);
+        assert_eq!(core.decrypt(&core.encrypt(b"core")), b"core");
+        let mut tampered = core.encrypt(b"core");
+        tampered[IV_LEN] ^= 1;
+        assert!(core.try_decrypt(&tampered).is_err());
+        core.wipe();
+        assert!(core.try_decrypt(&sealed).is_err());
+    }
+
+    #[test]
+    #[ignore = "needs SoftHSM2 (SOFTHSM2_CONF) with an ix-test token"]
+    fn softhsm_provider_kek_versions() {
+        let session = Arc::new(softhsm_session());
+        let kek = TestLabel::new(&session, "ix-test-kek");
+        let provider = Pkcs11Provider::new(session.clone());
+        assert!(provider.wrap(&kek.label, &[1; 32], b"").is_err());
+
+        assert_eq!(provider.generate_kek(&kek.label).unwrap(), 1);
+        let old = encrypt(&provider, &kek.label, DataCipher::Aes256Gcm, b"payload", b"aad").unwrap();
+        assert_eq!(provider.generate_kek(&kek.label).unwrap(), 2);
+        let wrapped = provider.wrap(&kek.label, &[1; 32], b"context").unwrap();
+        assert_eq!(wrapped.kek_version, 2);
+        assert_eq!(provider.unwrap(&wrapped, b"context").unwrap(), [1; 32]);
+        assert_eq!(provider.unwrap(&wrapped, b"other"), Err("Data key unwrap failed"));
+
+        assert_eq!(decrypt(&[&provider], &old, b"aad").unwrap(), b"payload");
+        let old_version = session.find_key(&kek.label, Some(&1u32.to_be_bytes())).unwrap();
+        session.destroy_key(old_version).unwrap();
+        assert!(decrypt(&[&provider], &old, b"aad").is_err());
+        assert_eq!(provider.generate_kek(&kek.label).unwrap(), 3);
+    }
+}
@@ -2,9 +2,14 @@
 
 //! Hybrid encryption combining lattice-based KEM with symmetric ChaCha20-Poly1305 cipher.
 //! Designed for quantum-resistant session key exchange and efficient data encryption.
+//!
+//! `IX-HybridLattice-v2` derives the session, epoch and exported keys through `KeyHierarchy`;
+//! v1 ciphertexts, keyed by the earlier derivation, do not decrypt under v2.
 
+use zeroize::Zeroize;
 use crate::core::IXCipherCore;
 use crate::core::hybrid::ChaChaQuantum;
+use crate::core::kdf::key_hierarchy::KeyHierarchy;
 use crate::core::postquantum::lattice_kem::LatticeKEM;
 
 pub struct HybridLatticeCipher {
@@ -22,27 +27,96 @@ impl HybridLatticeCipher {
         }
     }
 
+    /// Build around an existing KEM keypair, e.g. one loaded from a key file.
+    pub fn with_keypair(lattice_kem: LatticeKEM) -> Self {
+        Self {
+            lattice_kem,
+            symmetric_cipher: ChaChaQuantum::new(),
+            session_key: None,
+        }
+    }
+
+    pub fn public_key(&self) -> &[u8] {
+        &self.lattice_kem.public_key
+    }
+
+    /// Derive key material bound to the established session for use outside the symmetric
+    /// layer (e.g. wrapping a file key). Distinct purposes yield independent keys.
+    pub fn export_key(&self, purpose: &str, output_len: usize) -> Result<Vec<u8>, &'static str> {
+        let session_key = self.session_key.as_ref().ok_or("No session key established")?;
+        KeyHierarchy::from_master(session_key)
+            .child("hybrid-lattice/export")
+            .purpose_key(purpose, output_len)
+    }
+
     /// Generate and encapsulate session key to encrypt data
     pub fn encapsulate_key(&mut self, peer_public_key: &[u8]) -> Vec<u8> {
         let (ciphertext, shared_secret) = self.lattice_kem.encapsulate(peer_public_key);
-        self.session_key = Some(shared_secret.clone());
-        self.symmetric_cipher.initialize(&shared_secret, None);
+        self.install_session_key(&shared_secret);
         ciphertext
     }
 
     /// Decapsulate session key from ciphertext and initialize symmetric cipher
     pub fn decapsulate_key(&mut self, ciphertext: &[u8]) {
         let shared_secret = self.lattice_kem.decapsulate(ciphertext);
-        self.session_key = Some(shared_secret.clone());
-        self.symmetric_cipher.initialize(&shared_secret, None);
+        self.install_session_key(&shared_secret);
+    }
+
+    /// Rekey the symmetric layer for a new epoch; both peers must use the same epoch number.
+    /// Every epoch key derives from the retained session key, so this is not forward secret.
+    pub fn rekey(&mut self, epoch: u64) {
+        let session_key = self.session_key.as_ref().expect("No session key established");
+        let next = KeyHierarchy::from_master(session_key)
+            .rekey("hybrid-lattice/session", epoch, 32)
+            .expect("Session key length within HKDF limits");
+        self.symmetric_cipher.initialize(&next, None);
+    }
+
+    /// Derive the ChaCha20-Poly1305 key from the KEM shared secret rather than using it raw.
+    fn install_session_key(&mut self, shared_secret: &[u8]) {
+        let key = KeyHierarchy::from_master(shared_secret)
+            .purpose_key("hybrid-lattice/session", 32)
+            .expect("Session key length within HKDF limits");
+        self.symmetric_cipher.initialize(&key, None);
+        if let Some(old) = self.session_key.as_mut() {
+            old.zeroize();
+        }
+        self.session_key = Some(shared_secret.to_vec());
     }
 }
 
 impl IXCipherCore for HybridLatticeCipher {
-    fn initialize(&mut self, key: &[u8], salt: Option<&[u8]>) {
-        // For compatibility, initialize symmetric cipher directly with key
-        self.symmetric_cipher.initialize(key, salt);
-        self.session_key = Some(key.to_vec());
+    /// Treats `key` as a shared secret and derives the ChaCha20-Poly1305 key from it exactly as
+    /// `encapsulate_key` and `decapsulate_key` do. `salt` is unused.
+    fn initialize(&mut self, key: &[u8], _salt: Option<&[u8]>) {
+        self.install_session_key(key);
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        self.symmetric_cipher.encrypt(plaintext)
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Vec<u8> {
+        self.try_decrypt(ciphertext).expect("Decryption failed")
+    }
+
+    fn try_decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        self.symmetric_cipher.try_decrypt(ciphertext)
     }
 
-    f
+    fn wipe(&mut self) {
+        if let Some(session_key) = self.session_key.as_mut() {
+            session_key.zeroize();
+        }
+        self.session_key = None;
+        self.symmetric_cipher.wipe();
+    }
+
+    fn algorithm_id(&self) -> &'static str {
+        "IX-HybridLattice-v2"
+    }
+
+    fn trigger_lockdown(&self) -> bool {
+        self.symmetric_cipher.trigger_lockdown()
+    }
+}
@@ -0,0 +1,288 @@
+// ix-encryption/core/sharing/feldman.rs
+
+//! Feldman verifiable secret sharing over the P-256 group. The dealer draws a uniformly random
+//! scalar s, shares it with a polynomial of degree threshold - 1 and publishes commitments
+//! C_j = a_j * G to every coefficient. The secret itself is sealed with ChaCha20-Poly1305 under
+//! a key derived from s, and every share carries that sealed copy. A shareholder checks
+//! y * G against the sum of C_j * x^j, and the sealed copy against its digest in the
+//! commitments. The commitments reveal only s * G for a full-width random s, so the secret may
+//! have any length or entropy.
+//!
+//! Share value: scalar (32, big-endian evaluation at x = index) | sealed secret (secret len + 16)
+//! Commitments: "IXSC" | version | split id (8) | threshold (u8) | secret len (u16)
+//!              | threshold compressed points (33 each) | SHA-256 of the sealed secret (32)
+
+use p256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use p256::elliptic_curve::{Field, PrimeField};
+use p256::{AffinePoint, EncodedPoint, ProjectivePoint, Scalar};
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+use crate::core::IXCipherCore;
+use crate::core::hybrid::{ChaChaQuantum, NONCE_LEN};
+use crate::core::kdf::hkdf::{hkdf, HkdfHash};
+use crate::core::sharing::shamir::{self, Share, SharingScheme, SPLIT_ID_LEN};
+
+const MAGIC: &[u8; 4] = b"IXSC";
+/// Version 1 shared the secret itself in 31-byte chunks, which leaked short tail chunks.
+const COMMITMENT_VERSION: u8 = 2;
+const SCALAR_LEN: usize = 32;
+const POINT_LEN: usize = 33;
+const DIGEST_LEN: usize = 32;
+const TAG_LEN: usize = 16;
+const KEY_INFO: &[u8] = b"IX-Feldman-v2 secret key";
+/// Largest secret whose share value (scalar, sealed secret and tag) fits the u16 value length.
+pub const MAX_SECRET_LEN: usize = u16::MAX as usize - SCALAR_LEN - TAG_LEN;
+
+/// Public commitments for one verifiable split; safe to distribute with every share.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeldmanCommitments {
+    pub split_id: [u8; SPLIT_ID_LEN],
+    pub threshold: u8,
+    pub secret_len: u16,
+    points: Vec<ProjectivePoint>,
+    sealed_digest: [u8; DIGEST_LEN],
+}
+
+impl FeldmanCommitments {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.push(COMMITMENT_VERSION);
+        out.extend_from_slice(&self.split_id);
+        out.push(self.threshold);
+        out.extend_from_slice(&self.secret_len.to_be_bytes());
+        for point in &self.points {
+            out.extend_from_slice(point.to_affine().to_encoded_point(true).as_bytes());
+        }
+        out.extend_from_slice(&self.sealed_digest);
+        out
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, &'static str> {
+        const HEADER_LEN: usize = 4 + 1 + SPLIT_ID_LEN + 1 + 2;
+        if data.len() < HEADER_LEN || &data[..4] != MAGIC {
+            return Err("Not a Feldman commitment set");
+        }
+        if data[4] != COMMITMENT_VERSION {
+            return Err("Unsupported commitment version");
+        }
+        let split_id = data[5..5 + SPLIT_ID_LEN].try_into().unwrap();
+        let threshold = data[13];
+        let secret_len = u16::from_be_bytes([data[14], data[15]]);
+        if threshold == 0 || secret_len == 0 {
+            return Err("Invalid commitment parameters");
+        }
+        let points_end = HEADER_LEN + threshold as usize * POINT_LEN;
+        if data.len() != points_end + DIGEST_LEN {
+            return Err("Commitment length mismatch");
+        }
+
+        let points = data[HEADER_LEN..points_end]
+            .chunks(POINT_LEN)
+            .map(|bytes| {
+                let encoded = EncodedPoint::from_bytes(bytes).map_err(|_| "Invalid commitment point")?;
+                Option::<AffinePoint>::from(AffinePoint::from_encoded_point(&encoded))
+                    .map(ProjectivePoint::from)
+                    .ok_or("Invalid commitment point")
+            })
+            .collect::<Result<_, _>>()?;
+        let sealed_digest = data[points_end..].try_into().unwrap();
+        Ok(Self { split_id, threshold, secret_len, points, sealed_digest })
+    }
+}
+
+fn parse_scalar(bytes: &[u8]) -> Option<Scalar> {
+    let repr: [u8; SCALAR_LEN] = bytes.try_into().ok()?;
+    Scalar::from_repr(repr.into()).into()
+}
+
+fn value_len(secret_len: usize) -> usize {
+    SCALAR_LEN + secret_len + TAG_LEN
+}
+
+/// The split parameters, bound into the sealed secret as associated data.
+fn sealed_aad(split_id: &[u8; SPLIT_ID_LEN], threshold: u8, secret_len: u16) -> Vec<u8> {
+    [&split_id[..], &[threshold], &secret_len.to_be_bytes()].concat()
+}
+
+/// ChaCha20-Poly1305 keyed from the shared scalar. The key is unique to one split, so the
+/// nonce is fixed.
+fn secret_cipher(s: &Scalar, split_id: &[u8; SPLIT_ID_LEN]) -> Result<ChaChaQuantum, &'static str> {
+    let mut repr: [u8; SCALAR_LEN] = s.to_repr().into();
+    let key = hkdf(HkdfHash::Sha256, Some(split_id), &repr, KEY_INFO, 32);
+    repr.zeroize();
+    let mut key = key?;
+    let mut cipher = ChaChaQuantum::new();
+    cipher.initialize(&key, None);
+    key.zeroize();
+    Ok(cipher)
+}
+
+/// Split `secret` into `shares` verifiable shares with threshold `threshold`.
+pub fn split(secret: &[u8], threshold: u8, shares: u8) -> Result<(Vec<Share>, FeldmanCommitments), &'static str> {
+    if secret.len() > MAX_SECRET_LEN {
+        return Err("Verifiable secrets must be at most 65487 bytes");
+    }
+    let split_id = shamir::new_split(secret.len(), threshold, shares)?;
+    let secret_len = secret.len() as u16;
+
+    let mut coefficients: Vec<Scalar> = (0..threshold).map(|_| Scalar::random(&mut OsRng)).collect();
+    let points = coefficients.iter().map(|a| ProjectivePoint::GENERATOR * a).collect();
+
+    let sealed = secret_cipher(&coefficients[0], &split_id).and_then(|mut cipher| {
+        let sealed = cipher.seal_with_nonce(&[0u8; NONCE_LEN], &sealed_aad(&split_id, threshold, secret_len), secret);
+        cipher.wipe();
+        sealed
+    });
+    let sealed = match sealed {
+        Ok(sealed) => sealed,
+        Err(e) => {
+            coefficients.zeroize();
+            return Err(e);
+        }
+    };
+
+    let output = (1..=shares)
+        .map(|index| {
+            let x = Scalar::from(index as u64);
+            let mut y = coefficients.iter().rev().fold(Scalar::ZERO, |acc, a| acc * x + a);
+            let mut bytes: [u8; SCALAR_LEN] = y.to_repr().into();
+            let value = [&bytes[..], &sealed].concat();
+            bytes.zeroize();
+            y.zeroize();
+            Share { scheme: SharingScheme::FeldmanP256, split_id, threshold, index, secret_len, value }
+        })
+        .collect();
+    coefficients.zeroize();
+
+    let sealed_digest = Sha256::digest(&sealed).into();
+    Ok((output, FeldmanCommitments { split_id, threshold, secret_len, points, sealed_digest }))
+}
+
+/// Check `share` against the dealer's commitments.
+pub fn verify(share: &Share, commitments: &FeldmanCommitments) -> bool {
+    if share.scheme != SharingScheme::FeldmanP256
+        || (share.split_id, share.threshold, share.secret_len) != (commitments.split_id, commitments.threshold, commitments.secret_len)
+        || share.value.len() != value_len(share.secret_len as usize)
+    {
+        return false;
+    }
+
+    let (scalar, sealed) = share.value.split_at(SCALAR_LEN);
+    if !bool::from(Sha256::digest(sealed).ct_eq(&commitments.sealed_digest)) {
+        return false;
+    }
+    let Some(y) = parse_scalar(scalar) else { return false };
+    let x = Scalar::from(share.index as u64);
+    let mut power = Scalar::ONE;
+    let mut expected = ProjectivePoint::IDENTITY;
+    for point in &commitments.points {
+        expected += point * &power;
+        power *= x;
+    }
+    ProjectivePoint::GENERATOR * y == expected
+}
+
+/// Recover the secret from shares that verify against `commitments`. Shares that fail
+/// verification are skipped, so a bad share does not block recovery while enough good ones remain.
+pub fn combine(shares: &[Share], commitments: &FeldmanCommitments) -> Result<Vec<u8>, &'static str> {
+    let valid: Vec<Share> = shares.iter().filter(|share| verify(share, commitments)).cloned().collect();
+    if valid.len() < commitments.threshold as usize {
+        return Err("Not enough shares pass verification");
+    }
+    interpolate(shamir::select_shares(&valid)?)
+}
+
+/// Lagrange interpolation of s at zero over the scalar field, for exactly `threshold` shares of
+/// one split, then opening the sealed secret with the key derived from s.
+pub(crate) fn interpolate(shares: &[Share]) -> Result<Vec<u8>, &'static str> {
+    let first = &shares[0];
+    if shares.iter().any(|share| share.value.len() != value_len(first.secret_len as usize)) {
+        return Err("Share length mismatch");
+    }
+
+    let mut s = Scalar::ZERO;
+    for share in shares {
+        let xi = Scalar::from(share.index as u64);
+        let l = shares.iter().filter(|other| other.index != share.index).fold(Scalar::ONE, |l, other| {
+            let xj = Scalar::from(other.index as u64);
+            l * xj * (xj - xi).invert().expect("indices are distinct")
+        });
+        let Some(mut y) = parse_scalar(&share.value[..SCALAR_LEN]) else {
+            s.zeroize();
+            return Err("Invalid share value");
+        };
+        s += y * l;
+        y.zeroize();
+    }
+
+    let cipher = secret_cipher(&s, &first.split_id);
+    s.zeroize();
+    let mut cipher = cipher?;
+    let aad = sealed_aad(&first.split_id, first.threshold, first.secret_len);
+    // Shares from a consistent split always rebuild the key that opens their sealed copy.
+    let secret = cipher.open_with_nonce(&[0u8; NONCE_LEN], &aad, &first.value[SCALAR_LEN..]);
+    cipher.wipe();
+    secret.map_err(|_| "Shares are inconsistent")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"a secret of any length or entropy";
+
+    #[test]
+    fn verified_roundtrip() {
+        let (shares, commitments) = split(SECRET, 3, 5).unwrap();
+        assert!(shares.iter().all(|share| verify(share, &commitments)));
+        assert_eq!(shares[0].value.len(), value_len(SECRET.len()));
+        assert_eq!(combine(&shares[1..4], &commitments).unwrap(), SECRET);
+        assert_eq!(shamir::combine(&shares[2..]).unwrap(), SECRET);
+        assert_eq!(combine(&shares[..2], &commitments), Err("Not enough shares pass verification"));
+
+        let decoded = FeldmanCommitments::decode(&commitments.encode()).unwrap();
+        assert_eq!(decoded, commitments);
+        assert_eq!(Share::decode(&shares[0].encode()).unwrap(), shares[0]);
+    }
+
+    #[test]
+    fn threshold_one() {
+        let (shares, commitments) = split(SECRET, 1, 2).unwrap();
+        for share in &shares {
+            assert_eq!(combine(std::slice::from_ref(share), &commitments).unwrap(), SECRET);
+        }
+    }
+
+    #[test]
+    fn tampered_shares_are_skipped() {
+        let (mut shares, commitments) = split(SECRET, 2, 4).unwrap();
+        shares[0].value[SCALAR_LEN - 1] ^= 1;
+        shares[1].value[SCALAR_LEN + 1] ^= 1;
+        assert!(!verify(&shares[0], &commitments));
+        assert!(!verify(&shares[1], &commitments));
+        assert_eq!(combine(&shares, &commitments).unwrap(), SECRET);
+        assert!(combine(&shares[..3], &commitments).is_err());
+        // Unverified interpolation notices the tampered scalar only when opening the secret.
+        assert_eq!(shamir::combine(&shares[..2]), Err("Shares are inconsistent"));
+    }
+
+    #[test]
+    fn rejects_commitments_from_another_split() {
+        let (shares, _) = split(SECRET, 2, 3).unwrap();
+        let (_, other) = split(SECRET, 2, 3).unwrap();
+        assert!(!verify(&shares[0], &other));
+        assert!(combine(&shares, &other).is_err());
+    }
+
+    #[test]
+    fn caps_the_secret_length() {
+        let secret = vec![7u8; MAX_SECRET_LEN];
+        let (shares, commitments) = split(&secret, 1, 1).unwrap();
+        assert_eq!(shares[0].value.len(), u16::MAX as usize);
+        assert_eq!(Share::decode(&shares[0].encode()).unwrap(), shares[0]);
+        assert_eq!(combine(&shares, &commitments).unwrap(), secret);
+        assert!(split(&vec![7u8; MAX_SECRET_LEN + 1], 1, 1).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+// ix-encryption/core/sharing/key_sharing.rs
+
+//! Disaster-recovery helpers: split keystore master keys and `LatticeKEM` secret keys into
+//! threshold shares, and rebuild them. A shared keystore uses a random 32-byte master key
+//! (`Master::Key`) that exists only transiently while the store is created, opened or reshared.
+//! The `LatticeKEM` helpers are only built with the `ml_kem` feature, for a `LatticeKEM` that is
+//! a real FIPS 203 ML-KEM-768; only then does an encapsulation round trip confirm a rebuilt key.
+
+use std::path::Path;
+#[cfg(feature = "ml_kem")]
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+use crate::core::kdf::password::PasswordKdf;
+use crate::core::keystore::{Keystore, Master};
+#[cfg(feature = "ml_kem")]
+use crate::core::postquantum::lattice_kem::LatticeKEM;
+use crate::core::sharing::feldman::{self, FeldmanCommitments};
+use crate::core::sharing::shamir::{self, Share};
+
+/// Shares of one split, with Feldman commitments when verifiable sharing was requested.
+pub struct SharedSecret {
+    pub shares: Vec<Share>,
+    pub commitments: Option<FeldmanCommitments>,
+}
+
+pub fn split_secret(secret: &[u8], threshold: u8, shares: u8, verifiable: bool) -> Result<SharedSecret, &'static str> {
+    match verifiable {
+        true => feldman::split(secret, threshold, shares).map(|(shares, commitments)| SharedSecret { shares, commitments: Some(commitments) }),
+        false => shamir::split(secret, threshold, shares).map(|shares| SharedSecret { shares, commitments: None }),
+    }
+}
+
+/// Recover a secret, verifying shares first when `commitments` are given.
+pub fn combine_secret(shares: &[Share], commitments: Option<&FeldmanCommitments>) -> Result<Vec<u8>, &'static str> {
+    match commitments {
+        Some(commitments) => feldman::combine(shares, commitments),
+        None => shamir::combine(shares),
+    }
+}
+
+/// A 32-byte keystore master key, wiped on drop.
+pub struct MasterKey([u8; 32]);
+
+impl MasterKey {
+    pub fn generate() -> Result<Self, &'static str> {
+        let mut key = [0u8; 32];
+        getrandom::getrandom(&mut key).map_err(|_| "Random generation failed")?;
+        Ok(Self(key))
+    }
+
+    pub fn combine(shares: &[Share], commitments: Option<&FeldmanCommitments>) -> Result<Self, &'static str> {
+        let mut secret = combine_secret(shares, commitments)?;
+        let key = <[u8; 32]>::try_from(secret.as_slice()).map_err(|_| "Shares do not hold a master key");
+        secret.zeroize();
+        key.map(Self)
+    }
+
+    pub fn split(&self, threshold: u8, shares: u8, verifiable: bool) -> Result<SharedSecret, &'static str> {
+        split_secret(&self.0, threshold, shares, verifiable)
+    }
+
+    pub fn as_master(&self) -> Master<'_> {
+        Master::Key(&self.0)
+    }
+}
+
+impl Drop for MasterKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// Create a keystore under a fresh master key and split that key into shares.
+pub fn create_keystore_with_shares(path: impl AsRef<Path>, threshold: u8, shares: u8, verifiable: bool) -> Result<(Keystore, SharedSecret), &'static str> {
+    let master = MasterKey::generate()?;
+    let shared = master.split(threshold, shares, verifiable)?;
+    let keystore = Keystore::create(path, &master.as_master(), PasswordKdf::default())?;
+    Ok((keystore, shared))
+}
+
+pub fn open_keystore_with_shares(path: impl AsRef<Path>, shares: &[Share], commitments: Option<&FeldmanCommitments>) -> Result<Keystore, &'static str> {
+    let master = MasterKey::combine(shares, commitments)?;
+    Keystore::open(path, &master.as_master())
+}
+
+/// Move `keystore` (opened with any credential) to a fresh shared master key, invalidating the
+/// previous credential and shares once the keystore is saved.
+pub fn reshare_keystore_master(keystore: &mut Keystore, threshold: u8, shares: u8, verifiable: bool) -> Result<SharedSecret, &'static str> {
+    let master = MasterKey::generate()?;
+    let shared = master.split(threshold, shares, verifiable)?;
+    keystore.change_master(&master.as_master(), PasswordKdf::default())?;
+    Ok(shared)
+}
+
+#[cfg(feature = "ml_kem")]
+pub fn split_lattice_secret(kem: &LatticeKEM, threshold: u8, shares: u8, verifiable: bool) -> Result<SharedSecret, &'static str> {
+    split_secret(&kem.secret_key, threshold, shares, verifiable)
+}
+
+/// A `LatticeKEM` key pair rebuilt from shares; the secret key is wiped on drop.
+#[cfg(feature = "ml_kem")]
+pub struct RecoveredLatticeKey(LatticeKEM);
+
+#[cfg(feature = "ml_kem")]
+impl RecoveredLatticeKey {
+    pub fn kem(&self) -> &LatticeKEM {
+        &self.0
+    }
+}
+
+#[cfg(feature = "ml_kem")]
+impl Drop for RecoveredLatticeKey {
+    fn drop(&mut self) {
+        self.0.secret_key.zeroize();
+    }
+}
+
+/// Rebuild a `LatticeKEM` key pair and confirm the secret key matches `public_key` with an
+/// encapsulation round trip.
+#[cfg(feature = "ml_kem")]
+pub fn combine_lattice_secret(shares: &[Share], commitments: Option<&FeldmanCommitments>, public_key: &[u8]) -> Result<RecoveredLatticeKey, &'static str> {
+    let key = RecoveredLatticeKey(LatticeKEM { public_key: public_key.to_vec(), secret_key: combine_secret(shares, commitments)? });
+    let (ciphertext, mut expected) = key.kem().encapsulate(public_key);
+    let mut shared_secret = key.kem().decapsulate(&ciphertext);
+    let matches = bool::from(shared_secret.ct_eq(&expected));
+    expected.zeroize();
+    shared_secret.zeroize();
+
+    match matches {
+        true => Ok(key),
+        false => Err("Recovered secret key does not match the public key"),
+    }
+}
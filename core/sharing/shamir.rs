@@ -0,0 +1,287 @@
+// ix-encryption/core/sharing/shamir.rs
+
+//! Shamir secret sharing over GF(2^8) (reduction polynomial x^8 + x^4 + x^3 + x + 1, as in AES).
+//! Each secret byte gets its own random polynomial of degree threshold - 1; share `i` holds the
+//! evaluations at x = i. Field arithmetic is branch-free and table-free, so timing does not
+//! depend on secret bytes. Coefficients and interpolation buffers are wiped after use.
+//!
+//! Shares of one split carry a random split id, so shares from different splits are rejected
+//! instead of combining into garbage. The checksum catches transcription errors, not forgery;
+//! use `feldman` shares when shareholders must be able to detect a bad dealer or share.
+//!
+//! Share:  "IXSH" | version | scheme (u8) | split id (8) | threshold (u8) | index (u8)
+//!         | secret len (u16) | value len (u16) | value | checksum (4, SHA-256 prefix)
+
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+use crate::core::sharing::feldman;
+
+const MAGIC: &[u8; 4] = b"IXSH";
+pub const SHARE_VERSION: u8 = 1;
+pub const SPLIT_ID_LEN: usize = 8;
+const CHECKSUM_LEN: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SharingScheme {
+    /// Byte-wise Shamir over GF(2^8); the value is as long as the secret.
+    Gf256 = 1,
+    /// Feldman verifiable sharing over the P-256 scalar field; see `feldman`.
+    FeldmanP256 = 2,
+}
+
+/// One share. The value is wiped on drop.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Share {
+    pub scheme: SharingScheme,
+    pub split_id: [u8; SPLIT_ID_LEN],
+    pub threshold: u8,
+    /// Evaluation point, 1..=255.
+    pub index: u8,
+    pub secret_len: u16,
+    pub value: Vec<u8>,
+}
+
+impl Drop for Share {
+    fn drop(&mut self) {
+        self.value.zeroize();
+    }
+}
+
+impl std::fmt::Debug for Share {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Share")
+            .field("scheme", &self.scheme)
+            .field("threshold", &self.threshold)
+            .field("index", &self.index)
+            .field("secret_len", &self.secret_len)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Share {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.extend_from_slice(&[SHARE_VERSION, self.scheme as u8]);
+        out.extend_from_slice(&self.split_id);
+        out.extend_from_slice(&[self.threshold, self.index]);
+        out.extend_from_slice(&self.secret_len.to_be_bytes());
+        out.extend_from_slice(&(self.value.len() as u16).to_be_bytes());
+        out.extend_from_slice(&self.value);
+        let checksum = Sha256::digest(&out);
+        out.extend_from_slice(&checksum[..CHECKSUM_LEN]);
+        out
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, &'static str> {
+        const HEADER_LEN: usize = 4 + 2 + SPLIT_ID_LEN + 2 + 2 + 2;
+        if data.len() < HEADER_LEN + CHECKSUM_LEN || &data[..4] != MAGIC {
+            return Err("Not a secret share");
+        }
+        let (body, checksum) = data.split_at(data.len() - CHECKSUM_LEN);
+        if Sha256::digest(body)[..CHECKSUM_LEN] != *checksum {
+            return Err("Share checksum mismatch");
+        }
+        if body[4] != SHARE_VERSION {
+            return Err("Unsupported share version");
+        }
+        let scheme = match body[5] {
+            1 => SharingScheme::Gf256,
+            2 => SharingScheme::FeldmanP256,
+            _ => return Err("Unsupported sharing scheme"),
+        };
+        let split_id = body[6..6 + SPLIT_ID_LEN].try_into().unwrap();
+        let (threshold, index) = (body[14], body[15]);
+        let secret_len = u16::from_be_bytes([body[16], body[17]]);
+        let value_len = u16::from_be_bytes([body[18], body[19]]) as usize;
+        if body.len() != HEADER_LEN + value_len {
+            return Err("Share length mismatch");
+        }
+        if threshold == 0 || index == 0 {
+            return Err("Invalid share parameters");
+        }
+        Ok(Self { scheme, split_id, threshold, index, secret_len, value: body[HEADER_LEN..].to_vec() })
+    }
+}
+
+// ---- GF(2^8) ----
+
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = a >> 7;
+        a = (a << 1) ^ (0x1b & 0u8.wrapping_sub(carry));
+        b >>= 1;
+    }
+    product
+}
+
+/// a^254 = a^-1 for a != 0.
+fn gf_inv(a: u8) -> u8 {
+    let a2 = gf_mul(a, a);
+    let a3 = gf_mul(a2, a);
+    let a6 = gf_mul(a3, a3);
+    let a12 = gf_mul(a6, a6);
+    let a15 = gf_mul(a12, a3);
+    let a30 = gf_mul(a15, a15);
+    let a60 = gf_mul(a30, a30);
+    let a63 = gf_mul(a60, a3);
+    let a126 = gf_mul(a63, a63);
+    let a127 = gf_mul(a126, a);
+    gf_mul(a127, a127)
+}
+
+// ---- Split and combine ----
+
+/// Validate split parameters and draw a fresh split id.
+pub(crate) fn new_split(secret_len: usize, threshold: u8, shares: u8) -> Result<[u8; SPLIT_ID_LEN], &'static str> {
+    if secret_len == 0 || secret_len > u16::MAX as usize {
+        return Err("Secret must be 1 to 65535 bytes");
+    }
+    if threshold == 0 || threshold > shares {
+        return Err("Threshold must be between 1 and the number of shares");
+    }
+    let mut split_id = [0u8; SPLIT_ID_LEN];
+    getrandom::getrandom(&mut split_id).map_err(|_| "Random generation failed")?;
+    Ok(split_id)
+}
+
+/// Split `secret` into `shares` shares, any `threshold` of which recover it.
+pub fn split(secret: &[u8], threshold: u8, shares: u8) -> Result<Vec<Share>, &'static str> {
+    let split_id = new_split(secret.len(), threshold, shares)?;
+
+    // coefficients[k * len + b] is the coefficient of x^(k + 1) for secret byte b.
+    let mut coefficients = vec![0u8; (threshold as usize - 1) * secret.len()];
+    getrandom::getrandom(&mut coefficients).map_err(|_| "Random generation failed")?;
+
+    let output = (1..=shares)
+        .map(|x| {
+            let mut value = vec![0u8; secret.len()];
+            for (b, out) in value.iter_mut().enumerate() {
+                // Horner's rule from the highest coefficient down to the secret byte.
+                let mut y = 0u8;
+                for k in (0..threshold as usize - 1).rev() {
+                    y = gf_mul(y, x) ^ coefficients[k * secret.len() + b];
+                }
+                *out = gf_mul(y, x) ^ secret[b];
+            }
+            Share { scheme: SharingScheme::Gf256, split_id, threshold, index: x, secret_len: secret.len() as u16, value }
+        })
+        .collect();
+    coefficients.zeroize();
+    Ok(output)
+}
+
+/// Check that `shares` belong to one split and return the first `threshold` of them.
+pub(crate) fn select_shares(shares: &[Share]) -> Result<&[Share], &'static str> {
+    let first = shares.first().ok_or("No shares given")?;
+    for (i, share) in shares.iter().enumerate() {
+        if (share.scheme, share.split_id, share.threshold, share.secret_len) != (first.scheme, first.split_id, first.threshold, first.secret_len) {
+            return Err("Shares come from different splits");
+        }
+        if shares[..i].iter().any(|other| other.index == share.index) {
+            return Err("Duplicate share index");
+        }
+    }
+    if shares.len() < first.threshold as usize {
+        return Err("Not enough shares to reach the threshold");
+    }
+    Ok(&shares[..first.threshold as usize])
+}
+
+/// Recover the secret from at least `threshold` shares of one split. Feldman shares are
+/// interpolated without verification; use `feldman::combine` to check them first.
+pub fn combine(shares: &[Share]) -> Result<Vec<u8>, &'static str> {
+    let shares = select_shares(shares)?;
+    match shares[0].scheme {
+        SharingScheme::Gf256 => combine_gf256(shares),
+        SharingScheme::FeldmanP256 => feldman::interpolate(shares),
+    }
+}
+
+fn combine_gf256(shares: &[Share]) -> Result<Vec<u8>, &'static str> {
+    let secret_len = shares[0].secret_len as usize;
+    if shares.iter().any(|share| share.value.len() != secret_len) {
+        return Err("Share length mismatch");
+    }
+
+    // Lagrange basis at zero: l_i = prod_{j != i} x_j / (x_j - x_i); subtraction is XOR.
+    let mut basis: Vec<u8> = shares
+        .iter()
+        .map(|share| {
+            shares.iter().filter(|other| other.index != share.index).fold(1u8, |l, other| {
+                gf_mul(l, gf_mul(other.index, gf_inv(other.index ^ share.index)))
+            })
+        })
+        .collect();
+
+    let mut secret = vec![0u8; secret_len];
+    for (share, &l) in shares.iter().zip(&basis) {
+        for (out, &y) in secret.iter_mut().zip(&share.value) {
+            *out ^= gf_mul(l, y);
+        }
+    }
+    basis.zeroize();
+    Ok(secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"a 32-byte keystore master key!!!";
+
+    #[test]
+    fn threshold_subsets_recover_the_secret() {
+        let shares = split(SECRET, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+        for subset in [&shares[..3], &shares[2..], &shares[..], &[shares[4].clone(), shares[0].clone(), shares[2].clone()][..]] {
+            assert_eq!(combine(subset).unwrap(), SECRET);
+        }
+        assert_eq!(combine(&shares[..2]), Err("Not enough shares to reach the threshold"));
+        assert_eq!(combine(&[shares[0].clone(), shares[0].clone(), shares[1].clone()]), Err("Duplicate share index"));
+    }
+
+    #[test]
+    fn threshold_one_shares_are_the_secret() {
+        let shares = split(SECRET, 1, 3).unwrap();
+        for share in &shares {
+            assert_eq!(share.value, SECRET);
+            assert_eq!(combine(std::slice::from_ref(share)).unwrap(), SECRET);
+        }
+        assert!(split(SECRET, 0, 3).is_err());
+        assert!(split(SECRET, 4, 3).is_err());
+        assert!(split(b"", 2, 3).is_err());
+    }
+
+    #[test]
+    fn rejects_shares_from_another_split() {
+        let first = split(SECRET, 2, 3).unwrap();
+        let second = split(SECRET, 2, 3).unwrap();
+        assert_ne!(first[0].split_id, second[0].split_id);
+        assert_eq!(combine(&[first[0].clone(), second[1].clone()]), Err("Shares come from different splits"));
+    }
+
+    #[test]
+    fn encoding_roundtrip_and_checksum() {
+        let share = split(SECRET, 2, 3).unwrap().remove(1);
+        let encoded = share.encode();
+        assert_eq!(Share::decode(&encoded).unwrap(), share);
+
+        for index in [5, 15, 20, encoded.len() - 1] {
+            let mut corrupted = encoded.clone();
+            corrupted[index] ^= 1;
+            assert_eq!(Share::decode(&corrupted), Err("Share checksum mismatch"));
+        }
+        assert!(Share::decode(&encoded[..encoded.len() - 1]).is_err());
+        assert_eq!(Share::decode(b"IXSH"), Err("Not a secret share"));
+    }
+
+    #[test]
+    fn gf256_inverse() {
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1);
+        }
+        assert_eq!(gf_mul(0x57, 0x83), 0xc1);
+    }
+}
@@ -0,0 +1,163 @@
+// ix-encryption/core/siv_cipher.rs
+
+//! Nonce-misuse-resistant AEADs exposed through `IXCipherCore`.
+//! `AesGcmSivCipher` wraps RFC 8452 and `AesSivCipher` wraps RFC 5297. Both tolerate
+//! colliding nonces from distributed writers without losing confidentiality of distinct messages.
+
+use crate::core::IXCipherCore;
+use crate::core::ciphers::aes::AesCipher;
+use crate::core::mode_gcm_siv::{GCMSIVMode, GCM_SIV_NONCE_LEN, GCM_SIV_TAG_LEN};
+use crate::core::mode_siv::SIVMode;
+
+/// AES-GCM-SIV with a random 96-bit nonce prepended to each ciphertext.
+/// Output layout: nonce || ciphertext || tag.
+pub struct AesGcmSivCipher {
+    cipher: Option<AesCipher>,
+}
+
+impl AesGcmSivCipher {
+    pub fn new() -> Self {
+        Self { cipher: None }
+    }
+}
+
+impl IXCipherCore for AesGcmSivCipher {
+    fn initialize(&mut self, key: &[u8], _salt: Option<&[u8]>) {
+        assert!(key.len() == 16 || key.len() == 32, "AES-GCM-SIV key must be 16 or 32 bytes");
+        self.cipher = Some(AesCipher::new(key).expect("AES key setup failed"));
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        let cipher = self.cipher.as_ref().expect("Cipher not initialized");
+        let mut nonce = vec![0u8; GCM_SIV_NONCE_LEN];
+        getrandom::getrandom(&mut nonce).expect("Nonce generation failed");
+
+        let (ciphertext, tag) = GCMSIVMode::new(cipher, nonce.clone(), Vec::new())
+            .encrypt_and_tag(plaintext)
+            .expect("Encryption failed");
+
+        let mut output = nonce;
+        output.extend_from_slice(&ciphertext);
+        output.extend_from_slice(&tag);
+        output
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Vec<u8> {
+        self.try_decrypt(ciphertext).expect("Decryption failed")
+    }
+
+    fn try_decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        let cipher = self.cipher.as_ref().ok_or("Cipher not initialized")?;
+        if ciphertext.len() < GCM_SIV_NONCE_LEN + GCM_SIV_TAG_LEN {
+            return Err("Ciphertext too short");
+        }
+        let (nonce, rest) = ciphertext.split_at(GCM_SIV_NONCE_LEN);
+        let (body, tag) = rest.split_at(rest.len() - GCM_SIV_TAG_LEN);
+
+        GCMSIVMode::new(cipher, nonce.to_vec(), Vec::new())
+            .decrypt_and_verify(body, tag)
+            .ok_or("Decryption failed")
+    }
+
+    fn wipe(&mut self) {
+        self.cipher = None;
+    }
+
+    fn algorithm_id(&self) -> &'static str {
+        "IX-AES-GCM-SIV-v1"
+    }
+
+    fn trigger_lockdown(&self) -> bool {
+        false
+    }
+}
+
+/// Length of the random nonce component used by non-deterministic `AesSivCipher`.
+pub const SIV_NONCE_LEN: usize = 16;
+
+/// AES-SIV keyed with a double-length key (K1 || K2: 32, 48 or 64 bytes).
+/// In deterministic mode no nonce is used and equal plaintexts give equal ciphertexts,
+/// which is what key wrapping wants. Otherwise a random nonce is prepended.
+pub struct AesSivCipher {
+    keys: Option<(AesCipher, AesCipher)>,
+    deterministic: bool,
+}
+
+impl AesSivCipher {
+    pub fn new() -> Self {
+        Self { keys: None, deterministic: false }
+    }
+
+    /// Deterministic AES-SIV for key wrapping.
+    pub fn deterministic() -> Self {
+        Self { keys: None, deterministic: true }
+    }
+
+    fn mode(&self) -> SIVMode<'_, AesCipher> {
+        let (mac, ctr) = self.keys.as_ref().expect("Cipher not initialized");
+        SIVMode::new(mac, ctr)
+    }
+}
+
+impl IXCipherCore for AesSivCipher {
+    fn initialize(&mut self, key: &[u8], _salt: Option<&[u8]>) {
+        assert!(
+            matches!(key.len(), 32 | 48 | 64),
+            "AES-SIV key must be 32, 48 or 64 bytes"
+        );
+        let (k1, k2) = key.split_at(key.len() / 2);
+        self.keys = Some((
+            AesCipher::new(k1).expect("AES key setup failed"),
+            AesCipher::new(k2).expect("AES key setup failed"),
+        ));
+    }
+
+    fn encrypt(&self, plaintext: &[u8]) -> Vec<u8> {
+        if self.deterministic {
+            return self.mode().encrypt(&[], plaintext);
+        }
+
+        let mut nonce = vec![0u8; SIV_NONCE_LEN];
+        getrandom::getrandom(&mut nonce).expect("Nonce generation failed");
+        let sealed = self.mode().encrypt(&[&nonce], plaintext);
+
+        let mut output = nonce;
+        output.extend_from_slice(&sealed);
+        output
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Vec<u8> {
+        self.try_decrypt(ciphertext).expect("Decryption failed")
+    }
+
+    fn try_decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, &'static str> {
+        if self.keys.is_none() {
+            return Err("Cipher not initialized");
+        }
+        if self.deterministic {
+            return self.mode().decrypt(&[], ciphertext);
+        }
+
+        if ciphertext.len() < SIV_NONCE_LEN {
+            return Err("Ciphertext missing nonce");
+        }
+        let (nonce, sealed) = ciphertext.split_at(SIV_NONCE_LEN);
+        self.mode().decrypt(&[nonce], sealed)
+    }
+
+    fn wipe(&mut self) {
+        self.keys = None;
+    }
+
+    fn algorithm_id(&self) -> &'static str {
+        if self.deterministic {
+            "IX-AES-SIV-Det-v1"
+        } else {
+            "IX-AES-SIV-v1"
+        }
+    }
+
+    fn trigger_lockdown(&self) -> bool {
+        false
+    }
+}
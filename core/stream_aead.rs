@@ -0,0 +1,101 @@
+// ix-encryption/core/stream_aead.rs
+
+//! Streaming AEAD (the STREAM construction) over ChaCha20-Poly1305.
+//! A message is split into chunks sealed under nonce = prefix (7) || counter (u32 BE) || last flag,
+//! so reordering, dropping, duplicating or truncating chunks is detected, and arbitrarily large
+//! inputs can be processed with bounded memory.
+
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+
+pub const STREAM_KEY_LEN: usize = 32;
+pub const STREAM_NONCE_PREFIX_LEN: usize = 7;
+pub const STREAM_TAG_LEN: usize = 16;
+
+fn chunk_nonce(prefix: &[u8; STREAM_NONCE_PREFIX_LEN], counter: u32, last: bool) -> [u8; 12] {
+    let mut nonce = [0u8; 12];
+    nonce[..STREAM_NONCE_PREFIX_LEN].copy_from_slice(prefix);
+    nonce[7..11].copy_from_slice(&counter.to_be_bytes());
+    nonce[11] = last as u8;
+    nonce
+}
+
+fn stream_cipher(key: &[u8]) -> Result<ChaCha20Poly1305, &'static str> {
+    if key.len() != STREAM_KEY_LEN {
+        return Err("Stream key must be 32 bytes");
+    }
+    Ok(ChaCha20Poly1305::new(Key::from_slice(key)))
+}
+
+pub struct StreamEncryptor {
+    cipher: ChaCha20Poly1305,
+    prefix: [u8; STREAM_NONCE_PREFIX_LEN],
+    counter: u32,
+    finished: bool,
+}
+
+impl StreamEncryptor {
+    /// The (key, prefix) pair must never be reused for a second stream.
+    pub fn new(key: &[u8], prefix: [u8; STREAM_NONCE_PREFIX_LEN]) -> Result<Self, &'static str> {
+        Ok(Self { cipher: stream_cipher(key)?, prefix, counter: 0, finished: false })
+    }
+
+    /// Seal one chunk; `last` must be set on the final chunk and on no other.
+    pub fn seal_chunk(&mut self, chunk: &[u8], aad: &[u8], last: bool) -> Result<Vec<u8>, &'static str> {
+        if self.finished {
+            return Err("Stream already finished");
+        }
+        let nonce = chunk_nonce(&self.prefix, self.counter, last);
+        let sealed = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: chunk, aad })
+            .map_err(|_| "Chunk encryption failed")?;
+
+        if last {
+            self.finished = true;
+        } else {
+            self.counter = self.counter.checked_add(1).ok_or("Stream chunk counter exhausted")?;
+        }
+        Ok(sealed)
+    }
+}
+
+pub struct StreamDecryptor {
+    cipher: ChaCha20Poly1305,
+    prefix: [u8; STREAM_NONCE_PREFIX_LEN],
+    counter: u32,
+    finished: bool,
+}
+
+impl StreamDecryptor {
+    pub fn new(key: &[u8], prefix: [u8; STREAM_NONCE_PREFIX_LEN]) -> Result<Self, &'static str> {
+        Ok(Self { cipher: stream_cipher(key)?, prefix, counter: 0, finished: false })
+    }
+
+    /// Open the next chunk in sequence. Fails if it was sealed at another position or with a
+    /// different `last` flag.
+    pub fn open_chunk(&mut self, sealed: &[u8], aad: &[u8], last: bool) -> Result<Vec<u8>, &'static str> {
+        if self.finished {
+            return Err("Stream already finished");
+        }
+        let nonce = chunk_nonce(&self.prefix, self.counter, last);
+        let chunk = self
+            .cipher
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: sealed, aad })
+            .map_err(|_| "Chunk authentication failed")?;
+
+        if last {
+            self.finished = true;
+        } else {
+            self.counter = self.counter.checked_add(1).ok_or("Stream chunk counter exhausted")?;
+        }
+        Ok(chunk)
+    }
+
+    /// True once the final chunk has been opened; a stream that ends before this is truncated.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
@@ -0,0 +1,10 @@
+// ix-encryption/core/test_util.rs
+
+//! Helpers shared by the in-file known-answer tests.
+
+#![cfg(test)]
+
+/// Decodes a hex test vector.
+pub(crate) fn h(s: &str) -> Vec<u8> {
+    hex::decode(s).expect("invalid hex in test vector")
+}